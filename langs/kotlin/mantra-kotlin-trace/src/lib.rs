@@ -0,0 +1,167 @@
+use mantra_lang_tracing::{
+    collect::{comment_traces, node_span, AstNode, TraceEntry},
+    extract::{colon_trace_matcher, req_trace_matcher},
+    RawTraceEntry,
+};
+
+pub fn collect_traces_in_kotlin(
+    node: &AstNode,
+    src: &[u8],
+    _filepath: &str,
+    _collect_arg: &(),
+) -> Option<Vec<TraceEntry>> {
+    let node_kind = node.kind();
+
+    if node_kind == "annotation" {
+        let (user_type, args) = annotation_parts(*node)?;
+
+        if annotation_name(user_type, src)? != "Req" {
+            return None;
+        }
+
+        let declaration = annotated_declaration(*node);
+        let span = declaration.and_then(node_span);
+        let item_name = declaration.and_then(|decl| item_name(decl, src));
+
+        let mut ids = Vec::new();
+        collect_string_contents(args?, src, &mut ids);
+
+        if ids.is_empty() {
+            return None;
+        }
+
+        return Some(vec![TraceEntry::try_from(RawTraceEntry::new(
+            &ids.join(","),
+            node.start_position().row + 1,
+            span,
+            item_name,
+        ))
+        .ok()?]);
+    } else if is_comment(node_kind) {
+        let comment_content = node.utf8_text(src).ok()?;
+        let declaration = associated_declaration(*node);
+        let span = declaration.and_then(node_span);
+        let item_name = declaration.and_then(|decl| item_name(decl, src));
+
+        return comment_traces(
+            node,
+            comment_content,
+            &[colon_trace_matcher(), req_trace_matcher()],
+            span,
+            item_name,
+        );
+    }
+
+    None
+}
+
+/// Returns the type of the annotation, and the node containing its arguments.
+///
+/// Use-site targets (e.g. `@get:Req(...)`) are skipped.
+/// Annotations of top-level declarations may be parsed as annotated expression,
+/// in which case the arguments are in the following parenthesized expression.
+fn annotation_parts(annotation: AstNode) -> Option<(AstNode, Option<AstNode>)> {
+    let mut cursor = annotation.walk();
+    let content = annotation
+        .named_children(&mut cursor)
+        .find(|child| child.kind() != "use_site_target")?;
+
+    match content.kind() {
+        "constructor_invocation" => Some((content.named_child(0)?, content.named_child(1))),
+        "user_type" => {
+            let args = annotation
+                .parent()
+                .filter(|parent| parent.kind() == "prefix_expression")
+                .and_then(|_| annotation.next_named_sibling())
+                .filter(|sibling| sibling.kind() == "parenthesized_expression");
+
+            Some((content, args))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the simple name of the annotation type (e.g. `Req` for `com.example.Req`).
+fn annotation_name<'a>(user_type: AstNode, src: &'a [u8]) -> Option<&'a str> {
+    let mut cursor = user_type.walk();
+    let name = user_type
+        .named_children(&mut cursor)
+        .filter(|child| child.kind() == "type_identifier")
+        .last()?;
+
+    name.utf8_text(src).ok()
+}
+
+/// Collects the content of all string literals below the given node.
+/// This allows IDs to be set as multiple arguments, or as array (e.g. `@Req(["a", "b"])`).
+fn collect_string_contents<'a>(node: AstNode, src: &'a [u8], contents: &mut Vec<&'a str>) {
+    if node.kind() == "string_content" {
+        if let Ok(content) = node.utf8_text(src) {
+            contents.push(content);
+        }
+        return;
+    }
+
+    for child in node.named_children(&mut node.walk()) {
+        collect_string_contents(child, src, contents);
+    }
+}
+
+/// Returns the declaration the annotation is set on.
+fn annotated_declaration(annotation: AstNode) -> Option<AstNode> {
+    let parent = annotation.parent()?;
+
+    match parent.kind() {
+        // annotations are part of the modifiers of a declaration
+        "modifiers" => parent.parent().filter(|decl| is_declaration(decl.kind())),
+        "prefix_expression" => associated_declaration(parent),
+        _ => None,
+    }
+}
+
+/// Returns the declaration following the given node.
+/// Comments and other annotations between the node and the declaration are skipped.
+fn associated_declaration(mut node: AstNode) -> Option<AstNode> {
+    loop {
+        match node.next_named_sibling() {
+            Some(sibling) => {
+                let sibling_kind = sibling.kind();
+
+                if is_declaration(sibling_kind) {
+                    return Some(sibling);
+                } else if !is_comment(sibling_kind) && sibling_kind != "prefix_expression" {
+                    return None;
+                }
+
+                node = sibling;
+            }
+            None => {
+                // comments after the last import are part of the import list
+                node = node
+                    .parent()
+                    .filter(|parent| matches!(parent.kind(), "import_header" | "import_list"))?;
+            }
+        }
+    }
+}
+
+fn is_declaration(kind: &str) -> bool {
+    kind.ends_with("_declaration") && kind != "variable_declaration"
+}
+
+fn is_comment(kind: &str) -> bool {
+    kind == "line_comment" || kind == "multiline_comment"
+}
+
+fn item_name(declaration: AstNode, src: &[u8]) -> Option<String> {
+    let mut cursor = declaration.walk();
+    let name = declaration
+        .named_children(&mut cursor)
+        .find_map(|child| match child.kind() {
+            "simple_identifier" | "type_identifier" => Some(child),
+            "variable_declaration" => child.named_child(0),
+            _ => None,
+        })?;
+
+    name.utf8_text(src).ok().map(|name| name.to_string())
+}
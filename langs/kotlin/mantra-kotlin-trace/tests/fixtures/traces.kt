@@ -0,0 +1,29 @@
+package com.example
+
+import com.example.Req
+
+// [req:login]
+fun login(user: String): Boolean {
+    return true
+}
+
+/**
+ * Session handling [req(session, session.timeout)]
+ */
+@Deprecated("old")
+class Session {
+    @Req("session.valid")
+    val isValid: Boolean = true
+
+    @Suppress("unused")
+    @Req("session.close", "session.cleanup")
+    fun close() {
+        println("closed")
+    }
+
+    @get:Req("session.id")
+    val id: String = ""
+}
+
+@com.example.Req("profile")
+data class Profile(val name: String)
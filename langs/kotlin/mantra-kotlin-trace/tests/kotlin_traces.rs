@@ -0,0 +1,97 @@
+use mantra_lang_tracing::{
+    collect::{LineSpan, TraceEntry},
+    test_util::trace_of,
+};
+
+fn collect_fixture() -> Vec<TraceEntry> {
+    mantra_lang_tracing::test_util::collect_fixture(
+        include_str!("fixtures/traces.kt"),
+        &tree_sitter_kotlin::language(),
+        "traces.kt",
+        Box::new(mantra_kotlin_trace::collect_traces_in_kotlin),
+    )
+}
+
+#[test]
+fn comment_traces() {
+    let traces = collect_fixture();
+
+    let login = trace_of(&traces, "login");
+    assert_eq!(login.line, 5, "Wrong line for comment trace.");
+    assert_eq!(
+        login.line_span,
+        Some(LineSpan { start: 6, end: 8 }),
+        "Function span not attached to comment trace."
+    );
+    assert_eq!(
+        login.item_name.as_deref(),
+        Some("login"),
+        "Function name not set as item name."
+    );
+
+    let session = trace_of(&traces, "session.timeout");
+    assert_eq!(
+        session.ids,
+        vec!["session".to_string(), "session.timeout".to_string()],
+        "Multiple IDs in KDoc trace not extracted."
+    );
+    assert_eq!(
+        session.line, 11,
+        "Line of KDoc trace not taken from the trace itself."
+    );
+    assert_eq!(
+        session.line_span,
+        Some(LineSpan { start: 13, end: 26 }),
+        "Class span not attached to KDoc trace."
+    );
+}
+
+#[test]
+fn annotation_traces() {
+    let traces = collect_fixture();
+
+    let valid = trace_of(&traces, "session.valid");
+    assert_eq!(valid.line, 15, "Wrong line for annotation trace.");
+    assert_eq!(
+        valid.line_span,
+        Some(LineSpan { start: 15, end: 16 }),
+        "Property span not attached to annotation trace."
+    );
+    assert_eq!(
+        valid.item_name.as_deref(),
+        Some("isValid"),
+        "Property name not set as item name."
+    );
+
+    let close = trace_of(&traces, "session.close");
+    assert_eq!(
+        close.ids,
+        vec!["session.close".to_string(), "session.cleanup".to_string()],
+        "Multiple annotation arguments not extracted."
+    );
+    assert_eq!(
+        close.line_span,
+        Some(LineSpan { start: 18, end: 22 }),
+        "Function span with multiple annotations not attached."
+    );
+
+    let id = trace_of(&traces, "session.id");
+    assert_eq!(
+        id.line_span,
+        Some(LineSpan { start: 24, end: 25 }),
+        "Annotation with use-site target not handled."
+    );
+
+    let profile = trace_of(&traces, "profile");
+    assert_eq!(
+        profile.line_span,
+        Some(LineSpan { start: 29, end: 29 }),
+        "Qualified annotation on top-level class not handled."
+    );
+    assert_eq!(
+        profile.item_name.as_deref(),
+        Some("Profile"),
+        "Class name not set as item name."
+    );
+    assert_eq!(traces.len(), 6, "Unexpected number of traces.");
+}
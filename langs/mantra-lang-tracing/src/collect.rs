@@ -1,3 +1,4 @@
+use regex::Regex;
 use tree_sitter::{Language, Parser, Tree};
 
 use crate::RawTraceEntry;
@@ -56,6 +57,52 @@ pub use tree_sitter::Node as AstNode;
 pub type AstCollectorFn<'a, T> =
     Box<dyn FnMut(&AstNode, &'a [u8], &str, &T) -> Option<Vec<TraceEntry>>>;
 
+/// Returns the lines the given node spans in the source.
+pub fn node_span(node: AstNode) -> Option<LineSpan> {
+    let start = Line::try_from(node.start_position().row + 1).ok()?;
+    let end = Line::try_from(node.end_position().row + 1).ok()?;
+
+    Some(LineSpan { start, end })
+}
+
+/// Extracts the traces of all given matchers from the content of a comment node.
+/// Matchers must capture the requirement IDs in the group `ids`.
+///
+/// Comments may span multiple lines (e.g. doc blocks),
+/// so the line of a trace is the line its IDs are set in the comment.
+pub fn comment_traces(
+    comment: &AstNode,
+    comment_content: &str,
+    matchers: &[&Regex],
+    line_span: Option<LineSpan>,
+    item_name: Option<String>,
+) -> Option<Vec<TraceEntry>> {
+    let mut traces = Vec::new();
+
+    for matcher in matchers {
+        for capture in matcher.captures_iter(comment_content) {
+            let ids = capture.name("ids")?;
+            let line_offset = comment_content[..ids.start()].matches('\n').count();
+
+            traces.push(
+                TraceEntry::try_from(RawTraceEntry::new(
+                    ids.as_str(),
+                    comment.start_position().row + 1 + line_offset,
+                    line_span,
+                    item_name.clone(),
+                ))
+                .ok()?,
+            );
+        }
+    }
+
+    if traces.is_empty() {
+        None
+    } else {
+        Some(traces)
+    }
+}
+
 impl<'a, T> AstCollector<'a, T> {
     /// # Parameters
     ///
@@ -23,6 +23,8 @@ impl TryFrom<RawTraceEntry<'_>> for TraceEntry {
             line,
             line_span,
             item_name,
+            item_path: None,
+            test_name: None,
         })
     }
 }
@@ -36,6 +38,16 @@ pub fn req_trace_matcher() -> &'static Regex {
     })
 }
 
+static COLON_TRACE_MATCHER: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+
+/// Matches traces of the form `[req:<requirement id(s)>]`.
+pub fn colon_trace_matcher() -> &'static Regex {
+    COLON_TRACE_MATCHER.get_or_init(|| {
+        Regex::new(r"\[req:\s*(?<ids>[^\]]+)\]")
+            .expect("Regex to match a requirement trace in comments could **not** be created.")
+    })
+}
+
 pub fn extract_req_ids_from_str(s: &str) -> Result<Vec<ReqId>, String> {
     let tokens = TokenStream::from_str(s).map_err(|_| {
         format!("Given requirement IDs '{s}' contain one or more invalid characters.")
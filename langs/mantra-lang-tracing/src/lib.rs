@@ -4,6 +4,8 @@ use mantra_schema::traces::LineSpan;
 pub mod collect;
 #[cfg(feature = "collect")]
 pub mod lsif_graph;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 
 #[cfg(feature = "extract")]
 pub mod extract;
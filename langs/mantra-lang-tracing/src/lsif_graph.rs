@@ -347,6 +347,26 @@ impl LsifGraph {
         None
     }
 
+    /// Returns the identifier of the item that is **defined** in the given document at the given line.
+    ///
+    /// In contrast to [`Self::get_identifier`], `None` is returned if the definition of the resolved identifier
+    /// is located elsewhere, so the identifier always refers to the definition site.
+    pub fn get_definition_identifier(&self, doc: &str, line: u32) -> Option<String> {
+        let ident = self.get_identifier(doc, line)?;
+        let location = self.get_ident_location(&ident)?;
+
+        let doc_path = match &self.project_root {
+            Some(root) => self.abs_path(doc).strip_prefix(root.as_str())?.to_string(),
+            None => doc.to_string(),
+        };
+
+        if location.filepath == doc_path && location.start_line == line {
+            Some(ident)
+        } else {
+            None
+        }
+    }
+
     pub fn get_ident_location(&self, identifier: &str) -> Option<FileLocation> {
         let moniker_id = self.idents.get(identifier)?;
         let result_set_id = self.moniker_in.get(moniker_id)?;
@@ -459,6 +479,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn resolve_definition_ident() {
+        let lsif = include_str!("lsif_sample.json");
+        let graph = LsifGraph::create(lsif).unwrap();
+
+        assert_eq!(
+            graph.get_definition_identifier("src/main.rs", 8).as_deref(),
+            Some("lsif_test::foo"),
+            "Identifier at definition site not resolved."
+        );
+        assert_eq!(
+            graph.get_definition_identifier("src/main.rs", 9),
+            None,
+            "Identifier resolved for line without definition."
+        );
+    }
+
     #[test]
     fn resolve_location() {
         let lsif = include_str!("lsif_sample.json");
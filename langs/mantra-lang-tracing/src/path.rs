@@ -1,5 +1,6 @@
 use std::path::{Path, PathBuf};
 
+#[cfg(target_os = "windows")]
 use path_slash::PathBufExt;
 
 pub fn make_relative(filepath: &Path, root: &Path) -> Option<PathBuf> {
@@ -21,6 +22,9 @@ pub fn make_relative(filepath: &Path, root: &Path) -> Option<PathBuf> {
 }
 
 /// Custom PathBuf to only get forward slashes when displaying the path.
+///
+/// The displayed path is normalized, so the same file is always displayed the same way.
+/// `.` segments and repeated separators are removed (e.g. `./src//lib.rs` is displayed as `src/lib.rs`).
 pub struct SlashPathBuf(PathBuf);
 
 impl std::fmt::Display for SlashPathBuf {
@@ -28,9 +32,17 @@ impl std::fmt::Display for SlashPathBuf {
         #[cfg(target_os = "windows")]
         let slash_path = self.0.to_slash_lossy();
         #[cfg(not(target_os = "windows"))]
-        let slash_path = self.0.to_string_lossy();
+        let slash_path = self.0.to_string_lossy().replace('\\', "/");
 
-        write!(f, "{slash_path}")
+        // leading separators are kept for absolute and UNC paths
+        let relative = slash_path.trim_start_matches('/');
+        let root = &slash_path[..slash_path.len() - relative.len()];
+        let segments: Vec<&str> = relative
+            .split('/')
+            .filter(|segment| !segment.is_empty() && *segment != ".")
+            .collect();
+
+        write!(f, "{root}{}", segments.join("/"))
     }
 }
 
@@ -131,4 +143,21 @@ mod test {
             "Path not converted to forward slash."
         );
     }
+
+    #[test]
+    fn redundant_segments_removed() {
+        let path = SlashPathBuf::from_str(".\\src//cmd/./mod.rs").unwrap();
+        let absolute = SlashPathBuf::from_str("/home/user/./src/lib.rs").unwrap();
+
+        assert_eq!(
+            &path.to_string(),
+            "src/cmd/mod.rs",
+            "Redundant path segments not removed."
+        );
+        assert_eq!(
+            &absolute.to_string(),
+            "/home/user/src/lib.rs",
+            "Root of absolute path removed."
+        );
+    }
 }
@@ -0,0 +1,34 @@
+//! Helpers to test trace collectors of languages against fixture files.
+
+use tree_sitter::Language;
+
+use crate::collect::{AstCollector, AstCollectorFn, TraceCollector, TraceEntry};
+
+/// Collects all traces of the given fixture using the given collector function.
+///
+/// # Panics
+///
+/// If the fixture cannot be parsed, or contains no traces.
+pub fn collect_fixture<'a>(
+    src: &'a str,
+    lang: &Language,
+    filepath: &str,
+    collect_fn: AstCollectorFn<'a, ()>,
+) -> Vec<TraceEntry> {
+    let mut collector = AstCollector::new(src.as_bytes(), lang, filepath.to_string(), collect_fn)
+        .expect("Fixture is valid code of the given language.");
+
+    collector.collect(&()).expect("Traces found in fixture.")
+}
+
+/// Returns the first trace referencing the given requirement ID.
+///
+/// # Panics
+///
+/// If no trace references the ID.
+pub fn trace_of<'a>(traces: &'a [TraceEntry], id: &str) -> &'a TraceEntry {
+    traces
+        .iter()
+        .find(|trace| trace.ids.iter().any(|trace_id| trace_id == id))
+        .expect("Trace with ID exists in fixture.")
+}
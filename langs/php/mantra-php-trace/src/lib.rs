@@ -0,0 +1,96 @@
+use mantra_lang_tracing::{
+    collect::{comment_traces, node_span, AstNode, TraceEntry},
+    extract::{colon_trace_matcher, req_trace_matcher},
+};
+use regex::Regex;
+
+pub fn collect_traces_in_php(
+    node: &AstNode,
+    src: &[u8],
+    _filepath: &str,
+    _collect_arg: &(),
+) -> Option<Vec<TraceEntry>> {
+    if node.kind() != "comment" {
+        return None;
+    }
+
+    let comment_content = node.utf8_text(src).ok()?;
+    let declaration = associated_declaration(*node);
+    let span = declaration.and_then(node_span);
+    let item_name = declaration.and_then(|decl| item_name(decl, src));
+
+    comment_traces(
+        node,
+        comment_content,
+        &[
+            colon_trace_matcher(),
+            docblock_trace_matcher(),
+            req_trace_matcher(),
+        ],
+        span,
+        item_name,
+    )
+}
+
+static DOCBLOCK_TRACE_MATCHER: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+
+/// Matches docblock tags of the form `@req <requirement id(s)>`.
+/// Multiple IDs must be separated by commas, and the tag ends at the end of the line.
+fn docblock_trace_matcher() -> &'static Regex {
+    DOCBLOCK_TRACE_MATCHER.get_or_init(|| {
+        Regex::new(r"@req[ \t]+(?<ids>[^\s,*]+(?:[ \t]*,[ \t]*[^\s,*]+)*)")
+            .expect("Regex to match a php docblock trace could **not** be created.")
+    })
+}
+
+/// Returns the declaration following the given comment.
+/// Other comments between the comment and the declaration are skipped.
+/// Attributes (e.g. `#[Deprecated]`) are part of the declaration, and need no special handling.
+fn associated_declaration(mut node: AstNode) -> Option<AstNode> {
+    loop {
+        let sibling = node.next_named_sibling()?;
+        let sibling_kind = sibling.kind();
+
+        if is_declaration(sibling_kind) {
+            return Some(sibling);
+        } else if sibling_kind != "comment" {
+            return None;
+        }
+
+        node = sibling;
+    }
+}
+
+fn is_declaration(kind: &str) -> bool {
+    matches!(
+        kind,
+        "function_definition"
+            | "method_declaration"
+            | "class_declaration"
+            | "interface_declaration"
+            | "trait_declaration"
+            | "enum_declaration"
+            | "property_declaration"
+            | "const_declaration"
+    )
+}
+
+fn item_name(declaration: AstNode, src: &[u8]) -> Option<String> {
+    let name = match declaration.child_by_field_name("name") {
+        Some(name) => name,
+        None => {
+            // properties and constants have no name field, but one element per declared name
+            let mut cursor = declaration.walk();
+            let element = declaration
+                .named_children(&mut cursor)
+                .find(|child| matches!(child.kind(), "property_element" | "const_element"))?;
+            let mut cursor = element.walk();
+            let name = element
+                .named_children(&mut cursor)
+                .find(|child| matches!(child.kind(), "variable_name" | "name"))?;
+            name
+        }
+    };
+
+    name.utf8_text(src).ok().map(|name| name.to_string())
+}
@@ -0,0 +1,45 @@
+<?php
+
+namespace App\Auth;
+
+use App\Models\User;
+
+// [req:login]
+function login(string $user): bool
+{
+    return true;
+}
+
+/**
+ * Session handling.
+ *
+ * @req session, session.timeout
+ */
+final class Session
+{
+    /** @req session.valid */
+    public bool $valid = true;
+
+    // [req(session.close, session.cleanup)]
+    #[Deprecated]
+    public function close(): void
+    {
+        echo "closed";
+    }
+
+    /**
+     * @req session.id
+     */
+    public static function id(): string
+    {
+        return "";
+    }
+}
+
+namespace App\Profile {
+    /** @req profile */
+    interface Profile
+    {
+        public function name(): string;
+    }
+}
@@ -0,0 +1,98 @@
+use mantra_lang_tracing::{
+    collect::{LineSpan, TraceEntry},
+    test_util::trace_of,
+};
+
+fn collect_fixture() -> Vec<TraceEntry> {
+    mantra_lang_tracing::test_util::collect_fixture(
+        include_str!("fixtures/traces.php"),
+        &tree_sitter_php::language_php(),
+        "traces.php",
+        Box::new(mantra_php_trace::collect_traces_in_php),
+    )
+}
+
+#[test]
+fn comment_traces() {
+    let traces = collect_fixture();
+
+    let login = trace_of(&traces, "login");
+    assert_eq!(login.line, 7, "Wrong line for comment trace.");
+    assert_eq!(
+        login.line_span,
+        Some(LineSpan { start: 8, end: 11 }),
+        "Function span not attached to comment trace."
+    );
+    assert_eq!(
+        login.item_name.as_deref(),
+        Some("login"),
+        "Function name not set as item name."
+    );
+
+    let close = trace_of(&traces, "session.close");
+    assert_eq!(
+        close.ids,
+        vec!["session.close".to_string(), "session.cleanup".to_string()],
+        "Multiple IDs in comment trace not extracted."
+    );
+    assert_eq!(
+        close.line_span,
+        Some(LineSpan { start: 24, end: 28 }),
+        "Method span with attribute not attached to comment trace."
+    );
+    assert_eq!(
+        close.item_name.as_deref(),
+        Some("close"),
+        "Method name not set as item name."
+    );
+}
+
+#[test]
+fn docblock_traces() {
+    let traces = collect_fixture();
+
+    let session = trace_of(&traces, "session.timeout");
+    assert_eq!(session.line, 16, "Line of the docblock tag not used.");
+    assert_eq!(
+        session.ids,
+        vec!["session".to_string(), "session.timeout".to_string()],
+        "Multiple IDs in docblock trace not extracted."
+    );
+    assert_eq!(
+        session.line_span,
+        Some(LineSpan { start: 18, end: 37 }),
+        "Class span not attached to docblock trace."
+    );
+
+    let valid = trace_of(&traces, "session.valid");
+    assert_eq!(
+        valid.line_span,
+        Some(LineSpan { start: 21, end: 21 }),
+        "Property span not attached to single-line docblock trace."
+    );
+    assert_eq!(
+        valid.item_name.as_deref(),
+        Some("$valid"),
+        "Property name not set as item name."
+    );
+
+    let id = trace_of(&traces, "session.id");
+    assert_eq!(
+        id.line_span,
+        Some(LineSpan { start: 33, end: 36 }),
+        "Static method span not attached."
+    );
+
+    let profile = trace_of(&traces, "profile");
+    assert_eq!(
+        profile.line_span,
+        Some(LineSpan { start: 41, end: 44 }),
+        "Interface in bracketed namespace not handled."
+    );
+    assert_eq!(
+        profile.item_name.as_deref(),
+        Some("Profile"),
+        "Interface name not set as item name."
+    );
+    assert_eq!(traces.len(), 6, "Unexpected number of traces.");
+}
@@ -0,0 +1,128 @@
+use mantra_lang_tracing::{
+    collect::{comment_traces, node_span, AstNode, TraceEntry},
+    extract::req_trace_matcher,
+    RawTraceEntry,
+};
+
+pub fn collect_traces_in_python(
+    node: &AstNode,
+    src: &[u8],
+    _filepath: &str,
+    _collect_arg: &(),
+) -> Option<Vec<TraceEntry>> {
+    let node_kind = node.kind();
+
+    if node_kind == "decorator" {
+        let call = node.named_child(0).filter(|expr| expr.kind() == "call")?;
+
+        if !is_req_name(decorator_name(call.child_by_field_name("function")?, src)?) {
+            return None;
+        }
+
+        let definition = node.parent().and_then(definition);
+        let span = definition.and_then(node_span);
+        let item_name = definition.and_then(|def| item_name(def, src));
+
+        let args = call.child_by_field_name("arguments")?;
+        let mut cursor = args.walk();
+        let ids: Vec<&str> = args
+            .named_children(&mut cursor)
+            .filter_map(|arg| argument_ids(arg, src))
+            .collect();
+
+        if ids.is_empty() {
+            return None;
+        }
+
+        return Some(vec![TraceEntry::try_from(RawTraceEntry::new(
+            &ids.join(","),
+            node.start_position().row + 1,
+            span,
+            item_name,
+        ))
+        .ok()?]);
+    } else if node_kind == "comment" {
+        let comment_content = node.utf8_text(src).ok()?;
+        let definition = associated_definition(*node);
+        let span = definition.and_then(node_span);
+        let item_name = definition.and_then(|def| item_name(def, src));
+
+        return comment_traces(
+            node,
+            comment_content,
+            &[req_trace_matcher()],
+            span,
+            item_name,
+        );
+    }
+
+    None
+}
+
+fn is_req_name(name: &str) -> bool {
+    name == "req" || name == "requirements"
+}
+
+/// Returns the name of the called decorator function.
+/// For qualified decorators (e.g. `@mantra.req(...)`), only the last part is returned.
+fn decorator_name<'a>(function: AstNode, src: &'a [u8]) -> Option<&'a str> {
+    let name = match function.kind() {
+        "identifier" => function,
+        "attribute" => function.child_by_field_name("attribute")?,
+        _ => return None,
+    };
+
+    name.utf8_text(src).ok()
+}
+
+/// Returns the requirement IDs of one decorator argument.
+/// IDs may be given as string (e.g. `@req("my.req")`), or as plain expression (e.g. `@req(my.req)`).
+fn argument_ids<'a>(arg: AstNode, src: &'a [u8]) -> Option<&'a str> {
+    if arg.kind() == "string" {
+        let mut cursor = arg.walk();
+        let content = arg
+            .named_children(&mut cursor)
+            .find(|child| child.kind() == "string_content")?;
+        content.utf8_text(src).ok()
+    } else {
+        arg.utf8_text(src).ok()
+    }
+}
+
+/// Returns the function or class definition following the given comment.
+/// Other comments between the comment and the definition are skipped.
+fn associated_definition(mut node: AstNode) -> Option<AstNode> {
+    loop {
+        let mut sibling = node.next_named_sibling()?;
+
+        // comments before the first statement of a block are placed before the block node
+        if sibling.kind() == "block" {
+            sibling = sibling.named_child(0)?;
+        }
+
+        match sibling.kind() {
+            "function_definition" | "class_definition" | "decorated_definition" => {
+                return definition(sibling)
+            }
+            "comment" => node = sibling,
+            _ => return None,
+        }
+    }
+}
+
+/// Returns the function or class definition, skipping decorators of decorated definitions.
+fn definition(node: AstNode) -> Option<AstNode> {
+    match node.kind() {
+        "function_definition" | "class_definition" => Some(node),
+        "decorated_definition" => node.child_by_field_name("definition"),
+        _ => None,
+    }
+}
+
+fn item_name(definition: AstNode, src: &[u8]) -> Option<String> {
+    definition
+        .child_by_field_name("name")?
+        .utf8_text(src)
+        .ok()
+        .map(|name| name.to_string())
+}
@@ -0,0 +1,34 @@
+import mantra
+from mantra import req
+
+# [req(login)]
+def login(user: str) -> bool:
+    return True
+
+
+@req("session.open")
+@staticmethod
+def open_session():
+    pass
+
+
+class Session:
+    # Closes the session.
+    # [req(session.close, session.cleanup)]
+    @property
+    def close(self):
+        pass
+
+    @mantra.req("session.refresh", "session.extend")
+    def refresh(self):
+        pass
+
+
+@req(audit.log)
+class AuditLog:
+    pass
+
+
+def test_login():
+    # [req(login.test)]
+    assert login("user")
@@ -0,0 +1,98 @@
+use mantra_lang_tracing::{
+    collect::{LineSpan, TraceEntry},
+    test_util::trace_of,
+};
+
+fn collect_fixture() -> Vec<TraceEntry> {
+    mantra_lang_tracing::test_util::collect_fixture(
+        include_str!("fixtures/traces.py"),
+        &tree_sitter_python::language(),
+        "traces.py",
+        Box::new(mantra_python_trace::collect_traces_in_python),
+    )
+}
+
+#[test]
+fn comment_traces() {
+    let traces = collect_fixture();
+
+    let login = trace_of(&traces, "login");
+    assert_eq!(login.line, 4, "Wrong line for comment trace.");
+    assert_eq!(
+        login.line_span,
+        Some(LineSpan { start: 5, end: 6 }),
+        "Function span not attached to comment trace."
+    );
+    assert_eq!(
+        login.item_name.as_deref(),
+        Some("login"),
+        "Function name not set as item name."
+    );
+
+    let close = trace_of(&traces, "session.close");
+    assert_eq!(close.line, 17, "Wrong line for comment trace.");
+    assert_eq!(
+        close.ids,
+        vec!["session.close".to_string(), "session.cleanup".to_string()],
+        "Multiple IDs in comment trace not extracted."
+    );
+    assert_eq!(
+        close.line_span,
+        Some(LineSpan { start: 19, end: 20 }),
+        "Decorated method span not attached to comment trace."
+    );
+    assert_eq!(
+        close.item_name.as_deref(),
+        Some("close"),
+        "Method name not set as item name."
+    );
+
+    let test = trace_of(&traces, "login.test");
+    assert_eq!(
+        test.line, 33,
+        "Wrong line for comment trace in function body."
+    );
+    assert_eq!(
+        test.line_span, None,
+        "Span set for comment trace without following definition."
+    );
+}
+
+#[test]
+fn decorator_traces() {
+    let traces = collect_fixture();
+
+    let open = trace_of(&traces, "session.open");
+    assert_eq!(open.line, 9, "Wrong line for decorator trace.");
+    assert_eq!(
+        open.line_span,
+        Some(LineSpan { start: 11, end: 12 }),
+        "Function span not attached to decorator trace."
+    );
+    assert_eq!(
+        open.item_name.as_deref(),
+        Some("open_session"),
+        "Function name not set as item name."
+    );
+
+    let refresh = trace_of(&traces, "session.refresh");
+    assert_eq!(
+        refresh.ids,
+        vec!["session.refresh".to_string(), "session.extend".to_string()],
+        "Multiple IDs of qualified decorator not extracted."
+    );
+    assert_eq!(
+        refresh.line_span,
+        Some(LineSpan { start: 23, end: 24 }),
+        "Method span not attached to decorator trace."
+    );
+
+    let audit = trace_of(&traces, "audit.log");
+    assert_eq!(audit.line, 27, "Wrong line for decorator trace.");
+    assert_eq!(
+        audit.item_name.as_deref(),
+        Some("AuditLog"),
+        "Class name not set as item name."
+    );
+    assert_eq!(traces.len(), 6, "Wrong number of traces in fixture.");
+}
@@ -40,15 +40,15 @@ pub type MyType = bool;
 
 #[req(on_enum)]
 pub enum MyEnum {
-    /// [req(variant_req)]
-    /// Attribute macros cannot be set for variants directly.
+    /// `req` on variants is handled by the `req` macro of the enum.
+    #[req(variant_req)]
     First,
 }
 
 #[req(123)]
 pub struct Test {
-    /// [req(field_req)]
-    /// Attribute macros cannot be set for fields directly.
+    /// `req` on fields is handled by the `req` macro of the struct.
+    #[req(field_req)]
     pub my_field: bool,
 }
 
@@ -65,7 +65,7 @@ pub fn extract_covered_reqs(content: &[u8]) -> Option<Vec<CoveredReq>> {
 mod test {
     use std::path::PathBuf;
 
-    use crate::ReqCovStatic;
+    use crate::{ReqCovDynamic, ReqCovStatic};
 
     use super::*;
 
@@ -92,4 +92,25 @@ mod test {
             "Extracted line number differs from original."
         );
     }
+
+    #[test]
+    fn extract_dynamic_req() {
+        let id = format!("{}.{}", "parent", 2);
+        let file = file!();
+        let line = line!();
+
+        let displayed_req_cov = ReqCovDynamic {
+            id: &id,
+            file,
+            line,
+        }
+        .to_string();
+
+        let extracted_req = extract_first_coverage(&displayed_req_cov).unwrap();
+
+        assert_eq!(
+            extracted_req.id, id,
+            "Extracted runtime ID differs from original."
+        );
+    }
 }
@@ -21,6 +21,39 @@ pub fn req_print(req: ReqCovStatic) {
 
     #[cfg(feature = "stdout")]
     println!("{}", req);
+
+    #[cfg(feature = "otel")]
+    otel_event(req.id, req.file, req.line);
+}
+
+#[inline]
+#[allow(unused)]
+pub fn req_print_dyn(req: ReqCovDynamic<'_>) {
+    #[cfg(feature = "log")]
+    log::trace!("{}", req);
+
+    #[cfg(feature = "defmt")]
+    defmt::println!("{}", req);
+
+    #[cfg(feature = "stdout")]
+    println!("{}", req);
+
+    #[cfg(feature = "otel")]
+    otel_event(req.id, req.file, req.line);
+}
+
+/// Records the coverage as `tracing` event named `mantra.req_cov`.
+/// With a `tracing-opentelemetry` layer, the event becomes a span event of the current span.
+#[cfg(feature = "otel")]
+#[inline]
+fn otel_event(req_id: &str, file: &'static str, line: Line) {
+    tracing::event!(
+        name: "mantra.req_cov",
+        tracing::Level::TRACE,
+        req_id = req_id,
+        file = file,
+        line = line
+    );
 }
 
 #[macro_export]
@@ -32,6 +65,28 @@ macro_rules! mr_reqcov {
     };
 }
 
+/// Creates coverage logs for requirement IDs that are only known at runtime.
+///
+/// Each argument must implement `AsRef<str>`, and is taken as one requirement ID.
+/// In contrast to `reqcov`, no requirement trace is created, because IDs cannot be resolved by static analysis.
+///
+/// **Example:**
+///
+/// ```
+/// use mantra_rust_macros::reqcov_dyn;
+///
+/// let variant = "fast";
+/// reqcov_dyn!(format!("mode.{variant}"));
+/// ```
+#[macro_export]
+macro_rules! reqcov_dyn {
+    ($($req_id:expr),+ $(,)?) => {
+        $(
+            $crate::req_print_dyn($crate::ReqCovDynamic{id: ::core::convert::AsRef::<str>::as_ref(&$req_id), file: file!(), line: line!()});
+        )+
+    };
+}
+
 #[doc(hidden)]
 pub struct ReqCovStatic {
     pub id: &'static str,
@@ -61,3 +116,33 @@ impl core::fmt::Display for ReqCovStatic {
         )
     }
 }
+
+#[doc(hidden)]
+pub struct ReqCovDynamic<'a> {
+    pub id: &'a str,
+    pub file: &'static str,
+    pub line: Line,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ReqCovDynamic<'_> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "mantra: req-id=`{=str}`; file='{=str}'; line='{}';",
+            self.id,
+            self.file,
+            self.line
+        )
+    }
+}
+
+impl core::fmt::Display for ReqCovDynamic<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "mantra: req-id=`{}`; file='{}'; line='{}';",
+            self.id, self.file, self.line
+        )
+    }
+}
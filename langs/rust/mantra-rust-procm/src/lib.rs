@@ -1,21 +1,48 @@
+use std::sync::OnceLock;
+
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse_quote, Stmt};
 
-#[proc_macro_attribute]
-pub fn req(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let mut req_ids = mantra_lang_tracing::extract::extract_req_ids(attr.into())
-        .map_err(|err| panic!("{err}"))
-        .unwrap();
+/// Returns the base URL used to link requirement IDs in the generated documentation.
+///
+/// The environment variable `MANTRA_REQUIREMENT_BASE_URL` takes precedence
+/// over `requirement-base-url` set in `[package.metadata.mantra]` of the crate's `Cargo.toml`.
+/// The URL is only read once per compiled crate, and reused for all `#[req]` expansions.
+fn requirement_base_url() -> &'static Option<String> {
+    static BASE_URL: OnceLock<Option<String>> = OnceLock::new();
+    BASE_URL.get_or_init(read_requirement_base_url)
+}
+
+fn read_requirement_base_url() -> Option<String> {
+    if let Ok(url) = std::env::var("MANTRA_REQUIREMENT_BASE_URL") {
+        return Some(url);
+    }
+
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").ok()?;
+    let content =
+        std::fs::read_to_string(std::path::Path::new(&manifest_dir).join("Cargo.toml")).ok()?;
+    let manifest: toml::Table = content.parse().ok()?;
 
+    manifest
+        .get("package")?
+        .get("metadata")?
+        .get("mantra")?
+        .get("requirement-base-url")?
+        .as_str()
+        .map(|url| url.to_string())
+}
+
+/// Creates the `# Requirements` documentation section for the given requirement IDs.
+fn req_doc_attrbs(req_ids: &[String], base_url: &Option<String>) -> Vec<syn::Attribute> {
     let mut attrbs: Vec<syn::Attribute> = vec![parse_quote!(#[doc = "# Requirements"])];
 
-    for req in &req_ids {
+    for req in req_ids {
         let req_literal = syn::LitStr::new(req, proc_macro2::Span::call_site());
         let attrb: syn::Attribute;
 
-        if let Ok(url) = std::env::var("MANTRA_REQUIREMENT_BASE_URL") {
-            let url_literal = syn::LitStr::new(&url, proc_macro2::Span::call_site());
+        if let Some(url) = base_url {
+            let url_literal = syn::LitStr::new(url, proc_macro2::Span::call_site());
             attrb = parse_quote!(#[doc = concat!("- [", #req_literal, "](", #url_literal, #req_literal, ")")]);
         } else {
             attrb = parse_quote!(#[doc = concat!("- ", #req_literal)]);
@@ -23,6 +50,54 @@ pub fn req(attr: TokenStream, item: TokenStream) -> TokenStream {
         attrbs.push(attrb);
     }
 
+    attrbs
+}
+
+/// Replaces `#[req(...)]` attributes of struct fields and enum variants with their requirement documentation.
+///
+/// Attribute macros cannot be set on fields or variants directly,
+/// so these attributes are only valid if the enclosing item is annotated with `#[req(...)]`.
+/// Fields and variants have no body, so no coverage statements are added.
+fn expand_inner_reqs(attrs: &mut Vec<syn::Attribute>, base_url: &Option<String>) {
+    let mut expanded = Vec::with_capacity(attrs.len());
+
+    for attr in attrs.drain(..) {
+        let is_req = attr
+            .path()
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "req");
+
+        if is_req {
+            let tokens = attr
+                .meta
+                .require_list()
+                .map_err(|err| panic!("{err}"))
+                .unwrap()
+                .tokens
+                .clone();
+            let req_ids = mantra_lang_tracing::extract::extract_req_ids(tokens)
+                .map_err(|err| panic!("{err}"))
+                .unwrap();
+
+            expanded.append(&mut req_doc_attrbs(&req_ids, base_url));
+        } else {
+            expanded.push(attr);
+        }
+    }
+
+    *attrs = expanded;
+}
+
+#[proc_macro_attribute]
+pub fn req(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut req_ids = mantra_lang_tracing::extract::extract_req_ids(attr.into())
+        .map_err(|err| panic!("{err}"))
+        .unwrap();
+
+    let base_url = requirement_base_url();
+    let mut attrbs = req_doc_attrbs(&req_ids, base_url);
+
     if let Ok(parsed_item) = syn::parse::<syn::Item>(item) {
         match parsed_item {
             syn::Item::Const(mut const_item) => {
@@ -30,6 +105,14 @@ pub fn req(attr: TokenStream, item: TokenStream) -> TokenStream {
                 quote!(#const_item).into()
             }
             syn::Item::Enum(mut enum_item) => {
+                for variant in enum_item.variants.iter_mut() {
+                    expand_inner_reqs(&mut variant.attrs, base_url);
+
+                    for field in variant.fields.iter_mut() {
+                        expand_inner_reqs(&mut field.attrs, base_url);
+                    }
+                }
+
                 enum_item.attrs.append(&mut attrbs);
                 quote!(#enum_item).into()
             }
@@ -74,6 +157,10 @@ pub fn req(attr: TokenStream, item: TokenStream) -> TokenStream {
                 quote!(#static_item).into()
             }
             syn::Item::Struct(mut struct_item) => {
+                for field in struct_item.fields.iter_mut() {
+                    expand_inner_reqs(&mut field.attrs, base_url);
+                }
+
                 struct_item.attrs.append(&mut attrbs);
                 quote!(#struct_item).into()
             }
@@ -90,6 +177,10 @@ pub fn req(attr: TokenStream, item: TokenStream) -> TokenStream {
                 quote!(#type_item).into()
             }
             syn::Item::Union(mut union_item) => {
+                for field in union_item.fields.named.iter_mut() {
+                    expand_inner_reqs(&mut field.attrs, base_url);
+                }
+
                 union_item.attrs.append(&mut attrbs);
                 quote!(#union_item).into()
             }
@@ -3,6 +3,7 @@ use mantra_lang_tracing::{
     lsif_graph::LsifGraph,
     RawTraceEntry,
 };
+use regex::Regex;
 
 pub fn collect_traces_in_rust(
     node: &AstNode,
@@ -12,7 +13,10 @@ pub fn collect_traces_in_rust(
 ) -> Option<Vec<TraceEntry>> {
     let node_kind = node.kind();
 
-    if node_kind == "attribute_item" || node_kind == "macro_invocation" {
+    if node_kind == "attribute_item"
+        || node_kind == "inner_attribute_item"
+        || node_kind == "macro_invocation"
+    {
         let (macro_node, may_span) = if node_kind == "macro_invocation" {
             (*node, false)
         } else {
@@ -24,13 +28,9 @@ pub fn collect_traces_in_rust(
         let macro_content = macro_node.named_child(1)?;
 
         if is_req_macro(ident, src) {
-            let span = if may_span {
-                associated_item_span(*node)
-            } else {
-                None
-            };
+            let span = if may_span { item_span(*node) } else { None };
 
-            return Some(vec![TraceEntry::try_from(RawTraceEntry::new(
+            let mut entry = TraceEntry::try_from(RawTraceEntry::new(
                 macro_content
                     .utf8_text(src)
                     .ok()?
@@ -38,17 +38,19 @@ pub fn collect_traces_in_rust(
                     .and_then(|s| s.strip_suffix(')'))?,
                 ident.start_position().row + 1,
                 span,
-                get_ident(filepath, span, lsif_graphs.as_deref()),
+                get_ident(filepath, span, lsif_graphs.as_deref()).or_else(|| item_name(*node, src)),
             ))
-            .ok()?]);
+            .ok()?;
+
+            if node_kind == "attribute_item" {
+                entry.test_name = test_fn_name(*node, src);
+            }
+
+            return Some(vec![entry]);
         } else if ident.kind() == "identifier" && ident.utf8_text(src) == Ok("cfg_attrb") {
             let mut traces = Vec::new();
 
-            let span = if may_span {
-                associated_item_span(*node)
-            } else {
-                None
-            };
+            let span = if may_span { item_span(*node) } else { None };
             let start_line = ident.start_position().row + 1;
 
             for child in macro_content.named_children(&mut macro_content.walk()) {
@@ -60,12 +62,17 @@ pub fn collect_traces_in_rust(
                         .ok()?
                         .strip_prefix('(')
                         .and_then(|s| s.strip_suffix(')'))?;
-                    if let Ok(entry) = TraceEntry::try_from(RawTraceEntry::new(
+                    if let Ok(mut entry) = TraceEntry::try_from(RawTraceEntry::new(
                         ids,
                         start_line,
                         span,
-                        get_ident(filepath, span, lsif_graphs.as_deref()),
+                        get_ident(filepath, span, lsif_graphs.as_deref())
+                            .or_else(|| item_name(*node, src)),
                     )) {
+                        if node_kind == "attribute_item" {
+                            entry.test_name = test_fn_name(*node, src);
+                        }
+
                         traces.push(entry);
                     }
                 }
@@ -84,21 +91,58 @@ pub fn collect_traces_in_rust(
         let captures: Vec<_> = trace_matcher.captures_iter(comment_content).collect();
 
         if !captures.is_empty() {
-            let span = associated_item_span(*node);
+            let span = item_span(*node);
+            let test_name = if is_inner_doc_comment(node) {
+                None
+            } else {
+                test_fn_name(*node, src)
+            };
 
             let mut traces = Vec::new();
             for capture in captures {
+                let mut entry = TraceEntry::try_from(RawTraceEntry::new(
+                    capture.name("ids")?.as_str(),
+                    node.start_position().row + 1,
+                    span,
+                    get_ident(filepath, span, lsif_graphs.as_deref())
+                        .or_else(|| item_name(*node, src)),
+                ))
+                .ok()?;
+                entry.test_name = test_name.clone();
+
+                traces.push(entry);
+            }
+
+            return Some(traces);
+        }
+    } else if node_kind == "line_comment" {
+        // plain comments only trace if they justify an attribute like `#[allow(...)]`
+        let attribute = node
+            .next_named_sibling()
+            .filter(|sibling| sibling.kind() == "attribute_item")?;
+        let comment_content = node.utf8_text(src).ok()?;
+        let span = attribute_target(attribute).and_then(node_span);
+
+        let mut traces = Vec::new();
+        for matcher in [
+            attribute_comment_trace_matcher(),
+            mantra_lang_tracing::extract::req_trace_matcher(),
+        ] {
+            for capture in matcher.captures_iter(comment_content) {
                 traces.push(
                     TraceEntry::try_from(RawTraceEntry::new(
                         capture.name("ids")?.as_str(),
                         node.start_position().row + 1,
                         span,
-                        get_ident(filepath, span, lsif_graphs.as_deref()),
+                        get_ident(filepath, span, lsif_graphs.as_deref())
+                            .or_else(|| item_name(attribute, src)),
                     ))
                     .ok()?,
-                )
+                );
             }
+        }
 
+        if !traces.is_empty() {
             return Some(traces);
         }
     }
@@ -106,6 +150,30 @@ pub fn collect_traces_in_rust(
     None
 }
 
+static ATTRIBUTE_COMMENT_TRACE_MATCHER: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+
+/// Matches traces of the form `[req:<requirement id(s)>]`.
+fn attribute_comment_trace_matcher() -> &'static Regex {
+    ATTRIBUTE_COMMENT_TRACE_MATCHER.get_or_init(|| {
+        Regex::new(r"\[req:\s*(?<ids>[^\]]+)\]")
+            .expect("Regex to match a requirement trace in comments could **not** be created.")
+    })
+}
+
+/// Returns the item, statement, or expression the given attribute is set on.
+/// Other attributes and comments between the attribute and its target are skipped.
+fn attribute_target(mut node: AstNode) -> Option<AstNode> {
+    while let Some(sibling) = node.next_named_sibling() {
+        if sibling.kind() != "attribute_item" && !sibling.kind().ends_with("comment") {
+            return Some(sibling);
+        }
+
+        node = sibling;
+    }
+
+    None
+}
+
 fn get_ident(
     filepath: &str,
     span: Option<LineSpan>,
@@ -117,7 +185,7 @@ fn get_ident(
     for graph in graphs {
         if graph.contains_doc(filepath) {
             // "-1" because lsif starts with line nr 0
-            if let Some(ident) = graph.get_identifier(filepath, start_line - 1) {
+            if let Some(ident) = graph.get_definition_identifier(filepath, start_line - 1) {
                 return Some(ident);
             }
         }
@@ -126,18 +194,42 @@ fn get_ident(
     None
 }
 
-fn associated_item_span(mut node: AstNode) -> Option<LineSpan> {
+/// Returns the span of the item the attribute or doc-comment belongs to.
+///
+/// Inner attributes and inner doc-comments (e.g. `#![req(id)]` or `//! [req(id)]`)
+/// belong to the enclosing item, like a module or function.
+fn item_span(node: AstNode) -> Option<LineSpan> {
+    traced_item(node).and_then(node_span)
+}
+
+fn traced_item(node: AstNode) -> Option<AstNode> {
+    if node.kind() == "inner_attribute_item" || is_inner_doc_comment(&node) {
+        enclosing_item(node)
+    } else {
+        associated_item(node)
+    }
+}
+
+fn enclosing_item(node: AstNode) -> Option<AstNode> {
+    let body = node
+        .parent()
+        .filter(|parent| matches!(parent.kind(), "declaration_list" | "block"))?;
+
+    body.parent().filter(|item| item.kind().ends_with("_item"))
+}
+
+fn associated_item(mut node: AstNode) -> Option<AstNode> {
     while let Some(sibling) = node.next_named_sibling() {
         let sibling_kind = sibling.kind();
 
         if (sibling_kind.ends_with("_item") && sibling_kind != "attribute_item")
             || sibling_kind == "field_declaration"
             || sibling_kind == "enum_variant"
+            || is_expression_item(sibling_kind)
         {
-            let start = Line::try_from(sibling.start_position().row + 1).ok()?;
-            let end = Line::try_from(sibling.end_position().row + 1).ok()?;
-
-            return Some(LineSpan { start, end });
+            return Some(sibling);
+        } else if sibling_kind == "let_declaration" || sibling_kind == "expression_statement" {
+            return expression_item(sibling);
         } else if sibling_kind.ends_with("comment") && !is_doc_comment(&sibling) {
             return None;
         }
@@ -148,6 +240,118 @@ fn associated_item_span(mut node: AstNode) -> Option<LineSpan> {
     None
 }
 
+/// Returns the identifier of the nearest named item the trace is set in or on.
+/// Used as item name if no identifier could be resolved using LSIF.
+///
+/// Starts at the traced item, or at the trace itself for traces without one (e.g. `reqcov!()`),
+/// and walks up the tree until a named function, impl, struct, or similar item is found.
+fn item_name(node: AstNode, src: &[u8]) -> Option<String> {
+    let mut current = traced_item(node).or(node.parent());
+
+    while let Some(item) = current {
+        let name = match item.kind() {
+            "impl_item" => item.child_by_field_name("type"),
+            "function_item"
+            | "function_signature_item"
+            | "struct_item"
+            | "enum_item"
+            | "union_item"
+            | "trait_item"
+            | "mod_item"
+            | "const_item"
+            | "static_item"
+            | "type_item"
+            | "macro_definition"
+            | "field_declaration"
+            | "enum_variant" => item.child_by_field_name("name"),
+            _ => None,
+        };
+
+        if let Some(name) = name.and_then(|name| name.utf8_text(src).ok()) {
+            return Some(name.to_string());
+        }
+
+        current = item.parent();
+    }
+
+    None
+}
+
+/// Returns the name of the function the attribute or doc-comment belongs to,
+/// if the function is marked as test (e.g. `#[test]` or `#[tokio::test]`).
+fn test_fn_name(node: AstNode, src: &[u8]) -> Option<String> {
+    let is_attribute_or_comment =
+        |n: &AstNode| n.kind() == "attribute_item" || n.kind().ends_with("comment");
+    let mut is_test = false;
+
+    let mut prev = node.prev_named_sibling();
+    while let Some(sibling) = prev.filter(is_attribute_or_comment) {
+        is_test |= is_test_attribute(sibling, src);
+        prev = sibling.prev_named_sibling();
+    }
+
+    let mut next = node.next_named_sibling();
+    while let Some(sibling) = next {
+        if sibling.kind() == "function_item" {
+            return is_test.then(|| {
+                sibling
+                    .child_by_field_name("name")
+                    .and_then(|name| name.utf8_text(src).ok())
+                    .map(str::to_string)
+            })?;
+        } else if !is_attribute_or_comment(&sibling) {
+            return None;
+        }
+
+        is_test |= is_test_attribute(sibling, src);
+        next = sibling.next_named_sibling();
+    }
+
+    None
+}
+
+fn is_test_attribute(node: AstNode, src: &[u8]) -> bool {
+    if node.kind() != "attribute_item" {
+        return false;
+    }
+
+    let Some(ident) = node.named_child(0).and_then(|attr| attr.named_child(0)) else {
+        return false;
+    };
+
+    match ident.kind() {
+        "identifier" => ident.utf8_text(src) == Ok("test"),
+        "scoped_identifier" => ident
+            .child_by_field_name("name")
+            .is_some_and(|name| name.utf8_text(src) == Ok("test")),
+        _ => false,
+    }
+}
+
+fn node_span(node: AstNode) -> Option<LineSpan> {
+    let start = Line::try_from(node.start_position().row + 1).ok()?;
+    let end = Line::try_from(node.end_position().row + 1).ok()?;
+
+    Some(LineSpan { start, end })
+}
+
+/// Returns the closure, async block, or const block
+/// that is bound or evaluated by the given statement.
+fn expression_item(statement: AstNode) -> Option<AstNode> {
+    let expr = if statement.kind() == "let_declaration" {
+        statement.child_by_field_name("value")?
+    } else {
+        statement.named_child(0)?
+    };
+
+    is_expression_item(expr.kind()).then_some(expr)
+}
+
+/// Expressions that have their own body, and are therefore traced like items.
+fn is_expression_item(kind: &str) -> bool {
+    matches!(kind, "closure_expression" | "async_block" | "const_block")
+}
+
 fn is_doc_comment(node: &AstNode) -> bool {
     if let Some(doc_node) = node.named_child(1) {
         doc_node.kind() == "doc_comment"
@@ -156,15 +360,22 @@ fn is_doc_comment(node: &AstNode) -> bool {
     }
 }
 
+fn is_inner_doc_comment(node: &AstNode) -> bool {
+    is_doc_comment(node)
+        && node
+            .named_child(0)
+            .is_some_and(|marker| marker.kind() == "inner_doc_comment_marker")
+}
+
 fn is_req_macro(node: AstNode, src: &[u8]) -> bool {
-    ((node.kind() == "identifier" && node.utf8_text(src).map_or(false, is_req_ident))
+    ((node.kind() == "identifier" && node.utf8_text(src).is_ok_and(is_req_ident))
         || (node.kind() == "scoped_identifier"
             && node
                 .named_child(1)
-                .map_or(false, |n| n.utf8_text(src).map_or(false, is_req_ident))))
+                .is_some_and(|n| n.utf8_text(src).is_ok_and(is_req_ident))))
         && node
             .next_named_sibling()
-            .map_or(false, |n| n.kind() == "token_tree")
+            .is_some_and(|n| n.kind() == "token_tree")
 }
 
 fn is_req_ident(ident: &str) -> bool {
@@ -0,0 +1,49 @@
+use mantra_lang_tracing::collect::{AstCollector, LineSpan, TraceCollector, TraceEntry};
+
+fn collect_fixture(src: &str) -> Vec<TraceEntry> {
+    let mut collector = AstCollector::new(
+        src.as_bytes(),
+        &tree_sitter_rust::language(),
+        "fixture.rs".to_string(),
+        Box::new(mantra_rust_trace::collect_traces_in_rust),
+    )
+    .expect("Fixture is valid Rust code.");
+
+    collector.collect(&None).expect("Traces found in fixture.")
+}
+
+fn trace_of<'a>(traces: &'a [TraceEntry], id: &str) -> Option<&'a TraceEntry> {
+    traces
+        .iter()
+        .find(|trace| trace.ids.iter().any(|trace_id| trace_id == id))
+}
+
+#[test]
+fn comments_above_lint_attributes() {
+    let traces = collect_fixture(include_str!("fixtures/lint_allow.rs"));
+
+    let fn_trace = trace_of(&traces, "lint.fn").expect("Comment above `#[allow]` not traced.");
+    assert_eq!(fn_trace.line, 1, "Trace not set at the comment.");
+    assert_eq!(
+        fn_trace.line_span,
+        Some(LineSpan { start: 3, end: 9 }),
+        "Span of the allowed function not captured."
+    );
+    assert_eq!(
+        fn_trace.item_name.as_deref(),
+        Some("many_args"),
+        "Name of the allowed function not captured."
+    );
+
+    let stmt_trace = trace_of(&traces, "lint.stmt").expect("Comment above `#[allow]` not traced.");
+    assert_eq!(
+        stmt_trace.line_span,
+        Some(LineSpan { start: 6, end: 6 }),
+        "Span of the allowed statement not captured."
+    );
+
+    assert!(
+        trace_of(&traces, "lint.ignored").is_none(),
+        "Comment without attribute traced."
+    );
+}
@@ -0,0 +1,59 @@
+use mantra_lang_tracing::collect::{AstCollector, LineSpan, TraceCollector, TraceEntry};
+
+fn collect_fixture(src: &str) -> Vec<TraceEntry> {
+    let mut collector = AstCollector::new(
+        src.as_bytes(),
+        &tree_sitter_rust::language(),
+        "fixture.rs".to_string(),
+        Box::new(mantra_rust_trace::collect_traces_in_rust),
+    )
+    .expect("Fixture is valid Rust code.");
+
+    collector.collect(&None).expect("Traces found in fixture.")
+}
+
+fn span_of<'a>(traces: &'a [TraceEntry], id: &str) -> Option<&'a LineSpan> {
+    traces
+        .iter()
+        .find(|trace| trace.ids.iter().any(|trace_id| trace_id == id))
+        .expect("Trace with ID exists in fixture.")
+        .line_span
+        .as_ref()
+}
+
+#[test]
+fn closure_span() {
+    let traces = collect_fixture(include_str!("fixtures/closure.rs"));
+
+    assert_eq!(
+        span_of(&traces, "closure"),
+        Some(&LineSpan { start: 3, end: 6 }),
+        "Span of closure bound by `let` not captured."
+    );
+    assert_eq!(
+        span_of(&traces, "closure.arg"),
+        Some(&LineSpan { start: 16, end: 18 }),
+        "Span of closure argument not captured."
+    );
+}
+
+#[test]
+fn async_block_span() {
+    let traces = collect_fixture(include_str!("fixtures/async_block.rs"));
+
+    assert_eq!(
+        span_of(&traces, "async_block"),
+        Some(&LineSpan { start: 5, end: 8 }),
+        "Span of async block not captured."
+    );
+    assert_eq!(
+        span_of(&traces, "const_block"),
+        Some(&LineSpan { start: 11, end: 13 }),
+        "Span of const block not captured."
+    );
+    assert_eq!(
+        span_of(&traces, "no_span"),
+        None,
+        "Span set for `let` without expression item."
+    );
+}
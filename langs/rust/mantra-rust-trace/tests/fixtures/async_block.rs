@@ -0,0 +1,17 @@
+fn spawn_work() {
+    /// Doc comments are also supported for expression items.
+    ///
+    /// [req(async_block)]
+    let work = async {
+        let value = 1;
+        value + 1
+    };
+
+    #[req(const_block)]
+    const {
+        assert!(true);
+    };
+
+    #[req(no_span)]
+    let value = 5;
+}
@@ -0,0 +1,21 @@
+fn sum(values: &[u32]) -> u32 {
+    #[req(closure)]
+    let add = |acc: u32, value: &u32| {
+        let next = acc + value;
+        next
+    };
+
+    values.iter().fold(0, add)
+}
+
+fn doubled(values: &[u32]) -> Vec<u32> {
+    values
+        .iter()
+        .map(
+            #[req(closure.arg)]
+            |value| {
+                value * 2
+            },
+        )
+        .collect()
+}
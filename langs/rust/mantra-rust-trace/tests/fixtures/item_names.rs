@@ -0,0 +1,20 @@
+#[req(named.fn)]
+fn traced_fn() {
+    reqcov!(named.cov);
+
+    let _add_one = |value: u32| {
+        reqcov!(named.closure);
+        value + 1
+    };
+}
+
+struct Traced {
+    /// [req(named.field)]
+    field: bool,
+}
+
+impl Traced {
+    pub fn method(&self) {
+        reqcov!(named.method);
+    }
+}
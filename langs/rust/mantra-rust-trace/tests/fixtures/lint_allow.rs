@@ -0,0 +1,12 @@
+// [req:lint.fn]
+#[allow(clippy::too_many_arguments)]
+fn many_args(a: u8, b: u8, c: u8, d: u8, e: u8, f: u8, g: u8, h: u8) -> u8 {
+    // Overflow is intended here [req(lint.stmt)]
+    #[allow(arithmetic_overflow)]
+    let sum = a + b + c + d + e + f + g + h;
+
+    sum
+}
+
+// [req:lint.ignored] comments not above an attribute are not traces
+fn untraced() {}
@@ -0,0 +1,21 @@
+#[req(module)]
+mod traced {
+    pub fn covered() -> u32 {
+        1
+    }
+
+    mod nested {
+        #![req(module.nested)]
+
+        pub fn inner() -> u32 {
+            2
+        }
+    }
+}
+
+mod documented {
+    //! Module traced by [req(module.doc)].
+
+    /// [req(module.item)]
+    pub fn item() {}
+}
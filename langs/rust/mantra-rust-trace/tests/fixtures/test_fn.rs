@@ -0,0 +1,14 @@
+#[test]
+#[req(test_attr_before)]
+fn attr_before() {}
+
+#[req(test_attr_after)]
+#[tokio::test]
+async fn attr_after() {}
+
+/// Covers [req(test_doc_comment)]
+#[test]
+fn doc_comment() {}
+
+#[req(no_test)]
+fn no_test() {}
@@ -0,0 +1,53 @@
+use mantra_lang_tracing::collect::{AstCollector, TraceCollector, TraceEntry};
+
+fn collect_fixture(src: &str) -> Vec<TraceEntry> {
+    let mut collector = AstCollector::new(
+        src.as_bytes(),
+        &tree_sitter_rust::language(),
+        "fixture.rs".to_string(),
+        Box::new(mantra_rust_trace::collect_traces_in_rust),
+    )
+    .expect("Fixture is valid Rust code.");
+
+    collector.collect(&None).expect("Traces found in fixture.")
+}
+
+fn item_name_of<'a>(traces: &'a [TraceEntry], id: &str) -> Option<&'a str> {
+    traces
+        .iter()
+        .find(|trace| trace.ids.iter().any(|trace_id| trace_id == id))
+        .expect("Trace with ID exists in fixture.")
+        .item_name
+        .as_deref()
+}
+
+#[test]
+fn item_names_without_lsif() {
+    let traces = collect_fixture(include_str!("fixtures/item_names.rs"));
+
+    assert_eq!(
+        item_name_of(&traces, "named.fn"),
+        Some("traced_fn"),
+        "Name of traced function not captured."
+    );
+    assert_eq!(
+        item_name_of(&traces, "named.cov"),
+        Some("traced_fn"),
+        "Name of enclosing function not captured."
+    );
+    assert_eq!(
+        item_name_of(&traces, "named.closure"),
+        Some("traced_fn"),
+        "Closure not attributed to the enclosing function."
+    );
+    assert_eq!(
+        item_name_of(&traces, "named.field"),
+        Some("field"),
+        "Name of traced field not captured."
+    );
+    assert_eq!(
+        item_name_of(&traces, "named.method"),
+        Some("method"),
+        "Name of enclosing method not captured."
+    );
+}
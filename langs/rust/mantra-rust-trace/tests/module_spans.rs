@@ -0,0 +1,48 @@
+use mantra_lang_tracing::collect::{AstCollector, LineSpan, TraceCollector, TraceEntry};
+
+fn collect_fixture(src: &str) -> Vec<TraceEntry> {
+    let mut collector = AstCollector::new(
+        src.as_bytes(),
+        &tree_sitter_rust::language(),
+        "fixture.rs".to_string(),
+        Box::new(mantra_rust_trace::collect_traces_in_rust),
+    )
+    .expect("Fixture is valid Rust code.");
+
+    collector.collect(&None).expect("Traces found in fixture.")
+}
+
+fn span_of<'a>(traces: &'a [TraceEntry], id: &str) -> Option<&'a LineSpan> {
+    traces
+        .iter()
+        .find(|trace| trace.ids.iter().any(|trace_id| trace_id == id))
+        .expect("Trace with ID exists in fixture.")
+        .line_span
+        .as_ref()
+}
+
+#[test]
+fn module_spans() {
+    let traces = collect_fixture(include_str!("fixtures/module.rs"));
+
+    assert_eq!(
+        span_of(&traces, "module"),
+        Some(&LineSpan { start: 2, end: 14 }),
+        "Span of traced module does not cover its items."
+    );
+    assert_eq!(
+        span_of(&traces, "module.nested"),
+        Some(&LineSpan { start: 7, end: 13 }),
+        "Inner attribute not attributed to the enclosing module."
+    );
+    assert_eq!(
+        span_of(&traces, "module.doc"),
+        Some(&LineSpan { start: 16, end: 21 }),
+        "Inner doc-comment not attributed to the enclosing module."
+    );
+    assert_eq!(
+        span_of(&traces, "module.item"),
+        Some(&LineSpan { start: 20, end: 20 }),
+        "Span of item inside module not captured."
+    );
+}
@@ -0,0 +1,48 @@
+use mantra_lang_tracing::collect::{AstCollector, TraceCollector, TraceEntry};
+
+fn collect_fixture(src: &str) -> Vec<TraceEntry> {
+    let mut collector = AstCollector::new(
+        src.as_bytes(),
+        &tree_sitter_rust::language(),
+        "fixture.rs".to_string(),
+        Box::new(mantra_rust_trace::collect_traces_in_rust),
+    )
+    .expect("Fixture is valid Rust code.");
+
+    collector.collect(&None).expect("Traces found in fixture.")
+}
+
+fn test_name_of<'a>(traces: &'a [TraceEntry], id: &str) -> Option<&'a str> {
+    traces
+        .iter()
+        .find(|trace| trace.ids.iter().any(|trace_id| trace_id == id))
+        .expect("Trace with ID exists in fixture.")
+        .test_name
+        .as_deref()
+}
+
+#[test]
+fn traces_on_test_fns() {
+    let traces = collect_fixture(include_str!("fixtures/test_fn.rs"));
+
+    assert_eq!(
+        test_name_of(&traces, "test_attr_before"),
+        Some("attr_before"),
+        "Test attribute before trace not detected."
+    );
+    assert_eq!(
+        test_name_of(&traces, "test_attr_after"),
+        Some("attr_after"),
+        "Scoped test attribute after trace not detected."
+    );
+    assert_eq!(
+        test_name_of(&traces, "test_doc_comment"),
+        Some("doc_comment"),
+        "Trace in doc-comment of test not detected."
+    );
+    assert_eq!(
+        test_name_of(&traces, "no_test"),
+        None,
+        "Test name set for function without test attribute."
+    );
+}
@@ -0,0 +1,91 @@
+use mantra_lang_tracing::{
+    collect::{comment_traces, node_span, AstNode, TraceEntry},
+    extract::{colon_trace_matcher, req_trace_matcher},
+    RawTraceEntry,
+};
+
+pub fn collect_traces_in_swift(
+    node: &AstNode,
+    src: &[u8],
+    _filepath: &str,
+    _collect_arg: &(),
+) -> Option<Vec<TraceEntry>> {
+    let node_kind = node.kind();
+
+    if node_kind == "attribute" {
+        let ident = node.named_child(0)?;
+
+        if ident.utf8_text(src).ok()? != "Req" {
+            return None;
+        }
+
+        // attributes are part of the modifiers of a declaration
+        let declaration = node
+            .parent()
+            .filter(|modifiers| modifiers.kind() == "modifiers")
+            .and_then(|modifiers| modifiers.parent());
+        let span = declaration.and_then(node_span);
+        let item_name = declaration.and_then(|decl| item_name(decl, src));
+
+        let mut ids = Vec::new();
+        for arg in node.named_children(&mut node.walk()) {
+            if arg.kind() == "line_string_literal" {
+                ids.push(arg.named_child(0)?.utf8_text(src).ok()?);
+            }
+        }
+
+        if ids.is_empty() {
+            return None;
+        }
+
+        return Some(vec![TraceEntry::try_from(RawTraceEntry::new(
+            &ids.join(","),
+            node.start_position().row + 1,
+            span,
+            item_name,
+        ))
+        .ok()?]);
+    } else if node_kind == "comment" || node_kind == "multiline_comment" {
+        let comment_content = node.utf8_text(src).ok()?;
+        let declaration = associated_declaration(*node);
+        let span = declaration.and_then(node_span);
+        let item_name = declaration.and_then(|decl| item_name(decl, src));
+
+        return comment_traces(
+            node,
+            comment_content,
+            &[colon_trace_matcher(), req_trace_matcher()],
+            span,
+            item_name,
+        );
+    }
+
+    None
+}
+
+/// Returns the declaration following the given comment.
+/// Other comments between the comment and the declaration are skipped.
+fn associated_declaration(mut node: AstNode) -> Option<AstNode> {
+    while let Some(sibling) = node.next_named_sibling() {
+        let sibling_kind = sibling.kind();
+
+        if is_declaration(sibling_kind) {
+            return Some(sibling);
+        } else if sibling_kind != "comment" && sibling_kind != "multiline_comment" {
+            return None;
+        }
+
+        node = sibling;
+    }
+
+    None
+}
+
+fn is_declaration(kind: &str) -> bool {
+    kind.ends_with("_declaration") && kind != "import_declaration"
+}
+
+fn item_name(declaration: AstNode, src: &[u8]) -> Option<String> {
+    let name = declaration.child_by_field_name("name")?;
+    name.utf8_text(src).ok().map(|name| name.to_string())
+}
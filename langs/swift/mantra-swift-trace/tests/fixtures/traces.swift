@@ -0,0 +1,31 @@
+import Foundation
+
+// [req:login]
+func login(user: String) -> Bool {
+    return true
+}
+
+// [req:session, session.timeout]
+class Session {
+    @Req("session.valid")
+    var isValid: Bool {
+        return true
+    }
+
+    @Req("session.close")
+    func close() {
+        print("closed")
+    }
+}
+
+@Req("profile")
+struct Profile {
+    let name: String
+}
+
+/**
+ Settings of the app.
+ [req:settings]
+ */
+struct Settings {
+}
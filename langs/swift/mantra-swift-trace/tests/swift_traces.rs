@@ -0,0 +1,82 @@
+use mantra_lang_tracing::{
+    collect::{LineSpan, TraceEntry},
+    test_util::trace_of,
+};
+
+fn collect_fixture() -> Vec<TraceEntry> {
+    mantra_lang_tracing::test_util::collect_fixture(
+        include_str!("fixtures/traces.swift"),
+        &tree_sitter_swift::language(),
+        "traces.swift",
+        Box::new(mantra_swift_trace::collect_traces_in_swift),
+    )
+}
+
+#[test]
+fn comment_traces() {
+    let traces = collect_fixture();
+
+    let login = trace_of(&traces, "login");
+    assert_eq!(login.line, 3, "Wrong line for comment trace.");
+    assert_eq!(
+        login.line_span,
+        Some(LineSpan { start: 4, end: 6 }),
+        "Function span not attached to comment trace."
+    );
+    assert_eq!(
+        login.item_name.as_deref(),
+        Some("login"),
+        "Function name not set as item name."
+    );
+
+    let session = trace_of(&traces, "session.timeout");
+    assert_eq!(
+        session.ids,
+        vec!["session".to_string(), "session.timeout".to_string()],
+        "Multiple IDs in comment trace not extracted."
+    );
+    assert_eq!(
+        session.line_span,
+        Some(LineSpan { start: 9, end: 19 }),
+        "Class span not attached to comment trace."
+    );
+
+    let settings = trace_of(&traces, "settings");
+    assert_eq!(
+        settings.line, 28,
+        "Line of trace in multi-line comment not taken from the trace itself."
+    );
+    assert_eq!(
+        settings.line_span,
+        Some(LineSpan { start: 30, end: 31 }),
+        "Struct span not attached to multi-line comment trace."
+    );
+}
+
+#[test]
+fn attribute_traces() {
+    let traces = collect_fixture();
+
+    let computed = trace_of(&traces, "session.valid");
+    assert_eq!(computed.line, 10, "Wrong line for attribute trace.");
+    assert_eq!(
+        computed.line_span,
+        Some(LineSpan { start: 10, end: 13 }),
+        "Computed property span not attached to attribute trace."
+    );
+
+    let close = trace_of(&traces, "session.close");
+    assert_eq!(
+        close.line_span,
+        Some(LineSpan { start: 15, end: 18 }),
+        "Function span not attached to attribute trace."
+    );
+
+    let profile = trace_of(&traces, "profile");
+    assert_eq!(
+        profile.line_span,
+        Some(LineSpan { start: 21, end: 24 }),
+        "Struct span not attached to attribute trace."
+    );
+    assert_eq!(traces.len(), 6, "Unexpected number of traces.");
+}
@@ -1,8 +1,11 @@
 use std::path::PathBuf;
 
 use mantra::{
-    cfg::{MantraConfigPath, Project},
-    cmd::report::{ReportFormat, ReportTemplate},
+    cfg::{CollectCliConfig, MantraConfigPath, Project},
+    cmd::{
+        report::{ReportFormat, ReportOptions, ReportTemplate},
+        trace::WalkLimits,
+    },
 };
 
 #[tokio::main]
@@ -14,13 +17,19 @@ async fn main() {
     let _ = std::fs::remove_file("mantra/examples/usage.db");
     let db = mantra::db::Config {
         url: Some("sqlite://mantra/examples/usage.db?mode=rwc".to_string()),
+        connect_attempts: 1,
+        retry_delay_ms: 0,
+        branch: None,
     };
     let mantra_file: PathBuf = "mantra/examples/mantra.toml".into();
 
     let report_cfg = mantra::cfg::Config {
         db: db.clone(),
+        quiet: false,
+        profile: false,
         cmd: mantra::cmd::Cmd::Report(Box::new(mantra::cmd::report::ReportCliConfig {
-            path: PathBuf::from("mantra/examples/mantra_report.html"),
+            path: Some(PathBuf::from("mantra/examples/mantra_report.html")),
+            out_dir: None,
             mantra_config: Some(mantra_file.clone()),
             template: ReportTemplate::default(),
             formats: vec![ReportFormat::Json, ReportFormat::Html],
@@ -29,13 +38,30 @@ async fn main() {
                 name: Some("0.1.0".to_string()),
                 link: Some("https://github.com/mhatzl/mantra-wiki".to_string()),
             },
+            tag_from_git: false,
+            archive: None,
+            options: ReportOptions::default(),
         })),
     };
 
     let collect_cfg = mantra::cfg::Config {
         db,
-        cmd: mantra::cmd::Cmd::Collect(MantraConfigPath {
-            filepath: mantra_file,
+        quiet: false,
+        profile: false,
+        cmd: mantra::cmd::Cmd::Collect(CollectCliConfig {
+            config: MantraConfigPath {
+                filepath: mantra_file,
+            },
+            validate_schema: false,
+            strict: false,
+            walk_limits: WalkLimits::default(),
+            check_evidence: false,
+            skip_missing_coverage: false,
+            wait_coverage: None,
+            min_traced_ratio: None,
+            min_covered_ratio: None,
+            check_design_links: false,
+            resume: false,
         }),
     };
 
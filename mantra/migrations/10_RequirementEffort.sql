@@ -0,0 +1,2 @@
+-- optional effort estimate of requirements (e.g. story points)
+alter table Requirements add column effort real;
@@ -0,0 +1,8 @@
+-- links from requirements to supplementary design documentation (e.g. design documents or architecture decision records)
+-- links are kept in the order they are defined per requirement
+create table DesignLinks (
+    req_id text not null references Requirements(id) on delete cascade,
+    url text not null,
+    title text,
+    primary key (req_id, url)
+);
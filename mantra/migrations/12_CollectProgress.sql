@@ -0,0 +1,13 @@
+-- progress of collect runs, so an interrupted collect may be resumed.
+-- progress is removed once a collect run completes.
+create table CollectProgress (
+    -- step of the collect run (e.g. `requirements`, or `traces[0]` for the first configured trace source)
+    step text not null,
+    branch text not null default '',
+    -- generation of the data added in this step
+    generation integer not null,
+    -- file whose traces were fully stored in this step,
+    -- or an empty path if all data of the step was collected
+    filepath text not null,
+    primary key (step, branch, filepath)
+);
@@ -0,0 +1,14 @@
+-- trace and test status of requirements at the end of collect runs.
+-- only the latest two generations per branch are kept,
+-- so reports may be restricted to requirements whose status changed since the previous collect.
+create table StatusHistory (
+    -- requirement generation of the collect run
+    generation integer not null,
+    branch text not null default '',
+    -- no foreign key, because removed requirements must be kept to detect the removal
+    req_id text not null,
+    traced integer not null,
+    covered integer not null,
+    passed integer not null,
+    primary key (generation, branch, req_id)
+);
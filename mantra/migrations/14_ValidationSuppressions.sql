@@ -0,0 +1,21 @@
+-- validation errors of requirements that are acknowledged as intentional,
+-- e.g. traces of a deprecated requirement that document a migration.
+-- suppressed requirements are not invalid, but are listed separately in reports.
+create table ValidationSuppressions (
+    req_id text not null primary key references Requirements(id) on delete cascade,
+    -- justification why the validation error is acknowledged
+    reason text not null
+);
+
+drop view InvalidRequirements;
+
+create view InvalidRequirements as
+select d.id, t.branch
+from DeprecatedRequirements d, TracedRequirements t
+where d.id = t.id
+    and d.id not in (select req_id from ValidationSuppressions);
+
+create view SuppressedInvalidRequirements as
+select d.id, t.branch, s.reason
+from DeprecatedRequirements d, TracedRequirements t, ValidationSuppressions s
+where d.id = t.id and d.id = s.req_id;
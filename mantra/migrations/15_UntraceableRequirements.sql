@@ -0,0 +1,7 @@
+-- requirements that cannot be traced in code by definition (e.g. organizational requirements).
+-- untraceable requirements are excluded from the traced ratio, and listed separately in reports.
+alter table Requirements add column untraceable bool not null default false;
+
+create view UntraceableRequirements as
+select id from Requirements
+where untraceable = true;
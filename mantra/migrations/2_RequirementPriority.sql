@@ -0,0 +1,3 @@
+-- optional priority of requirements
+-- lower values mean higher priority
+alter table Requirements add column priority integer;
@@ -0,0 +1,536 @@
+-- adds the branch traces and test runs were collected for.
+-- the empty branch is the default branch, and keeps the previous single-branch behavior.
+--
+-- tables with the branch in their primary key are recreated.
+-- child tables are dropped before their parents to prevent cascading deletes.
+create temp table OldTraces as select * from Traces;
+create temp table OldTraceSpans as select * from TraceSpans;
+create temp table OldTracedItems as select * from TracedItems;
+create temp table OldUnrelatedTraces as select * from UnrelatedTraces;
+create temp table OldTestRuns as select * from TestRuns;
+create temp table OldTests as select * from Tests;
+create temp table OldSkippedTests as select * from SkippedTests;
+create temp table OldTestCoverage as select * from TestCoverage;
+create temp table OldUnrelatedTestCoverage as select * from UnrelatedTestCoverage;
+
+drop table TestCoverage;
+drop table UnrelatedTestCoverage;
+drop table TraceSpans;
+drop table SkippedTests;
+drop table Tests;
+drop table TestRuns;
+drop table Traces;
+drop table TracedItems;
+drop table UnrelatedTraces;
+
+create table Traces (
+    req_id text not null references Requirements(id) on delete cascade,
+    branch text not null default '',
+    generation integer not null,
+    filepath text not null,
+    line integer not null,
+    primary key (req_id, branch, filepath, line)
+);
+insert into Traces (req_id, generation, filepath, line)
+select req_id, generation, filepath, line from OldTraces;
+drop table OldTraces;
+
+create table TraceSpans (
+    req_id text not null,
+    branch text not null default '',
+    filepath text not null,
+    line integer not null,
+    start integer not null,
+    end integer not null,
+    primary key (req_id, branch, filepath, line),
+    foreign key (req_id, branch, filepath, line) references Traces(req_id, branch, filepath, line) on delete cascade
+);
+insert into TraceSpans (req_id, filepath, line, start, end)
+select req_id, filepath, line, start, end from OldTraceSpans;
+drop table OldTraceSpans;
+
+create table TracedItems (
+    name text not null,
+    branch text not null default '',
+    filepath text not null,
+    line integer not null,
+    primary key (branch, filepath, line)
+);
+insert into TracedItems (name, filepath, line)
+select name, filepath, line from OldTracedItems;
+drop table OldTracedItems;
+
+create table UnrelatedTraces (
+    req_id text not null,
+    branch text not null default '',
+    filepath text not null,
+    line integer not null,
+    primary key (req_id, branch, filepath, line)
+);
+insert into UnrelatedTraces (req_id, filepath, line)
+select req_id, filepath, line from OldUnrelatedTraces;
+drop table OldUnrelatedTraces;
+
+-- test runs with the same name and date may be collected for different branches
+create table TestRuns (
+    name text not null,
+    date text not null,
+    branch text not null default '',
+    nr_of_tests integer not null,
+    data text,
+    logs text,
+    primary key (name, date, branch)
+);
+insert into TestRuns (name, date, nr_of_tests, data, logs)
+select name, date, nr_of_tests, data, logs from OldTestRuns;
+drop table OldTestRuns;
+
+create table Tests (
+    test_run_name text not null,
+    test_run_date text not null,
+    branch text not null default '',
+    name text not null,
+    filepath text not null,
+    line integer not null,
+    passed integer not null,
+    primary key (test_run_name, test_run_date, branch, name),
+    foreign key (test_run_name, test_run_date, branch) references TestRuns(name, date, branch) on delete cascade
+);
+insert into Tests (test_run_name, test_run_date, name, filepath, line, passed)
+select test_run_name, test_run_date, name, filepath, line, passed from OldTests;
+drop table OldTests;
+
+create table SkippedTests (
+    test_run_name text not null,
+    test_run_date text not null,
+    branch text not null default '',
+    name text not null,
+    filepath text not null,
+    line integer not null,
+    reason text,
+    primary key (test_run_name, test_run_date, branch, name),
+    foreign key (test_run_name, test_run_date, branch) references TestRuns(name, date, branch) on delete cascade
+);
+insert into SkippedTests (test_run_name, test_run_date, name, filepath, line, reason)
+select test_run_name, test_run_date, name, filepath, line, reason from OldSkippedTests;
+drop table OldSkippedTests;
+
+create table TestCoverage (
+    req_id text not null references Requirements(id),
+    branch text not null default '',
+    test_run_name text not null,
+    test_run_date text not null,
+    test_name text not null,
+    trace_filepath text not null,
+    trace_line integer not null,
+    primary key (req_id, branch, test_run_name, test_run_date, test_name, trace_filepath, trace_line),
+    foreign key (test_run_name, test_run_date, branch, test_name) references Tests(test_run_name, test_run_date, branch, name) on delete cascade,
+    foreign key (req_id, branch, trace_filepath, trace_line) references Traces(req_id, branch, filepath, line) on delete cascade
+);
+insert into TestCoverage (req_id, test_run_name, test_run_date, test_name, trace_filepath, trace_line)
+select req_id, test_run_name, test_run_date, test_name, trace_filepath, trace_line from OldTestCoverage;
+drop table OldTestCoverage;
+
+create table UnrelatedTestCoverage (
+    req_id text not null,
+    branch text not null default '',
+    test_run_name text not null,
+    test_run_date text not null,
+    test_name text not null,
+    trace_filepath text not null,
+    trace_line integer not null,
+    primary key (req_id, branch, test_run_name, test_run_date, test_name, trace_filepath, trace_line),
+    foreign key (test_run_name, test_run_date, branch, test_name) references Tests(test_run_name, test_run_date, branch, name) on delete cascade
+);
+insert into UnrelatedTestCoverage (req_id, test_run_name, test_run_date, test_name, trace_filepath, trace_line)
+select req_id, test_run_name, test_run_date, test_name, trace_filepath, trace_line from OldUnrelatedTestCoverage;
+drop table OldUnrelatedTestCoverage;
+
+-----------------------------------------------------------------------------
+-- Views
+--
+-- trace and coverage views have one entry per branch,
+-- so queries must filter them by branch.
+-- overviews over all requirements are computed per branch in queries,
+-- because branches without traces and test runs have no entries.
+-----------------------------------------------------------------------------
+
+drop view DirectlyTracedRequirements;
+drop view UntracedRequirements;
+drop view IndirectlyTracedRequirements;
+drop view IndirectRequirementTraces;
+drop view IndirectTraceTree;
+drop view TracedRequirements;
+drop view FullyTracedRequirements;
+drop view InvalidRequirements;
+drop view DirectlyCoveredRequirements;
+drop view DirectRequirementCoverage;
+drop view DirectCoverageTree;
+drop view UncoveredRequirements;
+drop view IndirectlyCoveredRequirements;
+drop view IndirectRequirementTestCoverage;
+drop view IndirectTestCoverageTree;
+drop view CoveredRequirements;
+drop view FailedCoveredRequirements;
+drop view FailedRequirementCoverage;
+drop view PassedCoveredRequirements;
+drop view FullyCoveredRequirements;
+drop view RequirementCoverageOverview;
+drop view LeafChildOverview;
+drop view PassedTests;
+drop view FailedTestCoverage;
+drop view TestRunOverview;
+drop view OverallTestOverview;
+
+-- branches that traces or test runs were collected for.
+-- requirements are neither traced nor covered on other branches.
+create view Branches as
+select branch from Traces
+union
+select branch from TestRuns;
+
+create view DirectlyTracedRequirements as
+select distinct r.id, tr.branch from Requirements r, Traces tr
+where r.id = tr.req_id;
+
+create view UntracedRequirements as
+with recursive IsUntraced(id, branch) as (
+    -- Leaf requirements cannot be traced indirectly
+    select l.id, b.branch
+    from LeafRequirements l, Branches b
+    where not exists (
+        select 1 from DirectlyTracedRequirements d
+        where d.id = l.id and d.branch = b.branch
+    )
+    union all
+    -- Recursively get requirements that are not directly traced,
+    -- or have at least one untraced child
+    select r.id, u.branch
+    from NonLeafRequirements r, RequirementHierarchies rh, IsUntraced u
+    where r.id = rh.parent_id
+    and rh.child_id = u.id
+    and not exists (
+        select 1 from DirectlyTracedRequirements d
+        where d.id = r.id and d.branch = u.branch
+    )
+)
+select distinct id, branch from IsUntraced;
+
+-- A requirement is indirectly traced
+-- if **all** of its direct child requirements are either directly or indirectly traced.
+create view IndirectlyTracedRequirements as
+with HasUntracedChild(id, branch) as (
+    select rh.parent_id, u.branch
+    from RequirementHierarchies rh, UntracedRequirements u
+    where rh.child_id = u.id
+)
+-- Only non-leaf requirements can be indirectly traced
+select distinct n.id, b.branch
+from NonLeafRequirements n, Branches b
+where not exists (
+    select 1 from HasUntracedChild h
+    where h.id = n.id and h.branch = b.branch
+);
+
+-- Traces to child requirements.
+create view IndirectRequirementTraces as
+select ir.id, ir.branch, c.descendant_id as traced_id, t.filepath, t.line
+from IndirectlyTracedRequirements ir, RequirementDescendants c, Traces t
+where ir.id = c.id and c.descendant_id = t.req_id and ir.branch = t.branch;
+
+create view IndirectTraceTree as
+with CompactTraceEntry(id, branch, traced_id, trace) as (
+	select id, branch, traced_id, json_object('filepath', filepath, 'line', line)
+	from IndirectRequirementTraces
+), GroupedTraceEntry(id, branch, traced_id, trace_list) as (
+	select id, branch, traced_id, '[' || group_concat(json(trace)) || ']'
+	from CompactTraceEntry
+	group by id, branch, traced_id
+)
+select id, branch, traced_id, json(trace_list) as traces
+from GroupedTraceEntry;
+
+create view TracedRequirements as
+select id, branch from DirectlyTracedRequirements
+union
+select id, branch from IndirectlyTracedRequirements;
+
+-- A requirement is fully covered if all its leaf requirements are traced.
+-- Consequently, leaf requirements are fully traced if they are traced.
+create view FullyTracedRequirements as
+with HasUntracedLeaf(id, branch) as (
+    select rc.id, ur.branch
+    from RequirementDescendants rc, LeafRequirements lr, UntracedRequirements ur
+    where rc.descendant_id = lr.id and lr.id = ur.id
+)
+select lr.id, dr.branch
+from LeafRequirements lr, DirectlyTracedRequirements dr
+where lr.id = dr.id
+union all
+select n.id, b.branch
+from NonLeafRequirements n, Branches b
+where not exists (
+    select 1 from HasUntracedLeaf h
+    where h.id = n.id and h.branch = b.branch
+);
+
+create view InvalidRequirements as
+select d.id, t.branch
+from DeprecatedRequirements d, TracedRequirements t
+where d.id = t.id;
+
+create view DirectlyCoveredRequirements as
+select distinct r.id, tc.branch from Requirements r, TestCoverage tc
+where r.id = tc.req_id;
+
+create view DirectRequirementCoverage as
+select v.req_id as id, v.branch, v.test_run_name, v.test_run_date, v.test_name,
+v.trace_filepath, v.trace_line, coalesce(t.passed, 0) as test_passed
+from TestCoverage v, Tests t
+where v.test_run_name = t.test_run_name and v.test_run_date = t.test_run_date
+and v.branch = t.branch and v.test_name = t.name;
+
+create view DirectCoverageTree as
+with CompactTraceEntry(id, branch, test_run_name, test_run_date, test_name, test_passed, trace) as (
+	select id, branch, test_run_name, test_run_date, test_name, test_passed,
+	json_object('filepath', trace_filepath, 'line', trace_line)
+	from DirectRequirementCoverage
+), GroupedTraceEntry(id, branch, test_run_name, test_run_date, test_name, test_passed, trace_list) as (
+	select id, branch, test_run_name, test_run_date, test_name, test_passed, '[' || group_concat(trace) || ']'
+	from CompactTraceEntry
+	group by id, branch, test_run_name, test_run_date, test_name
+), CompactTestEntry(id, branch, test_run_name, test_run_date, test) as (
+	select id, branch, test_run_name, test_run_date,
+	json_object('name', test_name, 'passed', case when test_passed = 1 then json('true') else json('false') end, 'traces', json(trace_list))
+	from GroupedTraceEntry
+), GroupedTestEntry(id, branch, test_run_name, test_run_date, test_list) as (
+	select id, branch, test_run_name, test_run_date, '[' || group_concat(test) || ']'
+	from CompactTestEntry
+	group by id, branch, test_run_name, test_run_date
+)
+select id, branch, test_run_name, test_run_date, json(test_list) as tests from GroupedTestEntry;
+
+create view UncoveredRequirements as
+with recursive IsUncovered(id, branch) as (
+    -- Leaf requirements cannot be covered indirectly
+    select l.id, b.branch
+    from LeafRequirements l, Branches b
+    where not exists (
+        select 1 from DirectlyCoveredRequirements d
+        where d.id = l.id and d.branch = b.branch
+    )
+    union all
+    -- Recursively get requirements that are not directly covered,
+    -- or have at least one uncovered child
+    select r.id, u.branch
+    from NonLeafRequirements r, RequirementHierarchies rh, IsUncovered u
+    where r.id = rh.parent_id
+    and rh.child_id = u.id
+    and not exists (
+        select 1 from DirectlyCoveredRequirements d
+        where d.id = r.id and d.branch = u.branch
+    )
+)
+select distinct id, branch from IsUncovered;
+
+-- Indirectly covered requirements have the same constraint
+-- as indirectly traced requirements.
+--
+-- See description for indirectly traced requirements for more information.
+create view IndirectlyCoveredRequirements as
+with HasUncoveredChild(id, branch) as (
+    select rh.parent_id, u.branch
+    from RequirementHierarchies rh, UncoveredRequirements u
+    where rh.child_id = u.id
+)
+-- Only non-leaf requirements can be indirectly uncovered
+select distinct n.id, b.branch
+from NonLeafRequirements n, Branches b
+where not exists (
+    select 1 from HasUncoveredChild h
+    where h.id = n.id and h.branch = b.branch
+);
+
+-- Test coverage of child requirements.
+create view IndirectRequirementTestCoverage as
+select r.id, r.branch, c.descendant_id as covered_id,
+v.test_run_name, v.test_run_date, v.test_name,
+v.trace_filepath, v.trace_line,
+coalesce(t.passed, 0) as test_passed
+from IndirectlyCoveredRequirements r, RequirementDescendants c, TestCoverage v, Tests t
+where r.id = c.id and c.descendant_id = v.req_id and r.branch = v.branch
+and v.test_run_name = t.test_run_name and v.test_run_date = t.test_run_date
+and v.branch = t.branch and v.test_name = t.name;
+
+-- Groups all coverage information by id, branch, and covered_id
+-- creating a JSON tree of covered_id->test_runs->tests->traces
+create view IndirectTestCoverageTree as
+with CompactTraceEntry(id, branch, covered_id, test_run_name, test_run_date, test_name, test_passed, trace) as (
+	select id, branch, covered_id, test_run_name, test_run_date, test_name, test_passed,
+	json_object('filepath', trace_filepath, 'line', trace_line)
+	from IndirectRequirementTestCoverage
+), GroupedTraceEntry(id, branch, covered_id, test_run_name, test_run_date, test_name, test_passed, trace_list) as (
+	select id, branch, covered_id, test_run_name, test_run_date, test_name, test_passed, '[' || group_concat(trace) || ']'
+	from CompactTraceEntry
+	group by id, branch, covered_id, test_run_name, test_run_date, test_name
+), CompactTestEntry(id, branch, covered_id, test_run_name, test_run_date, test) as (
+	select id, branch, covered_id, test_run_name, test_run_date,
+	json_object('name', test_name, 'passed', case when test_passed = 1 then json('true') else json('false') end, 'traces', json(trace_list))
+	from GroupedTraceEntry
+), GroupedTestEntry(id, branch, covered_id, test_run_name, test_run_date, test_list) as (
+	select id, branch, covered_id, test_run_name, test_run_date, '[' || group_concat(test) || ']'
+	from CompactTestEntry
+	group by id, branch, covered_id, test_run_name, test_run_date
+), CompactTestRunEntry(id, branch, covered_id, test_run) as (
+	select id, branch, covered_id,
+	json_object('name', test_run_name, 'date', test_run_date, 'tests', json(test_list))
+	from GroupedTestEntry
+), GroupedTestRunEntry(id, branch, covered_id, test_run_list) as (
+	select id, branch, covered_id, '[' || group_concat(test_run) || ']'
+	from CompactTestRunEntry
+	group by id, branch, covered_id
+)
+select id, branch, covered_id, json(test_run_list) as test_runs from GroupedTestRunEntry;
+
+create view CoveredRequirements as
+select id, branch from DirectlyCoveredRequirements
+union
+select id, branch from IndirectlyCoveredRequirements;
+
+-- Coverage of a requirement failed if either one of the following holds:
+--
+-- - one of the tests failed that directly covered the requirement
+-- - one of the child requirements has failed coverage
+create view FailedCoveredRequirements as
+with HasFailedChild(id, branch, covered_id) as (
+    select r.id, f.branch, rc.descendant_id from Requirements r, RequirementDescendants rc, FailedTestCoverage f
+    where r.id = rc.id and rc.descendant_id = f.req_id
+)
+select c.id, c.branch, hf.covered_id
+from CoveredRequirements c, HasFailedChild hf
+where c.id = hf.id and c.branch = hf.branch
+union all
+select c.id, c.branch, null as covered_id
+from CoveredRequirements c, FailedTestCoverage f
+where c.id = f.req_id and c.branch = f.branch;
+
+create view FailedRequirementCoverage as
+select fr.id, fr.branch, null as covered_id, fc.test_run_name, fc.test_run_date, fc.test_name, fc.filepath, fc.line
+from FailedCoveredRequirements fr, FailedTestCoverage fc
+where fr.id = fc.req_id and fr.branch = fc.branch
+union all
+select fr.id, fr.branch, fr.covered_id as covered_id, fc.test_run_name, fc.test_run_date, fc.test_name, fc.filepath, fc.line
+from FailedCoveredRequirements fr, FailedTestCoverage fc
+where fr.covered_id = fc.req_id and fr.branch = fc.branch;
+
+create view PassedCoveredRequirements as
+select id, branch from CoveredRequirements
+except
+select id, branch from FailedCoveredRequirements;
+
+-- A requirement is fully covered if all its leaf requirements are passed covered.
+-- Consequently, leaf requirements are fully covered if they are passed covered.
+create view FullyCoveredRequirements as
+with HasUncoveredOrFailedLeaf(id, branch) as (
+    select rc.id, ur.branch
+    from RequirementDescendants rc, LeafRequirements lr, UncoveredRequirements ur
+    where rc.descendant_id = lr.id and lr.id = ur.id
+    union all
+    select rc.id, fr.branch
+    from RequirementDescendants rc, LeafRequirements lr, FailedCoveredRequirements fr
+    where rc.descendant_id = lr.id and lr.id = fr.id
+)
+select lr.id, pr.branch
+from LeafRequirements lr, PassedCoveredRequirements pr
+where lr.id = pr.id
+union all
+select n.id, b.branch
+from NonLeafRequirements n, Branches b
+where not exists (
+    select 1 from HasUncoveredOrFailedLeaf h
+    where h.id = n.id and h.branch = b.branch
+);
+
+create view PassedTests as
+select test_run_name, test_run_date, branch, name, filepath, line
+from Tests
+where passed = 1;
+
+create view FailedTestCoverage as
+select tc.req_id, tc.branch, tc.test_run_name, tc.test_run_date, tc.test_name, tc.trace_filepath, tc.trace_line
+from TestCoverage tc, Tests t
+where tc.test_run_name = t.test_run_name and tc.test_run_date = t.test_run_date
+    and tc.branch = t.branch and tc.test_name = t.name and (t.passed <> 1 or t.passed is null);
+
+create view TestRunOverview as
+with NrTests(name, date, branch, cnt) as
+(
+    select tr.name, tr.date, tr.branch, tr.nr_of_tests
+    from TestRuns tr
+),
+NrRanTests(name, date, branch, cnt) as
+(
+    select tr.name, tr.date, tr.branch, count(*)
+    from TestRuns tr, Tests t
+    where tr.name = t.test_run_name and tr.date = t.test_run_date and tr.branch = t.branch
+    group by tr.name, tr.date, tr.branch
+),
+NrPassed(name, date, branch, cnt) as
+(
+    select tr.name, tr.date, tr.branch, count(*)
+    from TestRuns tr, PassedTests t
+    where tr.name = t.test_run_name and tr.date = t.test_run_date and tr.branch = t.branch
+    group by tr.name, tr.date, tr.branch
+),
+NrFailed(name, date, branch, cnt) as
+(
+    select tr.name, tr.date, tr.branch, count(*)
+    from TestRuns tr, Tests t
+    where tr.name = t.test_run_name and tr.date = t.test_run_date and tr.branch = t.branch
+        and (t.passed <> 1 or t.passed is null)
+    group by tr.name, tr.date, tr.branch
+),
+NrSkipped(name, date, branch, cnt) as
+(
+    select tr.name, tr.date, tr.branch, count(*)
+    from TestRuns tr, SkippedTests t
+    where tr.name = t.test_run_name and tr.date = t.test_run_date and tr.branch = t.branch
+    group by tr.name, tr.date, tr.branch
+),
+TestRunCnts(name, date, branch, test_cnt, ran_cnt, passed_cnt, failed_cnt, skipped_cnt) as
+(
+    select name, date, branch, sum(test_cnt), sum(ran_cnt), sum(passed_cnt), sum(failed_cnt), sum(skipped_cnt)
+    from (
+        select name, date, branch, cnt as test_cnt, 0 as ran_cnt, 0 as passed_cnt, 0 as failed_cnt, 0 as skipped_cnt
+        from NrTests
+        union all
+        select name, date, branch, 0 as test_cnt, cnt as ran_cnt, 0 as passed_cnt, 0 as failed_cnt, 0 as skipped_cnt
+        from NrRanTests
+        union all
+        select name, date, branch, 0 as test_cnt, 0 as ran_cnt, cnt as passed_cnt, 0 as failed_cnt, 0 as skipped_cnt
+        from NrPassed
+        union all
+        select name, date, branch, 0 as test_cnt, 0 as ran_cnt, 0 as passed_cnt, cnt as failed_cnt, 0 as skipped_cnt
+        from NrFailed
+        union all
+        select name, date, branch, 0 as test_cnt, 0 as ran_cnt, 0 as passed_cnt, 0 as failed_cnt, cnt as skipped_cnt
+        from NrSkipped
+    )
+    where name not null and date not null
+    group by name, date, branch
+)
+select name, date, branch, test_cnt,
+    ran_cnt, case when test_cnt = 0 then 0.0 else (ran_cnt * 1.0 / test_cnt) end as ran_ratio,
+    passed_cnt, case when test_cnt = 0 then 0.0 else (passed_cnt * 1.0 / test_cnt) end as passed_ratio,
+    failed_cnt, case when test_cnt = 0 then 0.0 else (failed_cnt * 1.0 / test_cnt) end as failed_ratio,
+    skipped_cnt, case when test_cnt = 0 then 0.0 else (skipped_cnt * 1.0 / test_cnt) end as skipped_ratio
+from TestRunCnts;
+
+create view OverallTestOverview as
+select branch, sum(test_cnt) as test_cnt,
+    sum(ran_cnt) as ran_cnt, case when sum(test_cnt) = 0 then 0.0 else (sum(ran_cnt) * 1.0 / sum(test_cnt)) end as ran_ratio,
+    sum(passed_cnt) as passed_cnt, case when sum(test_cnt) = 0 then 0.0 else (sum(passed_cnt) * 1.0 / sum(test_cnt)) end as passed_ratio,
+    sum(failed_cnt) as failed_cnt, case when sum(test_cnt) = 0 then 0.0 else (sum(failed_cnt) * 1.0 / sum(test_cnt)) end as failed_ratio,
+    sum(skipped_cnt) as skipped_cnt, case when sum(test_cnt) = 0 then 0.0 else (sum(skipped_cnt) * 1.0 / sum(test_cnt)) end as skipped_ratio
+from TestRunOverview
+group by branch;
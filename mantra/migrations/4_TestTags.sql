@@ -0,0 +1,19 @@
+-- tags to categorize tests (e.g. unit, integration, e2e)
+create table TestTags (
+    test_run_name text not null,
+    test_run_date text not null,
+    branch text not null default '',
+    test_name text not null,
+    tag text not null,
+    primary key (test_run_name, test_run_date, branch, test_name, tag),
+    foreign key (test_run_name, test_run_date, branch, test_name) references Tests(test_run_name, test_run_date, branch, name) on delete cascade
+);
+
+-- requirements directly covered by at least one test having the tag
+create view TagCoveredRequirements as
+select distinct tt.tag, tc.branch, tc.req_id
+from TestTags tt, TestCoverage tc
+where tt.test_run_name = tc.test_run_name
+and tt.test_run_date = tc.test_run_date
+and tt.branch = tc.branch
+and tt.test_name = tc.test_name;
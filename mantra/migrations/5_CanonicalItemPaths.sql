@@ -0,0 +1,2 @@
+-- canonical path of traced items (e.g. `crate::module::func`) resolved from LSIF data
+alter table TracedItems add column path text;
@@ -0,0 +1,11 @@
+-- requirements referenced in commit messages.
+-- commit traces show which change implemented a requirement, and are kept separate from code traces.
+create table CommitTraces (
+    req_id text not null references Requirements(id) on delete cascade,
+    branch text not null default '',
+    sha text not null,
+    summary text not null,
+    -- commit date in ISO8601 format
+    date text not null,
+    primary key (req_id, branch, sha)
+);
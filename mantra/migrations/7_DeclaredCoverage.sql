@@ -0,0 +1,11 @@
+-- tests that declare to cover requirements, because the requirement is traced on the test function.
+-- declared coverage is known at collect time, and may be confirmed by executed coverage of test runs.
+create table DeclaredCoverage (
+    req_id text not null,
+    branch text not null default '',
+    test_name text not null,
+    filepath text not null,
+    line integer not null,
+    primary key (req_id, branch, filepath, line),
+    foreign key (req_id, branch, filepath, line) references Traces(req_id, branch, filepath, line) on delete cascade
+);
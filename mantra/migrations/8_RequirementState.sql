@@ -0,0 +1,2 @@
+-- optional workflow state of requirements (e.g. draft, review, approved)
+alter table Requirements add column state text;
@@ -0,0 +1,3 @@
+-- URLs or filepaths of documents supporting a manual verification, stored as JSON array
+alter table ManuallyVerified add column evidence text;
+alter table UnrelatedManuallyVerified add column evidence text;
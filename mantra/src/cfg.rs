@@ -7,6 +7,15 @@ pub struct Config {
     #[command(flatten)]
     pub db: db::Config,
 
+    /// Hide progress indicators and info logs.
+    /// Progress indicators are also hidden if stdout is not a terminal.
+    #[arg(long, short, global = true)]
+    pub quiet: bool,
+
+    /// Print the time spent per phase of the command, e.g. per collected file type or report section.
+    #[arg(long, global = true)]
+    pub profile: bool,
+
     #[command(subcommand)]
     pub cmd: Cmd,
 }
@@ -17,6 +26,42 @@ pub struct MantraConfigPath {
     pub filepath: PathBuf,
 }
 
+#[derive(Debug, Clone, clap::Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct TraceCliConfig {
+    #[command(subcommand)]
+    pub cmd: Option<crate::cmd::trace::TraceCmd>,
+    #[command(flatten)]
+    pub config: MantraConfigPath,
+    /// Emit each collected trace in the given format as it is found.
+    #[arg(long, value_enum)]
+    pub emit_traces: Option<crate::cmd::trace::EmitFormat>,
+    /// File the traces are emitted to.
+    /// Traces are emitted to stdout if not set.
+    #[arg(long, requires = "emit_traces")]
+    pub emit_to: Option<PathBuf>,
+    /// Only emit traces without storing them in the database.
+    #[arg(long, requires = "emit_traces")]
+    pub no_store: bool,
+    /// Fail on traces to deprecated requirements instead of logging a warning.
+    #[arg(long)]
+    pub strict: bool,
+    /// Only print the collector chosen for each walked file, and the number of files per collector.
+    /// No traces are collected or stored.
+    #[arg(long, conflicts_with = "emit_traces")]
+    pub explain: bool,
+    /// Resume an interrupted trace collection.
+    /// Trace sources and files whose traces were already stored are skipped.
+    #[arg(long)]
+    pub resume: bool,
+    /// File listing changed files, one filepath per line (e.g. output of `git diff --name-only`).
+    /// Only these files are re-traced for source trace roots, and their traces are merged into the current generation.
+    #[arg(long, value_name = "FILE", conflicts_with = "explain")]
+    pub changed_files: Option<PathBuf>,
+    #[command(flatten)]
+    pub walk_limits: crate::cmd::trace::WalkLimits,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct MantraConfigFile {
     #[serde(default)]
@@ -33,6 +78,224 @@ pub struct MantraConfigFile {
         skip_serializing_if = "crate::cmd::report::ReportTemplate::is_none"
     )]
     pub report_template: crate::cmd::report::ReportTemplate,
+    /// Regular expression every requirement and trace ID must match.
+    #[serde(default, alias = "id-pattern", skip_serializing_if = "Option::is_none")]
+    pub id_pattern: Option<String>,
+    /// Fail collect on IDs not matching `id_pattern` instead of only logging a warning.
+    #[serde(default, alias = "deny-id-mismatch")]
+    pub deny_id_mismatch: bool,
+    /// Case requirement and trace IDs are normalized to before they are stored.
+    #[serde(
+        default,
+        alias = "id-case",
+        skip_serializing_if = "IdCase::is_preserve"
+    )]
+    pub id_case: IdCase,
+    /// ID prefixes or globs of requirements that require manual verification.
+    /// Only applied to requirements without explicit manual marker.
+    #[serde(default, alias = "manual-rules", skip_serializing_if = "Vec::is_empty")]
+    pub manual_rules: Vec<String>,
+    /// Maximum depth of requirement IDs in the hierarchy.
+    /// The depth is the number of `.` separated ID parts, so `a.b.c` has depth `3`.
+    #[serde(default, alias = "max-depth", skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<usize>,
+    /// Fail collect on requirement IDs exceeding `max_depth` instead of only logging a warning.
+    #[serde(default, alias = "deny-max-depth")]
+    pub deny_max_depth: bool,
+    /// States requirements may be in (e.g. `draft`, `review`, `approved`).
+    /// Any state is allowed if no states are configured.
+    #[serde(
+        default,
+        alias = "allowed-states",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub allowed_states: Vec<String>,
+    /// Weights of the factors adding to the risk score of requirements in the report.
+    #[serde(default, alias = "risk-weights")]
+    pub risk_weights: crate::cmd::report::RiskWeights,
+    /// Links from requirements to design documentation, in addition to the design links of the requirement sources.
+    #[serde(default, alias = "design-links", skip_serializing_if = "Vec::is_empty")]
+    pub design_links: Vec<crate::cmd::requirements::ConfiguredDesignLink>,
+    /// Validation errors of requirements that are acknowledged as intentional.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppressions: Vec<ValidationSuppression>,
+}
+
+impl MantraConfigFile {
+    /// Returns the compiled ID pattern if one is configured.
+    pub fn id_pattern(&self) -> Result<Option<IdPattern>, regex::Error> {
+        match &self.id_pattern {
+            Some(pattern) => Ok(Some(IdPattern {
+                regex: regex::Regex::new(pattern)?,
+                deny: self.deny_id_mismatch,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the depth limit for requirement IDs if one is configured.
+    pub fn depth_limit(&self) -> Option<DepthLimit> {
+        self.max_depth.map(|max| DepthLimit {
+            max,
+            deny: self.deny_max_depth,
+        })
+    }
+
+    /// Returns the allowed requirement states if any are configured.
+    pub fn allowed_states(&self) -> Option<&[String]> {
+        if self.allowed_states.is_empty() {
+            None
+        } else {
+            Some(&self.allowed_states)
+        }
+    }
+
+    /// Returns the compiled manual rules if any are configured.
+    pub fn manual_rules(&self) -> Result<Option<ManualRules>, globset::Error> {
+        if self.manual_rules.is_empty() {
+            Ok(None)
+        } else {
+            ManualRules::new(&self.manual_rules).map(Some)
+        }
+    }
+}
+
+/// Rules to mark requirements as manual based on their ID.
+///
+/// Rules containing glob characters (`*`, `?`, `[`) are matched as glob against the full ID.
+/// All other rules are matched as ID prefix.
+#[derive(Debug, Clone)]
+pub struct ManualRules {
+    prefixes: Vec<String>,
+    globs: globset::GlobSet,
+}
+
+impl ManualRules {
+    pub fn new(rules: &[String]) -> Result<Self, globset::Error> {
+        let mut prefixes = Vec::new();
+        let mut globs = globset::GlobSetBuilder::new();
+
+        for rule in rules {
+            if rule.contains(['*', '?', '[']) {
+                globs.add(globset::Glob::new(rule)?);
+            } else {
+                prefixes.push(rule.clone());
+            }
+        }
+
+        Ok(Self {
+            prefixes,
+            globs: globs.build()?,
+        })
+    }
+
+    /// Returns `true` if the given requirement ID is matched by one of the rules.
+    pub fn is_manual(&self, id: &str) -> bool {
+        self.prefixes.iter().any(|prefix| id.starts_with(prefix)) || self.globs.is_match(id)
+    }
+}
+
+/// Acknowledges that a *deprecated* requirement is traced on purpose, e.g. to document a migration.
+/// The requirement is then not invalid, but listed as suppressed in reports.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ValidationSuppression {
+    /// ID of the *deprecated* requirement that is traced on purpose.
+    pub id: mantra_schema::requirements::ReqId,
+    /// Justification why the validation error is acknowledged.
+    pub reason: String,
+}
+
+/// Project convention requirement and trace IDs are validated against at collect time.
+#[derive(Debug, Clone)]
+pub struct IdPattern {
+    regex: regex::Regex,
+    deny: bool,
+}
+
+impl IdPattern {
+    /// Checks if the given ID matches the pattern.
+    ///
+    /// A mismatch is only logged as warning, unless mismatches are denied.
+    /// In this case, the mismatch is returned as error message.
+    pub fn check(&self, id: &str, location: impl std::fmt::Display) -> Result<(), String> {
+        if self.regex.is_match(id) {
+            return Ok(());
+        }
+
+        let msg = format!(
+            "ID '{}' at '{}' does not match the ID pattern '{}'.",
+            id,
+            location,
+            self.regex.as_str()
+        );
+
+        if self.deny {
+            Err(msg)
+        } else {
+            log::warn!("{msg}");
+            Ok(())
+        }
+    }
+}
+
+/// Canonical case requirement and trace IDs are normalized to at collect time,
+/// so traces match requirements regardless of how the ID is cased by authors.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdCase {
+    /// IDs are kept as written, so IDs only differing in case refer to different requirements.
+    #[default]
+    Preserve,
+    Lower,
+    Upper,
+}
+
+impl IdCase {
+    pub fn is_preserve(&self) -> bool {
+        *self == IdCase::Preserve
+    }
+
+    /// Returns the given ID in this case.
+    pub fn normalize(&self, id: &str) -> String {
+        match self {
+            IdCase::Preserve => id.to_string(),
+            IdCase::Lower => id.to_lowercase(),
+            IdCase::Upper => id.to_uppercase(),
+        }
+    }
+}
+
+/// Maximum depth of requirement IDs in the hierarchy that is validated at collect time.
+#[derive(Debug, Clone, Copy)]
+pub struct DepthLimit {
+    max: usize,
+    deny: bool,
+}
+
+impl DepthLimit {
+    /// Checks if the depth of the given ID is within the limit.
+    ///
+    /// Exceeding the limit is only logged as warning, unless it is denied.
+    /// In this case, the violation is returned as error message.
+    pub fn check(&self, id: &str, location: impl std::fmt::Display) -> Result<(), String> {
+        let depth = id.split('.').count();
+
+        if depth <= self.max {
+            return Ok(());
+        }
+
+        let msg = format!(
+            "ID '{}' at '{}' has depth '{}', which exceeds the maximum depth '{}'.",
+            id, location, depth, self.max
+        );
+
+        if self.deny {
+            Err(msg)
+        } else {
+            log::warn!("{msg}");
+            Ok(())
+        }
+    }
 }
 
 #[derive(
@@ -65,6 +328,34 @@ impl Project {
     }
 }
 
+#[derive(Debug, Clone, clap::Args)]
+pub struct ClearConfig {
+    /// Only clear the given tables.
+    /// All tables are cleared if neither `--tables` nor `--keep` is set.
+    #[arg(long, value_delimiter = ',', conflicts_with = "keep")]
+    pub tables: Option<Vec<db::DbTable>>,
+    /// Clear all tables except the given ones.
+    #[arg(long, value_delimiter = ',')]
+    pub keep: Option<Vec<db::DbTable>>,
+}
+
+impl ClearConfig {
+    /// Returns the tables that should be cleared.
+    pub fn tables(&self) -> Vec<db::DbTable> {
+        use clap::ValueEnum;
+
+        match (&self.tables, &self.keep) {
+            (Some(tables), _) => tables.clone(),
+            (None, Some(keep)) => db::DbTable::value_variants()
+                .iter()
+                .filter(|table| !keep.contains(table))
+                .copied()
+                .collect(),
+            (None, None) => db::DbTable::value_variants().to_vec(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, clap::Args)]
 pub struct DeleteOldConfig {
     /// Delete test runs and reviews that have no linked requirement or coverage remaining.
@@ -90,6 +381,61 @@ pub struct DeleteTracesConfig {
     pub before: Option<i64>,
 }
 
+#[derive(Debug, Clone, clap::Args)]
+pub struct CollectCliConfig {
+    #[command(flatten)]
+    pub config: MantraConfigPath,
+    /// Validate requirements files against the JSON schema of the `RequirementSchema` before they are added.
+    /// All schema violations are reported with the path to the invalid field.
+    #[arg(long)]
+    pub validate_schema: bool,
+    /// Fail on traces to deprecated requirements instead of logging a warning.
+    #[arg(long)]
+    pub strict: bool,
+    #[command(flatten)]
+    pub walk_limits: crate::cmd::trace::WalkLimits,
+    /// Fail if evidence filepaths of manual verifications in reviews do not exist.
+    /// Filepaths are resolved relative to the review file.
+    #[arg(long)]
+    pub check_evidence: bool,
+    /// Skip configured coverage files that do not exist with a warning instead of failing.
+    /// Useful if not all CI jobs produce coverage data.
+    #[arg(long)]
+    pub skip_missing_coverage: bool,
+    /// Seconds to wait for configured coverage files that do not exist yet, before they are considered missing.
+    /// Overrides `wait-secs` of the coverage configuration.
+    #[arg(long = "wait-coverage", value_name = "SECS")]
+    pub wait_coverage: Option<u64>,
+    /// Fail if design links of requirements are neither well-formed URLs nor paths to existing files.
+    /// Filepaths are resolved relative to the working directory.
+    #[arg(long)]
+    pub check_design_links: bool,
+    /// Resume an interrupted collect.
+    /// Requirements, trace sources, and files whose traces were already stored are skipped.
+    #[arg(long)]
+    pub resume: bool,
+    /// Fail if the ratio of traced requirements is below the given ratio between `0` and `1` after collecting.
+    #[arg(long, value_name = "RATIO")]
+    pub min_traced_ratio: Option<f64>,
+    /// Fail if the ratio of covered requirements is below the given ratio between `0` and `1` after collecting.
+    #[arg(long, value_name = "RATIO")]
+    pub min_covered_ratio: Option<f64>,
+}
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct PruneConfig {
+    /// Delete test runs that are not kept by the retention policy set with `--keep-last` and `--keep-days`.
+    /// All test runs are kept if no retention policy is set.
+    #[arg(long)]
+    pub test_runs: bool,
+    /// Number of most recent test runs that are kept per test run name.
+    #[arg(long, requires = "test_runs")]
+    pub keep_last: Option<u32>,
+    /// Number of days test runs are kept.
+    #[arg(long, requires = "test_runs")]
+    pub keep_days: Option<u32>,
+}
+
 #[derive(Debug, Clone, clap::Args)]
 pub struct DeleteTestRunsConfig {
     #[arg(long, alias = "older-than")]
@@ -154,4 +500,108 @@ mod test {
             "Coverage info not correctly extracted."
         );
     }
+
+    #[test]
+    fn id_pattern_matching_id() {
+        let content = r#"
+                            id-pattern = '^[A-Z]+-\d+(\.\w+)*$'
+                            deny-id-mismatch = true
+                            "#;
+
+        let file: crate::cfg::MantraConfigFile = toml::from_str(content).unwrap();
+        let pattern = file.id_pattern().unwrap().unwrap();
+
+        assert!(
+            pattern.check("JIRA-35604", "reqs.md").is_ok(),
+            "Matching ID rejected."
+        );
+        assert!(
+            pattern.check("JIRA-35604.sub_req", "reqs.md").is_ok(),
+            "Matching sub-requirement ID rejected."
+        );
+    }
+
+    #[test]
+    fn id_pattern_non_matching_id() {
+        let content = r#"
+                            id-pattern = '^JIRA-\d+$'
+                            "#;
+
+        let mut file: crate::cfg::MantraConfigFile = toml::from_str(content).unwrap();
+
+        assert!(
+            file.id_pattern()
+                .unwrap()
+                .unwrap()
+                .check("JIRAX-35604", "src/lib.rs:3")
+                .is_ok(),
+            "Mismatch not only warned without `deny-id-mismatch`."
+        );
+
+        file.deny_id_mismatch = true;
+        let err = file
+            .id_pattern()
+            .unwrap()
+            .unwrap()
+            .check("JIRAX-35604", "src/lib.rs:3")
+            .unwrap_err();
+
+        assert!(
+            err.contains("JIRAX-35604") && err.contains("src/lib.rs:3"),
+            "Offending ID or location missing in error."
+        );
+    }
+
+    #[test]
+    fn manual_rules_prefix_and_glob() {
+        let rules =
+            crate::cfg::ManualRules::new(&["safety.".to_string(), "*.manual_check".to_string()])
+                .unwrap();
+
+        assert!(rules.is_manual("safety.brakes"), "Prefix rule not applied.");
+        assert!(
+            rules.is_manual("ui.login.manual_check"),
+            "Glob rule not applied."
+        );
+        assert!(
+            !rules.is_manual("ui.safety.login"),
+            "Prefix rule matched in the middle of the ID."
+        );
+    }
+
+    #[test]
+    fn clear_keep_requirements() {
+        use crate::db::DbTable;
+
+        let cfg = crate::cfg::ClearConfig {
+            tables: None,
+            keep: Some(vec![DbTable::Requirements, DbTable::Traces]),
+        };
+
+        assert_eq!(
+            cfg.tables(),
+            vec![DbTable::Tests, DbTable::Coverage, DbTable::Reviews],
+            "Kept tables not excluded from clear."
+        );
+    }
+
+    #[test]
+    fn max_depth_exceeded() {
+        let content = r#"
+                            max-depth = 2
+                            deny-max-depth = true
+                            "#;
+
+        let file: crate::cfg::MantraConfigFile = toml::from_str(content).unwrap();
+        let limit = file.depth_limit().unwrap();
+
+        assert!(
+            limit.check("parent.child", "reqs.json").is_ok(),
+            "ID within the maximum depth rejected."
+        );
+        assert!(
+            limit.check("parent.child.grandchild", "reqs.json").is_err(),
+            "ID exceeding the maximum depth not rejected with `deny-max-depth`."
+        );
+    }
 }
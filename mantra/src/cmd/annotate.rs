@@ -0,0 +1,329 @@
+use std::path::{Path, PathBuf};
+
+use mantra_schema::requirements::ReqId;
+
+use crate::db::{DbError, MantraDb};
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct AnnotateConfig {
+    /// Source file to annotate.
+    pub file: PathBuf,
+    /// Filepath of the source file as stored for traces.
+    /// Default uses the longest stored filepath the given file ends with.
+    #[arg(long)]
+    pub trace_path: Option<String>,
+    /// Output format of the annotated source file.
+    #[arg(long, value_enum, default_value_t = AnnotateFormat::Text)]
+    pub format: AnnotateFormat,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum AnnotateFormat {
+    /// Source lines with a gutter showing line numbers and trace status.
+    #[default]
+    Text,
+    /// Standalone HTML page with highlighted trace lines.
+    Html,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AnnotateError {
+    #[error("{}", .0)]
+    Db(DbError),
+    #[error("Could not access file '{}'.", .0)]
+    CouldNotAccessFile(String),
+}
+
+/// Coverage status of a trace.
+/// Ordered from worst to best, so the minimum of multiple traces is the status shown for a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TraceStatus {
+    /// At least one test covering the trace failed.
+    Failed,
+    /// The trace was not reached by any test.
+    Uncovered,
+    /// All tests covering the trace passed.
+    Passed,
+}
+
+impl TraceStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TraceStatus::Failed => "failed",
+            TraceStatus::Uncovered => "uncovered",
+            TraceStatus::Passed => "passed",
+        }
+    }
+}
+
+/// Trace in the annotated source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnnotatedTrace {
+    pub req_id: ReqId,
+    pub line: u32,
+    /// Lines affected by the trace (e.g. the body of a traced function).
+    pub span: Option<(u32, u32)>,
+    pub status: TraceStatus,
+}
+
+pub async fn annotate(db: &MantraDb, cfg: &AnnotateConfig) -> Result<(), AnnotateError> {
+    let source = tokio::fs::read_to_string(&cfg.file)
+        .await
+        .map_err(|_| AnnotateError::CouldNotAccessFile(cfg.file.display().to_string()))?;
+
+    let traces = file_traces(db, cfg).await?;
+
+    if traces.is_empty() {
+        log::warn!("No traces found for file '{}'.", cfg.file.display());
+    }
+
+    match cfg.format {
+        AnnotateFormat::Text => print!("{}", to_text(&source, &traces)),
+        AnnotateFormat::Html => print!("{}", to_html(&cfg.file, &source, &traces)),
+    }
+
+    Ok(())
+}
+
+/// Returns the traces of the given file together with their coverage status, ordered by line.
+pub async fn file_traces(
+    db: &MantraDb,
+    cfg: &AnnotateConfig,
+) -> Result<Vec<AnnotatedTrace>, AnnotateError> {
+    let branch = db.branch();
+    let trace_path = match &cfg.trace_path {
+        Some(path) => path.clone(),
+        None => {
+            let filepaths: Vec<String> = sqlx::query!(
+                "select distinct filepath from Traces where branch = $1",
+                branch
+            )
+            .fetch_all(db.pool())
+            .await
+            .map_err(|err| AnnotateError::Db(DbError::Query(err.to_string())))?
+            .into_iter()
+            .map(|record| record.filepath)
+            .collect();
+
+            match matching_trace_path(&cfg.file, &filepaths) {
+                Some(path) => path.to_string(),
+                None => return Ok(Vec::new()),
+            }
+        }
+    };
+
+    let records = sqlx::query!(
+        r#"select
+            t.req_id,
+            t.line,
+            s.start as "start?: i64",
+            s.end as "end?: i64",
+            (
+                select count(*) from TestCoverage c
+                where c.req_id = t.req_id and c.branch = t.branch and c.trace_filepath = t.filepath and c.trace_line = t.line
+            ) as "covered_cnt!: i64",
+            (
+                select count(*) from TestCoverage c
+                join Tests ts on ts.test_run_name = c.test_run_name and ts.test_run_date = c.test_run_date and ts.branch = c.branch and ts.name = c.test_name
+                where c.req_id = t.req_id and c.branch = t.branch and c.trace_filepath = t.filepath and c.trace_line = t.line and ts.passed = 0
+            ) as "failed_cnt!: i64"
+        from Traces t
+        left join TraceSpans s
+        on t.req_id = s.req_id and t.branch = s.branch and t.filepath = s.filepath and t.line = s.line
+        where t.filepath = $1 and t.branch = $2
+        order by t.line, t.req_id"#,
+        trace_path,
+        branch
+    )
+    .fetch_all(db.pool())
+    .await
+    .map_err(|err| AnnotateError::Db(DbError::Query(err.to_string())))?;
+
+    Ok(records
+        .into_iter()
+        .map(|record| AnnotatedTrace {
+            req_id: record.req_id,
+            line: u32::try_from(record.line).unwrap_or_default(),
+            span: record.start.zip(record.end).map(|(start, end)| {
+                (
+                    u32::try_from(start).unwrap_or_default(),
+                    u32::try_from(end).unwrap_or_default(),
+                )
+            }),
+            status: if record.failed_cnt > 0 {
+                TraceStatus::Failed
+            } else if record.covered_cnt > 0 {
+                TraceStatus::Passed
+            } else {
+                TraceStatus::Uncovered
+            },
+        })
+        .collect())
+}
+
+/// Returns the longest stored filepath the given file ends with.
+fn matching_trace_path<'a>(file: &Path, filepaths: &'a [String]) -> Option<&'a str> {
+    filepaths
+        .iter()
+        .filter(|filepath| file.ends_with(filepath.as_str()))
+        .max_by_key(|filepath| Path::new(filepath.as_str()).components().count())
+        .map(|filepath| filepath.as_str())
+}
+
+/// Status and traces shown in the gutter of one source line.
+struct LineAnnotation<'a> {
+    status: Option<TraceStatus>,
+    in_span: Option<TraceStatus>,
+    traces: Vec<&'a AnnotatedTrace>,
+}
+
+fn line_annotation(line_nr: u32, traces: &[AnnotatedTrace]) -> LineAnnotation<'_> {
+    let line_traces: Vec<&AnnotatedTrace> = traces
+        .iter()
+        .filter(|trace| trace.line == line_nr)
+        .collect();
+
+    LineAnnotation {
+        status: line_traces.iter().map(|trace| trace.status).min(),
+        in_span: traces
+            .iter()
+            .filter(|trace| {
+                trace
+                    .span
+                    .is_some_and(|(start, end)| start <= line_nr && line_nr <= end)
+            })
+            .map(|trace| trace.status)
+            .min(),
+        traces: line_traces,
+    }
+}
+
+/// Renders the source with a gutter showing the line number and the status of traces.
+/// Trace lines list the traced requirements with their status after the source line,
+/// and lines affected by a trace are marked with `|`.
+pub fn to_text(source: &str, traces: &[AnnotatedTrace]) -> String {
+    let mut out = String::new();
+
+    for (nr, line) in source.lines().enumerate() {
+        let annotation = line_annotation(nr as u32 + 1, traces);
+
+        let gutter = match (annotation.status, annotation.in_span) {
+            (Some(status), _) => status.as_str(),
+            (None, Some(_)) => "|",
+            (None, None) => "",
+        };
+
+        out.push_str(&format!("{:>5} {:<9} | {}", nr + 1, gutter, line));
+
+        if !annotation.traces.is_empty() {
+            let reqs: Vec<String> = annotation
+                .traces
+                .iter()
+                .map(|trace| format!("{}: {}", trace.req_id, trace.status.as_str()))
+                .collect();
+            out.push_str(&format!("  [{}]", reqs.join(", ")));
+        }
+
+        out.push('\n');
+    }
+
+    out
+}
+
+/// Renders the source as standalone HTML page.
+/// Trace lines and lines affected by a trace are colored by their status.
+pub fn to_html(file: &Path, source: &str, traces: &[AnnotatedTrace]) -> String {
+    let title = html_escape(&file.display().to_string());
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"UTF-8\">\n<title>{title}</title>\n<style>\n\
+        table {{ border-collapse: collapse; font-family: monospace; }}\n\
+        td {{ padding: 0 0.5em; white-space: pre; vertical-align: top; }}\n\
+        .nr {{ color: #888; text-align: right; }}\n\
+        .passed {{ background-color: #d4f7d4; }}\n\
+        .failed {{ background-color: #f7d4d4; }}\n\
+        .uncovered {{ background-color: #f7ecd4; }}\n\
+        .span {{ opacity: 0.6; }}\n\
+        </style>\n</head>\n<body>\n<h1>{title}</h1>\n<table>\n"
+    );
+
+    for (nr, line) in source.lines().enumerate() {
+        let annotation = line_annotation(nr as u32 + 1, traces);
+
+        let class = match (annotation.status, annotation.in_span) {
+            (Some(status), _) => status.as_str().to_string(),
+            (None, Some(status)) => format!("{} span", status.as_str()),
+            (None, None) => String::new(),
+        };
+        let reqs: Vec<String> = annotation
+            .traces
+            .iter()
+            .map(|trace| format!("{}: {}", html_escape(&trace.req_id), trace.status.as_str()))
+            .collect();
+
+        out.push_str(&format!(
+            "<tr class=\"{}\"><td class=\"nr\">{}</td><td>{}</td><td>{}</td></tr>\n",
+            class,
+            nr + 1,
+            html_escape(line),
+            reqs.join(", ")
+        ));
+    }
+
+    out.push_str("</table>\n</body>\n</html>\n");
+    out
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::{matching_trace_path, to_text, AnnotatedTrace, TraceStatus};
+
+    #[test]
+    fn gutter_shows_trace_status() {
+        let source = "#[test]\n#[req(a)]\n#[req(b)]\nfn covers_a() {\n}\n";
+        let traces = vec![
+            AnnotatedTrace {
+                req_id: "a".to_string(),
+                line: 2,
+                span: Some((4, 5)),
+                status: TraceStatus::Passed,
+            },
+            AnnotatedTrace {
+                req_id: "b".to_string(),
+                line: 3,
+                span: None,
+                status: TraceStatus::Uncovered,
+            },
+        ];
+
+        assert_eq!(
+            to_text(source, &traces),
+            "    1           | #[test]\n    2 passed    | #[req(a)]  [a: passed]\n    3 uncovered | #[req(b)]  [b: uncovered]\n    4 |         | fn covers_a() {\n    5 |         | }\n",
+            "Trace status not shown in gutter."
+        );
+    }
+
+    #[test]
+    fn longest_trace_path_matched() {
+        let filepaths = vec!["lib.rs".to_string(), "src/lib.rs".to_string()];
+
+        assert_eq!(
+            matching_trace_path(Path::new("crate/src/lib.rs"), &filepaths),
+            Some("src/lib.rs"),
+            "Longest stored filepath not matched."
+        );
+        assert_eq!(
+            matching_trace_path(Path::new("crate/src/mylib.rs"), &filepaths),
+            None,
+            "Filepath matched on partial filename."
+        );
+    }
+}
@@ -5,13 +5,19 @@ use std::{
 
 use mantra_lang_tracing::path::SlashPathBuf;
 use mantra_schema::{
-    coverage::{CoverageSchema, CoveredFileTrace, CoveredLine, TestRunPk},
+    coverage::{
+        CoverageSchema, CoveredFile, CoveredFileTrace, CoveredLine, Test, TestRun, TestRunPk,
+        TestState,
+    },
     requirements::ReqId,
     Line,
 };
 use time::OffsetDateTime;
 
-use crate::db::{DbError, MantraDb, TracePk};
+use crate::{
+    cfg::IdCase,
+    db::{DbError, MantraDb, TracePk},
+};
 
 pub struct CoverageChanges {
     inserted: Vec<TracePk>,
@@ -34,14 +40,78 @@ impl std::fmt::Display for CoverageChanges {
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Config {
-    /// Files containing coverage data according to the *mantra* CoverageSchema.
-    /// The file format may either be JSON or TOML.
+    /// Files containing coverage data in the set format.
     #[serde(
         alias = "filepaths",
         alias = "external-files",
         alias = "external-filepaths"
     )]
     pub files: Vec<PathBuf>,
+    /// Format of the coverage files.
+    /// Default is the *mantra* CoverageSchema.
+    #[serde(default)]
+    pub format: CoverageFormat,
+    /// Prefix that is stripped from filepaths in the coverage data,
+    /// so they match the filepaths of the stored traces.
+    #[serde(default, alias = "base-path")]
+    pub base_path: Option<PathBuf>,
+    /// Filepath prefixes in the coverage data that are replaced,
+    /// so paths of other environments (e.g. CI containers) match the filepaths of the stored traces.
+    /// Only the first matching rewrite is applied.
+    #[serde(default, alias = "path-prefix-map")]
+    pub path_prefix_map: Vec<PathPrefixRewrite>,
+    /// Name of the test run created for coverage formats without test run information.
+    /// Default is the file stem of the coverage file.
+    #[serde(default, alias = "test-run")]
+    pub test_run: Option<String>,
+    /// Tags added to all tests of the coverage files (e.g. unit, integration, e2e).
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Seconds to wait for coverage files that do not exist yet,
+    /// e.g. if coverage data is written to a shared location while collect already runs.
+    /// Default is no waiting.
+    #[serde(default, alias = "wait-secs")]
+    pub wait_secs: Option<u64>,
+}
+
+/// Interval in which the existence of coverage files is checked while waiting for them.
+const COVERAGE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Replaces the prefix `from` of filepaths with `to`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PathPrefixRewrite {
+    pub from: PathBuf,
+    /// Default removes the prefix.
+    #[serde(default)]
+    pub to: PathBuf,
+}
+
+impl PathPrefixRewrite {
+    /// Returns the path with the prefix of the first matching rewrite replaced.
+    /// `None` if no rewrite matches.
+    pub fn rewrite(rewrites: &[Self], path: &Path) -> Option<PathBuf> {
+        rewrites.iter().find_map(|rewrite| {
+            path.strip_prefix(&rewrite.from)
+                .ok()
+                .map(|rest| rewrite.to.join(rest))
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CoverageFormat {
+    /// JSON data according to the *mantra* CoverageSchema.
+    #[default]
+    #[serde(alias = "mantra")]
+    Schema,
+    /// JSON data created by `llvm-cov export`.
+    #[serde(alias = "llvm_cov")]
+    LlvmCov,
+    /// JUnit XML test reports with `<testsuite>` and `<testcase>` elements.
+    /// Only test states are collected, because JUnit reports have no coverage information.
+    #[serde(alias = "junit-xml", alias = "junit_xml")]
+    Junit,
 }
 
 pub fn iso8601_str_to_offsetdatetime(time_str: &str) -> OffsetDateTime {
@@ -56,15 +126,137 @@ pub fn iso8601_str_to_offsetdatetime(time_str: &str) -> OffsetDateTime {
 pub enum CoverageError {
     #[error("{}", .0)]
     ReadingData(String),
+    #[error("Coverage file '{}' does not exist. Make sure the tests producing it ran, or use `--skip-missing-coverage` to skip missing coverage files.", .0.display())]
+    MissingFile(PathBuf),
     #[error("{}", .0)]
     Deserialize(serde_json::Error),
     #[error("{}", .0)]
     Db(DbError),
+    #[error("No test run found{}.", .0.as_ref().map(|name| format!(" with name '{name}'")).unwrap_or_default())]
+    NoTestRun(Option<String>),
+    #[error("Could not serialize uncovered traces. Cause: {}", .0)]
+    Serialize(serde_json::Error),
+    #[error("Invalid coverage file pattern. Cause: {}", .0)]
+    InvalidPattern(String),
+}
+
+/// Collects coverage from all configured coverage files.
+///
+/// Missing coverage files are skipped with a warning if `skip_missing` is set.
+/// Otherwise, the first missing file is returned as error.
+/// Glob patterns are expanded after waiting for the literal filepaths.
+/// Requirement IDs of covered traces are normalized to the given case.
+pub async fn collect(
+    db: &MantraDb,
+    cfg: &Config,
+    skip_missing: bool,
+    id_case: IdCase,
+) -> Result<(), CoverageError> {
+    if let Some(wait_secs) = cfg.wait_secs {
+        let literal_files: Vec<PathBuf> = cfg
+            .files
+            .iter()
+            .filter(|file| !super::is_glob(file))
+            .cloned()
+            .collect();
+        wait_for_files(&literal_files, std::time::Duration::from_secs(wait_secs)).await;
+    }
+
+    for file in &coverage_files(&cfg.files)? {
+        if !file.exists() {
+            if skip_missing {
+                log::warn!("Skipping missing coverage file '{}'.", file.display());
+                continue;
+            }
+
+            return Err(CoverageError::MissingFile(file.clone()));
+        }
+
+        let coverage_changes = match cfg.format {
+            CoverageFormat::Schema => {
+                collect_from_path(db, file, &cfg.tags, &cfg.path_prefix_map, id_case).await
+            }
+            CoverageFormat::LlvmCov => {
+                collect_from_llvm_cov_path(
+                    db,
+                    file,
+                    cfg.base_path.as_deref(),
+                    cfg.test_run.as_deref(),
+                    &cfg.tags,
+                    &cfg.path_prefix_map,
+                )
+                .await
+            }
+            CoverageFormat::Junit => {
+                collect_from_junit_path(
+                    db,
+                    file,
+                    cfg.test_run.as_deref(),
+                    &cfg.tags,
+                    &cfg.path_prefix_map,
+                )
+                .await
+            }
+        }?;
+
+        println!("{coverage_changes}");
+    }
+
+    Ok(())
+}
+
+/// Expands glob patterns (e.g. `target/coverage-*.json`) to all matching files.
+/// Patterns without matching files are skipped with a warning, and literal filepaths are kept as is.
+fn coverage_files(files: &[PathBuf]) -> Result<Vec<PathBuf>, CoverageError> {
+    let mut expanded = Vec::new();
+
+    for file in files {
+        let mut matched = super::expand_files(std::slice::from_ref(file), |_| true)
+            .map_err(|err| CoverageError::InvalidPattern(err.to_string()))?;
+
+        if matched.is_empty() {
+            log::warn!("No coverage file matches '{}'.", file.display());
+        }
+
+        expanded.append(&mut matched);
+    }
+
+    Ok(expanded)
+}
+
+/// Waits until all given files exist, or the timeout is reached.
+/// Returns the files that still do not exist.
+async fn wait_for_files(files: &[PathBuf], timeout: std::time::Duration) -> Vec<&PathBuf> {
+    let deadline = tokio::time::Instant::now() + timeout;
+
+    loop {
+        let missing: Vec<&PathBuf> = files.iter().filter(|file| !file.exists()).collect();
+
+        if missing.is_empty() || tokio::time::Instant::now() >= deadline {
+            return missing;
+        }
+
+        log::info!(
+            "Waiting for coverage files: '{}'.",
+            missing
+                .iter()
+                .map(|file| file.display().to_string())
+                .collect::<Vec<_>>()
+                .join("', '")
+        );
+        tokio::time::sleep_until(
+            deadline.min(tokio::time::Instant::now() + COVERAGE_POLL_INTERVAL),
+        )
+        .await;
+    }
 }
 
 pub async fn collect_from_path(
     db: &MantraDb,
     data_file: &Path,
+    tags: &[String],
+    path_prefix_map: &[PathPrefixRewrite],
+    id_case: IdCase,
 ) -> Result<CoverageChanges, CoverageError> {
     let data = std::fs::read_to_string(data_file).map_err(|_| {
         CoverageError::ReadingData(format!(
@@ -73,13 +265,87 @@ pub async fn collect_from_path(
         ))
     })?;
 
-    collect_from_str(db, &data).await
+    collect_from_str(db, &data, tags, path_prefix_map, id_case).await
 }
 
-pub async fn collect_from_str(db: &MantraDb, data: &str) -> Result<CoverageChanges, CoverageError> {
-    let coverage =
+/// Collects coverage from JSON data adhering to the `CoverageSchema`.
+/// The given tags are added to all tests, filepaths are rewritten using the given prefix map,
+/// and requirement IDs of covered traces are normalized to the given case.
+pub async fn collect_from_str(
+    db: &MantraDb,
+    data: &str,
+    tags: &[String],
+    path_prefix_map: &[PathPrefixRewrite],
+    id_case: IdCase,
+) -> Result<CoverageChanges, CoverageError> {
+    let mut coverage =
         serde_json::from_str::<CoverageSchema>(data).map_err(CoverageError::Deserialize)?;
+    add_tags(&mut coverage, tags);
+    rewrite_paths(&mut coverage, path_prefix_map);
+    normalize_ids(&mut coverage, id_case);
+
+    collect_from_schema(db, coverage).await
+}
+
+fn add_tags(coverage: &mut CoverageSchema, tags: &[String]) {
+    for test in coverage
+        .test_runs
+        .iter_mut()
+        .flat_map(|test_run| test_run.tests.iter_mut())
+    {
+        for tag in tags {
+            if !test.tags.contains(tag) {
+                test.tags.push(tag.clone());
+            }
+        }
+    }
+}
+
+/// Rewrites filepaths of tests and covered files using the given prefix map.
+fn rewrite_paths(coverage: &mut CoverageSchema, path_prefix_map: &[PathPrefixRewrite]) {
+    if path_prefix_map.is_empty() {
+        return;
+    }
+
+    for test in coverage
+        .test_runs
+        .iter_mut()
+        .flat_map(|test_run| test_run.tests.iter_mut())
+    {
+        if let Some(filepath) = PathPrefixRewrite::rewrite(path_prefix_map, &test.filepath) {
+            test.filepath = filepath;
+        }
+
+        for file in &mut test.covered_files {
+            if let Some(filepath) = PathPrefixRewrite::rewrite(path_prefix_map, &file.filepath) {
+                file.filepath = filepath;
+            }
+        }
+    }
+}
+
+fn normalize_ids(coverage: &mut CoverageSchema, id_case: IdCase) {
+    if id_case.is_preserve() {
+        return;
+    }
+
+    for trace in coverage
+        .test_runs
+        .iter_mut()
+        .flat_map(|test_run| test_run.tests.iter_mut())
+        .flat_map(|test| test.covered_files.iter_mut())
+        .flat_map(|file| file.covered_traces.iter_mut())
+    {
+        for req_id in &mut trace.req_ids {
+            *req_id = id_case.normalize(req_id);
+        }
+    }
+}
 
+pub async fn collect_from_schema(
+    db: &MantraDb,
+    coverage: CoverageSchema,
+) -> Result<CoverageChanges, CoverageError> {
     let mut changes = CoverageChanges {
         inserted: Vec::new(),
     };
@@ -116,6 +382,7 @@ pub async fn collect_from_str(db: &MantraDb, data: &str) -> Result<CoverageChang
                 &test.filepath,
                 test.line,
                 test.state,
+                &test.tags,
             )
             .await
             .map_err(CoverageError::Db)?;
@@ -169,6 +436,401 @@ pub async fn collect_from_str(db: &MantraDb, data: &str) -> Result<CoverageChang
     Ok(changes)
 }
 
+/// Collects coverage from a file created by `llvm-cov export`.
+///
+/// The export has no information about single tests,
+/// so one test run with one passed test is created that covers all executed lines.
+pub async fn collect_from_llvm_cov_path(
+    db: &MantraDb,
+    data_file: &Path,
+    base_path: Option<&Path>,
+    test_run: Option<&str>,
+    tags: &[String],
+    path_prefix_map: &[PathPrefixRewrite],
+) -> Result<CoverageChanges, CoverageError> {
+    let data = std::fs::read_to_string(data_file).map_err(|_| {
+        CoverageError::ReadingData(format!(
+            "Could not read coverage data from '{}'.",
+            data_file.display()
+        ))
+    })?;
+
+    let test_run_name = match test_run {
+        Some(name) => name.to_string(),
+        None => data_file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or("llvm-cov".to_string()),
+    };
+    let date = std::fs::metadata(data_file)
+        .and_then(|meta| meta.modified())
+        .map(OffsetDateTime::from)
+        .unwrap_or(OffsetDateTime::now_utc());
+
+    let mut coverage = llvm_cov_to_schema(&data, data_file, &test_run_name, date, base_path)?;
+    add_tags(&mut coverage, tags);
+    rewrite_paths(&mut coverage, path_prefix_map);
+
+    collect_from_schema(db, coverage).await
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct LlvmCovExport {
+    data: Vec<LlvmCovData>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct LlvmCovData {
+    #[serde(default)]
+    files: Vec<LlvmCovFile>,
+    #[serde(default)]
+    functions: Vec<LlvmCovFunction>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct LlvmCovFile {
+    filename: PathBuf,
+    /// Segments in the form `[line, col, count, has_count, is_region_entry, is_gap_region]`.
+    ///
+    /// **Note:** Older llvm-cov versions do not export `is_gap_region`.
+    #[serde(default)]
+    segments: Vec<Vec<serde_json::Value>>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct LlvmCovFunction {
+    count: u64,
+    /// Regions in the form `[line_start, col_start, line_end, col_end, count, file_id, expanded_file_id, kind]`.
+    #[serde(default)]
+    regions: Vec<Vec<u64>>,
+    #[serde(default)]
+    filenames: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LlvmCovSegment {
+    line: Line,
+    count: u64,
+    has_count: bool,
+    is_region_entry: bool,
+    is_gap_region: bool,
+}
+
+impl LlvmCovSegment {
+    fn try_from_raw(raw: &[serde_json::Value]) -> Option<Self> {
+        Some(Self {
+            line: Line::try_from(raw.first()?.as_u64()?).ok()?,
+            count: raw.get(2)?.as_u64()?,
+            has_count: raw.get(3)?.as_bool()?,
+            is_region_entry: raw.get(4)?.as_bool()?,
+            is_gap_region: raw.get(5).and_then(|v| v.as_bool()).unwrap_or(false),
+        })
+    }
+}
+
+fn llvm_cov_to_schema(
+    data: &str,
+    data_file: &Path,
+    test_run_name: &str,
+    date: OffsetDateTime,
+    base_path: Option<&Path>,
+) -> Result<CoverageSchema, CoverageError> {
+    let export = serde_json::from_str::<LlvmCovExport>(data).map_err(CoverageError::Deserialize)?;
+
+    let mut file_lines: HashMap<PathBuf, HashMap<Line, usize>> = HashMap::new();
+    let mut files_with_segments: HashSet<PathBuf> = HashSet::new();
+
+    for data in export.data {
+        for file in data.files {
+            let segments: Vec<LlvmCovSegment> = file
+                .segments
+                .iter()
+                .filter_map(|raw| LlvmCovSegment::try_from_raw(raw))
+                .collect();
+
+            if !segments.is_empty() {
+                files_with_segments.insert(file.filename.clone());
+            }
+
+            let lines = file_lines.entry(file.filename).or_default();
+
+            for covered_line in executed_lines(&segments) {
+                let hits = lines.entry(covered_line.line).or_default();
+                *hits = (*hits).max(covered_line.hits);
+            }
+        }
+
+        // Function regions are only used for files without segments,
+        // because regions of executed functions also contain not executed lines.
+        for function in data.functions {
+            if function.count == 0 {
+                continue;
+            }
+
+            for region in function.regions {
+                let (Some(start), Some(end), Some(count), Some(file_id)) =
+                    (region.first(), region.get(2), region.get(4), region.get(5))
+                else {
+                    continue;
+                };
+
+                let Some(filename) = usize::try_from(*file_id)
+                    .ok()
+                    .and_then(|id| function.filenames.get(id))
+                else {
+                    continue;
+                };
+
+                if *count == 0 || files_with_segments.contains(filename) {
+                    continue;
+                }
+
+                let lines = file_lines.entry(filename.clone()).or_default();
+                for line in *start..=*end {
+                    if let Ok(line) = Line::try_from(line) {
+                        let hits = lines.entry(line).or_default();
+                        *hits = (*hits).max(*count as usize);
+                    }
+                }
+            }
+        }
+    }
+
+    let covered_files = file_lines
+        .into_iter()
+        .filter(|(_, lines)| !lines.is_empty())
+        .map(|(filepath, lines)| {
+            let filepath = match base_path {
+                Some(base) => filepath
+                    .strip_prefix(base)
+                    .map(|p| p.to_path_buf())
+                    .unwrap_or(filepath),
+                None => filepath,
+            };
+            let mut covered_lines: Vec<CoveredLine> = lines
+                .into_iter()
+                .map(|(line, hits)| CoveredLine { line, hits })
+                .collect();
+            covered_lines.sort();
+
+            CoveredFile {
+                filepath,
+                covered_traces: Vec::new(),
+                covered_lines,
+            }
+        })
+        .collect();
+
+    Ok(CoverageSchema {
+        version: None,
+        test_runs: vec![TestRun {
+            name: test_run_name.to_string(),
+            date,
+            nr_of_tests: 1,
+            data: None,
+            logs: None,
+            tests: vec![Test {
+                name: test_run_name.to_string(),
+                filepath: data_file.to_path_buf(),
+                line: 0,
+                state: TestState::Passed,
+                covered_files,
+                tags: Vec::new(),
+            }],
+        }],
+    })
+}
+
+/// Collects test states from a JUnit XML test report.
+///
+/// All test cases of the report are added to one test run.
+/// A test case fails if it has a `<failure>` or `<error>` child, and is skipped if it has a `<skipped>` child.
+pub async fn collect_from_junit_path(
+    db: &MantraDb,
+    data_file: &Path,
+    test_run: Option<&str>,
+    tags: &[String],
+    path_prefix_map: &[PathPrefixRewrite],
+) -> Result<CoverageChanges, CoverageError> {
+    let data = std::fs::read_to_string(data_file).map_err(|_| {
+        CoverageError::ReadingData(format!(
+            "Could not read coverage data from '{}'.",
+            data_file.display()
+        ))
+    })?;
+
+    let file_date = std::fs::metadata(data_file)
+        .and_then(|meta| meta.modified())
+        .map(OffsetDateTime::from)
+        .unwrap_or(OffsetDateTime::now_utc());
+
+    let mut coverage = junit_to_schema(&data, data_file, test_run, file_date)?;
+    add_tags(&mut coverage, tags);
+    rewrite_paths(&mut coverage, path_prefix_map);
+
+    collect_from_schema(db, coverage).await
+}
+
+/// Converts a JUnit XML report into one test run.
+///
+/// The test run name is taken from `test_run`, the `name` of the root `<testsuites>` element, or the file stem.
+/// The test run date is the earliest `timestamp` of all test suites, or `file_date` if no suite has a timestamp.
+/// Test names are prefixed with the `classname` of the test case if set, because test names are only unique per class.
+fn junit_to_schema(
+    data: &str,
+    data_file: &Path,
+    test_run: Option<&str>,
+    file_date: OffsetDateTime,
+) -> Result<CoverageSchema, CoverageError> {
+    let doc = roxmltree::Document::parse(data).map_err(|err| {
+        CoverageError::ReadingData(format!(
+            "Invalid JUnit XML in '{}'. Cause: {}",
+            data_file.display(),
+            err
+        ))
+    })?;
+    let root = doc.root_element();
+
+    let test_run_name = match test_run {
+        Some(name) => name.to_string(),
+        None => root
+            .attribute("name")
+            .filter(|_| root.has_tag_name("testsuites"))
+            .map(|name| name.to_string())
+            .or_else(|| {
+                data_file
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+            })
+            .unwrap_or("junit".to_string()),
+    };
+    let date = doc
+        .descendants()
+        .filter(|node| node.has_tag_name("testsuite"))
+        .filter_map(|suite| suite.attribute("timestamp").and_then(junit_timestamp))
+        .min()
+        .unwrap_or(file_date);
+
+    let tests: Vec<Test> = doc
+        .descendants()
+        .filter(|node| node.has_tag_name("testcase"))
+        .map(|testcase| Test {
+            name: junit_test_name(testcase),
+            filepath: testcase
+                .attribute("file")
+                .map(PathBuf::from)
+                .unwrap_or(data_file.to_path_buf()),
+            line: testcase
+                .attribute("line")
+                .and_then(|line| line.parse().ok())
+                .unwrap_or_default(),
+            state: junit_test_state(testcase),
+            covered_files: Vec::new(),
+            tags: Vec::new(),
+        })
+        .collect();
+
+    Ok(CoverageSchema {
+        version: None,
+        test_runs: vec![TestRun {
+            name: test_run_name,
+            date,
+            nr_of_tests: u32::try_from(tests.len()).unwrap_or(u32::MAX),
+            data: None,
+            logs: None,
+            tests,
+        }],
+    })
+}
+
+fn junit_test_name(testcase: roxmltree::Node) -> String {
+    let name = testcase.attribute("name").unwrap_or_default();
+
+    match testcase.attribute("classname") {
+        Some(classname) if !classname.is_empty() => format!("{classname}::{name}"),
+        _ => name.to_string(),
+    }
+}
+
+fn junit_test_state(testcase: roxmltree::Node) -> TestState {
+    let mut state = TestState::Passed;
+
+    for child in testcase.children().filter(|child| child.is_element()) {
+        match child.tag_name().name() {
+            "failure" | "error" => return TestState::Failed,
+            "skipped" => {
+                let reason = child
+                    .attribute("message")
+                    .or(child.text())
+                    .map(str::trim)
+                    .filter(|reason| !reason.is_empty())
+                    .map(|reason| reason.to_string());
+                state = TestState::Skipped { reason };
+            }
+            _ => {}
+        }
+    }
+
+    state
+}
+
+/// Parses JUnit timestamps, which are given in ISO8601 format, but often without offset.
+/// Timestamps without offset are taken as UTC.
+fn junit_timestamp(timestamp: &str) -> Option<OffsetDateTime> {
+    let format = time::format_description::well_known::Iso8601::DEFAULT;
+
+    OffsetDateTime::parse(timestamp, &format)
+        .or_else(|_| {
+            time::PrimitiveDateTime::parse(timestamp, &format).map(|date| date.assume_utc())
+        })
+        .ok()
+}
+
+/// Determines executed lines from llvm-cov segments the same way `llvm-cov` does for its line coverage.
+///
+/// A line takes the count of the segment that is active at the start of the line,
+/// or the maximum count of region entries starting on this line.
+fn executed_lines(segments: &[LlvmCovSegment]) -> Vec<CoveredLine> {
+    let mut lines = Vec::new();
+
+    let (Some(first), Some(last)) = (segments.first(), segments.last()) else {
+        return lines;
+    };
+
+    let mut wrapped: Option<&LlvmCovSegment> = None;
+    let mut idx = 0;
+
+    for line in first.line..=last.line {
+        let line_start = idx;
+        while idx < segments.len() && segments[idx].line == line {
+            idx += 1;
+        }
+        let line_segments = &segments[line_start..idx];
+
+        let mut hits = wrapped.filter(|s| s.has_count).map(|s| s.count);
+        for segment in line_segments {
+            if segment.has_count && segment.is_region_entry && !segment.is_gap_region {
+                hits = Some(hits.unwrap_or_default().max(segment.count));
+            }
+        }
+
+        if let Some(hits) = hits {
+            if hits > 0 {
+                lines.push(CoveredLine {
+                    line,
+                    hits: hits as usize,
+                });
+            }
+        }
+
+        if let Some(last_segment) = line_segments.last() {
+            wrapped = Some(last_segment);
+        }
+    }
+
+    lines
+}
+
 async fn covered_lines_to_traces(
     db: &MantraDb,
     filepath: PathBuf,
@@ -178,10 +840,12 @@ async fn covered_lines_to_traces(
 
     let file = SlashPathBuf::from(filepath);
     let file_str = file.to_string();
+    let branch = db.branch();
 
     let trace_spans = sqlx::query!(
-        "select req_id, filepath, line, start, end from TraceSpans where filepath = $1",
+        "select req_id, filepath, line, start, end from TraceSpans where filepath = $1 and branch = $2",
         file_str,
+        branch,
     )
     .fetch_all(db.pool())
     .await
@@ -226,12 +890,189 @@ fn get_covered_traces(
     })
 }
 
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum CoverageCmd {
+    /// List traces that were not executed in a test run, grouped by file.
+    Report(UncoveredConfig),
+}
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct UncoveredConfig {
+    /// Name of the test run to check.
+    /// Default is the most recent test run.
+    #[arg(long)]
+    pub test_run: Option<String>,
+    /// Output format of the uncovered traces.
+    #[arg(long, value_enum, default_value_t = UncoveredFormat::Text)]
+    pub format: UncoveredFormat,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum UncoveredFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Traces that were not executed in a test run.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct UncoveredTraces {
+    pub test_run_name: String,
+    pub test_run_date: String,
+    pub files: Vec<UncoveredFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct UncoveredFile {
+    pub filepath: String,
+    pub traces: Vec<UncoveredTrace>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct UncoveredTrace {
+    pub req_id: ReqId,
+    pub line: Line,
+}
+
+impl UncoveredTraces {
+    /// Groups `(req_id, filepath, line)` entries by file.
+    /// Entries must be ordered by filepath.
+    fn from_entries(
+        test_run_name: String,
+        test_run_date: String,
+        entries: impl IntoIterator<Item = (ReqId, String, Line)>,
+    ) -> Self {
+        let mut files: Vec<UncoveredFile> = Vec::new();
+
+        for (req_id, filepath, line) in entries {
+            let trace = UncoveredTrace { req_id, line };
+
+            match files.last_mut() {
+                Some(file) if file.filepath == filepath => file.traces.push(trace),
+                _ => files.push(UncoveredFile {
+                    filepath,
+                    traces: vec![trace],
+                }),
+            }
+        }
+
+        Self {
+            test_run_name,
+            test_run_date,
+            files,
+        }
+    }
+}
+
+impl std::fmt::Display for UncoveredTraces {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.files.is_empty() {
+            return writeln!(
+                f,
+                "All traces were executed in test run '{}' from '{}'.",
+                self.test_run_name, self.test_run_date
+            );
+        }
+
+        writeln!(
+            f,
+            "Traces not executed in test run '{}' from '{}':",
+            self.test_run_name, self.test_run_date
+        )?;
+
+        for file in &self.files {
+            writeln!(f, "{}", file.filepath)?;
+            for trace in &file.traces {
+                writeln!(f, "- `{}` at line '{}'", trace.req_id, trace.line)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub async fn report(db: &MantraDb, cfg: &UncoveredConfig) -> Result<(), CoverageError> {
+    let uncovered = uncovered_traces(db, cfg.test_run.as_deref()).await?;
+
+    match cfg.format {
+        UncoveredFormat::Text => print!("{uncovered}"),
+        UncoveredFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&uncovered).map_err(CoverageError::Serialize)?
+        ),
+    }
+
+    Ok(())
+}
+
+/// Returns all traces that were not executed in the given test run.
+/// The most recent test run is used if no test run name is given.
+///
+/// If multiple test runs have the same name, the most recent one is used.
+pub async fn uncovered_traces(
+    db: &MantraDb,
+    test_run: Option<&str>,
+) -> Result<UncoveredTraces, CoverageError> {
+    let branch = db.branch();
+
+    let test_run_record = sqlx::query!(
+        "select name, date from TestRuns where branch = $1 and ($2 is null or name = $2) order by date desc limit 1",
+        branch,
+        test_run
+    )
+    .fetch_optional(db.pool())
+    .await
+    .map_err(|err| CoverageError::Db(DbError::Query(err.to_string())))?
+    .ok_or_else(|| CoverageError::NoTestRun(test_run.map(|name| name.to_string())))?;
+
+    let records = sqlx::query!(
+        r#"
+        select t.req_id, t.filepath, t.line as "line: u32"
+        from Traces t
+        where t.branch = $1
+        and not exists (
+            select 1 from TestCoverage c
+            where c.req_id = t.req_id
+            and c.branch = t.branch
+            and c.trace_filepath = t.filepath
+            and c.trace_line = t.line
+            and c.test_run_name = $2
+            and c.test_run_date = $3
+        )
+        order by t.filepath, t.line, t.req_id
+        "#,
+        branch,
+        test_run_record.name,
+        test_run_record.date
+    )
+    .fetch_all(db.pool())
+    .await
+    .map_err(|err| CoverageError::Db(DbError::Query(err.to_string())))?;
+
+    Ok(UncoveredTraces::from_entries(
+        test_run_record.name,
+        test_run_record.date,
+        records
+            .into_iter()
+            .map(|record| (record.req_id, record.filepath, record.line)),
+    ))
+}
+
 #[cfg(test)]
 mod test {
     use intervaltree::Element;
-    use mantra_schema::coverage::{CoveredFileTrace, CoveredLine};
+    use mantra_schema::coverage::{CoveredFileTrace, CoveredLine, TestState};
+
+    use crate::{
+        cfg::IdCase,
+        test_util::{req, test_db},
+    };
 
-    use super::get_covered_traces;
+    use super::{
+        add_tags, collect, collect_from_str, coverage_files, executed_lines, get_covered_traces,
+        junit_to_schema, llvm_cov_to_schema, wait_for_files, CoverageError, LlvmCovSegment,
+        PathPrefixRewrite, UncoveredTraces,
+    };
 
     #[test]
     fn disjoint_traces() {
@@ -291,4 +1132,427 @@ mod test {
             "The same trace was matched twice."
         );
     }
+
+    #[test]
+    fn llvm_cov_executed_lines() {
+        let segment = |line, count, has_count, is_region_entry| LlvmCovSegment {
+            line,
+            count,
+            has_count,
+            is_region_entry,
+            is_gap_region: false,
+        };
+
+        // fn body from line 2 to 6 executed once, branch body from line 3 to 5 never executed
+        let segments = vec![
+            segment(2, 1, true, true),
+            segment(3, 0, true, true),
+            segment(5, 1, true, false),
+            segment(6, 0, false, false),
+        ];
+
+        let lines: Vec<u32> = executed_lines(&segments)
+            .into_iter()
+            .map(|l| l.line)
+            .collect();
+
+        assert_eq!(
+            lines,
+            vec![2, 3, 6],
+            "Executed lines not correctly derived from segments."
+        );
+    }
+
+    #[test]
+    fn llvm_cov_regions_without_segments() {
+        // `src/a.rs` has no segments, so all executed regions of its functions are used
+        let export = r#"{"data": [{
+            "files": [
+                {"filename": "src/a.rs", "segments": []},
+                {"filename": "src/b.rs", "segments": [[1, 1, 1, true, true, false], [2, 1, 0, false, false, false]]}
+            ],
+            "functions": [
+                {"count": 1, "filenames": ["src/a.rs"], "regions": [[2, 1, 3, 2, 1, 0, 0, 0], [10, 1, 11, 2, 4, 0, 0, 0]]},
+                {"count": 2, "filenames": ["src/a.rs"], "regions": [[20, 1, 20, 9, 2, 0, 0, 0]]},
+                {"count": 1, "filenames": ["src/b.rs"], "regions": [[1, 1, 5, 2, 1, 0, 0, 0]]}
+            ]
+        }]}"#;
+
+        let coverage = llvm_cov_to_schema(
+            export,
+            std::path::Path::new("coverage.json"),
+            "llvm-cov",
+            time::OffsetDateTime::UNIX_EPOCH,
+            None,
+        )
+        .unwrap();
+
+        let mut files: Vec<(String, Vec<u32>)> = coverage.test_runs[0].tests[0]
+            .covered_files
+            .iter()
+            .map(|file| {
+                (
+                    file.filepath.display().to_string(),
+                    file.covered_lines.iter().map(|l| l.line).collect(),
+                )
+            })
+            .collect();
+        files.sort();
+
+        assert_eq!(
+            files,
+            vec![
+                ("src/a.rs".to_string(), vec![2, 3, 10, 11, 20]),
+                ("src/b.rs".to_string(), vec![1, 2]),
+            ],
+            "Later regions of a file without segments skipped, or regions used despite segments."
+        );
+    }
+
+    #[test]
+    fn uncovered_traces_grouped_by_file() {
+        let uncovered = UncoveredTraces::from_entries(
+            "unit-tests".to_string(),
+            "2024-05-01T10:00:00Z".to_string(),
+            vec![
+                ("first".to_string(), "src/a.rs".to_string(), 3),
+                ("second".to_string(), "src/a.rs".to_string(), 10),
+                ("first".to_string(), "src/b.rs".to_string(), 1),
+            ],
+        );
+
+        assert_eq!(uncovered.files.len(), 2, "Traces not grouped by file.");
+        assert_eq!(
+            uncovered.files.first().unwrap().traces.len(),
+            2,
+            "Traces of the first file not grouped together."
+        );
+    }
+
+    #[test]
+    fn config_tags_added_to_tests() {
+        let mut coverage: mantra_schema::coverage::CoverageSchema = serde_json::from_str(
+            r#"{
+                "version": null,
+                "test-runs": [{
+                    "name": "run",
+                    "date": "2024-05-01T10:00:00Z",
+                    "nr-of-tests": 1,
+                    "data": null,
+                    "logs": null,
+                    "tests": [{
+                        "name": "test",
+                        "filepath": "src/lib.rs",
+                        "line": 1,
+                        "state": "passed",
+                        "tags": ["unit"]
+                    }]
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        add_tags(&mut coverage, &["unit".to_string(), "fast".to_string()]);
+
+        assert_eq!(
+            coverage.test_runs[0].tests[0].tags,
+            vec!["unit".to_string(), "fast".to_string()],
+            "Config tags not merged into test tags."
+        );
+    }
+
+    #[tokio::test]
+    async fn mixed_case_coverage_normalized() {
+        let db = test_db("coverage_id_case").await;
+
+        db.add_reqs(vec![req("a", &[])]).await.unwrap();
+        db.add_traces(
+            std::path::Path::new("src/lib.rs"),
+            &[mantra_schema::traces::TraceEntry {
+                ids: vec!["a".to_string()],
+                line: 1,
+                line_span: None,
+                item_name: None,
+                item_path: None,
+                test_name: None,
+            }],
+            1,
+        )
+        .await
+        .unwrap();
+
+        let coverage = r#"{
+            "test-runs": [{
+                "name": "run",
+                "date": "2024-05-01T10:00:00Z",
+                "nr-of-tests": 1,
+                "tests": [{
+                    "name": "test",
+                    "filepath": "tests/a.rs",
+                    "line": 1,
+                    "state": "passed",
+                    "covered-files": [{
+                        "filepath": "src/lib.rs",
+                        "covered-traces": [{ "req-ids": ["A"], "line": 1 }]
+                    }]
+                }]
+            }]
+        }"#;
+
+        let changes = collect_from_str(&db, coverage, &[], &[], IdCase::Lower).await;
+
+        assert_eq!(
+            changes.unwrap().inserted,
+            vec![crate::db::TracePk {
+                req_id: "a".to_string(),
+                filepath: "src/lib.rs".into(),
+                line: 1,
+            }],
+            "Covered requirement ID not normalized to the configured case."
+        );
+    }
+
+    #[tokio::test]
+    async fn prefixed_path_matches_after_rewrite() {
+        let db = test_db("prefix_map").await;
+
+        db.add_reqs(vec![req("a", &[])]).await.unwrap();
+        db.add_traces(
+            std::path::Path::new("src/lib.rs"),
+            &[mantra_schema::traces::TraceEntry {
+                ids: vec!["a".to_string()],
+                line: 1,
+                line_span: None,
+                item_name: None,
+                item_path: None,
+                test_name: None,
+            }],
+            1,
+        )
+        .await
+        .unwrap();
+
+        let coverage = r#"{
+            "version": null,
+            "test-runs": [{
+                "name": "run",
+                "date": "2024-05-01T10:00:00Z",
+                "nr-of-tests": 1,
+                "data": null,
+                "logs": null,
+                "tests": [{
+                    "name": "test",
+                    "filepath": "/builds/project/tests/a.rs",
+                    "line": 1,
+                    "state": "passed",
+                    "covered-files": [{
+                        "filepath": "/builds/project/src/lib.rs",
+                        "covered-traces": [{ "req-ids": ["a"], "line": 1 }]
+                    }]
+                }]
+            }]
+        }"#;
+        let prefix_map = vec![PathPrefixRewrite {
+            from: "/builds/project".into(),
+            to: "".into(),
+        }];
+
+        let changes = collect_from_str(&db, coverage, &[], &prefix_map, IdCase::Preserve).await;
+
+        assert_eq!(
+            changes.unwrap().inserted,
+            vec![crate::db::TracePk {
+                req_id: "a".to_string(),
+                filepath: "src/lib.rs".into(),
+                line: 1,
+            }],
+            "Rewritten coverage path does not match the trace."
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_coverage_file() {
+        let db = test_db("missing_cov").await;
+        let cfg: super::Config = toml::from_str(r#"files = ["shard-2/coverage.json"]"#).unwrap();
+
+        let failed = collect(&db, &cfg, false, IdCase::Preserve).await;
+        let skipped = collect(&db, &cfg, true, IdCase::Preserve).await;
+
+        assert!(
+            matches!(failed, Err(CoverageError::MissingFile(ref path)) if path.ends_with("shard-2/coverage.json")),
+            "Missing coverage file not reported with its path."
+        );
+        assert!(skipped.is_ok(), "Missing coverage file not skipped.");
+    }
+
+    #[tokio::test]
+    async fn wait_for_late_coverage_file() {
+        let late_file =
+            std::env::temp_dir().join(format!("mantra_late_cov_test_{}.json", std::process::id()));
+        let missing_file = std::env::temp_dir().join("mantra_never_written_cov.json");
+        let files = vec![late_file.clone()];
+
+        let writer = {
+            let late_file = late_file.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+                std::fs::write(late_file, "{}").unwrap();
+            })
+        };
+        let still_missing = wait_for_files(&files, std::time::Duration::from_secs(10)).await;
+        writer.await.unwrap();
+        let _ = std::fs::remove_file(&late_file);
+
+        assert!(
+            still_missing.is_empty(),
+            "Waiting stopped before the coverage file was written."
+        );
+
+        let start = std::time::Instant::now();
+        let missing_files = vec![missing_file.clone()];
+        let timed_out = wait_for_files(&missing_files, std::time::Duration::from_millis(600)).await;
+        assert_eq!(
+            timed_out,
+            vec![&missing_file],
+            "Missing coverage file not returned after the timeout."
+        );
+        assert!(
+            start.elapsed() < std::time::Duration::from_secs(5),
+            "Waiting did not stop at the timeout."
+        );
+    }
+
+    #[test]
+    fn coverage_file_patterns() {
+        let dir = std::env::temp_dir().join(format!("mantra_cov_glob_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for shard in ["coverage-1.json", "coverage-2.json", "other.json"] {
+            std::fs::write(dir.join(shard), "{}").unwrap();
+        }
+
+        let files = coverage_files(&[
+            dir.join("coverage-*.json"),
+            dir.join("missing-*.json"),
+            dir.join("literal.json"),
+        ]);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            files.unwrap(),
+            vec![
+                dir.join("coverage-1.json"),
+                dir.join("coverage-2.json"),
+                dir.join("literal.json"),
+            ],
+            "Glob not expanded, or literal filepath not kept."
+        );
+    }
+
+    #[test]
+    fn junit_test_states() {
+        let junit = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="ci">
+  <testsuite name="auth" tests="2" timestamp="2024-05-02T08:00:00">
+    <testcase classname="auth" name="login" file="tests/auth.py" line="12"/>
+    <testcase classname="auth" name="logout">
+      <failure message="assertion failed">expected true</failure>
+    </testcase>
+  </testsuite>
+  <testsuite name="session" tests="2" timestamp="2024-05-01T10:30:00Z">
+    <testcase classname="session" name="expire">
+      <skipped message="flaky"/>
+    </testcase>
+    <testcase classname="session" name="refresh">
+      <error type="panic"/>
+    </testcase>
+  </testsuite>
+</testsuites>"#;
+        let file_date = time::macros::datetime!(2024-06-01 0:00 UTC);
+
+        let coverage = junit_to_schema(
+            junit,
+            std::path::Path::new("target/junit.xml"),
+            None,
+            file_date,
+        )
+        .unwrap();
+
+        let test_run = &coverage.test_runs[0];
+        assert_eq!(coverage.test_runs.len(), 1, "Suites not in one test run.");
+        assert_eq!(test_run.name, "ci", "Test run not named after the report.");
+        assert_eq!(
+            test_run.date,
+            time::macros::datetime!(2024-05-01 10:30 UTC),
+            "Test run date not taken from the earliest suite."
+        );
+        assert_eq!(test_run.nr_of_tests, 4, "Wrong number of tests.");
+
+        let states: Vec<(&str, &TestState)> = test_run
+            .tests
+            .iter()
+            .map(|test| (test.name.as_str(), &test.state))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                ("auth::login", &TestState::Passed),
+                ("auth::logout", &TestState::Failed),
+                (
+                    "session::expire",
+                    &TestState::Skipped {
+                        reason: Some("flaky".to_string())
+                    }
+                ),
+                ("session::refresh", &TestState::Failed),
+            ],
+            "Wrong test states."
+        );
+        assert_eq!(
+            (test_run.tests[0].filepath.as_path(), test_run.tests[0].line),
+            (std::path::Path::new("tests/auth.py"), 12),
+            "Test location not taken from the test case."
+        );
+        assert_eq!(
+            test_run.tests[1].filepath,
+            std::path::Path::new("target/junit.xml"),
+            "Report file not used for test cases without location."
+        );
+    }
+
+    #[tokio::test]
+    async fn junit_duplicate_test_names() {
+        let junit = r#"<testsuites name="ci">
+  <testsuite name="auth">
+    <testcase classname="auth.LoginTest" name="setup"/>
+    <testcase classname="auth.LogoutTest" name="setup">
+      <failure/>
+    </testcase>
+  </testsuite>
+</testsuites>"#;
+        let db = test_db("junit_duplicates").await;
+
+        let coverage = junit_to_schema(
+            junit,
+            std::path::Path::new("target/junit.xml"),
+            None,
+            time::macros::datetime!(2024-06-01 0:00 UTC),
+        )
+        .unwrap();
+        super::collect_from_schema(&db, coverage).await.unwrap();
+
+        let tests: Vec<(String, bool)> =
+            sqlx::query_as("select name, passed from Tests order by name")
+                .fetch_all(db.pool())
+                .await
+                .unwrap();
+
+        assert_eq!(
+            tests,
+            vec![
+                ("auth.LoginTest::setup".to_string(), true),
+                ("auth.LogoutTest::setup".to_string(), false),
+            ],
+            "Test cases with the same name in different classes not stored separately."
+        );
+    }
 }
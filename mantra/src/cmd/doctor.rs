@@ -0,0 +1,457 @@
+use std::path::Path;
+
+use ignore::WalkBuilder;
+
+use crate::{
+    cfg::{MantraConfigFile, MantraConfigPath},
+    db::{self, MantraDb},
+};
+
+use super::{requirements, trace::TraceKind};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DoctorError {
+    #[error("'{}' of '{}' checks failed.", .failed, .total)]
+    Failed { failed: usize, total: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    /// The setup works, but the result is likely not what is intended.
+    Warn,
+    Fail,
+}
+
+/// Result of one diagnostic check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    pub details: String,
+    /// Actionable hint to fix a failed or suspicious check.
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: impl Into<String>, details: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: CheckStatus::Pass,
+            details: details.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: impl Into<String>, details: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: CheckStatus::Warn,
+            details: details.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: impl Into<String>, details: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: CheckStatus::Fail,
+            details: details.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+impl std::fmt::Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let marker = match self.status {
+            CheckStatus::Pass => "[ok]  ",
+            CheckStatus::Warn => "[warn]",
+            CheckStatus::Fail => "[fail]",
+        };
+        write!(f, "{marker} {}: {}", self.name, self.details)?;
+
+        if let Some(hint) = &self.hint {
+            write!(f, "\n       hint: {hint}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs all checks, and prints them as checklist.
+///
+/// Returns an error if at least one check failed.
+pub async fn doctor(db_cfg: &db::Config, cfg: &MantraConfigPath) -> Result<(), DoctorError> {
+    let checks = run_checks(db_cfg, cfg).await;
+
+    for check in &checks {
+        println!("{check}");
+    }
+
+    let failed = checks
+        .iter()
+        .filter(|check| check.status == CheckStatus::Fail)
+        .count();
+
+    if failed == 0 {
+        println!("\nAll checks passed.");
+        Ok(())
+    } else {
+        Err(DoctorError::Failed {
+            failed,
+            total: checks.len(),
+        })
+    }
+}
+
+pub async fn run_checks(db_cfg: &db::Config, cfg: &MantraConfigPath) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let config_file = check_config_file(&cfg.filepath, &mut checks).await;
+    if let Some(config_file) = &config_file {
+        check_config_paths(config_file, &mut checks);
+    }
+
+    match MantraDb::new(db_cfg).await {
+        Ok(db) => {
+            checks.push(Check::pass(
+                "Database",
+                "Connected, and the database schema is up to date.",
+            ));
+            check_db_content(&db, config_file.as_ref(), &mut checks).await;
+        }
+        Err(err) => checks.push(Check::fail(
+            "Database",
+            format!("Could not open the database. Cause: {err}"),
+            "Check the database URL set with `--url` or `MANTRA_DB`, and that the directory is writable.",
+        )),
+    }
+
+    checks
+}
+
+async fn check_config_file(filepath: &Path, checks: &mut Vec<Check>) -> Option<MantraConfigFile> {
+    let name = "Configuration";
+
+    let content = match tokio::fs::read_to_string(filepath).await {
+        Ok(content) => content,
+        Err(err) => {
+            checks.push(Check::fail(
+                name,
+                format!("Could not read '{}'. Cause: {err}", filepath.display()),
+                "Create a `mantra.toml` file, or pass the path to your configuration file.",
+            ));
+            return None;
+        }
+    };
+
+    let config = match toml::from_str::<MantraConfigFile>(&content) {
+        Ok(config) => config,
+        Err(err) => {
+            checks.push(Check::fail(
+                name,
+                format!("Could not parse '{}'. Cause: {err}", filepath.display()),
+                "Compare your configuration with the file structure described in the README.",
+            ));
+            return None;
+        }
+    };
+
+    checks.push(Check::pass(
+        name,
+        format!("'{}' is valid.", filepath.display()),
+    ));
+
+    if let Err(err) = config.id_pattern() {
+        checks.push(Check::fail(
+            "ID pattern",
+            format!("The ID pattern is not a valid regular expression. Cause: {err}"),
+            "Fix the regular expression set for `id-pattern`.",
+        ));
+    }
+    if let Err(err) = config.manual_rules() {
+        checks.push(Check::fail(
+            "Manual rules",
+            format!("A manual rule is not a valid glob. Cause: {err}"),
+            "Fix the globs set for `manual-rules`.",
+        ));
+    }
+
+    Some(config)
+}
+
+fn check_config_paths(config: &MantraConfigFile, checks: &mut Vec<Check>) {
+    if config.requirements.is_empty() {
+        checks.push(Check::warn(
+            "Requirement sources",
+            "No requirement source is configured.",
+            "Add a `[[requirements]]` section to your configuration.",
+        ));
+    }
+
+    for source in &config.requirements {
+        let check = match source {
+            requirements::Format::FromWiki(wiki) => check_dir(
+                "Requirement source",
+                &wiki.root,
+                Some("md"),
+                "Set `root` to the directory containing your Markdown requirements.",
+            ),
+            requirements::Format::FromSchema { files } => check_files(
+                "Requirement source",
+                files,
+                "Set `files` to JSON or TOML files adhering to the `RequirementSchema`.",
+            ),
+        };
+        checks.push(check);
+    }
+
+    if config.traces.is_empty() {
+        checks.push(Check::warn(
+            "Trace sources",
+            "No trace source is configured.",
+            "Add a `[[traces]]` section to your configuration.",
+        ));
+    }
+
+    for source in &config.traces {
+        let check = match source {
+            TraceKind::FromSource(source_cfg) => check_dir(
+                "Trace source",
+                &source_cfg.root,
+                None,
+                "Set `root` to the directory containing your source code.",
+            ),
+            TraceKind::FromSchema { files } => check_files(
+                "Trace source",
+                files,
+                "Set `files` to JSON files adhering to the `TraceSchema`.",
+            ),
+            TraceKind::FromBinary(binary_cfg) => check_files(
+                "Trace source",
+                &[binary_cfg.binary.clone(), binary_cfg.symbol_map.clone()],
+                "Build the binary before running mantra, and check the path of the symbol map.",
+            ),
+            TraceKind::FromGit(git_cfg) => check_dir(
+                "Trace source",
+                git_cfg.repository.as_deref().unwrap_or(Path::new("")),
+                None,
+                "Set `repository` to the path of your git repository.",
+            ),
+        };
+        checks.push(check);
+    }
+
+    if let Some(coverage) = &config.coverage {
+        let hint = "Run your tests to create the coverage files before running mantra.";
+
+        checks.push(match super::expand_files(&coverage.files, |_| true) {
+            Ok(files) => check_files("Coverage source", &files, hint),
+            Err(err) => Check::fail(
+                "Coverage source",
+                format!("Invalid glob pattern: {err}"),
+                hint,
+            ),
+        });
+    }
+
+    if let Some(review) = &config.review {
+        let hint = "Set `files` to existing review files, directories, or glob patterns.";
+
+        checks.push(match super::expand_files(&review.files, |_| true) {
+            Ok(files) => check_files("Review source", &files, hint),
+            Err(err) => Check::fail(
+                "Review source",
+                format!("Invalid glob pattern: {err}"),
+                hint,
+            ),
+        });
+    }
+}
+
+/// Checks that the directory exists and contains at least one file with the given extension.
+fn check_dir(name: &str, root: &Path, extension: Option<&str>, hint: &str) -> Check {
+    let root = if root == Path::new("") {
+        Path::new("./")
+    } else {
+        root
+    };
+
+    if !root.exists() {
+        return Check::fail(name, format!("'{}' does not exist.", root.display()), hint);
+    }
+
+    let has_file = WalkBuilder::new(root).build().flatten().any(|entry| {
+        entry.file_type().is_some_and(|t| t.is_file())
+            && extension
+                .is_none_or(|ext| entry.path().extension().and_then(|e| e.to_str()) == Some(ext))
+    });
+
+    match (has_file, extension) {
+        (true, _) => Check::pass(name, format!("'{}' contains files.", root.display())),
+        (false, Some(ext)) => Check::fail(
+            name,
+            format!("'{}' contains no `.{ext}` files.", root.display()),
+            hint,
+        ),
+        (false, None) => Check::fail(
+            name,
+            format!("'{}' contains no files.", root.display()),
+            hint,
+        ),
+    }
+}
+
+/// Checks that all files exist.
+fn check_files(name: &str, files: &[std::path::PathBuf], hint: &str) -> Check {
+    if files.is_empty() {
+        return Check::warn(name, "No files are configured.", hint);
+    }
+
+    let missing: Vec<String> = files
+        .iter()
+        .filter(|file| !file.is_file())
+        .map(|file| format!("'{}'", file.display()))
+        .collect();
+
+    if missing.is_empty() {
+        Check::pass(name, format!("All '{}' files exist.", files.len()))
+    } else {
+        Check::fail(name, format!("Missing {}.", missing.join(", ")), hint)
+    }
+}
+
+async fn check_db_content(
+    db: &MantraDb,
+    config: Option<&MantraConfigFile>,
+    checks: &mut Vec<Check>,
+) {
+    match db.is_valid().await {
+        Ok(()) => checks.push(Check::pass(
+            "Validation",
+            "The collected data is valid.",
+        )),
+        Err(err) => checks.push(Check::fail(
+            "Validation",
+            err.to_string(),
+            "Remove traces to deprecated requirements or acknowledge them in `suppressions`, and make sure the requirement hierarchy has no cycles.",
+        )),
+    }
+
+    let req_cnt = sqlx::query!(r#"select count(*) as "cnt!: i64" from Requirements"#)
+        .fetch_one(db.pool())
+        .await
+        .map(|r| r.cnt)
+        .unwrap_or_default();
+    if req_cnt == 0 {
+        checks.push(Check::fail(
+            "Requirements",
+            "No requirements are stored in the database.",
+            "Run `mantra collect` and check that your requirement sources contain requirements.",
+        ));
+    } else {
+        checks.push(Check::pass(
+            "Requirements",
+            format!("'{req_cnt}' requirements are stored."),
+        ));
+    }
+
+    let branch = db.branch();
+    let trace_cnt = sqlx::query!(
+        r#"select count(*) as "cnt!: i64" from Traces where branch = $1"#,
+        branch
+    )
+    .fetch_one(db.pool())
+    .await
+    .map(|r| r.cnt)
+    .unwrap_or_default();
+    if trace_cnt == 0 {
+        checks.push(Check::warn(
+            "Traces",
+            "No traces to existing requirements are stored in the database.",
+            "Run `mantra collect`, and reference requirement IDs in your code, e.g. `#[req(<id>)]` in Rust.",
+        ));
+    } else {
+        checks.push(Check::pass(
+            "Traces",
+            format!("'{trace_cnt}' traces are stored."),
+        ));
+    }
+
+    let unrelated_ids: Vec<String> = sqlx::query!(
+        "select distinct req_id from UnrelatedTraces where branch = $1 order by req_id",
+        branch
+    )
+    .fetch_all(db.pool())
+    .await
+    .map(|records| records.into_iter().map(|r| r.req_id).collect())
+    .unwrap_or_default();
+    if !unrelated_ids.is_empty() {
+        let examples: Vec<&str> = unrelated_ids.iter().take(5).map(|id| id.as_str()).collect();
+        let more = if unrelated_ids.len() > examples.len() {
+            format!(" and '{}' more", unrelated_ids.len() - examples.len())
+        } else {
+            String::new()
+        };
+
+        checks.push(Check::fail(
+            "Trace IDs",
+            format!(
+                "Traces reference unknown requirements: `{}`{more}.",
+                examples.join("`, `")
+            ),
+            "Your trace IDs don't match any requirement. Check the ID format, e.g. case, separators, and prefixes.",
+        ));
+    }
+
+    if config.is_some_and(|cfg| cfg.coverage.is_some()) {
+        let test_run_cnt = sqlx::query!(
+            r#"select count(*) as "cnt!: i64" from TestRuns where branch = $1"#,
+            branch
+        )
+        .fetch_one(db.pool())
+        .await
+        .map(|r| r.cnt)
+        .unwrap_or_default();
+
+        if test_run_cnt == 0 {
+            checks.push(Check::warn(
+                "Coverage",
+                "Coverage is configured, but no test run is stored.",
+                "Run `mantra collect` after your tests created the coverage files.",
+            ));
+        } else {
+            checks.push(Check::pass(
+                "Coverage",
+                format!("'{test_run_cnt}' test runs are stored."),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::{check_files, CheckStatus};
+
+    #[test]
+    fn missing_file_fails_check() {
+        let check = check_files(
+            "Coverage source",
+            &[PathBuf::from(file!()), PathBuf::from("does/not/exist.json")],
+            "hint",
+        );
+
+        assert_eq!(
+            check.status,
+            CheckStatus::Fail,
+            "Missing file not detected."
+        );
+        assert!(
+            check.details.contains("does/not/exist.json"),
+            "Missing file not named in details."
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use std::path::PathBuf;
+
+use mantra_schema::{
+    reviews::REVIEW_DATE_FORMAT,
+    sbor::{SborProject, SborRequirement, SborReview, SborSchema, SborStatus, SborTest, SborTrace},
+};
+use time::OffsetDateTime;
+
+use crate::{
+    cfg::Project,
+    cmd::report::{ReportError, RequirementInfo},
+    db::{DbError, MantraDb},
+};
+
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum ExportCmd {
+    /// Export a software bill of requirements (SBOR) adhering to the `SborSchema`.
+    Sbor(SborConfig),
+}
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct SborConfig {
+    /// File the SBOR is written to.
+    /// The SBOR is printed to stdout if no file is set.
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+    #[command(flatten)]
+    pub project: Project,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ExportError {
+    #[error("{}", .0)]
+    Db(DbError),
+    #[error("Could not read the requirements. Cause: {}", .0)]
+    Requirements(ReportError),
+    #[error("Could not serialize the export. Cause: {}", .0)]
+    Serialize(serde_json::Error),
+    #[error("Could not write the export to '{}'.", .0.display())]
+    Write(PathBuf),
+}
+
+pub async fn export(db: &MantraDb, cmd: &ExportCmd) -> Result<(), ExportError> {
+    match cmd {
+        ExportCmd::Sbor(cfg) => sbor(db, cfg).await,
+    }
+}
+
+async fn sbor(db: &MantraDb, cfg: &SborConfig) -> Result<(), ExportError> {
+    let sbor = sbor_from_db(db, &cfg.project).await?;
+
+    let content = serde_json::to_string_pretty(&sbor).map_err(ExportError::Serialize)?;
+
+    match &cfg.output {
+        Some(output) => tokio::fs::write(output, content)
+            .await
+            .map_err(|_| ExportError::Write(output.clone())),
+        None => {
+            println!("{content}");
+            Ok(())
+        }
+    }
+}
+
+/// Creates the software bill of requirements from the collected data.
+pub async fn sbor_from_db(db: &MantraDb, project: &Project) -> Result<SborSchema, ExportError> {
+    let req_ids = sqlx::query!("select id from Requirements order by id")
+        .fetch_all(db.pool())
+        .await
+        .map_err(|err| ExportError::Requirements(ReportError::Db(err)))?;
+
+    let mut requirements = Vec::new();
+    for record in req_ids {
+        let req = RequirementInfo::try_from(db, record.id, None)
+            .await
+            .map_err(ExportError::Requirements)?;
+        requirements.push(SborRequirement::from(req));
+    }
+
+    Ok(SborSchema {
+        version: None,
+        project: SborProject {
+            name: project.name.clone(),
+            version: project.version.clone(),
+            repository: project.repository.clone(),
+        },
+        creation_date: OffsetDateTime::now_utc(),
+        requirements,
+    })
+}
+
+impl From<RequirementInfo> for SborRequirement {
+    fn from(value: RequirementInfo) -> Self {
+        let status = sbor_status(&value);
+        let traces = value
+            .trace_info
+            .direct_traces
+            .into_iter()
+            .map(|trace| SborTrace {
+                filepath: PathBuf::from(trace.filepath),
+                line: trace.line,
+                item_name: trace.item_name,
+            })
+            .collect();
+        let tests = value
+            .test_coverage_info
+            .direct_coverage
+            .into_iter()
+            .flat_map(|test_run| {
+                test_run.tests.into_iter().map(move |test| SborTest {
+                    test_run: test_run.name.clone(),
+                    test_run_date: test_run.date,
+                    name: test.name,
+                    passed: test.passed,
+                })
+            })
+            .collect();
+        let reviews = value
+            .verified_info
+            .into_iter()
+            .map(|verified| SborReview {
+                name: verified.review_name,
+                date: verified
+                    .review_date
+                    .format(REVIEW_DATE_FORMAT)
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        Self {
+            id: value.meta.id,
+            title: value.meta.title,
+            origin: value.meta.origin,
+            parents: value.meta.parents.unwrap_or_default(),
+            state: value.meta.state,
+            priority: value.meta.priority,
+            status,
+            traces,
+            tests,
+            reviews,
+        }
+    }
+}
+
+fn sbor_status(req: &RequirementInfo) -> SborStatus {
+    let coverage = &req.test_coverage_info;
+
+    if req.meta.deprecated {
+        SborStatus::Deprecated
+    } else if req.meta.manual.unwrap_or_default() {
+        if req.verified_info.is_empty() {
+            SborStatus::Unverified
+        } else {
+            SborStatus::Verified
+        }
+    } else if req.meta.untraceable {
+        SborStatus::Untraceable
+    } else if !req.trace_info.traced {
+        SborStatus::Untraced
+    } else if !coverage.covered {
+        SborStatus::Uncovered
+    } else if coverage.passed {
+        SborStatus::Passed
+    } else {
+        SborStatus::Failed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use mantra_schema::{
+        sbor::{SborStatus, SborTrace},
+        traces::{LineSpan, TraceEntry},
+    };
+
+    use crate::test_util::{req, test_db};
+
+    use super::sbor_from_db;
+
+    #[tokio::test]
+    async fn sbor_lists_requirement_status() {
+        let db = test_db("sbor").await;
+        let mut org = req("org", &[]);
+        org.untraceable = true;
+        db.add_reqs(vec![req("a", &[]), req("a.b", &["a"]), org])
+            .await
+            .unwrap();
+        db.add_traces(
+            std::path::Path::new("src/lib.rs"),
+            &[TraceEntry {
+                ids: vec!["a.b".to_string()],
+                line: 3,
+                line_span: Some(LineSpan { start: 4, end: 6 }),
+                item_name: Some("handler".to_string()),
+                item_path: None,
+                test_name: None,
+            }],
+            1,
+        )
+        .await
+        .unwrap();
+
+        let sbor = sbor_from_db(&db, &Default::default()).await;
+
+        let sbor = sbor.unwrap();
+        let status: Vec<(&str, SborStatus)> = sbor
+            .requirements
+            .iter()
+            .map(|req| (req.id.as_str(), req.status))
+            .collect();
+        assert_eq!(
+            status,
+            vec![
+                ("a", SborStatus::Uncovered),
+                ("a.b", SborStatus::Uncovered),
+                ("org", SborStatus::Untraceable),
+            ],
+            "Requirements not ordered by ID, or wrong status."
+        );
+        assert_eq!(
+            sbor.requirements[1].parents,
+            vec!["a".to_string()],
+            "Parent not listed."
+        );
+        assert_eq!(
+            sbor.requirements[1].traces,
+            vec![SborTrace {
+                filepath: "src/lib.rs".into(),
+                line: 3,
+                item_name: Some("handler".to_string()),
+            }],
+            "Trace location not listed."
+        );
+        assert!(
+            sbor.requirements[0].traces.is_empty(),
+            "Traces of children listed as direct traces."
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use std::path::PathBuf;
+
+use crate::db::{self, DbError, MantraDb};
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct MergeConfig {
+    /// SQLite database files to merge, e.g. the databases of all CI shards.
+    #[arg(required = true)]
+    pub inputs: Vec<PathBuf>,
+    /// SQLite database file the inputs are merged into.
+    /// The file is created if it does not exist, and data already in the file is kept.
+    #[arg(long, short)]
+    pub output: PathBuf,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MergeError {
+    #[error("Input database '{}' does not exist.", .0.display())]
+    MissingInput(PathBuf),
+    #[error("Input database '{}' is also the output database.", .0.display())]
+    InputIsOutput(PathBuf),
+    #[error("Could not open the output database. Cause: {}", .0)]
+    Output(DbError),
+    #[error("Could not merge database '{}'. Cause: {}", .0.display(), .1)]
+    Input(PathBuf, DbError),
+}
+
+/// Merges all input databases into the output database.
+/// The input databases are not modified.
+pub async fn merge(cfg: &MergeConfig) -> Result<(), MergeError> {
+    let output_path = std::fs::canonicalize(&cfg.output).ok();
+
+    for input in &cfg.inputs {
+        if !input.is_file() {
+            return Err(MergeError::MissingInput(input.clone()));
+        }
+        if output_path.is_some() && std::fs::canonicalize(input).ok() == output_path {
+            return Err(MergeError::InputIsOutput(input.clone()));
+        }
+    }
+
+    let output = MantraDb::new(&db::Config {
+        url: Some(format!("sqlite://{}?mode=rwc", cfg.output.display())),
+        connect_attempts: 1,
+        retry_delay_ms: 0,
+        branch: None,
+    })
+    .await
+    .map_err(MergeError::Output)?;
+
+    for input in &cfg.inputs {
+        output
+            .merge_from(input)
+            .await
+            .map_err(|err| MergeError::Input(input.clone(), err))?;
+        log::info!("Merged '{}'.", input.display());
+    }
+
+    output.pool().close().await;
+
+    Ok(())
+}
@@ -1,12 +1,20 @@
-use crate::cfg::MantraConfigPath;
+use std::path::{Path, PathBuf};
+
+use crate::cfg::{ClearConfig, CollectCliConfig, MantraConfigPath, PruneConfig, TraceCliConfig};
 
 use self::report::ReportCliConfig;
 
 pub mod analyze;
+pub mod annotate;
 pub mod coverage;
+pub mod doctor;
+pub mod export;
+pub mod merge;
+pub mod outline;
 pub mod report;
 pub mod requirements;
 pub mod review;
+pub mod summary;
 pub mod trace;
 
 const REVIEW_DATE_FORMAT: &[time::format_description::BorrowedFormatItem<'static>] = time::macros::format_description!(
@@ -17,10 +25,146 @@ time::serde::format_description!(review_date_format, PrimitiveDateTime, REVIEW_D
 
 #[derive(Debug, Clone, clap::Subcommand)]
 pub enum Cmd {
+    /// Create a report of the collected data.
     Report(Box<ReportCliConfig>),
-    Collect(MantraConfigPath),
+    /// Collect requirements, traces, coverage, and reviews set in the configuration file.
+    Collect(CollectCliConfig),
+    /// Collect only the traces set in the configuration file.
+    Trace(TraceCliConfig),
+    /// Inspect collected coverage data.
+    Coverage {
+        #[command(subcommand)]
+        cmd: coverage::CoverageCmd,
+    },
     /// Delete test runs and reviews that have no linked requirement or coverage remaining.
-    Prune,
+    Prune(PruneConfig),
     /// Delete all collected date in the database.
-    Clear,
+    Clear(ClearConfig),
+    /// Diagnose common setup problems, and print a checklist with hints.
+    Doctor(MantraConfigPath),
+    /// Check that traces in the given files refer to known requirements.
+    CheckTraces(trace::CheckTracesConfig),
+    /// Print the requirement hierarchy as nested outline.
+    Outline(outline::OutlineConfig),
+    /// Print the ratios of traced, covered, passed, and verified requirements, and the test overview.
+    #[command(alias = "stats")]
+    Summary(summary::SummaryConfig),
+    /// Print a source file with the coverage status of its traces annotated per line.
+    Annotate(annotate::AnnotateConfig),
+    /// Merge multiple databases into one, e.g. the databases of CI shards with partial coverage.
+    Merge(merge::MergeConfig),
+    /// Export the collected data in tool-agnostic interchange formats.
+    Export {
+        #[command(subcommand)]
+        cmd: export::ExportCmd,
+    },
+}
+
+/// Expands the given paths to the files they refer to, keeping the order of the given paths.
+///
+/// - Directories are expanded to all files directly inside the directory that are accepted by `accept`.
+/// - Paths containing glob characters (`*`, `?`, `[`) are expanded to all matching files that are accepted by `accept`.
+///   Matching files are searched in the longest leading part of the path without glob characters.
+/// - All other paths are taken as is.
+pub(crate) fn expand_files(
+    paths: &[PathBuf],
+    accept: impl Fn(&Path) -> bool,
+) -> Result<Vec<PathBuf>, globset::Error> {
+    let mut files = Vec::new();
+
+    for path in paths {
+        let path_str = path.to_string_lossy();
+
+        if path.is_dir() {
+            let mut dir_files: Vec<PathBuf> = std::fs::read_dir(path)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|file| file.is_file() && accept(file))
+                .collect();
+            dir_files.sort();
+            files.append(&mut dir_files);
+        } else if is_glob(path) {
+            let glob = globset::Glob::new(&path_str)?.compile_matcher();
+            let base: PathBuf = path
+                .components()
+                .take_while(|component| !is_glob(component.as_os_str()))
+                .collect();
+            let base = if base.as_os_str().is_empty() {
+                PathBuf::from(".")
+            } else {
+                base
+            };
+
+            let mut matched: Vec<PathBuf> = ignore::WalkBuilder::new(&base)
+                .standard_filters(false)
+                .build()
+                .flatten()
+                .map(|entry| entry.into_path())
+                .filter(|file| {
+                    // walking `.` prefixes all paths with `./`
+                    let file_path = file.strip_prefix(".").unwrap_or(file);
+                    file.is_file() && glob.is_match(file_path) && accept(file)
+                })
+                .collect();
+            matched.sort();
+            files.append(&mut matched);
+        } else {
+            files.push(path.clone());
+        }
+    }
+
+    Ok(files)
+}
+
+/// Returns `true` if the path contains glob characters (`*`, `?`, `[`).
+pub(crate) fn is_glob(path: impl AsRef<std::ffi::OsStr>) -> bool {
+    path.as_ref().to_string_lossy().contains(['*', '?', '['])
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::expand_files;
+
+    #[test]
+    fn directories_and_globs_expanded() {
+        let dir = std::env::temp_dir().join(format!("mantra_expand_test_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("cycle-1")).unwrap();
+        for file in [
+            "cycle-1/alice.toml",
+            "cycle-1/bob.yaml",
+            "cycle-1/notes.md",
+            "single.toml",
+        ] {
+            std::fs::write(dir.join(file), "").unwrap();
+        }
+
+        let is_review = |file: &std::path::Path| file.extension().is_some_and(|ext| ext != "md");
+        let from_dir = expand_files(&[dir.join("cycle-1"), dir.join("single.toml")], is_review);
+        let from_glob = expand_files(&[dir.join("**/*.toml")], is_review);
+
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            from_dir.unwrap(),
+            vec![
+                dir.join("cycle-1/alice.toml"),
+                dir.join("cycle-1/bob.yaml"),
+                dir.join("single.toml")
+            ],
+            "Directory not expanded to accepted files."
+        );
+        assert_eq!(
+            from_glob.unwrap(),
+            vec![dir.join("cycle-1/alice.toml"), dir.join("single.toml")],
+            "Glob not expanded to matching files."
+        );
+        assert!(
+            expand_files(&[PathBuf::from("[invalid")], is_review).is_err(),
+            "Invalid glob accepted."
+        );
+    }
 }
@@ -0,0 +1,227 @@
+use std::collections::{HashMap, HashSet};
+
+use mantra_schema::requirements::ReqId;
+
+use crate::db::{DbError, MantraDb};
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct OutlineConfig {
+    /// ID of the requirement whose subtree is exported.
+    /// Default exports all requirements without parent.
+    #[arg(long)]
+    pub root: Option<ReqId>,
+    /// Output format of the outline.
+    #[arg(long, value_enum, default_value_t = OutlineFormat::Markdown)]
+    pub format: OutlineFormat,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutlineFormat {
+    /// Nested Markdown list.
+    #[default]
+    Markdown,
+    /// Outline Processor Markup Language.
+    Opml,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum OutlineError {
+    #[error("{}", .0)]
+    Db(DbError),
+    #[error("No requirement with ID `{}` found.", .0)]
+    UnknownRoot(ReqId),
+}
+
+/// Requirement in the outline, with its sub-requirements ordered by ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutlineNode {
+    pub id: ReqId,
+    pub title: String,
+    pub children: Vec<OutlineNode>,
+}
+
+pub async fn outline(db: &MantraDb, cfg: &OutlineConfig) -> Result<(), OutlineError> {
+    let nodes = outline_nodes(db, cfg.root.as_deref()).await?;
+
+    match cfg.format {
+        OutlineFormat::Markdown => print!("{}", to_markdown(&nodes)),
+        OutlineFormat::Opml => print!("{}", to_opml(&nodes)),
+    }
+
+    Ok(())
+}
+
+/// Returns the requirement hierarchy starting at the given root.
+/// All requirements without parent are used as roots if no root is given.
+pub async fn outline_nodes(
+    db: &MantraDb,
+    root: Option<&str>,
+) -> Result<Vec<OutlineNode>, OutlineError> {
+    let requirements: HashMap<ReqId, String> =
+        sqlx::query!("select id, title from Requirements order by id")
+            .fetch_all(db.pool())
+            .await
+            .map_err(|err| OutlineError::Db(DbError::Query(err.to_string())))?
+            .into_iter()
+            .map(|record| (record.id, record.title))
+            .collect();
+
+    let mut children: HashMap<ReqId, Vec<ReqId>> = HashMap::new();
+    let edges = sqlx::query!(
+        "select parent_id, child_id from RequirementHierarchies order by parent_id, child_id"
+    )
+    .fetch_all(db.pool())
+    .await
+    .map_err(|err| OutlineError::Db(DbError::Query(err.to_string())))?;
+
+    for edge in edges {
+        children
+            .entry(edge.parent_id)
+            .or_default()
+            .push(edge.child_id);
+    }
+
+    let roots = match root {
+        Some(root) => {
+            if !requirements.contains_key(root) {
+                return Err(OutlineError::UnknownRoot(root.to_string()));
+            }
+
+            vec![root.to_string()]
+        }
+        None => {
+            let child_ids: HashSet<&ReqId> = children.values().flatten().collect();
+            let mut roots: Vec<ReqId> = requirements
+                .keys()
+                .filter(|id| !child_ids.contains(id))
+                .cloned()
+                .collect();
+            roots.sort();
+            roots
+        }
+    };
+
+    Ok(roots
+        .into_iter()
+        .map(|id| build_node(id, &requirements, &children))
+        .collect())
+}
+
+fn build_node(
+    id: ReqId,
+    requirements: &HashMap<ReqId, String>,
+    children: &HashMap<ReqId, Vec<ReqId>>,
+) -> OutlineNode {
+    let sub_nodes = children
+        .get(&id)
+        .map(|child_ids| {
+            child_ids
+                .iter()
+                .map(|child| build_node(child.clone(), requirements, children))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    OutlineNode {
+        title: requirements.get(&id).cloned().unwrap_or_default(),
+        id,
+        children: sub_nodes,
+    }
+}
+
+/// Renders the nodes depth-first as nested Markdown list.
+pub fn to_markdown(nodes: &[OutlineNode]) -> String {
+    fn write_node(out: &mut String, node: &OutlineNode, depth: usize) {
+        out.push_str(&format!(
+            "{}- `{}`: {}\n",
+            "  ".repeat(depth),
+            node.id,
+            node.title
+        ));
+
+        for child in &node.children {
+            write_node(out, child, depth + 1);
+        }
+    }
+
+    let mut out = String::new();
+    for node in nodes {
+        write_node(&mut out, node, 0);
+    }
+    out
+}
+
+/// Renders the nodes depth-first as OPML document.
+pub fn to_opml(nodes: &[OutlineNode]) -> String {
+    fn write_node(out: &mut String, node: &OutlineNode, depth: usize) {
+        let indent = "  ".repeat(depth + 2);
+        let text = xml_escape(&format!("{}: {}", node.id, node.title));
+
+        if node.children.is_empty() {
+            out.push_str(&format!("{indent}<outline text=\"{text}\"/>\n"));
+        } else {
+            out.push_str(&format!("{indent}<outline text=\"{text}\">\n"));
+            for child in &node.children {
+                write_node(out, child, depth + 1);
+            }
+            out.push_str(&format!("{indent}</outline>\n"));
+        }
+    }
+
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n  <head>\n    <title>Requirements</title>\n  </head>\n  <body>\n",
+    );
+    for node in nodes {
+        write_node(&mut out, node, 0);
+    }
+    out.push_str("  </body>\n</opml>\n");
+    out
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{to_markdown, to_opml, OutlineNode};
+
+    fn node(id: &str, title: &str, children: Vec<OutlineNode>) -> OutlineNode {
+        OutlineNode {
+            id: id.to_string(),
+            title: title.to_string(),
+            children,
+        }
+    }
+
+    #[test]
+    fn nested_outline() {
+        let nodes = vec![node(
+            "a",
+            "Parent",
+            vec![
+                node("a.b", "Child", vec![node("a.b.c", "Grandchild", vec![])]),
+                node("a.d", "A & B", vec![]),
+            ],
+        )];
+
+        assert_eq!(
+            to_markdown(&nodes),
+            "- `a`: Parent\n  - `a.b`: Child\n    - `a.b.c`: Grandchild\n  - `a.d`: A & B\n",
+            "Markdown outline not ordered depth-first."
+        );
+
+        let opml = to_opml(&nodes);
+        assert!(
+            opml.contains("      <outline text=\"a.b: Child\">\n        <outline text=\"a.b.c: Grandchild\"/>\n      </outline>"),
+            "Children not nested in OPML outline."
+        );
+        assert!(
+            opml.contains("<outline text=\"a.d: A &amp; B\"/>"),
+            "Title not escaped in OPML outline."
+        );
+    }
+}
@@ -1,19 +1,19 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
 };
 
 use mantra_schema::{
     coverage::TestState,
-    requirements::{ReqId, Requirement},
+    requirements::{DesignLink, ReqId, Requirement},
     Line,
 };
 use time::{OffsetDateTime, PrimitiveDateTime};
 
 use crate::{
     cfg::Project,
-    cmd::review::VerifiedRequirement,
-    db::{MantraDb, TracePk},
+    cmd::review::{evidence_from_db, VerifiedRequirement},
+    db::{MantraDb, RequirementStatus, TracePk},
 };
 
 use super::{coverage::iso8601_str_to_offsetdatetime, review::Review};
@@ -32,11 +32,55 @@ pub enum ReportError {
     Write,
     #[error("Failed to read the given template.")]
     Template,
+    #[error("Failed to materialize the database views. Cause: {}", .0)]
+    Materialize(crate::db::DbError),
+    #[error("Failed to validate the collected data. Cause: {}", .0)]
+    Validate(crate::db::DbError),
+    #[error("Failed to open the baseline database for comparison. Cause: {}", .0)]
+    Compare(crate::db::DbError),
+    #[error("The collected data is invalid. {}", invalid_cause(.0))]
+    Invalid(ValidationInfo),
+    #[error("Coverage thresholds not met. {}", .0)]
+    Threshold(String),
+    #[error("Failed to archive the database. Cause: {}", .0)]
+    Archive(crate::db::DbError),
+    #[error("Failed to read the status of the previous collect. Cause: {}", .0)]
+    Changes(crate::db::DbError),
+}
+
+fn invalid_cause(validation: &ValidationInfo) -> String {
+    let mut causes = Vec::new();
+
+    if !validation.invalid_reqs.is_empty() {
+        causes.push(format!(
+            "Invalid requirements: '{}'.",
+            validation.invalid_reqs.join("', '")
+        ));
+    }
+    if let Some(cycle) = &validation.hierarchy_cycle {
+        causes.push(format!(
+            "Cycle in the requirement hierarchy: '{}'.",
+            cycle.join("' -> '")
+        ));
+    }
+
+    if causes.is_empty() {
+        validation.criteria.to_string()
+    } else {
+        causes.join(" ")
+    }
 }
 
 #[derive(Debug, Clone, clap::Args)]
 pub struct ReportCliConfig {
-    pub path: PathBuf,
+    /// Path of the report files.
+    /// If `--out-dir` is set, the path is relative to the output directory.
+    #[arg(required_unless_present = "out_dir")]
+    pub path: Option<PathBuf>,
+    /// Directory all report files are written to.
+    /// A `report_manifest.json` listing all generated files is added to this directory.
+    #[arg(long = "out-dir")]
+    pub out_dir: Option<PathBuf>,
     #[arg(long = "mantra-config")]
     pub mantra_config: Option<PathBuf>,
     #[command(flatten)]
@@ -47,6 +91,17 @@ pub struct ReportCliConfig {
     pub project: Project,
     #[command(flatten)]
     pub tag: Tag,
+    /// Use the nearest git tag, or the short commit SHA if no tag exists, as report tag.
+    /// The tag link is derived from the project repository.
+    /// Explicitly set tag name and link take precedence.
+    #[arg(long = "tag-from-git")]
+    pub tag_from_git: bool,
+    /// Directory a snapshot of the database and copies of the report files are written to.
+    /// An `archive_manifest.json` ties the snapshot to the tag and creation date of the report.
+    #[arg(long)]
+    pub archive: Option<PathBuf>,
+    #[command(flatten)]
+    pub options: ReportOptions,
 }
 
 impl ReportCliConfig {
@@ -58,10 +113,15 @@ impl ReportCliConfig {
 #[derive(Debug, Clone)]
 pub struct ReportConfig {
     pub path: PathBuf,
+    /// Directory the report files and the report manifest are written to.
+    pub out_dir: Option<PathBuf>,
     pub template: ReportTemplate,
     pub formats: Vec<ReportFormat>,
     pub project: Project,
     pub tag: Tag,
+    /// Directory the database snapshot and the report files are archived in.
+    pub archive: Option<PathBuf>,
+    pub options: ReportOptions,
 }
 
 impl ReportConfig {
@@ -69,8 +129,15 @@ impl ReportConfig {
         match tokio::fs::read_to_string(&value.mantra_config.unwrap_or("mantra.toml".into())).await {
             Ok(content) => match toml::from_str::<crate::cfg::MantraConfigFile>(&content) {
                 Ok(mantra_cfg) => {
-                    if value.template.base.is_none() && mantra_cfg.report_template.base.is_some() {
-                        value.template.base = mantra_cfg.report_template.base;
+                    // a template set via CLI replaces the configured one
+                    if value.template.base.is_none() && value.template.dir.is_none() {
+                        if mantra_cfg.report_template.base.is_some() {
+                            value.template.base = mantra_cfg.report_template.base;
+                        }
+                        if mantra_cfg.report_template.dir.is_some() {
+                            value.template.dir = mantra_cfg.report_template.dir;
+                            value.template.entry = mantra_cfg.report_template.entry;
+                        }
                     }
                     if value.template.req_data.is_none()
                         && mantra_cfg.report_template.req_data.is_some()
@@ -82,6 +149,12 @@ impl ReportConfig {
                     {
                         value.template.test_run_data = mantra_cfg.report_template.test_run_data;
                     }
+                    if value.template.theme.is_none() {
+                        value.template.theme = mantra_cfg.report_template.theme;
+                    }
+                    if value.template.theme_css.is_none() {
+                        value.template.theme_css = mantra_cfg.report_template.theme_css;
+                    }
 
                     if value.project.name.is_none() && mantra_cfg.project.name.is_some() {
                         value.project.name = mantra_cfg.project.name;
@@ -96,18 +169,42 @@ impl ReportConfig {
                     if value.project.homepage.is_none() && mantra_cfg.project.homepage.is_some() {
                         value.project.homepage = mantra_cfg.project.homepage;
                     }
+
+                    value.options.risk_weights = mantra_cfg.risk_weights;
                 }
                 Err(err) => log::error!("Could not parse the mantra configuration. Cause: {}", err),
             },
             Err(err) => log::warn!("Could not read the mantra configuration. Only CLI arguments will be considered. Cause: {}", err),
         }
 
+        if value.tag_from_git {
+            match Tag::from_git(value.project.repository.as_deref()).await {
+                Some(git_tag) => {
+                    if value.tag.name.is_none() {
+                        value.tag.name = git_tag.name;
+                    }
+                    if value.tag.link.is_none() {
+                        value.tag.link = git_tag.link;
+                    }
+                }
+                None => log::warn!("Could not derive the report tag from git."),
+            }
+        }
+
+        let path = match &value.out_dir {
+            Some(out_dir) => out_dir.join(value.path.unwrap_or_default()),
+            None => value.path.unwrap_or_default(),
+        };
+
         Self {
-            path: value.path,
+            path,
+            out_dir: value.out_dir,
             template: value.template,
             formats: value.formats,
             project: value.project,
             tag: value.tag,
+            archive: value.archive,
+            options: value.options,
         }
     }
 }
@@ -125,6 +222,24 @@ impl ReportConfig {
 pub struct ReportTemplate {
     #[arg(id = "base-template", long = "base-template")]
     pub base: Option<PathBuf>,
+    /// Directory of Tera templates that may include, import, or extend each other.
+    /// The report is rendered using the entry template of this directory.
+    #[arg(
+        id = "template-dir",
+        long = "template-dir",
+        conflicts_with = "base-template"
+    )]
+    #[serde(alias = "template-dir")]
+    pub dir: Option<PathBuf>,
+    /// Name of the entry template relative to the template directory.
+    /// Default is `report.html`.
+    #[arg(
+        id = "template-entry",
+        long = "template-entry",
+        requires = "template-dir"
+    )]
+    #[serde(alias = "template-entry")]
+    pub entry: Option<String>,
     /// Path to a Tera template that is used to render the custom information of requirements.
     #[arg(id = "req-data-template", long = "req-data-template")]
     #[serde(alias = "req-data")]
@@ -133,14 +248,83 @@ pub struct ReportTemplate {
     #[arg(id = "test-run-template", long = "test-run-template")]
     #[serde(alias = "test-run-data")]
     pub test_run_data: Option<PathBuf>,
+    /// Color theme of the HTML report. Default is `light`.
+    #[arg(id = "theme", long = "theme", value_enum)]
+    pub theme: Option<ReportTheme>,
+    /// Path to a CSS file that is added after the styles of the report template,
+    /// e.g. to match the colors of a dashboard the report is embedded in.
+    #[arg(id = "theme-css", long = "theme-css")]
+    #[serde(alias = "theme-css")]
+    pub theme_css: Option<PathBuf>,
 }
 
 impl ReportTemplate {
     pub(crate) fn is_none(&self) -> bool {
-        self.base.is_none() && self.req_data.is_none() && self.test_run_data.is_none()
+        self.base.is_none()
+            && self.dir.is_none()
+            && self.entry.is_none()
+            && self.req_data.is_none()
+            && self.test_run_data.is_none()
+            && self.theme.is_none()
+            && self.theme_css.is_none()
+    }
+
+    /// Returns the directory local assets referenced in the template are relative to.
+    fn asset_dir(&self) -> PathBuf {
+        match (&self.dir, &self.base) {
+            (Some(dir), _) => dir.clone(),
+            (None, Some(base)) => base.parent().map(Path::to_path_buf).unwrap_or_default(),
+            (None, None) => PathBuf::new(),
+        }
+    }
+
+    /// Returns the theme that is available as `theme` in HTML templates.
+    async fn theme_context(&self) -> Result<ThemeContext, ReportError> {
+        let css = match &self.theme_css {
+            Some(css_file) => Some(tokio::fs::read_to_string(css_file).await.map_err(|_| {
+                log::error!("Could not read the theme CSS '{}'.", css_file.display());
+                ReportError::Template
+            })?),
+            None => None,
+        };
+
+        Ok(ThemeContext {
+            name: self.theme.unwrap_or_default(),
+            css,
+        })
     }
 }
 
+/// Color themes of the default report template.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    clap::ValueEnum,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportTheme {
+    #[default]
+    Light,
+    Dark,
+    /// Follows the color scheme preferred by the browser.
+    Auto,
+}
+
+/// Theme of the HTML report as passed to templates.
+#[derive(Debug, Clone, serde::Serialize)]
+struct ThemeContext {
+    name: ReportTheme,
+    /// Content of the custom CSS file.
+    css: Option<String>,
+}
+
 #[derive(
     Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, clap::Args, schemars::JsonSchema,
 )]
@@ -151,14 +335,178 @@ pub struct Tag {
     pub link: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, clap::ValueEnum)]
+impl Tag {
+    /// Creates a tag from the nearest git tag, or the short commit SHA if no tag is reachable.
+    /// Links point to the release or commit page of the given repository.
+    pub async fn from_git(repository: Option<&str>) -> Option<Self> {
+        let repository = repository.map(|repo| repo.trim_end_matches('/'));
+
+        if let Some(name) = git_output(&["describe", "--tags", "--abbrev=0"]).await {
+            let link = repository.map(|repo| format!("{repo}/releases/tag/{name}"));
+            return Some(Self {
+                name: Some(name),
+                link,
+            });
+        }
+
+        let sha = git_output(&["rev-parse", "--short", "HEAD"]).await?;
+        let link = repository.map(|repo| format!("{repo}/commit/{sha}"));
+        Some(Self {
+            name: Some(sha),
+            link,
+        })
+    }
+}
+
+async fn git_output(args: &[&str]) -> Option<String> {
+    let output = tokio::process::Command::new("git")
+        .args(args)
+        .output()
+        .await
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let out = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    if out.is_empty() {
+        None
+    } else {
+        Some(out)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, clap::ValueEnum, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum ReportFormat {
     Html,
+    /// HTML report with all local CSS, JS, and image files referenced by the template inlined,
+    /// so the report is a single file viewable offline.
+    HtmlEmbed,
     Json,
+    /// One row per requirement with its status and trace counts, e.g. for spreadsheets.
+    Csv,
+}
+
+/// Options affecting which requirements are part of the report, and how they are ordered.
+#[derive(Debug, Default, Clone, PartialEq, clap::Args)]
+pub struct ReportOptions {
+    /// Order of the requirements in the report.
+    #[arg(long, default_value = "id")]
+    pub sort: ReportSort,
+    /// Only include the given requirements in the report.
+    #[arg(long)]
+    pub only: Vec<ReqId>,
+    /// Only include the given requirements and all their descendants in the report.
+    #[arg(long = "only-subtree")]
+    pub only_subtree: Vec<ReqId>,
+    /// Only include requirements in the given state in the report.
+    #[arg(long = "state")]
+    pub states: Vec<String>,
+    /// Path to a baseline SQLite database file the collected data is compared against.
+    /// Adds a delta section with added/removed requirements and status changes to the report.
+    #[arg(long)]
+    pub compare: Option<PathBuf>,
+    /// Only include requirements whose trace or test status changed since the previous collect.
+    /// Adds a section summarizing the changes to the report.
+    #[arg(long)]
+    pub only_changed: bool,
+    /// How test results of multiple test runs are combined to decide if a requirement passed.
+    #[arg(long, value_enum, default_value_t = CoverageAcrossRuns::All)]
+    pub coverage_across_runs: CoverageAcrossRuns,
+    /// Replace all filepaths in the report with a hash of the path.
+    /// Line numbers and requirement data are kept.
+    #[arg(long)]
+    pub redact: bool,
+    /// Compute all database views once into tables of a temporary database copy before creating the report.
+    /// Speeds up reports for large databases.
+    #[arg(long)]
+    pub materialize: bool,
+    /// Warn about traced items that trace more than the given number of requirements.
+    #[arg(long)]
+    pub max_reqs_per_item: Option<u32>,
+    /// Warn about requirement IDs and unrelated trace IDs that are suspiciously close to other requirement IDs,
+    /// and may be typos.
+    #[arg(long)]
+    pub fuzzy_ids: bool,
+    /// Partition the requirements into groups with an overview per group.
+    #[arg(long, value_enum)]
+    pub group_by: Option<ReportGrouping>,
+    /// Exit with an error after writing the report if the collected data is invalid
+    /// (e.g. a *deprecated* requirement is traced).
+    #[arg(long)]
+    pub fail_on_invalid: bool,
+    /// Exit with an error after writing the report if the ratio of traced requirements is below the given ratio between `0` and `1`.
+    #[arg(long, value_name = "RATIO")]
+    pub min_traced_ratio: Option<f64>,
+    /// Exit with an error after writing the report if the ratio of covered requirements is below the given ratio between `0` and `1`.
+    #[arg(long, value_name = "RATIO")]
+    pub min_covered_ratio: Option<f64>,
+    /// Weights of the factors adding to the risk score of requirements.
+    /// Set in the `[risk-weights]` section of the *mantra* configuration.
+    #[arg(skip)]
+    pub risk_weights: RiskWeights,
+}
+
+impl ReportOptions {
+    /// `true` if the report is restricted to a subset of requirements.
+    pub fn is_scoped(&self) -> bool {
+        !self.only.is_empty()
+            || !self.only_subtree.is_empty()
+            || !self.states.is_empty()
+            || self.only_changed
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum)]
+pub enum CoverageAcrossRuns {
+    /// Requirements are passed if all tests of all test runs covering the requirement passed.
+    #[default]
+    All,
+    /// Requirements are passed if all tests covering the requirement passed in at least one test run.
+    /// Failed tests of other test runs (e.g. flaky CI shards) do not affect the passed state.
+    Any,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum)]
+pub enum ReportGrouping {
+    /// Requirements are grouped by the source of their origin.
+    /// For URLs, the host and the first two path segments are used (e.g. `github.com/mhatzl/mantra`).
+    /// For other origins, the origin up to its last `/` is used.
+    Origin,
+    /// Requirements are grouped by their state.
+    State,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum)]
+pub enum ReportSort {
+    /// Requirements are ordered by their ID.
+    #[default]
+    Id,
+    /// Requirements are ordered by their priority, and then by their ID.
+    /// Requirements without priority are placed last.
+    Priority,
 }
 
 pub async fn report(db: &MantraDb, cfg: ReportConfig) -> Result<(), ReportError> {
-    let mut filepath = if cfg.path.extension().is_some() {
+    if cfg.options.materialize {
+        // the copy is created per report, so materialized data is never outdated
+        let snapshot = db
+            .materialized_snapshot()
+            .await
+            .map_err(ReportError::Materialize)?;
+        let res = write_reports(&snapshot.db, cfg).await;
+        snapshot.remove().await;
+
+        res
+    } else {
+        write_reports(db, cfg).await
+    }
+}
+
+async fn write_reports(db: &MantraDb, cfg: ReportConfig) -> Result<(), ReportError> {
+    let report_path = if cfg.path.extension().is_some() {
         cfg.path
     } else {
         let now = OffsetDateTime::now_utc();
@@ -171,52 +519,386 @@ pub async fn report(db: &MantraDb, cfg: ReportConfig) -> Result<(), ReportError>
         cfg.path.join(filename)
     };
 
-    let formats: HashSet<ReportFormat> = HashSet::from_iter(cfg.formats.into_iter());
+    if let Some(out_dir) = &cfg.out_dir {
+        tokio::fs::create_dir_all(out_dir)
+            .await
+            .map_err(|_| ReportError::Write)?;
+    }
+
+    let formats: HashSet<ReportFormat> = HashSet::from_iter(cfg.formats);
+    let mut manifest_files = Vec::new();
+    let mut report_files = Vec::new();
 
     for format in formats {
+        let timer = crate::profile::phase(format!("report ({format:?})").to_lowercase());
+        let filepath = match format {
+            ReportFormat::Html => report_path.with_extension("html"),
+            // suffix prevents overwriting the regular HTML report if both formats are created
+            ReportFormat::HtmlEmbed => report_path.with_extension("embed.html"),
+            ReportFormat::Json => report_path.with_extension("json"),
+            ReportFormat::Csv => report_path.with_extension("csv"),
+        };
         let report = match format {
-            ReportFormat::Html => {
-                filepath.set_extension("html");
-
-                let template_content = match &cfg.template.base {
-                    Some(template) => tokio::fs::read_to_string(template)
-                        .await
-                        .map_err(|_| ReportError::Template)?,
-                    None => include_str!("report_default_template.html").to_string(),
+            ReportFormat::Html | ReportFormat::HtmlEmbed => {
+                let mut context = tera_context(
+                    db,
+                    &cfg.project,
+                    &cfg.tag,
+                    cfg.template.req_data.as_deref(),
+                    cfg.template.test_run_data.as_deref(),
+                    &cfg.options,
+                )
+                .await?;
+                context.insert("theme", &cfg.template.theme_context().await?);
+
+                let html = match &cfg.template.dir {
+                    Some(template_dir) => tera_from_dir(
+                        template_dir,
+                        cfg.template.entry.as_deref().unwrap_or("report.html"),
+                        &context,
+                    )?,
+                    None => {
+                        let template_content = match &cfg.template.base {
+                            Some(template) => tokio::fs::read_to_string(template)
+                                .await
+                                .map_err(|_| ReportError::Template)?,
+                            None => include_str!("report_default_template.html").to_string(),
+                        };
+
+                        tera_one_off(&template_content, &context).map_err(|_| ReportError::Tera)?
+                    }
                 };
 
-                create_tera_report(
+                if format == ReportFormat::HtmlEmbed {
+                    embed_assets(&html, &cfg.template.asset_dir())
+                } else {
+                    html
+                }
+            }
+            ReportFormat::Json => {
+                create_json_report(
                     db,
                     &cfg.project,
                     &cfg.tag,
                     cfg.template.req_data.as_deref(),
                     cfg.template.test_run_data.as_deref(),
-                    &template_content,
+                    &cfg.options,
                 )
                 .await?
             }
-            ReportFormat::Json => {
-                filepath.set_extension("json");
-
-                create_json_report(
+            ReportFormat::Csv => {
+                create_csv_report(
                     db,
                     &cfg.project,
                     &cfg.tag,
                     cfg.template.req_data.as_deref(),
                     cfg.template.test_run_data.as_deref(),
+                    &cfg.options,
                 )
                 .await?
             }
         };
+        timer.finish();
+
+        tokio::fs::write(&filepath, &report)
+            .await
+            .map_err(|_| ReportError::Write)?;
+
+        let manifest_path = match &cfg.out_dir {
+            Some(out_dir) => filepath.strip_prefix(out_dir).unwrap_or(&filepath),
+            None => &filepath,
+        };
+        manifest_files.push(ManifestFile {
+            path: manifest_path.to_path_buf(),
+            format: format.clone(),
+            size: report.len() as u64,
+        });
+        report_files.push(ManifestFile {
+            path: filepath.clone(),
+            format,
+            size: report.len() as u64,
+        });
+    }
+
+    let creation_date = OffsetDateTime::now_utc();
+    let branch = if db.branch().is_empty() {
+        None
+    } else {
+        Some(db.branch().to_string())
+    };
+
+    if let Some(out_dir) = &cfg.out_dir {
+        manifest_files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let manifest = ReportManifest {
+            project: cfg.project.clone(),
+            tag: cfg.tag.clone(),
+            branch: branch.clone(),
+            creation_date,
+            files: manifest_files,
+        };
+        let content =
+            serde_json::to_string_pretty(&manifest).map_err(|_| ReportError::Serialize)?;
 
-        tokio::fs::write(&filepath, report)
+        tokio::fs::write(out_dir.join(REPORT_MANIFEST), content)
             .await
             .map_err(|_| ReportError::Write)?;
     }
 
+    if let Some(archive_dir) = &cfg.archive {
+        let _timer = crate::profile::phase("archive");
+        let manifest = ArchiveManifest {
+            project: cfg.project,
+            tag: cfg.tag,
+            branch,
+            creation_date,
+            database: PathBuf::from(ARCHIVE_DB),
+            files: report_files,
+        };
+
+        archive(db, archive_dir, manifest).await?;
+    }
+
+    // checked after writing, so the report shows why the data is invalid
+    if cfg.options.fail_on_invalid {
+        let validation = ValidationInfo::try_from(db).await?;
+
+        if !validation.is_valid || !validation.invalid_reqs.is_empty() {
+            return Err(ReportError::Invalid(validation));
+        }
+    }
+
+    if cfg.options.min_traced_ratio.is_some() || cfg.options.min_covered_ratio.is_some() {
+        let overview = RequirementsOverview::try_from(db).await?;
+        let violations = crate::threshold_violations(
+            &overview,
+            cfg.options.min_traced_ratio,
+            cfg.options.min_covered_ratio,
+        );
+
+        if !violations.is_empty() {
+            return Err(ReportError::Threshold(violations.join(" ")));
+        }
+    }
+
     Ok(())
 }
 
+/// Filename of the manifest listing all generated report files.
+pub const REPORT_MANIFEST: &str = "report_manifest.json";
+
+/// Lists all files generated by one report run, so following CI steps may find them.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ReportManifest {
+    pub project: Project,
+    pub tag: Tag,
+    /// Branch the report was created for.
+    /// `None` for the default branch.
+    pub branch: Option<String>,
+    /// Creation date given in ISO8601 format.
+    #[serde(serialize_with = "time::serde::iso8601::serialize")]
+    pub creation_date: OffsetDateTime,
+    pub files: Vec<ManifestFile>,
+}
+
+/// Filename of the manifest tying the archived database snapshot to the archived report files.
+pub const ARCHIVE_MANIFEST: &str = "archive_manifest.json";
+/// Filename of the database snapshot in the archive directory.
+pub const ARCHIVE_DB: &str = "mantra.db";
+
+/// Ties the database snapshot of an archive to the report created from it.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ArchiveManifest {
+    pub project: Project,
+    pub tag: Tag,
+    /// Branch the report was created for.
+    /// `None` for the default branch.
+    pub branch: Option<String>,
+    /// Creation date of the report given in ISO8601 format.
+    #[serde(serialize_with = "time::serde::iso8601::serialize")]
+    pub creation_date: OffsetDateTime,
+    /// Path of the database snapshot relative to the archive directory.
+    pub database: PathBuf,
+    /// Report files relative to the archive directory.
+    pub files: Vec<ManifestFile>,
+}
+
+static ASSET_TAG_MATCHER: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+
+/// Matches `<link>`, `<script>`, and `<img>` tags, including the closing tag of scripts.
+fn asset_tag_matcher() -> &'static regex::Regex {
+    ASSET_TAG_MATCHER.get_or_init(|| {
+        regex::Regex::new(
+            r"(?is)<(?<tag>link|img)\b[^>]*>|<(?<script>script)\b[^>]*>\s*</script\s*>",
+        )
+        .expect("Regex to match asset tags could **not** be created.")
+    })
+}
+
+static ASSET_ATTR_MATCHER: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+
+/// Matches `src` and `href` attributes.
+fn asset_attr_matcher() -> &'static regex::Regex {
+    ASSET_ATTR_MATCHER.get_or_init(|| {
+        regex::Regex::new(r#"(?i)\b(?:src|href)\s*=\s*(?:"(?<dq>[^"]*)"|'(?<sq>[^']*)')"#)
+            .expect("Regex to match asset attributes could **not** be created.")
+    })
+}
+
+static CSS_URL_MATCHER: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+
+/// Matches `url(...)` references in CSS.
+fn css_url_matcher() -> &'static regex::Regex {
+    CSS_URL_MATCHER.get_or_init(|| {
+        regex::Regex::new(r#"url\(\s*["']?(?<url>[^"')]+)["']?\s*\)"#)
+            .expect("Regex to match CSS urls could **not** be created.")
+    })
+}
+
+/// Inlines local CSS, JS, and image files referenced in the given HTML.
+/// Stylesheets become `<style>` elements, scripts get their content inlined, and all other files are set as data URIs.
+/// Paths are resolved relative to `asset_dir`.
+///
+/// Remote URLs are kept as is, and local files that cannot be read are kept with a warning.
+fn embed_assets(html: &str, asset_dir: &Path) -> String {
+    asset_tag_matcher()
+        .replace_all(html, |captures: &regex::Captures| {
+            let tag = &captures[0];
+            embed_asset_tag(tag, captures.name("script").is_some(), asset_dir)
+                .unwrap_or_else(|| tag.to_string())
+        })
+        .into_owned()
+}
+
+fn embed_asset_tag(tag: &str, is_script: bool, asset_dir: &Path) -> Option<String> {
+    let attr = asset_attr_matcher().captures(tag)?;
+    let url = attr.name("dq").or_else(|| attr.name("sq"))?.as_str();
+    let filepath = local_asset_path(url, asset_dir)?;
+
+    let lowercase_tag = tag.to_lowercase();
+    let is_stylesheet = lowercase_tag.starts_with("<link")
+        && (lowercase_tag.contains("stylesheet") || url.to_lowercase().ends_with(".css"));
+
+    if is_script || is_stylesheet {
+        let content = read_asset(&filepath, std::fs::read_to_string(&filepath))?;
+
+        if is_script {
+            let open_tag = tag.split_once('>')?.0;
+            let open_tag = open_tag.replacen(attr.get(0)?.as_str(), "", 1);
+            Some(format!("{}>{content}</script>", open_tag.trim_end()))
+        } else {
+            let css_dir = filepath.parent().unwrap_or(asset_dir);
+            Some(format!(
+                "<style>{}</style>",
+                embed_css_urls(&content, css_dir)
+            ))
+        }
+    } else {
+        let data_uri = data_uri(&filepath)?;
+        Some(tag.replacen(url, &data_uri, 1))
+    }
+}
+
+/// Sets local files referenced via `url(...)` in CSS as data URIs.
+fn embed_css_urls(css: &str, css_dir: &Path) -> String {
+    css_url_matcher()
+        .replace_all(css, |captures: &regex::Captures| {
+            local_asset_path(&captures["url"], css_dir)
+                .and_then(|filepath| data_uri(&filepath))
+                .map(|data_uri| format!("url(\"{data_uri}\")"))
+                .unwrap_or_else(|| captures[0].to_string())
+        })
+        .into_owned()
+}
+
+/// Returns the path of the referenced file, or `None` if the URL is not local.
+fn local_asset_path(url: &str, asset_dir: &Path) -> Option<PathBuf> {
+    let url = url.trim();
+    let is_remote = url.is_empty()
+        || url.starts_with("//")
+        || url.starts_with('#')
+        || url
+            .split_once(':')
+            // single letters are Windows drive letters
+            .is_some_and(|(scheme, _)| scheme.len() > 1 && !scheme.contains(['/', '.', '\\']));
+
+    if is_remote {
+        return None;
+    }
+
+    // query and fragment are not part of the file name
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    Some(asset_dir.join(path))
+}
+
+fn data_uri(filepath: &Path) -> Option<String> {
+    use base64::Engine;
+
+    let content = read_asset(filepath, std::fs::read(filepath))?;
+    let mime = mime_guess::from_path(filepath).first_or_octet_stream();
+
+    Some(format!(
+        "data:{mime};base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(content)
+    ))
+}
+
+fn read_asset<T>(filepath: &Path, content: std::io::Result<T>) -> Option<T> {
+    match content {
+        Ok(content) => Some(content),
+        Err(err) => {
+            log::warn!(
+                "Could not embed '{}' in the report. Cause: {err}",
+                filepath.display()
+            );
+            None
+        }
+    }
+}
+
+/// Writes a snapshot of the database, copies of the report files, and the archive manifest to the archive directory.
+///
+/// The paths of the report files in the given manifest are replaced by the paths of their copies.
+async fn archive(
+    db: &MantraDb,
+    archive_dir: &Path,
+    mut manifest: ArchiveManifest,
+) -> Result<(), ReportError> {
+    tokio::fs::create_dir_all(archive_dir)
+        .await
+        .map_err(|_| ReportError::Write)?;
+
+    db.export(&archive_dir.join(&manifest.database))
+        .await
+        .map_err(ReportError::Archive)?;
+
+    for file in &mut manifest.files {
+        let filename = PathBuf::from(file.path.file_name().unwrap_or_default());
+
+        tokio::fs::copy(&file.path, archive_dir.join(&filename))
+            .await
+            .map_err(|_| ReportError::Write)?;
+
+        file.path = filename;
+    }
+    manifest.files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let content = serde_json::to_string_pretty(&manifest).map_err(|_| ReportError::Serialize)?;
+
+    tokio::fs::write(archive_dir.join(ARCHIVE_MANIFEST), content)
+        .await
+        .map_err(|_| ReportError::Write)
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ManifestFile {
+    /// Path of the file relative to the output directory.
+    pub path: PathBuf,
+    pub format: ReportFormat,
+    /// Size of the file in bytes.
+    pub size: u64,
+}
+
 pub async fn create_tera_report(
     db: &MantraDb,
     project: &Project,
@@ -224,12 +906,186 @@ pub async fn create_tera_report(
     req_template: Option<&Path>,
     test_run_template: Option<&Path>,
     template: &str,
+    options: &ReportOptions,
 ) -> Result<String, ReportError> {
-    let context = tera::Context::from_serialize(
-        ReportContext::try_from(db, project, tag, req_template, test_run_template).await?,
+    let context = tera_context(db, project, tag, req_template, test_run_template, options).await?;
+    tera_one_off(template, &context).map_err(|_| ReportError::Tera)
+}
+
+async fn tera_context(
+    db: &MantraDb,
+    project: &Project,
+    tag: &Tag,
+    req_template: Option<&Path>,
+    test_run_template: Option<&Path>,
+    options: &ReportOptions,
+) -> Result<tera::Context, ReportError> {
+    tera::Context::from_serialize(
+        ReportContext::try_from(db, project, tag, req_template, test_run_template, options).await?,
     )
-    .map_err(|_| ReportError::Tera)?;
-    tera::Tera::one_off(template, &context, true).map_err(|_| ReportError::Tera)
+    .map_err(|_| ReportError::Tera)
+}
+
+/// Loads all templates in the given directory, and renders the entry template.
+/// Templates may include, import, or extend other templates of the directory using their relative path.
+/// The same filters and functions as for [`tera_one_off`] are available.
+pub fn tera_from_dir(
+    dir: &Path,
+    entry: &str,
+    context: &tera::Context,
+) -> Result<String, ReportError> {
+    let glob = dir.join("**").join("*");
+    let mut tera = tera::Tera::new(&glob.to_string_lossy()).map_err(|err| {
+        log::error!(
+            "Could not load templates in '{}'. Cause: {}",
+            dir.display(),
+            err
+        );
+        ReportError::Template
+    })?;
+    register_mantra_extensions(&mut tera, context);
+
+    tera.render(entry, context).map_err(|err| {
+        log::error!("Could not render template '{}'. Cause: {}", entry, err);
+        ReportError::Tera
+    })
+}
+
+/// Renders the given template with autoescaping enabled,
+/// and with the custom *mantra* filters and functions registered.
+///
+/// **Filters:**
+///
+/// - `percent(decimals=1)`: Formats a ratio as percentage (e.g. `0.873` -> `87.3%`)
+/// - `datefmt(format="[year]-[month]-[day] [hour]:[minute]:[second]")`: Reformats a date using the [time format description](https://time-rs.github.io/book/api/format-description.html)
+///
+/// **Functions:**
+///
+/// - `ratio_color(ratio)`: Returns the CSS color used by the default template for the given ratio
+/// - `req_link(id)`: Returns the origin of the requirement with the given ID in the `requirements` of the context,
+///   or an empty string if the requirement is not part of the context
+pub fn tera_one_off(template: &str, context: &tera::Context) -> Result<String, tera::Error> {
+    let mut tera = tera::Tera::default();
+    register_mantra_extensions(&mut tera, context);
+
+    tera.render_str(template, context)
+}
+
+fn register_mantra_extensions(tera: &mut tera::Tera, context: &tera::Context) {
+    // empty suffix enables autoescaping for all templates
+    tera.autoescape_on(vec![""]);
+    tera.register_filter("percent", percent_filter);
+    tera.register_filter("datefmt", datefmt_filter);
+    tera.register_function("ratio_color", ratio_color_fn);
+    tera.register_function("req_link", req_link_fn(context));
+}
+
+fn percent_filter(
+    value: &tera::Value,
+    args: &std::collections::HashMap<String, tera::Value>,
+) -> tera::Result<tera::Value> {
+    let ratio = value
+        .as_f64()
+        .ok_or(tera::Error::msg("Filter `percent` requires a number."))?;
+    let decimals = match args.get("decimals") {
+        Some(decimals) => decimals.as_u64().ok_or(tera::Error::msg(
+            "Argument `decimals` of filter `percent` must be a positive integer.",
+        ))? as usize,
+        None => 1,
+    };
+
+    Ok(tera::Value::String(format!(
+        "{:.decimals$}%",
+        ratio * 100.0,
+        decimals = decimals
+    )))
+}
+
+fn datefmt_filter(
+    value: &tera::Value,
+    args: &std::collections::HashMap<String, tera::Value>,
+) -> tera::Result<tera::Value> {
+    let date_str = value
+        .as_str()
+        .ok_or(tera::Error::msg("Filter `datefmt` requires a date string."))?;
+    let format_str = match args.get("format") {
+        Some(format) => format.as_str().ok_or(tera::Error::msg(
+            "Argument `format` of filter `datefmt` must be a string.",
+        ))?,
+        None => "[year]-[month]-[day] [hour]:[minute]:[second]",
+    };
+    let format = time::format_description::parse_borrowed::<2>(format_str).map_err(|err| {
+        tera::Error::msg(format!(
+            "Invalid format '{format_str}' for filter `datefmt`. Cause: {err}"
+        ))
+    })?;
+
+    let formatted = match OffsetDateTime::parse(
+        date_str,
+        &time::format_description::well_known::Iso8601::DEFAULT,
+    ) {
+        Ok(date) => date.format(&format),
+        Err(_) => PrimitiveDateTime::parse(date_str, &super::REVIEW_DATE_FORMAT)
+            .map_err(|_| {
+                tera::Error::msg(format!(
+                    "Filter `datefmt` could not parse date '{date_str}'."
+                ))
+            })?
+            .format(&format),
+    }
+    .map_err(|err| tera::Error::msg(format!("Filter `datefmt` failed. Cause: {err}")))?;
+
+    Ok(tera::Value::String(formatted))
+}
+
+fn ratio_color_fn(
+    args: &std::collections::HashMap<String, tera::Value>,
+) -> tera::Result<tera::Value> {
+    let ratio = args
+        .get("ratio")
+        .and_then(|ratio| ratio.as_f64())
+        .ok_or(tera::Error::msg(
+            "Function `ratio_color` requires the number argument `ratio`.",
+        ))?;
+
+    let color = if ratio >= 1.0 {
+        "green"
+    } else if ratio > 0.0 {
+        "orange"
+    } else {
+        "red"
+    };
+
+    Ok(tera::Value::String(color.to_string()))
+}
+
+/// Creates the `req_link` function with the origins of all requirements in the given context.
+fn req_link_fn(context: &tera::Context) -> impl tera::Function {
+    let origins: HashMap<String, String> = context
+        .get("requirements")
+        .and_then(|reqs| reqs.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|req| {
+            Some((
+                req.get("id")?.as_str()?.to_string(),
+                req.get("origin")?.as_str()?.to_string(),
+            ))
+        })
+        .collect();
+
+    move |args: &std::collections::HashMap<String, tera::Value>| -> tera::Result<tera::Value> {
+        let id = args
+            .get("id")
+            .and_then(|id| id.as_str())
+            .ok_or(tera::Error::msg(
+                "Function `req_link` requires the string argument `id`.",
+            ))?;
+
+        Ok(tera::Value::String(
+            origins.get(id).cloned().unwrap_or_default(),
+        ))
+    }
 }
 
 pub async fn create_json_report(
@@ -238,11 +1094,62 @@ pub async fn create_json_report(
     tag: &Tag,
     req_template: Option<&Path>,
     test_run_template: Option<&Path>,
+    options: &ReportOptions,
 ) -> Result<String, ReportError> {
-    let report = ReportContext::try_from(db, project, tag, req_template, test_run_template).await?;
+    let report =
+        ReportContext::try_from(db, project, tag, req_template, test_run_template, options).await?;
     serde_json::to_string_pretty(&report).map_err(|_| ReportError::Serialize)
 }
 
+pub async fn create_csv_report(
+    db: &MantraDb,
+    project: &Project,
+    tag: &Tag,
+    req_template: Option<&Path>,
+    test_run_template: Option<&Path>,
+    options: &ReportOptions,
+) -> Result<String, ReportError> {
+    let report =
+        ReportContext::try_from(db, project, tag, req_template, test_run_template, options).await?;
+    Ok(csv_report(&report.requirements))
+}
+
+const CSV_HEADER: &str =
+    "id,title,traced,covered,passed,deprecated,manual,untraceable,direct_trace_cnt";
+
+/// Flattens the requirements into CSV rows.
+/// Nested data like traces is summarized as counts.
+fn csv_report(requirements: &[RequirementInfo]) -> String {
+    let mut csv = format!("{CSV_HEADER}\n");
+
+    for req in requirements {
+        let row = [
+            csv_field(&req.meta.id),
+            csv_field(&req.meta.title),
+            req.trace_info.traced.to_string(),
+            req.test_coverage_info.covered.to_string(),
+            req.test_coverage_info.passed.to_string(),
+            req.meta.deprecated.to_string(),
+            req.meta.manual.unwrap_or_default().to_string(),
+            req.meta.untraceable.to_string(),
+            req.trace_info.direct_traces.len().to_string(),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// Quotes the field if it contains characters with special meaning in CSV.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
 const REPORT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 fn serialize_report_version<S>(_value: &Option<String>, ser: S) -> Result<S::Ok, S::Error>
@@ -258,7 +1165,26 @@ pub struct ReportContext {
     pub version: Option<String>,
     pub project: Project,
     pub tag: Tag,
+    /// Branch the report was created for.
+    /// `None` for the default branch.
+    pub branch: Option<String>,
     pub overview: RequirementsOverview,
+    /// Requirement coverage per test tag.
+    pub tag_coverage: Vec<TagCoverage>,
+    /// Number of requirements per state.
+    /// Empty if no requirement has a state.
+    pub state_overview: Vec<StateCount>,
+    /// Coverage weighted by the effort of requirements.
+    /// `None` if no requirement has an effort set.
+    pub effort_overview: Option<EffortOverview>,
+    /// Requirements with a risk score above zero, ordered from highest to lowest risk.
+    pub risks: Vec<RequirementRisk>,
+    /// Requirements partitioned into groups if set with `--group-by`.
+    pub groups: Option<Vec<RequirementGroup>>,
+    /// Changes compared to a baseline database.
+    pub delta: Option<ReportDelta>,
+    /// Status changes since the previous collect if set with `--only-changed`.
+    pub changes: Option<ReportChanges>,
     pub requirements: Vec<RequirementInfo>,
     pub tests: TestStatistics,
     pub reviews: Vec<Review>,
@@ -272,6 +1198,7 @@ pub struct ReportContext {
     #[schemars(with = "String")]
     pub creation_date: OffsetDateTime,
     pub validation: ValidationInfo,
+    pub warnings: ReportWarnings,
     pub unrelated: Unrelated,
 }
 
@@ -282,21 +1209,151 @@ impl ReportContext {
         tag: &Tag,
         req_template: Option<&Path>,
         test_run_template: Option<&Path>,
+        options: &ReportOptions,
     ) -> Result<Self, ReportError> {
-        let overview = RequirementsOverview::try_from(db).await?;
+        let mut req_ids: Vec<ReqId> = match options.sort {
+            ReportSort::Id => sqlx::query!("select id from Requirements order by id")
+                .fetch_all(db.pool())
+                .await
+                .map_err(ReportError::Db)?
+                .into_iter()
+                .map(|r| r.id)
+                .collect(),
+            ReportSort::Priority => {
+                sqlx::query!("select id from Requirements order by priority is null, priority, id")
+                    .fetch_all(db.pool())
+                    .await
+                    .map_err(ReportError::Db)?
+                    .into_iter()
+                    .map(|r| r.id)
+                    .collect()
+            }
+        };
 
-        let req_records = sqlx::query!("select id from Requirements order by id")
-            .fetch_all(db.pool())
-            .await
-            .map_err(ReportError::Db)?;
+        let mut scope: Option<HashSet<ReqId>> = None;
+        if options.is_scoped() {
+            let mut selected: HashSet<ReqId> = options.only.iter().cloned().collect();
+
+            for root_id in &options.only_subtree {
+                selected.insert(root_id.clone());
+
+                let descendants = sqlx::query!(
+                    r#"select descendant_id as "descendant_id!" from RequirementDescendants where id = $1"#,
+                    root_id
+                )
+                .fetch_all(db.pool())
+                .await
+                .map_err(ReportError::Db)?;
+                selected.extend(descendants.into_iter().map(|r| r.descendant_id));
+            }
+
+            for id in &selected {
+                if !req_ids.contains(id) {
+                    log::warn!("Requirement `{id}` selected for the report does not exist.");
+                }
+            }
 
+            if !options.only.is_empty() || !options.only_subtree.is_empty() {
+                req_ids.retain(|id| selected.contains(id));
+                scope = Some(selected);
+            }
+
+            if !options.states.is_empty() {
+                let in_state: HashSet<ReqId> = sqlx::query!(
+                    r#"select id, state as "state!" from Requirements where state is not null"#
+                )
+                .fetch_all(db.pool())
+                .await
+                .map_err(ReportError::Db)?
+                .into_iter()
+                .filter(|r| options.states.contains(&r.state))
+                .map(|r| r.id)
+                .collect();
+
+                req_ids.retain(|id| in_state.contains(id));
+                scope = Some(match scope {
+                    Some(selected) => selected.intersection(&in_state).cloned().collect(),
+                    None => in_state,
+                });
+            }
+        }
+
+        let timer = crate::profile::phase("requirements");
+        let progress = crate::progress::bar(req_ids.len(), "Requirements rendered");
         let mut requirements = Vec::new();
-        for req in req_records {
-            requirements.push(RequirementInfo::try_from(db, req.id, req_template).await?);
+        for req_id in req_ids {
+            requirements.push(RequirementInfo::try_from(db, req_id, req_template).await?);
+            progress.inc(1);
+        }
+        progress.finish_and_clear();
+        timer.finish();
+
+        if options.coverage_across_runs == CoverageAcrossRuns::Any {
+            for req in &mut requirements {
+                req.test_coverage_info.passed = req.test_coverage_info.passed_in_any_run();
+            }
+        }
+
+        let changes = if options.only_changed {
+            let previous = db.previous_status().await.map_err(ReportError::Changes)?;
+            if previous.is_none() {
+                log::warn!("No previous collect recorded. All requirements are taken as added.");
+            }
+
+            let changes = ReportChanges::new(previous, &requirements, scope.as_ref());
+            let changed = changes.changed_ids();
+            requirements.retain(|req| changed.contains(&req.meta.id));
+            scope = Some(match scope {
+                Some(selected) => selected.intersection(&changed).cloned().collect(),
+                None => changed,
+            });
+
+            Some(changes)
+        } else {
+            None
+        };
+
+        let timer = crate::profile::phase("overview");
+        let mut overview =
+            if options.is_scoped() || options.coverage_across_runs != CoverageAcrossRuns::All {
+                RequirementsOverview::from_requirements(&requirements)
+            } else {
+                RequirementsOverview::try_from(db).await?
+            };
+
+        let tag_coverage = TagCoverage::try_from(db, &requirements).await?;
+        let state_overview = StateCount::from_requirements(&requirements);
+        let effort_overview = EffortOverview::from_requirements(&requirements);
+        let risks = RequirementRisk::from_requirements(&requirements, &options.risk_weights);
+        let mut groups = options
+            .group_by
+            .map(|grouping| RequirementGroup::from_requirements(&requirements, grouping));
+
+        let span_lines = SpanCoverage::span_lines(db).await?;
+        overview.span_coverage = SpanCoverage::from_ids(
+            requirements.iter().map(|req| req.meta.id.as_str()),
+            &span_lines,
+        );
+        for group in groups.iter_mut().flatten() {
+            group.overview.span_coverage =
+                SpanCoverage::from_ids(group.requirements.iter().map(String::as_str), &span_lines);
         }
+        timer.finish();
+
+        let timer = crate::profile::phase("delta");
+        let delta = match &options.compare {
+            Some(baseline) => {
+                Some(ReportDelta::try_from(db, baseline, &requirements, scope.as_ref()).await?)
+            }
+            None => None,
+        };
+        timer.finish();
 
+        let timer = crate::profile::phase("tests");
         let tests = TestStatistics::try_from(db, test_run_template).await?;
+        timer.finish();
 
+        let timer = crate::profile::phase("reviews");
         let review_records = sqlx::query!("select name, date from Reviews order by name, date")
             .fetch_all(db.pool())
             .await
@@ -308,14 +1365,16 @@ impl ReportContext {
                 .expect("Review date was added to db in custom review-date format.");
             reviews.push(Review::try_from(db, review.name, date).await?);
         }
+        timer.finish();
 
         let trace_criteria = "Requirements are traced if one of the following criteria is met:
 
 - A trace directly referring to the requirement exists (Directly traced)
 - All of the sub-requirements of the requirement are traced (Indirectly traced)";
 
-        let test_coverage_criteria =
-            "Requirements are covered through a test if one of the following criteria is met:
+        let test_coverage_criteria = match options.coverage_across_runs {
+            CoverageAcrossRuns::All => {
+                "Requirements are covered through a test if one of the following criteria is met:
 
 - At least one direct trace to the requirement was reached during test execution (Direct coverage)
 - All sub-requirements of the requirement were covered by the test (Indirect coverage)
@@ -324,19 +1383,54 @@ Requirements are passed if all of the following criteria are met:
 
 - The requirement is covered at least once
 - All tests covering the requirement passed
-- All tests covering child requirements of the requirement passed";
+- All tests covering child requirements of the requirement passed"
+            }
+            CoverageAcrossRuns::Any => {
+                "Requirements are covered through a test if one of the following criteria is met:
+
+- At least one direct trace to the requirement was reached during test execution (Direct coverage)
+- All sub-requirements of the requirement were covered by the test (Indirect coverage)
+
+Requirements are passed if all of the following criteria are met for at least one test run:
+
+- The requirement is covered in the test run
+- All tests of the test run covering the requirement passed
+- All tests of the test run covering child requirements of the requirement passed"
+            }
+        };
 
         let creation_date = OffsetDateTime::now_utc();
 
+        let timer = crate::profile::phase("validation");
         let validation = ValidationInfo::try_from(db).await?;
+        timer.finish();
 
+        let timer = crate::profile::phase("warnings");
+        let warnings =
+            ReportWarnings::try_from(db, options.max_reqs_per_item, options.fuzzy_ids).await?;
+        timer.finish();
+
+        let timer = crate::profile::phase("unrelated");
         let unrelated = Unrelated::try_from(db).await?;
+        timer.finish();
 
-        Ok(Self {
+        let mut context = Self {
             version: Some(REPORT_VERSION.to_string()),
             project: project.clone(),
             tag: tag.clone(),
+            branch: if db.branch().is_empty() {
+                None
+            } else {
+                Some(db.branch().to_string())
+            },
             overview,
+            tag_coverage,
+            state_overview,
+            effort_overview,
+            risks,
+            groups,
+            delta,
+            changes,
             requirements,
             tests,
             reviews,
@@ -344,50 +1438,1019 @@ Requirements are passed if all of the following criteria are met:
             test_coverage_criteria,
             creation_date,
             validation,
+            warnings,
             unrelated,
+        };
+
+        if options.redact {
+            context.redact_paths();
+        }
+
+        Ok(context)
+    }
+
+    /// Replaces all filepaths with a hash of the path, so the report does not expose the directory structure.
+    /// The same path is always replaced by the same hash, and the file extension is kept.
+    pub fn redact_paths(&mut self) {
+        fn redact_locations(traces: &mut [TraceLocation]) {
+            for trace in traces {
+                trace.filepath = redact_path(&trace.filepath);
+            }
+        }
+
+        fn redact_test_runs(test_runs: &mut [TestCoverageTestRunInfo]) {
+            for test in test_runs.iter_mut().flat_map(|run| run.tests.iter_mut()) {
+                redact_locations(&mut test.traces);
+            }
+        }
+
+        for req in &mut self.requirements {
+            for trace in &mut req.trace_info.direct_traces {
+                trace.filepath = redact_path(&trace.filepath);
+            }
+            for indirect in &mut req.trace_info.indirect_traces {
+                redact_locations(&mut indirect.traces);
+            }
+
+            let coverage = &mut req.test_coverage_info;
+            redact_test_runs(&mut coverage.direct_coverage);
+            for indirect in &mut coverage.indirect_coverage {
+                redact_test_runs(&mut indirect.test_runs);
+            }
+            for test in &mut coverage.declared_coverage {
+                test.filepath = redact_path(&test.filepath);
+            }
+        }
+
+        for test in self
+            .tests
+            .test_runs
+            .iter_mut()
+            .flat_map(|run| run.tests.iter_mut())
+        {
+            test.filepath = PathBuf::from(redact_path(&test.filepath.to_string_lossy()));
+        }
+
+        for item in &mut self.warnings.dense_items {
+            item.filepath = PathBuf::from(redact_path(&item.filepath.to_string_lossy()));
+        }
+
+        for trace in &mut self.unrelated.traces {
+            trace.filepath = PathBuf::from(redact_path(&trace.filepath.to_string_lossy()));
+        }
+        for coverage in &mut self.unrelated.coverage {
+            coverage.trace_filepath =
+                PathBuf::from(redact_path(&coverage.trace_filepath.to_string_lossy()));
+        }
+
+        if let Some(delta) = &mut self.delta {
+            delta.baseline = redact_path(&delta.baseline);
+        }
+    }
+}
+
+/// Returns a stable hash of the path, keeping the file extension.
+///
+/// FNV-1a is used, because its result does not change between Rust versions.
+fn redact_path(path: &str) -> String {
+    let hash = path.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    });
+
+    match Path::new(path).extension() {
+        Some(ext) => format!("{hash:016x}.{}", ext.to_string_lossy()),
+        None => format!("{hash:016x}"),
+    }
+}
+
+/// Changes of the collected data compared to a baseline database.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ReportDelta {
+    /// Path to the baseline database.
+    pub baseline: String,
+    /// Requirements that do not exist in the baseline.
+    pub added_reqs: Vec<ReqId>,
+    /// Requirements that only exist in the baseline.
+    pub removed_reqs: Vec<ReqId>,
+    pub traced_delta: i64,
+    pub covered_delta: i64,
+    pub passed_delta: i64,
+    /// Requirements that were traced in the baseline, but are no longer traced.
+    pub newly_untraced: Vec<ReqId>,
+    /// Requirements that passed in the baseline, but are covered by failed tests now.
+    pub regressions: Vec<ReqId>,
+}
+
+/// Trace and test status of one requirement in the baseline.
+struct BaselineStatus {
+    traced: bool,
+    covered: bool,
+    passed: bool,
+}
+
+impl ReportDelta {
+    /// Compares the given requirements against the data of the baseline database.
+    /// If a scope is given, only baseline requirements inside the scope are considered.
+    pub async fn try_from(
+        db: &MantraDb,
+        baseline: &Path,
+        requirements: &[RequirementInfo],
+        scope: Option<&HashSet<ReqId>>,
+    ) -> Result<Self, ReportError> {
+        let snapshot = db
+            .file_snapshot(baseline)
+            .await
+            .map_err(ReportError::Compare)?;
+        let baseline_status = Self::baseline_status(&snapshot.db, scope).await;
+        snapshot.remove().await;
+        let baseline_status = baseline_status?;
+
+        let mut delta = Self {
+            baseline: baseline.display().to_string(),
+            added_reqs: Vec::new(),
+            removed_reqs: Vec::new(),
+            traced_delta: 0,
+            covered_delta: 0,
+            passed_delta: 0,
+            newly_untraced: Vec::new(),
+            regressions: Vec::new(),
+        };
+
+        for req in requirements {
+            let id = &req.meta.id;
+            let traced = req.trace_info.traced;
+            let covered = req.test_coverage_info.covered;
+            let passed = req.test_coverage_info.passed;
+
+            delta.traced_delta += traced as i64;
+            delta.covered_delta += covered as i64;
+            delta.passed_delta += passed as i64;
+
+            match baseline_status.get(id) {
+                Some(status) => {
+                    if status.traced && !traced {
+                        delta.newly_untraced.push(id.clone());
+                    }
+                    if status.passed && covered && !passed {
+                        delta.regressions.push(id.clone());
+                    }
+                }
+                None => delta.added_reqs.push(id.clone()),
+            }
+        }
+
+        let current_ids: HashSet<&ReqId> = requirements.iter().map(|r| &r.meta.id).collect();
+        for (id, status) in &baseline_status {
+            delta.traced_delta -= status.traced as i64;
+            delta.covered_delta -= status.covered as i64;
+            delta.passed_delta -= status.passed as i64;
+
+            if !current_ids.contains(id) {
+                delta.removed_reqs.push(id.clone());
+            }
+        }
+
+        Ok(delta)
+    }
+
+    async fn baseline_status(
+        baseline: &MantraDb,
+        scope: Option<&HashSet<ReqId>>,
+    ) -> Result<std::collections::BTreeMap<ReqId, BaselineStatus>, ReportError> {
+        let req_ids = sqlx::query!("select id from Requirements order by id")
+            .fetch_all(baseline.pool())
+            .await
+            .map_err(ReportError::Db)?;
+
+        let mut status = std::collections::BTreeMap::new();
+        for id in req_ids.into_iter().map(|r| r.id) {
+            if scope.is_some_and(|scope| !scope.contains(&id)) {
+                continue;
+            }
+
+            let trace_info = RequirementTraceInfo::try_from(baseline, &id).await?;
+            let coverage_info = RequirementTestCoverageInfo::try_from(baseline, &id).await?;
+            status.insert(
+                id,
+                BaselineStatus {
+                    traced: trace_info.traced,
+                    covered: coverage_info.covered,
+                    passed: coverage_info.passed,
+                },
+            );
+        }
+
+        Ok(status)
+    }
+}
+
+/// Status changes of requirements compared to the previous collect.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ReportChanges {
+    /// Generation of the previous collect.
+    /// `None` if no previous collect was recorded, in which case all requirements are added.
+    pub previous_generation: Option<i64>,
+    /// Requirements that did not exist in the previous collect.
+    pub added: Vec<ReqId>,
+    /// Requirements that only existed in the previous collect.
+    pub removed: Vec<ReqId>,
+    pub newly_traced: Vec<ReqId>,
+    pub newly_untraced: Vec<ReqId>,
+    pub newly_covered: Vec<ReqId>,
+    pub newly_uncovered: Vec<ReqId>,
+    pub newly_passed: Vec<ReqId>,
+    /// Requirements that passed in the previous collect, but no longer pass.
+    pub newly_failed: Vec<ReqId>,
+}
+
+impl ReportChanges {
+    /// Compares the status of the given requirements against the status of the previous collect.
+    /// If a scope is given, only previous requirements inside the scope are considered.
+    pub fn new(
+        previous: Option<(i64, HashMap<ReqId, RequirementStatus>)>,
+        requirements: &[RequirementInfo],
+        scope: Option<&HashSet<ReqId>>,
+    ) -> Self {
+        let (previous_generation, previous_status) = match previous {
+            Some((generation, status)) => (Some(generation), status),
+            None => (None, HashMap::new()),
+        };
+
+        let mut changes = Self {
+            previous_generation,
+            added: Vec::new(),
+            removed: Vec::new(),
+            newly_traced: Vec::new(),
+            newly_untraced: Vec::new(),
+            newly_covered: Vec::new(),
+            newly_uncovered: Vec::new(),
+            newly_passed: Vec::new(),
+            newly_failed: Vec::new(),
+        };
+
+        for req in requirements {
+            let id = &req.meta.id;
+            let Some(prev) = previous_status.get(id) else {
+                changes.added.push(id.clone());
+                continue;
+            };
+
+            let traced = req.trace_info.traced;
+            let covered = req.test_coverage_info.covered;
+            let passed = req.test_coverage_info.passed;
+
+            match (prev.traced, traced) {
+                (false, true) => changes.newly_traced.push(id.clone()),
+                (true, false) => changes.newly_untraced.push(id.clone()),
+                _ => {}
+            }
+            match (prev.covered, covered) {
+                (false, true) => changes.newly_covered.push(id.clone()),
+                (true, false) => changes.newly_uncovered.push(id.clone()),
+                _ => {}
+            }
+            match (prev.passed, passed) {
+                (false, true) => changes.newly_passed.push(id.clone()),
+                (true, false) => changes.newly_failed.push(id.clone()),
+                _ => {}
+            }
+        }
+
+        let current_ids: HashSet<&ReqId> = requirements.iter().map(|r| &r.meta.id).collect();
+        let mut removed: Vec<ReqId> = previous_status
+            .into_keys()
+            .filter(|id| !current_ids.contains(id) && scope.is_none_or(|scope| scope.contains(id)))
+            .collect();
+        removed.sort();
+        changes.removed = removed;
+
+        changes
+    }
+
+    /// IDs of existing requirements whose status changed.
+    pub fn changed_ids(&self) -> HashSet<ReqId> {
+        [
+            &self.added,
+            &self.newly_traced,
+            &self.newly_untraced,
+            &self.newly_covered,
+            &self.newly_uncovered,
+            &self.newly_passed,
+            &self.newly_failed,
+        ]
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect()
+    }
+}
+
+/// Requirements directly covered by tests having the tag.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct TagCoverage {
+    pub tag: String,
+    pub covered_cnt: i64,
+    pub covered_ratio: f64,
+}
+
+impl TagCoverage {
+    /// Computes the coverage per tag over the given requirements.
+    pub async fn try_from(
+        db: &MantraDb,
+        requirements: &[RequirementInfo],
+    ) -> Result<Vec<Self>, ReportError> {
+        let branch = db.branch();
+        let records = sqlx::query!(
+            r#"select tag as "tag!", req_id as "req_id!" from TagCoveredRequirements where branch = $1 order by tag"#,
+            branch
+        )
+        .fetch_all(db.pool())
+        .await
+        .map_err(ReportError::Db)?;
+
+        let req_ids: HashSet<&str> = requirements.iter().map(|r| r.meta.id.as_str()).collect();
+        let mut tag_coverage: Vec<Self> = Vec::new();
+
+        for record in records {
+            if tag_coverage.last().map(|t| &t.tag) != Some(&record.tag) {
+                tag_coverage.push(Self {
+                    tag: record.tag.clone(),
+                    covered_cnt: 0,
+                    covered_ratio: 0.0,
+                });
+            }
+
+            if req_ids.contains(record.req_id.as_str()) {
+                if let Some(entry) = tag_coverage.last_mut() {
+                    entry.covered_cnt += 1;
+                }
+            }
+        }
+
+        for entry in &mut tag_coverage {
+            entry.covered_ratio = if req_ids.is_empty() {
+                0.0
+            } else {
+                entry.covered_cnt as f64 / req_ids.len() as f64
+            };
+        }
+
+        Ok(tag_coverage)
+    }
+}
+
+/// Requirements having the state.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct StateCount {
+    /// `None` for requirements without state.
+    pub state: Option<String>,
+    pub req_cnt: i64,
+    pub req_ratio: f64,
+}
+
+impl StateCount {
+    /// Counts the given requirements per state.
+    /// States are ordered by name, and requirements without state are placed last.
+    pub fn from_requirements(requirements: &[RequirementInfo]) -> Vec<Self> {
+        if requirements.iter().all(|req| req.meta.state.is_none()) {
+            return Vec::new();
+        }
+
+        let mut counts: std::collections::BTreeMap<Option<&str>, i64> =
+            std::collections::BTreeMap::new();
+
+        for req in requirements {
+            *counts.entry(req.meta.state.as_deref()).or_default() += 1;
+        }
+
+        // no state is the first key in the ordered map
+        let without_state = counts.remove(&None);
+        let total = requirements.len() as f64;
+
+        counts
+            .into_iter()
+            .map(|(state, cnt)| (state.map(|s| s.to_string()), cnt))
+            .chain(without_state.map(|cnt| (None, cnt)))
+            .map(|(state, req_cnt)| Self {
+                state,
+                req_cnt,
+                req_ratio: req_cnt as f64 / total,
+            })
+            .collect()
+    }
+}
+
+/// Effort of requirements that are covered or passed.
+/// Requirements without effort are not considered.
+#[derive(
+    Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
+)]
+pub struct EffortOverview {
+    pub total_effort: f64,
+    pub covered_effort: f64,
+    pub covered_ratio: f64,
+    pub passed_effort: f64,
+    pub passed_ratio: f64,
+}
+
+impl EffortOverview {
+    pub fn from_requirements(requirements: &[RequirementInfo]) -> Option<Self> {
+        let efforts: Vec<(f64, &RequirementInfo)> = requirements
+            .iter()
+            .filter_map(|req| req.meta.effort.map(|effort| (effort, req)))
+            .collect();
+
+        if efforts.is_empty() {
+            return None;
+        }
+
+        // summing an empty float iterator results in `-0.0`
+        let sum = |filter: fn(&RequirementInfo) -> bool| {
+            efforts
+                .iter()
+                .filter(|(_, req)| filter(req))
+                .fold(0.0, |sum, (effort, _)| sum + effort)
+        };
+
+        let total_effort = sum(|_| true);
+        let covered_effort = sum(|req| req.test_coverage_info.covered);
+        let passed_effort = sum(|req| req.test_coverage_info.passed);
+
+        let ratio = |effort: f64| {
+            if total_effort == 0.0 {
+                0.0
+            } else {
+                effort / total_effort
+            }
+        };
+
+        Some(Self {
+            total_effort,
+            covered_effort,
+            covered_ratio: ratio(covered_effort),
+            passed_effort,
+            passed_ratio: ratio(passed_effort),
         })
     }
 }
 
+/// Weights of the factors adding to the risk score of requirements.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct RiskWeights {
+    /// Requirement is not traced.
+    pub untraced: u32,
+    /// Requirement is traced, but not covered by any test.
+    pub uncovered: u32,
+    /// Tests covering the requirement failed in all test runs.
+    pub failing: u32,
+    /// Tests covering the requirement failed in some test runs, but passed in others.
+    pub flaky: u32,
+    /// Manual requirement without any verification.
+    pub unverified: u32,
+    /// Latest verification of a manual requirement is older than `verification_max_age_days`.
+    #[serde(alias = "expired-verification")]
+    pub expired_verification: u32,
+    /// Deprecated requirement that is still traced.
+    #[serde(alias = "deprecated-traced")]
+    pub deprecated_traced: u32,
+    /// Days after which manual verifications expire.
+    /// Verifications never expire if not set.
+    #[serde(alias = "verification-max-age-days")]
+    pub verification_max_age_days: Option<u32>,
+}
+
+impl Default for RiskWeights {
+    fn default() -> Self {
+        Self {
+            untraced: 5,
+            uncovered: 3,
+            failing: 8,
+            flaky: 4,
+            unverified: 4,
+            expired_verification: 2,
+            deprecated_traced: 6,
+            verification_max_age_days: None,
+        }
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum RiskFactor {
+    Untraced,
+    Uncovered,
+    Failing,
+    Flaky,
+    Unverified,
+    ExpiredVerification,
+    DeprecatedTraced,
+}
+
+/// Factor that adds to the risk score of a requirement.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct RiskContribution {
+    pub factor: RiskFactor,
+    /// Weight of the factor added to the risk score.
+    pub score: u32,
+}
+
+/// Risk score of a requirement, combining all signals that indicate the requirement may not be fulfilled.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct RequirementRisk {
+    pub id: ReqId,
+    pub score: u32,
+    pub factors: Vec<RiskContribution>,
+}
+
+impl RequirementRisk {
+    /// Returns the risk of all requirements with a score above zero, ordered from highest to lowest score.
+    /// Requirements with the same score are ordered by ID.
+    pub fn from_requirements(requirements: &[RequirementInfo], weights: &RiskWeights) -> Vec<Self> {
+        let expiry = weights.verification_max_age_days.map(|days| {
+            let expiry = OffsetDateTime::now_utc() - time::Duration::days(i64::from(days));
+            PrimitiveDateTime::new(expiry.date(), expiry.time())
+        });
+
+        let mut risks: Vec<Self> = requirements
+            .iter()
+            .map(|req| Self::from_requirement(req, weights, expiry))
+            .filter(|risk| risk.score > 0)
+            .collect();
+
+        risks.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.id.cmp(&b.id)));
+        risks
+    }
+
+    fn from_requirement(
+        req: &RequirementInfo,
+        weights: &RiskWeights,
+        expiry: Option<PrimitiveDateTime>,
+    ) -> Self {
+        let mut factors = Vec::new();
+        let coverage = &req.test_coverage_info;
+
+        if req.meta.deprecated {
+            // other factors do not matter for requirements that are no longer relevant
+            if req.trace_info.traced {
+                factors.push((RiskFactor::DeprecatedTraced, weights.deprecated_traced));
+            }
+        } else if req.meta.manual.unwrap_or_default() {
+            match req
+                .verified_info
+                .iter()
+                .map(|verified| verified.review_date)
+                .max()
+            {
+                Some(latest) if expiry.is_some_and(|expiry| latest < expiry) => factors.push((
+                    RiskFactor::ExpiredVerification,
+                    weights.expired_verification,
+                )),
+                Some(_) => {}
+                None => factors.push((RiskFactor::Unverified, weights.unverified)),
+            }
+        } else if req.meta.untraceable {
+            // missing traces and tests are expected for requirements that cannot be traced
+        } else if !req.trace_info.traced {
+            factors.push((RiskFactor::Untraced, weights.untraced));
+        } else if !coverage.covered {
+            factors.push((RiskFactor::Uncovered, weights.uncovered));
+        }
+
+        if !req.meta.deprecated && coverage.covered && !coverage.passed {
+            if coverage.passed_in_any_run() {
+                factors.push((RiskFactor::Flaky, weights.flaky));
+            } else {
+                factors.push((RiskFactor::Failing, weights.failing));
+            }
+        }
+
+        let factors: Vec<RiskContribution> = factors
+            .into_iter()
+            .filter(|(_, score)| *score > 0)
+            .map(|(factor, score)| RiskContribution { factor, score })
+            .collect();
+
+        Self {
+            id: req.meta.id.clone(),
+            score: factors.iter().map(|factor| factor.score).sum(),
+            factors,
+        }
+    }
+}
+
+/// Requirements sharing the same grouping key.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct RequirementGroup {
+    /// `None` for requirements without state if grouped by state.
+    pub key: Option<String>,
+    pub overview: RequirementsOverview,
+    /// IDs of the requirements in the group, in report order.
+    pub requirements: Vec<ReqId>,
+}
+
+impl RequirementGroup {
+    /// Partitions the given requirements into groups.
+    /// Groups are ordered by their key, and requirements without key are placed last.
+    pub fn from_requirements(
+        requirements: &[RequirementInfo],
+        grouping: ReportGrouping,
+    ) -> Vec<Self> {
+        let mut partitions: std::collections::BTreeMap<Option<String>, Vec<RequirementInfo>> =
+            std::collections::BTreeMap::new();
+
+        for req in requirements {
+            let key = match grouping {
+                ReportGrouping::Origin => Some(origin_group(&req.meta.origin)),
+                ReportGrouping::State => req.meta.state.clone(),
+            };
+
+            partitions.entry(key).or_default().push(req.clone());
+        }
+
+        // no key is the first key in the ordered map
+        let without_key = partitions.remove(&None);
+
+        partitions
+            .into_iter()
+            .chain(without_key.map(|reqs| (None, reqs)))
+            .map(|(key, reqs)| Self {
+                key,
+                overview: RequirementsOverview::from_requirements(&reqs),
+                requirements: reqs.into_iter().map(|req| req.meta.id).collect(),
+            })
+            .collect()
+    }
+}
+
+/// Returns the source of the given origin.
+///
+/// e.g. `https://github.com/mhatzl/mantra/issues/1` -> `github.com/mhatzl/mantra`,
+/// and `wiki/5-Requirements/1-tracing` -> `wiki/5-Requirements`
+fn origin_group(origin: &str) -> String {
+    match origin.split_once("://") {
+        Some((_, rest)) => {
+            let path = rest.split(['?', '#']).next().unwrap_or_default();
+            path.split('/')
+                .filter(|segment| !segment.is_empty())
+                .take(3)
+                .collect::<Vec<_>>()
+                .join("/")
+        }
+        None => match origin.rsplit_once('/') {
+            Some((source, _)) => source.to_string(),
+            None => origin.to_string(),
+        },
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub struct ValidationInfo {
     pub is_valid: bool,
     pub criteria: &'static str,
     pub invalid_reqs: Vec<String>,
+    /// Path of a cycle in the requirement hierarchy, starting and ending at the same requirement.
+    pub hierarchy_cycle: Option<Vec<ReqId>>,
+    /// Requirements whose validation errors are acknowledged by a suppression in the *mantra* configuration.
+    pub suppressed: Vec<SuppressedRequirement>,
+}
+
+/// Requirement that would be invalid without its suppression.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct SuppressedRequirement {
+    pub id: ReqId,
+    /// Justification why the validation error is acknowledged.
+    pub reason: String,
 }
 
 impl ValidationInfo {
     pub async fn try_from(db: &MantraDb) -> Result<Self, ReportError> {
         let validation_criteria =
-            "The collected data is valid if no *deprecated* requirement is traced.";
+            "The collected data is valid if no *deprecated* requirement is traced, and the requirement hierarchy has no cycles. Traced *deprecated* requirements with a suppression are listed separately, and do not invalidate the data.";
         let is_valid = db.is_valid().await.is_ok();
+        let branch = db.branch();
+
+        let suppressed = sqlx::query_as!(
+            SuppressedRequirement,
+            r#"select id as "id!", reason as "reason!" from SuppressedInvalidRequirements where branch = $1 order by id"#,
+            branch
+        )
+        .fetch_all(db.pool())
+        .await
+        .map_err(ReportError::Db)?;
 
         if is_valid {
             Ok(Self {
                 is_valid,
                 criteria: validation_criteria,
                 invalid_reqs: vec![],
+                hierarchy_cycle: None,
+                suppressed,
             })
         } else {
-            let invalid_records = sqlx::query!(r#"select id as "id!" from InvalidRequirements"#)
-                .fetch_all(db.pool())
-                .await
-                .map_err(ReportError::Db)?;
+            let invalid_records = sqlx::query!(
+                r#"select id as "id!" from InvalidRequirements where branch = $1"#,
+                branch
+            )
+            .fetch_all(db.pool())
+            .await
+            .map_err(ReportError::Db)?;
             let invalid_reqs = invalid_records.into_iter().map(|r| r.id).collect();
+            let hierarchy_cycle = db.hierarchy_cycle().await.map_err(ReportError::Validate)?;
+
             Ok(Self {
                 is_valid,
                 criteria: validation_criteria,
                 invalid_reqs,
+                hierarchy_cycle,
+                suppressed,
             })
         }
     }
 }
 
+/// Findings that do not invalidate the collected data, but may indicate problems.
+#[derive(
+    Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
+)]
+pub struct ReportWarnings {
+    /// Maximum number of requirements a traced item may trace before it is listed in `dense_items`.
+    pub max_reqs_per_item: Option<u32>,
+    /// Traced items that trace more requirements than allowed.
+    pub dense_items: Vec<DenseTracedItem>,
+    /// IDs that are suspiciously close to requirement IDs, if enabled with `--fuzzy-ids`.
+    pub similar_ids: Vec<SimilarId>,
+}
+
+/// Item that traces more requirements than allowed.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct DenseTracedItem {
+    /// Name of the item if it is known.
+    pub name: Option<String>,
+    pub filepath: PathBuf,
+    pub start: Line,
+    pub end: Line,
+    pub req_ids: Vec<ReqId>,
+}
+
+/// ID that is suspiciously close to a requirement ID, and may be a typo.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct SimilarId {
+    pub id: ReqId,
+    /// Requirement the ID is close to.
+    pub similar_to: ReqId,
+    /// Edit distance between both IDs.
+    pub distance: usize,
+    /// `true` if the ID is no requirement, but only used by unrelated traces.
+    pub unrelated: bool,
+    pub trace_cnt: i64,
+    pub similar_trace_cnt: i64,
+}
+
+/// Maximum edit distance of unrelated trace IDs to requirement IDs to be listed as similar.
+const MAX_UNRELATED_ID_DISTANCE: usize = 2;
+/// Maximum edit distance between requirement IDs to be listed as similar.
+const MAX_REQ_ID_DISTANCE: usize = 1;
+
+impl ReportWarnings {
+    pub async fn try_from(
+        db: &MantraDb,
+        max_reqs_per_item: Option<u32>,
+        fuzzy_ids: bool,
+    ) -> Result<Self, ReportError> {
+        let dense_items = match max_reqs_per_item {
+            Some(max_reqs) => Self::dense_items(db, max_reqs).await?,
+            None => Vec::new(),
+        };
+
+        let similar_ids = if fuzzy_ids {
+            let branch = db.branch();
+            let reqs: Vec<(ReqId, i64)> = sqlx::query!(
+                r#"select r.id, (select count(*) from Traces t where t.req_id = r.id and t.branch = $1) as "trace_cnt!: i64" from Requirements r order by r.id"#,
+                branch
+            )
+            .fetch_all(db.pool())
+            .await
+            .map_err(ReportError::Db)?
+            .into_iter()
+            .map(|r| (r.id, r.trace_cnt))
+            .collect();
+
+            let unrelated: Vec<(ReqId, i64)> = sqlx::query!(
+                r#"select req_id, count(*) as "trace_cnt!: i64" from UnrelatedTraces where branch = $1 group by req_id order by req_id"#,
+                branch
+            )
+            .fetch_all(db.pool())
+            .await
+            .map_err(ReportError::Db)?
+            .into_iter()
+            .map(|r| (r.req_id, r.trace_cnt))
+            .collect();
+
+            let similar_ids = similar_ids(&reqs, &unrelated);
+
+            for similar in &similar_ids {
+                log::warn!(
+                    "{} `{}` with '{}' traces is similar to requirement `{}` with '{}' traces. This may be a typo.",
+                    if similar.unrelated { "Unrelated trace ID" } else { "Requirement" },
+                    similar.id,
+                    similar.trace_cnt,
+                    similar.similar_to,
+                    similar.similar_trace_cnt
+                );
+            }
+
+            similar_ids
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            max_reqs_per_item,
+            dense_items,
+            similar_ids,
+        })
+    }
+
+    /// Items are identified by the line span of their traces.
+    async fn dense_items(
+        db: &MantraDb,
+        max_reqs: u32,
+    ) -> Result<Vec<DenseTracedItem>, ReportError> {
+        let branch = db.branch();
+        let records = sqlx::query!(
+            r#"
+                select
+                ts.req_id,
+                ts.filepath,
+                ts.start as "start!: u32",
+                ts.end as "end!: u32",
+                ti.name as "name?"
+                from TraceSpans ts
+                left join TracedItems ti
+                on ts.filepath = ti.filepath and ts.start = ti.line and ts.branch = ti.branch
+                where ts.branch = $1
+                order by ts.filepath, ts.start, ts.end, ts.req_id
+            "#,
+            branch
+        )
+        .fetch_all(db.pool())
+        .await
+        .map_err(ReportError::Db)?;
+
+        let mut items: Vec<DenseTracedItem> = Vec::new();
+        for record in records {
+            let filepath = PathBuf::from(record.filepath);
+
+            match items.last_mut() {
+                Some(item)
+                    if item.filepath == filepath
+                        && item.start == record.start
+                        && item.end == record.end =>
+                {
+                    if !item.req_ids.contains(&record.req_id) {
+                        item.req_ids.push(record.req_id);
+                    }
+                    if item.name.is_none() {
+                        item.name = record.name;
+                    }
+                }
+                _ => items.push(DenseTracedItem {
+                    name: record.name,
+                    filepath,
+                    start: record.start,
+                    end: record.end,
+                    req_ids: vec![record.req_id],
+                }),
+            }
+        }
+
+        items.retain(|item| item.req_ids.len() > max_reqs as usize);
+
+        if !items.is_empty() {
+            log::warn!(
+                "'{}' traced items trace more than '{}' requirements.",
+                items.len(),
+                max_reqs
+            );
+        }
+
+        Ok(items)
+    }
+}
+
+/// Returns unrelated trace IDs and requirement IDs that are close to other requirement IDs.
+///
+/// Of two similar requirements, the one with fewer traces is listed as `id`.
+/// IDs that only differ in digits (e.g. `req.1` and `req.2`) are not similar,
+/// because numbered sibling requirements are common.
+/// The edit distance must also be at most a quarter of the length of the shorter ID.
+/// Unrelated IDs are listed first, followed by the largest difference in trace counts.
+fn similar_ids(reqs: &[(ReqId, i64)], unrelated: &[(ReqId, i64)]) -> Vec<SimilarId> {
+    fn without_digits(id: &str) -> String {
+        id.chars().filter(|c| !c.is_ascii_digit()).collect()
+    }
+
+    fn similarity(a: &str, b: &str, max: usize) -> Option<usize> {
+        if a.chars().count().abs_diff(b.chars().count()) > max
+            || without_digits(a) == without_digits(b)
+        {
+            return None;
+        }
+
+        // short IDs like `a` and `b` are always close
+        let min_len = a.chars().count().min(b.chars().count());
+        let distance = edit_distance(a, b);
+        (distance <= max && distance * 4 <= min_len).then_some(distance)
+    }
+
+    let mut similar = Vec::new();
+
+    for (id, trace_cnt) in unrelated {
+        if let Some(((req_id, req_trace_cnt), distance)) = reqs
+            .iter()
+            .filter_map(|req| similarity(id, &req.0, MAX_UNRELATED_ID_DISTANCE).map(|d| (req, d)))
+            .min_by_key(|(_, distance)| *distance)
+        {
+            similar.push(SimilarId {
+                id: id.clone(),
+                similar_to: req_id.clone(),
+                distance,
+                unrelated: true,
+                trace_cnt: *trace_cnt,
+                similar_trace_cnt: *req_trace_cnt,
+            });
+        }
+    }
+
+    for (i, (a, a_cnt)) in reqs.iter().enumerate() {
+        for (b, b_cnt) in &reqs[i + 1..] {
+            if let Some(distance) = similarity(a, b, MAX_REQ_ID_DISTANCE) {
+                let ((id, trace_cnt), (similar_to, similar_trace_cnt)) = if a_cnt <= b_cnt {
+                    ((a, a_cnt), (b, b_cnt))
+                } else {
+                    ((b, b_cnt), (a, a_cnt))
+                };
+
+                similar.push(SimilarId {
+                    id: id.clone(),
+                    similar_to: similar_to.clone(),
+                    distance,
+                    unrelated: false,
+                    trace_cnt: *trace_cnt,
+                    similar_trace_cnt: *similar_trace_cnt,
+                });
+            }
+        }
+    }
+
+    similar.sort_by(|a, b| {
+        b.unrelated
+            .cmp(&a.unrelated)
+            .then((b.similar_trace_cnt - b.trace_cnt).cmp(&(a.similar_trace_cnt - a.trace_cnt)))
+            .then(a.id.cmp(&b.id))
+    });
+    similar
+}
+
+/// Levenshtein distance between the characters of both strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(prev[j + 1] + 1).min(current[j] + 1);
+        }
+
+        prev = current;
+    }
+
+    prev[b.len()]
+}
+
 #[derive(
     Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
 )]
 pub struct RequirementsOverview {
     pub req_cnt: i64,
+    /// Number of requirements that cannot be traced by definition.
+    /// Untraceable requirements are excluded from the traced count and ratio.
+    #[serde(default)]
+    pub untraceable_cnt: i64,
     pub traced_cnt: i64,
     pub traced_ratio: f64,
     pub covered_cnt: i64,
@@ -396,22 +2459,100 @@ pub struct RequirementsOverview {
     pub passed_ratio: f64,
     pub verified_cnt: Option<i64>,
     pub verified_ratio: f64,
+    /// Coverage weighted by the line count of trace spans.
+    /// `None` if no trace of the requirements has a span.
+    pub span_coverage: Option<SpanCoverage>,
 }
 
 impl RequirementsOverview {
+    /// Computes the overview over the given requirements only.
+    pub fn from_requirements(requirements: &[RequirementInfo]) -> Self {
+        let ratio = |cnt: i64, total: i64| {
+            if total == 0 {
+                0.0
+            } else {
+                cnt as f64 / total as f64
+            }
+        };
+
+        let req_cnt = requirements.len() as i64;
+        let untraceable_cnt = requirements.iter().filter(|r| r.meta.untraceable).count() as i64;
+        let traced_cnt = requirements
+            .iter()
+            .filter(|r| r.trace_info.traced && !r.meta.untraceable)
+            .count() as i64;
+        let covered_cnt = requirements
+            .iter()
+            .filter(|r| r.test_coverage_info.covered)
+            .count() as i64;
+        let passed_cnt = requirements
+            .iter()
+            .filter(|r| r.test_coverage_info.passed)
+            .count() as i64;
+        let manual_cnt = requirements
+            .iter()
+            .filter(|r| r.meta.manual.unwrap_or_default())
+            .count() as i64;
+        let verified_cnt = requirements
+            .iter()
+            .filter(|r| r.meta.manual.unwrap_or_default() && !r.verified_info.is_empty())
+            .count() as i64;
+
+        Self {
+            req_cnt,
+            untraceable_cnt,
+            traced_cnt,
+            traced_ratio: ratio(traced_cnt, req_cnt - untraceable_cnt),
+            covered_cnt,
+            covered_ratio: ratio(covered_cnt, req_cnt),
+            passed_cnt,
+            passed_ratio: ratio(passed_cnt, req_cnt),
+            verified_cnt: if manual_cnt == 0 {
+                None
+            } else {
+                Some(verified_cnt)
+            },
+            verified_ratio: ratio(verified_cnt, manual_cnt),
+            span_coverage: None,
+        }
+    }
+
+    /// Computes the overview over all requirements for the branch of the database.
     pub async fn try_from(db: &MantraDb) -> Result<Self, ReportError> {
+        let branch = db.branch();
         let record = sqlx::query!(
-            r#"select
-                req_cnt,
-                traced_cnt,
-                traced_ratio,
-                covered_cnt,
-                covered_ratio,
-                passed_cnt,
-                passed_ratio,
-                verified_cnt as "verified_cnt?: i64",
-                verified_ratio
-             from RequirementCoverageOverview"#
+            r#"
+            with NrRequirements(cnt) as (select count(*) from Requirements),
+            NrUntraceable(cnt) as (select count(*) from UntraceableRequirements),
+            NrTraced(cnt) as (
+                -- Only consider traceable requirements for traced cnt and ratio
+                select count(*) from TracedRequirements
+                where branch = $1 and id not in (select id from UntraceableRequirements)
+            ),
+            NrCovered(cnt) as (select count(*) from CoveredRequirements where branch = $1),
+            NrPassed(cnt) as (select count(*) from PassedCoveredRequirements where branch = $1),
+            VerifiedOverview(cnt, ratio) as (
+                -- Only consider manual requirements for verified cnt and ratio
+                select
+                    case when m.nr_manuals = 0 then null else c.cnt end as cnt,
+                    case when m.nr_manuals = 0 then 0.0 else (c.cnt * 1.0 / m.nr_manuals) end as ratio
+                from (
+                    select count(*) as cnt
+                    from ManuallyVerifiedRequirements m, ManualRequirements r
+                    where m.req_id = r.id
+                ) as c, (
+                    select count(*) as nr_manuals
+                    from ManualRequirements
+                ) as m
+            )
+            select r.cnt as "req_cnt!: i64", u.cnt as "untraceable_cnt!: i64",
+                t.cnt as "traced_cnt!: i64", case when r.cnt - u.cnt = 0 then 0.0 else (t.cnt * 1.0 / (r.cnt - u.cnt)) end as "traced_ratio!: f64",
+                c.cnt as "covered_cnt!: i64", case when r.cnt = 0 then 0.0 else (c.cnt * 1.0 / r.cnt) end as "covered_ratio!: f64",
+                p.cnt as "passed_cnt!: i64", case when r.cnt = 0 then 0.0 else (p.cnt * 1.0 / r.cnt) end as "passed_ratio!: f64",
+                v.cnt as "verified_cnt?: i64", v.ratio as "verified_ratio!: f64"
+            from NrRequirements r, NrUntraceable u, NrTraced t, NrCovered c, NrPassed p, VerifiedOverview v
+            "#,
+            branch
         )
         .fetch_one(db.pool())
         .await
@@ -419,6 +2560,7 @@ impl RequirementsOverview {
 
         Ok(Self {
             req_cnt: record.req_cnt,
+            untraceable_cnt: record.untraceable_cnt,
             traced_cnt: record.traced_cnt,
             traced_ratio: record.traced_ratio,
             covered_cnt: record.covered_cnt,
@@ -427,16 +2569,96 @@ impl RequirementsOverview {
             passed_ratio: record.passed_ratio,
             verified_cnt: record.verified_cnt,
             verified_ratio: record.verified_ratio,
+            span_coverage: None,
         })
     }
 }
 
+/// Lines of traced code per requirement.
+pub type SpanLines = HashMap<ReqId, SpanCoverage>;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
+)]
+pub struct SpanCoverage {
+    /// Number of lines spanned by traces.
+    /// Lines traced by several requirements are counted once per requirement.
+    pub traced_lines: i64,
+    /// Number of lines spanned by traces that are covered by at least one test.
+    pub covered_lines: i64,
+    pub covered_ratio: f64,
+}
+
+impl SpanCoverage {
+    /// Sums the lines of traced and covered trace spans per requirement.
+    pub async fn span_lines(db: &MantraDb) -> Result<SpanLines, ReportError> {
+        let branch = db.branch();
+        let records = sqlx::query!(
+            r#"
+            select ts.req_id as "req_id!",
+                sum(ts.end - ts.start + 1) as "traced_lines!: i64",
+                sum(case when exists (
+                    select 1 from TestCoverage tc
+                    where tc.req_id = ts.req_id and tc.trace_filepath = ts.filepath
+                        and tc.trace_line = ts.line and tc.branch = ts.branch
+                ) then ts.end - ts.start + 1 else 0 end) as "covered_lines!: i64"
+            from TraceSpans ts
+            where ts.branch = $1
+            group by ts.req_id
+        "#,
+            branch
+        )
+        .fetch_all(db.pool())
+        .await
+        .map_err(ReportError::Db)?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| {
+                (
+                    record.req_id,
+                    Self::new(record.traced_lines, record.covered_lines),
+                )
+            })
+            .collect())
+    }
+
+    fn new(traced_lines: i64, covered_lines: i64) -> Self {
+        Self {
+            traced_lines,
+            covered_lines,
+            covered_ratio: if traced_lines == 0 {
+                0.0
+            } else {
+                covered_lines as f64 / traced_lines as f64
+            },
+        }
+    }
+
+    /// Combines the span lines of the given requirements.
+    /// Returns `None` if none of the requirements has traced lines.
+    pub fn from_ids<'a>(ids: impl IntoIterator<Item = &'a str>, lines: &SpanLines) -> Option<Self> {
+        let (traced_lines, covered_lines) = ids
+            .into_iter()
+            .filter_map(|id| lines.get(id))
+            .fold((0, 0), |(traced, covered), span| {
+                (traced + span.traced_lines, covered + span.covered_lines)
+            });
+
+        (traced_lines > 0).then(|| Self::new(traced_lines, covered_lines))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub struct RequirementInfo {
     #[serde(flatten)]
     pub meta: Requirement,
     pub rendered_data: Option<String>,
     pub direct_children: Vec<String>,
+    /// Number of requirements on the longest path up to a high-level requirement.
+    /// High-level requirements without parents have depth `0`.
+    #[serde(default)]
+    pub depth: u32,
     pub leaf_statistic: Option<LeafChildrenStatistic>,
     pub trace_info: RequirementTraceInfo,
     pub test_coverage_info: RequirementTestCoverageInfo,
@@ -459,7 +2681,11 @@ impl RequirementInfo {
                 origin,
                 data,
                 case when id in (select id from DeprecatedRequirements) then true else false end as "deprecated!: bool",
-                case when id in (select id from ManualRequirements) then true else false end as "manual!: bool"
+                case when id in (select id from ManualRequirements) then true else false end as "manual!: bool",
+                untraceable,
+                priority,
+                state,
+                effort
             from Requirements
             where id = $1
         "#, id).fetch_one(db.pool()).await.map_err(ReportError::Db)?;
@@ -471,6 +2697,10 @@ impl RequirementInfo {
             .map(|a| serde_json::from_str(&a).expect("Requirement data must be valid JSON."));
         let deprecated = record.deprecated;
         let manual = record.manual;
+        let untraceable = record.untraceable;
+        let priority = record.priority.and_then(|p| u8::try_from(p).ok());
+        let state = record.state;
+        let effort = record.effort;
 
         let mut record = sqlx::query!(
             r#"
@@ -505,6 +2735,26 @@ impl RequirementInfo {
         .map_err(ReportError::Db)?;
 
         let children = records.into_iter().map(|r| r.child_id).collect();
+
+        // depth is bounded by the number of requirements to stop on cyclic hierarchies
+        let depth = sqlx::query!(
+            r#"
+                with recursive Ancestors(id, depth) as (
+                    select $1, 0
+                    union
+                    select rh.parent_id, a.depth + 1
+                    from RequirementHierarchies rh, Ancestors a
+                    where rh.child_id = a.id
+                        and a.depth < (select count(*) from Requirements)
+                )
+                select max(depth) as "depth!: u32" from Ancestors
+            "#,
+            id
+        )
+        .fetch_one(db.pool())
+        .await
+        .map_err(ReportError::Db)?
+        .depth;
         let leaf_statistic = LeafChildrenStatistic::try_from(db, &id).await?;
 
         let trace_info = RequirementTraceInfo::try_from(db, &id).await?;
@@ -512,7 +2762,7 @@ impl RequirementInfo {
 
         let records = sqlx::query!(
             r#"
-                select review_name, review_date, comment
+                select review_name, review_date, comment, evidence
                 from ManuallyVerified
                 where req_id = $1
                 order by review_name, review_date
@@ -523,6 +2773,20 @@ impl RequirementInfo {
         .await
         .map_err(ReportError::Db)?;
 
+        let design_links = sqlx::query!(
+            "select url, title from DesignLinks where req_id = $1 order by rowid",
+            id
+        )
+        .fetch_all(db.pool())
+        .await
+        .map_err(ReportError::Db)?
+        .into_iter()
+        .map(|record| DesignLink {
+            url: record.url,
+            title: record.title,
+        })
+        .collect();
+
         let mut verified_info = Vec::with_capacity(records.len());
         for record in records {
             verified_info.push(VerifiedRequirementInfo {
@@ -533,15 +2797,18 @@ impl RequirementInfo {
                 )
                 .expect("Review date was added to db in custom review-date format."),
                 comment: record.comment,
+                evidence: evidence_from_db(record.evidence),
             });
         }
 
+        let branch = db.branch();
         let valid = sqlx::query!(
             r#"
                 select * from InvalidRequirements
-                where id = $1
+                where id = $1 and branch = $2
             "#,
-            id
+            id,
+            branch
         )
         .fetch_optional(db.pool())
         .await
@@ -556,8 +2823,8 @@ impl RequirementInfo {
             if let Some(value) = &data {
                 let context = tera::Context::from_serialize(value)
                     .expect("Requirement data value is valid JSON.");
-                let rendered = tera::Tera::one_off(&template_content, &context, true)
-                    .map_err(|_| ReportError::Tera)?;
+                let rendered =
+                    tera_one_off(&template_content, &context).map_err(|_| ReportError::Tera)?;
                 Some(rendered)
             } else {
                 None
@@ -571,13 +2838,19 @@ impl RequirementInfo {
                 id,
                 title,
                 origin,
-                manual,
+                manual: Some(manual),
                 deprecated,
+                untraceable,
                 data,
                 parents,
+                priority,
+                state,
+                effort,
+                design_links,
             },
             rendered_data,
             direct_children: children,
+            depth,
             leaf_statistic,
             trace_info,
             test_coverage_info,
@@ -600,21 +2873,31 @@ pub struct LeafChildrenStatistic {
 
 impl LeafChildrenStatistic {
     pub async fn try_from(db: &MantraDb, id: &str) -> Result<Option<Self>, ReportError> {
+        let branch = db.branch();
         sqlx::query_as!(
             LeafChildrenStatistic,
             r#"
-                select 
-                leaf_cnt,
-                traced_leaf_cnt,
-                traced_leaf_ratio as "traced_leaf_ratio!: f64",
-                covered_leaf_cnt,
-                covered_leaf_ratio as "covered_leaf_ratio!: f64",
-                passed_covered_leaf_cnt,
-                passed_covered_leaf_ratio as "passed_covered_leaf_ratio!: f64"
-                from LeafChildOverview
-                where id = $1
+                select
+                leaf_cnt as "leaf_cnt!: i64",
+                traced_leaf_cnt as "traced_leaf_cnt!: i64",
+                traced_leaf_cnt * 1.0 / leaf_cnt as "traced_leaf_ratio!: f64",
+                covered_leaf_cnt as "covered_leaf_cnt!: i64",
+                covered_leaf_cnt * 1.0 / leaf_cnt as "covered_leaf_ratio!: f64",
+                passed_covered_leaf_cnt as "passed_covered_leaf_cnt!: i64",
+                passed_covered_leaf_cnt * 1.0 / leaf_cnt as "passed_covered_leaf_ratio!: f64"
+                from (
+                    select count(*) as leaf_cnt,
+                    coalesce(sum(lr.id in (select id from DirectlyTracedRequirements where branch = $2)), 0) as traced_leaf_cnt,
+                    coalesce(sum(lr.id in (select id from DirectlyCoveredRequirements where branch = $2)), 0) as covered_leaf_cnt,
+                    coalesce(sum(lr.id in (select id from PassedCoveredRequirements where branch = $2)), 0) as passed_covered_leaf_cnt
+                    from RequirementDescendants rc, LeafRequirements lr
+                    where rc.id = $1 and rc.descendant_id = lr.id
+                )
+                -- leaf requirements have no leaf children
+                where leaf_cnt > 0
             "#,
-            id
+            id,
+            branch
         )
         .fetch_optional(db.pool())
         .await
@@ -628,19 +2911,24 @@ pub struct RequirementTraceInfo {
     pub fully_traced: bool,
     pub direct_traces: Vec<TraceInfo>,
     pub indirect_traces: Vec<IndirectTraceInfo>,
+    /// Commits referencing the requirement in their message.
+    /// Commit traces do not affect the traced state.
+    pub commit_traces: Vec<CommitTraceInfo>,
 }
 
 impl RequirementTraceInfo {
     pub async fn try_from(db: &MantraDb, id: &str) -> Result<Self, ReportError> {
+        let branch = db.branch();
         let records = sqlx::query_as!(
             TraceLocation,
             r#"
             select filepath, line as "line: Line"
             from Traces
-            where req_id = $1
+            where req_id = $1 and branch = $2
             order by filepath, line
         "#,
-            id
+            id,
+            branch
         )
         .fetch_all(db.pool())
         .await
@@ -649,28 +2937,33 @@ impl RequirementTraceInfo {
         let mut direct_traces = Vec::new();
 
         for record in records {
-            let item_name = sqlx::query!(
+            let item = sqlx::query!(
                 r#"
-                select ti.name
+                select ti.name, ti.path
                 from Traces t, TraceSpans ts, TracedItems ti
                 where t.req_id = $1 and t.filepath = $2
-                    and t.line = $3 and t.req_id = ts.req_id
-                    and t.filepath = ts.filepath and t.line = ts.line
-                    and t.filepath = ti.filepath and ts.start = ti.line
+                    and t.line = $3 and t.branch = $4 and t.req_id = ts.req_id
+                    and t.filepath = ts.filepath and t.line = ts.line and t.branch = ts.branch
+                    and t.filepath = ti.filepath and ts.start = ti.line and t.branch = ti.branch
             "#,
                 id,
                 record.filepath,
-                record.line
+                record.line,
+                branch
             )
             .fetch_optional(db.pool())
             .await
-            .map_err(ReportError::Db)?
-            .map(|r| r.name);
+            .map_err(ReportError::Db)?;
+            let (item_name, item_path) = match item {
+                Some(item) => (Some(item.name), item.path),
+                None => (None, None),
+            };
 
             direct_traces.push(TraceInfo {
                 filepath: record.filepath,
                 line: record.line,
                 item_name,
+                item_path,
             });
         }
 
@@ -678,10 +2971,11 @@ impl RequirementTraceInfo {
             r#"
             select traced_id as "traced_id!", traces as "traces!: String"
             from IndirectTraceTree
-            where id = $1
+            where id = $1 and branch = $2
             order by traced_id
         "#,
-            id
+            id,
+            branch
         )
         .fetch_all(db.pool())
         .await
@@ -701,20 +2995,32 @@ impl RequirementTraceInfo {
             r#"
                 select *
                 from FullyTracedRequirements
-                where id = $1
+                where id = $1 and branch = $2
             "#,
-            id
+            id,
+            branch
         )
         .fetch_optional(db.pool())
         .await
         .map_err(ReportError::Db)?
         .is_some();
 
+        let commit_traces = sqlx::query_as!(
+            CommitTraceInfo,
+            "select sha, summary, date from CommitTraces where req_id = $1 and branch = $2 order by date desc, sha",
+            id,
+            branch
+        )
+        .fetch_all(db.pool())
+        .await
+        .map_err(ReportError::Db)?;
+
         Ok(Self {
             traced: !direct_traces.is_empty() || !indirect_traces.is_empty(),
             fully_traced,
             direct_traces,
             indirect_traces,
+            commit_traces,
         })
     }
 }
@@ -727,6 +3033,17 @@ pub struct TraceLocation {
     pub line: Line,
 }
 
+#[derive(
+    Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, sqlx::Type, schemars::JsonSchema,
+)]
+pub struct CommitTraceInfo {
+    pub sha: String,
+    /// First line of the commit message.
+    pub summary: String,
+    /// Commit date given in ISO8601 format.
+    pub date: String,
+}
+
 #[derive(
     Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, sqlx::Type, schemars::JsonSchema,
 )]
@@ -734,6 +3051,8 @@ pub struct TraceInfo {
     pub filepath: String,
     pub line: Line,
     pub item_name: Option<String>,
+    /// Canonical path of the traced item (e.g. `crate::module::func`).
+    pub item_path: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
@@ -749,18 +3068,23 @@ pub struct RequirementTestCoverageInfo {
     pub fully_covered: bool,
     pub direct_coverage: Vec<TestCoverageTestRunInfo>,
     pub indirect_coverage: Vec<IndirectTestCoverageInfo>,
+    /// Tests declaring to cover the requirement, because the requirement is traced on the test function.
+    /// Declared coverage does not affect the covered state.
+    pub declared_coverage: Vec<DeclaredTestInfo>,
 }
 
 impl RequirementTestCoverageInfo {
     pub async fn try_from(db: &MantraDb, id: &str) -> Result<Self, ReportError> {
+        let branch = db.branch();
         let records = sqlx::query!(
             r#"
                 select test_run_name, test_run_date, tests as "tests!: String"
                 from DirectCoverageTree
-                where id = $1
+                where id = $1 and branch = $2
                 order by test_run_name, test_run_date
             "#,
-            id
+            id,
+            branch
         )
         .fetch_all(db.pool())
         .await
@@ -781,10 +3105,11 @@ impl RequirementTestCoverageInfo {
             r#"
                 select covered_id, test_runs as "test_runs!: String"
                 from IndirectTestCoverageTree
-                where id = $1
+                where id = $1 and branch = $2
                 order by covered_id
             "#,
-            id
+            id,
+            branch
         )
         .fetch_all(db.pool())
         .await
@@ -804,9 +3129,10 @@ impl RequirementTestCoverageInfo {
             "
             select *
             from PassedCoveredRequirements
-            where id = $1
+            where id = $1 and branch = $2
             ",
-            id
+            id,
+            branch
         )
         .fetch_one(db.pool())
         .await
@@ -817,25 +3143,70 @@ impl RequirementTestCoverageInfo {
             r#"
                 select *
                 from FullyCoveredRequirements
-                where id = $1
+                where id = $1 and branch = $2
             "#,
-            id
+            id,
+            branch
         )
         .fetch_optional(db.pool())
         .await
         .map_err(ReportError::Db)?
         .is_some();
 
+        let declared_coverage = sqlx::query_as!(
+            DeclaredTestInfo,
+            r#"
+                select d.test_name as name, d.filepath, d.line as "line: Line",
+                    exists(
+                        select 1 from TestCoverage tc
+                        where tc.req_id = d.req_id and tc.branch = d.branch
+                            and (tc.test_name = d.test_name or tc.test_name like '%::' || d.test_name)
+                    ) as "executed!: bool"
+                from DeclaredCoverage d
+                where d.req_id = $1 and d.branch = $2
+                order by d.filepath, d.line
+            "#,
+            id,
+            branch
+        )
+        .fetch_all(db.pool())
+        .await
+        .map_err(ReportError::Db)?;
+
         Ok(Self {
             covered: !direct_coverage.is_empty() || !indirect_coverage.is_empty(),
             passed,
             fully_covered,
             direct_coverage,
             indirect_coverage,
+            declared_coverage,
         })
     }
 }
 
+impl RequirementTestCoverageInfo {
+    /// `true` if all tests covering the requirement or its sub-requirements passed in at least one test run.
+    pub fn passed_in_any_run(&self) -> bool {
+        let mut run_results: HashMap<(&str, OffsetDateTime), bool> = HashMap::new();
+
+        let test_runs = self.direct_coverage.iter().chain(
+            self.indirect_coverage
+                .iter()
+                .flat_map(|coverage| coverage.test_runs.iter()),
+        );
+
+        for test_run in test_runs {
+            let passed = test_run.tests.iter().all(|test| test.passed);
+            run_results
+                .entry((test_run.name.as_str(), test_run.date))
+                .and_modify(|run_passed| *run_passed &= passed)
+                .or_insert(passed);
+        }
+
+        run_results.values().any(|passed| *passed)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub struct TestCoverageTestRunInfo {
     pub name: String,
@@ -856,6 +3227,16 @@ pub struct TestCoverageTestInfo {
     pub traces: Vec<TraceLocation>,
 }
 
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct DeclaredTestInfo {
+    /// Name of the test function.
+    pub name: String,
+    pub filepath: String,
+    pub line: Line,
+    /// `true` if a test run confirmed that the test covers the requirement.
+    pub executed: bool,
+}
+
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub struct IndirectTestCoverageInfo {
     pub covered_id: String,
@@ -870,6 +3251,8 @@ pub struct VerifiedRequirementInfo {
     #[schemars(with = "String")]
     pub review_date: PrimitiveDateTime,
     pub comment: Option<String>,
+    /// URLs or filepaths of documents supporting the verification.
+    pub evidence: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
@@ -885,12 +3268,15 @@ impl TestStatistics {
     ) -> Result<Self, ReportError> {
         let overview = TestsOverview::try_from(db).await?;
 
+        let branch = db.branch();
         let test_run_records = sqlx::query!(
             "
             select name, date
             from TestRuns
+            where branch = $1
             order by name, date
-            "
+            ",
+            branch
         )
         .fetch_all(db.pool())
         .await
@@ -912,7 +3298,14 @@ impl TestStatistics {
 }
 
 #[derive(
-    Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    serde::Serialize,
+    serde::Deserialize,
+    schemars::JsonSchema,
 )]
 pub struct TestsOverview {
     pub test_cnt: i64,
@@ -927,8 +3320,11 @@ pub struct TestsOverview {
 }
 
 impl TestsOverview {
+    /// Sums the overviews of all test runs of the branch of the database.
+    /// All counts are zero if the branch has no test runs.
     pub async fn try_from(db: &MantraDb) -> Result<Self, ReportError> {
-        sqlx::query_as!(
+        let branch = db.branch();
+        let overview = sqlx::query_as!(
             TestsOverview,
             r#"
                 select 
@@ -942,11 +3338,15 @@ impl TestsOverview {
                 skipped_cnt as "skipped_cnt!: i64",
                 skipped_ratio as "skipped_ratio!: f64"
                 from OverallTestOverview
-                "#
+                where branch = $1
+                "#,
+            branch
         )
-        .fetch_one(db.pool())
+        .fetch_optional(db.pool())
         .await
-        .map_err(ReportError::Db)
+        .map_err(ReportError::Db)?;
+
+        Ok(overview.unwrap_or_default())
     }
 }
 
@@ -975,6 +3375,7 @@ impl TestRunInfo {
         test_run_template: Option<&Path>,
     ) -> Result<Self, ReportError> {
         let name: String = name.into();
+        let branch = db.branch();
         let overview = TestRunOverview::try_from(db, &name, &date).await?;
 
         let test_records = sqlx::query!(
@@ -986,7 +3387,7 @@ impl TestRunInfo {
                 false as skipped,
                 null as reason
                 from Tests
-                where test_run_name = $1 and test_run_date = $2
+                where test_run_name = $1 and test_run_date = $2 and branch = $3
                 
                 union all
                 
@@ -996,12 +3397,13 @@ impl TestRunInfo {
                 true as skipped,
                 reason
                 from SkippedTests
-                where test_run_name = $1 and test_run_date = $2
+                where test_run_name = $1 and test_run_date = $2 and branch = $3
             )
             order by name, filepath, line
         "#,
             name,
-            date
+            date,
+            branch
         )
         .fetch_all(db.pool())
         .await
@@ -1015,11 +3417,13 @@ impl TestRunInfo {
                 select req_id from TestCoverage
                 where test_run_name = $1 and
                 test_run_date = $2 and
-                test_name = $3
+                branch = $3 and
+                test_name = $4
                 order by req_id
                 ",
                 name,
                 date,
+                branch,
                 test.name
             )
             .fetch_all(db.pool())
@@ -1051,10 +3455,11 @@ impl TestRunInfo {
         let record = sqlx::query!(
             r#"
             select data, logs from TestRuns
-            where name = $1 and date = $2
+            where name = $1 and date = $2 and branch = $3
             "#,
             name,
-            date
+            date,
+            branch
         )
         .fetch_one(db.pool())
         .await
@@ -1072,8 +3477,8 @@ impl TestRunInfo {
             if let Some(value) = &data {
                 let context = tera::Context::from_serialize(value)
                     .expect("Test-run data value is valid JSON.");
-                let rendered = tera::Tera::one_off(&template_content, &context, true)
-                    .map_err(|_| ReportError::Tera)?;
+                let rendered =
+                    tera_one_off(&template_content, &context).map_err(|_| ReportError::Tera)?;
                 Some(rendered)
             } else {
                 None
@@ -1115,6 +3520,7 @@ impl TestRunOverview {
         name: &str,
         date: &OffsetDateTime,
     ) -> Result<Self, ReportError> {
+        let branch = db.branch();
         sqlx::query_as!(
             TestRunOverview,
             r#"
@@ -1129,10 +3535,11 @@ impl TestRunOverview {
                 skipped_cnt,
                 skipped_ratio as "skipped_ratio!: f64"
                 from TestRunOverview
-                where name = $1 and date = $2
+                where name = $1 and date = $2 and branch = $3
                 "#,
             name,
-            date
+            date,
+            branch
         )
         .fetch_one(db.pool())
         .await
@@ -1158,10 +3565,9 @@ impl Review {
     ) -> Result<Self, ReportError> {
         let name: String = name.into();
 
-        let requirements = sqlx::query_as!(
-            VerifiedRequirement,
+        let requirements = sqlx::query!(
             "
-                select req_id as id, comment
+                select req_id as id, comment, evidence
                 from ManuallyVerified
                 where review_name = $1 and review_date = $2
                 order by req_id
@@ -1171,7 +3577,14 @@ impl Review {
         )
         .fetch_all(db.pool())
         .await
-        .map_err(ReportError::Db)?;
+        .map_err(ReportError::Db)?
+        .into_iter()
+        .map(|record| VerifiedRequirement {
+            id: record.id,
+            comment: record.comment,
+            evidence: evidence_from_db(record.evidence),
+        })
+        .collect();
 
         let record = sqlx::query!(
             "
@@ -1205,6 +3618,7 @@ pub struct Unrelated {
 
 impl Unrelated {
     pub async fn try_from(db: &MantraDb) -> Result<Self, ReportError> {
+        let branch = db.branch();
         let traces = sqlx::query_as!(
             TracePk,
             r#"
@@ -1213,7 +3627,9 @@ impl Unrelated {
                 filepath,
                 line as "line!: u32"
                 from UnrelatedTraces
-            "#
+                where branch = $1
+            "#,
+            branch
         )
         .fetch_all(db.pool())
         .await
@@ -1248,6 +3664,7 @@ pub struct UnrelatedCoverage {
 
 impl UnrelatedCoverage {
     pub async fn try_from(db: &MantraDb) -> Result<Vec<Self>, ReportError> {
+        let branch = db.branch();
         let records = sqlx::query!(
             r#"
                 select
@@ -1258,7 +3675,9 @@ impl UnrelatedCoverage {
                 trace_filepath,
                 trace_line as "trace_line!: u32"
                 from UnrelatedTestCoverage
+                where branch = $1
             "#,
+            branch
         )
         .fetch_all(db.pool())
         .await
@@ -1324,3 +3743,725 @@ impl UnrelatedVerified {
         Ok(unrelated)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use mantra_schema::requirements::Requirement;
+
+    use crate::{
+        db::RequirementStatus,
+        test_util::{req, test_db},
+    };
+
+    use super::{
+        archive, csv_report, origin_group, redact_path, report, similar_ids, tera_from_dir,
+        tera_one_off, ArchiveManifest, EffortOverview, ManifestFile, ReportChanges, ReportConfig,
+        ReportContext, ReportError, ReportFormat, ReportOptions, ReportSort, ReportTemplate,
+        ReportTheme, RequirementInfo, RequirementRisk, RequirementTestCoverageInfo,
+        RequirementTraceInfo, RequirementsOverview, RiskFactor, RiskWeights, SpanCoverage, Tag,
+        TestCoverageTestInfo, TestCoverageTestRunInfo,
+    };
+
+    #[test]
+    fn typo_ids_detected() {
+        let reqs = vec![
+            ("req_id".to_string(), 12),
+            ("req-id".to_string(), 1),
+            ("foo.bar".to_string(), 3),
+            ("foo.baz".to_string(), 0),
+            ("list.1".to_string(), 2),
+            ("list.2".to_string(), 2),
+        ];
+        let unrelated = vec![("fo.bar".to_string(), 1), ("other".to_string(), 1)];
+
+        let similar = similar_ids(&reqs, &unrelated);
+        let similar: Vec<(&str, &str, bool)> = similar
+            .iter()
+            .map(|s| (s.id.as_str(), s.similar_to.as_str(), s.unrelated))
+            .collect();
+
+        assert_eq!(
+            similar,
+            vec![
+                ("fo.bar", "foo.bar", true),
+                ("req-id", "req_id", false),
+                ("foo.baz", "foo.bar", false),
+            ],
+            "Similar IDs not detected, or numbered siblings detected as similar."
+        );
+    }
+
+    #[test]
+    fn embedded_local_assets() {
+        let asset_dir =
+            std::env::temp_dir().join(format!("mantra-embed-test-{}", std::process::id()));
+        std::fs::create_dir_all(asset_dir.join("img")).unwrap();
+        std::fs::write(
+            asset_dir.join("style.css"),
+            "body { background: url('img/bg.png'); }",
+        )
+        .unwrap();
+        std::fs::write(asset_dir.join("app.js"), "console.log(1);").unwrap();
+        std::fs::write(asset_dir.join("img/bg.png"), [1, 2, 3]).unwrap();
+
+        let html = r#"<link rel="stylesheet" href="style.css">
+<link rel="stylesheet" href="https://example.com/remote.css">
+<script type="module" src="app.js"></script>
+<img alt="bg" src="img/bg.png">
+<img src="missing.png">"#;
+        let embedded = super::embed_assets(html, &asset_dir);
+        std::fs::remove_dir_all(&asset_dir).unwrap();
+
+        assert_eq!(
+            embedded,
+            r#"<style>body { background: url("data:image/png;base64,AQID"); }</style>
+<link rel="stylesheet" href="https://example.com/remote.css">
+<script type="module">console.log(1);</script>
+<img alt="bg" src="data:image/png;base64,AQID">
+<img src="missing.png">"#,
+            "Local assets not inlined, or remote and missing assets changed."
+        );
+    }
+
+    #[tokio::test]
+    async fn theme_with_custom_css() {
+        let css_file =
+            std::env::temp_dir().join(format!("mantra-theme-test-{}.css", std::process::id()));
+        std::fs::write(&css_file, "body { color: teal; }").unwrap();
+
+        let template = ReportTemplate {
+            theme: Some(ReportTheme::Dark),
+            theme_css: Some(css_file.clone()),
+            ..Default::default()
+        };
+        let theme = template.theme_context().await.unwrap();
+        std::fs::remove_file(&css_file).unwrap();
+
+        let mut context = tera::Context::new();
+        context.insert("theme", &theme);
+        let rendered =
+            tera::Tera::one_off("{{ theme.name }}|{{ theme.css | safe }}", &context, true).unwrap();
+        assert_eq!(
+            rendered, "dark|body { color: teal; }",
+            "Theme not available in the template context."
+        );
+
+        let default_theme = ReportTemplate::default().theme_context().await.unwrap();
+        assert_eq!(
+            default_theme.name,
+            ReportTheme::Light,
+            "Light theme is not the default."
+        );
+        assert!(
+            default_theme.css.is_none(),
+            "Custom CSS set without a file."
+        );
+    }
+
+    #[test]
+    fn origin_groups() {
+        assert_eq!(
+            origin_group("https://github.com/mhatzl/mantra/issues/1"),
+            "github.com/mhatzl/mantra",
+            "URL origin not grouped by host and repository."
+        );
+        assert_eq!(
+            origin_group("https://jira.example.com/browse/PROJ-12?focus=1"),
+            "jira.example.com/browse/PROJ-12",
+            "Query of URL origin not removed."
+        );
+        assert_eq!(
+            origin_group("wiki/5-Requirements/1-tracing"),
+            "wiki/5-Requirements",
+            "Path origin not grouped by its directory."
+        );
+    }
+
+    fn req_info(id: &str, effort: Option<f64>, covered: bool, passed: bool) -> RequirementInfo {
+        RequirementInfo {
+            meta: Requirement {
+                origin: "wiki".to_string(),
+                effort,
+                ..req(id, &[])
+            },
+            rendered_data: None,
+            direct_children: Vec::new(),
+            depth: 0,
+            leaf_statistic: None,
+            trace_info: RequirementTraceInfo {
+                traced: covered,
+                fully_traced: covered,
+                direct_traces: Vec::new(),
+                indirect_traces: Vec::new(),
+                commit_traces: Vec::new(),
+            },
+            test_coverage_info: RequirementTestCoverageInfo {
+                covered,
+                passed,
+                fully_covered: covered,
+                direct_coverage: Vec::new(),
+                indirect_coverage: Vec::new(),
+                declared_coverage: Vec::new(),
+            },
+            verified_info: Vec::new(),
+            valid: true,
+        }
+    }
+
+    #[test]
+    fn effort_weighted_ratios() {
+        assert_eq!(
+            EffortOverview::from_requirements(&[req_info("a", None, true, true)]),
+            None,
+            "Effort overview created without any effort."
+        );
+
+        let reqs = vec![
+            req_info("a", Some(5.0), true, true),
+            req_info("b", Some(3.0), true, false),
+            req_info("c", Some(2.0), false, false),
+            req_info("d", None, true, true),
+        ];
+        let uncovered = EffortOverview::from_requirements(&reqs[2..])
+            .expect("Effort overview created for requirement with effort.");
+
+        assert!(
+            uncovered.covered_effort.is_sign_positive(),
+            "Effort of no covered requirement is negative zero."
+        );
+
+        assert_eq!(
+            EffortOverview::from_requirements(&reqs),
+            Some(EffortOverview {
+                total_effort: 10.0,
+                covered_effort: 8.0,
+                covered_ratio: 0.8,
+                passed_effort: 5.0,
+                passed_ratio: 0.5,
+            }),
+            "Ratios not weighted by effort."
+        );
+    }
+
+    #[test]
+    fn span_weighted_coverage() {
+        let lines = [
+            ("short".to_string(), SpanCoverage::new(5, 5)),
+            ("long".to_string(), SpanCoverage::new(495, 0)),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            SpanCoverage::from_ids(["short", "long", "no-span"], &lines),
+            Some(SpanCoverage {
+                traced_lines: 500,
+                covered_lines: 5,
+                covered_ratio: 0.01,
+            }),
+            "Coverage not weighted by span lines."
+        );
+        assert_eq!(
+            SpanCoverage::from_ids(["no-span"], &lines),
+            None,
+            "Span coverage set for requirements without spans."
+        );
+    }
+
+    #[test]
+    fn changes_since_previous_collect() {
+        let passed = RequirementStatus {
+            traced: true,
+            covered: true,
+            passed: true,
+        };
+        let failed = RequirementStatus {
+            passed: false,
+            ..passed
+        };
+        let previous = [
+            ("a".to_string(), passed),
+            ("b".to_string(), failed),
+            ("gone".to_string(), passed),
+        ]
+        .into_iter()
+        .collect();
+        let reqs = vec![
+            req_info("a", None, true, false),
+            req_info("b", None, true, false),
+            req_info("c", None, false, false),
+        ];
+
+        let changes = ReportChanges::new(Some((1, previous)), &reqs, None);
+
+        assert_eq!(
+            changes.added,
+            vec!["c".to_string()],
+            "New requirement not added."
+        );
+        assert_eq!(
+            changes.removed,
+            vec!["gone".to_string()],
+            "Removed requirement not detected."
+        );
+        assert_eq!(
+            changes.newly_failed,
+            vec!["a".to_string()],
+            "Failing requirement not detected."
+        );
+        assert_eq!(
+            changes.changed_ids(),
+            ["a".to_string(), "c".to_string()].into_iter().collect(),
+            "Unchanged requirement taken as changed."
+        );
+    }
+
+    #[test]
+    fn risk_ranking() {
+        let mut untraced = req_info("untraced", None, false, false);
+        untraced.trace_info.traced = false;
+        let mut flaky = req_info("flaky", None, true, false);
+        flaky.test_coverage_info.direct_coverage =
+            vec![test_run("shard-1", false), test_run("shard-2", true)];
+        let failing = req_info("failing", None, true, false);
+        let mut unverified = req_info("unverified", None, false, false);
+        unverified.meta.manual = Some(true);
+        let mut deprecated = req_info("deprecated", None, true, true);
+        deprecated.meta.deprecated = true;
+        let passed = req_info("passed", None, true, true);
+
+        let reqs = vec![untraced, flaky, failing, unverified, deprecated, passed];
+        let risks = RequirementRisk::from_requirements(&reqs, &RiskWeights::default());
+
+        assert_eq!(
+            risks
+                .iter()
+                .map(|risk| (risk.id.as_str(), risk.score))
+                .collect::<Vec<_>>(),
+            vec![
+                ("failing", 8),
+                ("deprecated", 6),
+                ("untraced", 5),
+                ("flaky", 4),
+                ("unverified", 4),
+            ],
+            "Requirements not ranked by risk, or passed requirement at risk."
+        );
+        assert_eq!(
+            risks[3].factors[0].factor,
+            RiskFactor::Flaky,
+            "Requirement passing in one test run not detected as flaky."
+        );
+
+        let weights = RiskWeights {
+            failing: 0,
+            ..Default::default()
+        };
+        assert!(
+            RequirementRisk::from_requirements(&reqs, &weights)
+                .iter()
+                .all(|risk| risk.id != "failing"),
+            "Factor without weight added to risk."
+        );
+    }
+
+    fn test_run(name: &str, passed: bool) -> TestCoverageTestRunInfo {
+        TestCoverageTestRunInfo {
+            name: name.to_string(),
+            date: time::OffsetDateTime::UNIX_EPOCH,
+            tests: vec![TestCoverageTestInfo {
+                name: "test".to_string(),
+                passed,
+                traces: Vec::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn passed_in_any_run() {
+        let mut coverage = RequirementTestCoverageInfo {
+            covered: true,
+            passed: false,
+            fully_covered: false,
+            direct_coverage: vec![test_run("shard-1", false), test_run("shard-2", true)],
+            indirect_coverage: Vec::new(),
+            declared_coverage: Vec::new(),
+        };
+
+        assert!(
+            coverage.passed_in_any_run(),
+            "Passing test run not considered."
+        );
+
+        coverage.direct_coverage = vec![test_run("shard-1", false)];
+        assert!(
+            !coverage.passed_in_any_run(),
+            "Failing test run considered passed."
+        );
+    }
+
+    #[test]
+    fn csv_rows_per_requirement() {
+        let mut quoted = req_info("b", None, true, false);
+        quoted.meta.title = "Say \"hi\", then leave".to_string();
+        quoted.trace_info.direct_traces.push(super::TraceInfo {
+            filepath: "src/lib.rs".to_string(),
+            line: 1,
+            item_name: None,
+            item_path: None,
+        });
+
+        let csv = csv_report(&[req_info("a", None, false, false), quoted]);
+
+        assert_eq!(
+            csv,
+            "id,title,traced,covered,passed,deprecated,manual,untraceable,direct_trace_cnt\n\
+            a,a,false,false,false,false,false,false,0\n\
+            b,\"Say \"\"hi\"\", then leave\",true,true,false,false,false,false,1\n",
+            "Wrong CSV rows."
+        );
+    }
+
+    #[test]
+    fn redacted_paths_are_stable() {
+        let redacted = redact_path("src/internal/module.rs");
+
+        assert!(
+            !redacted.contains("internal"),
+            "Directory not removed from path."
+        );
+        assert!(redacted.ends_with(".rs"), "File extension not kept.");
+        assert_eq!(
+            redacted,
+            redact_path("src/internal/module.rs"),
+            "Same path redacted differently."
+        );
+        assert_ne!(
+            redacted,
+            redact_path("src/internal/other.rs"),
+            "Different paths redacted the same."
+        );
+    }
+
+    #[test]
+    fn template_dir_includes() {
+        let dir = std::env::temp_dir().join(format!("mantra_template_dir_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("partials")).unwrap();
+        std::fs::write(
+            dir.join("report.html"),
+            r#"<main>{% include "partials/ratio.html" %}</main>"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("partials/ratio.html"), "{{ ratio | percent }}").unwrap();
+
+        let mut context = tera::Context::new();
+        context.insert("ratio", &0.5);
+        let rendered = tera_from_dir(&dir, "report.html", &context);
+
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            rendered.unwrap(),
+            "<main>50.0%</main>",
+            "Partial not included, or mantra filters not available."
+        );
+    }
+
+    #[test]
+    fn req_link_from_context() {
+        let mut context = tera::Context::new();
+        context.insert(
+            "requirements",
+            &vec![
+                req_info("a", None, true, true),
+                req_info("b", None, true, true),
+            ],
+        );
+
+        let rendered = tera_one_off(
+            r#"{{ req_link(id="a") }}|{{ req_link(id="unknown") }}"#,
+            &context,
+        );
+
+        assert_eq!(
+            rendered.unwrap(),
+            "wiki|",
+            "Origin not looked up, or unknown requirement not empty."
+        );
+    }
+
+    #[tokio::test]
+    async fn hierarchy_depth() {
+        let db = test_db("depth").await;
+        db.add_reqs(vec![
+            req("a", &[]),
+            req("a.b", &["a"]),
+            req("a.b.c", &["a.b", "a"]),
+        ])
+        .await
+        .unwrap();
+
+        let mut depths = Vec::new();
+        for id in ["a", "a.b", "a.b.c"] {
+            depths.push(
+                RequirementInfo::try_from(&db, id, None)
+                    .await
+                    .unwrap()
+                    .depth,
+            );
+        }
+
+        assert_eq!(
+            depths,
+            vec![0, 1, 2],
+            "Depth not taken from the longest path to a high-level requirement."
+        );
+    }
+
+    #[tokio::test]
+    async fn report_gates_checked_after_writing() {
+        let db = test_db("report_gates").await;
+        let mut deprecated = req("deprecated", &[]);
+        deprecated.deprecated = true;
+        db.add_reqs(vec![req("a", &[]), deprecated]).await.unwrap();
+        db.add_traces(
+            std::path::Path::new("src/lib.rs"),
+            &[mantra_schema::traces::TraceEntry {
+                ids: vec!["deprecated".to_string()],
+                line: 1,
+                line_span: None,
+                item_name: None,
+                item_path: None,
+                test_name: None,
+            }],
+            1,
+        )
+        .await
+        .unwrap();
+
+        let report_path = std::env::temp_dir().join(format!(
+            "mantra_report_gates_test_{}.json",
+            std::process::id()
+        ));
+        let cfg = |options: ReportOptions| ReportConfig {
+            path: report_path.clone(),
+            out_dir: None,
+            template: ReportTemplate::default(),
+            formats: vec![ReportFormat::Json],
+            project: Default::default(),
+            tag: Tag {
+                name: None,
+                link: None,
+            },
+            archive: None,
+            options,
+        };
+
+        let invalid = report(
+            &db,
+            cfg(ReportOptions {
+                fail_on_invalid: true,
+                ..Default::default()
+            }),
+        )
+        .await;
+        let written = report_path.exists();
+        let _ = std::fs::remove_file(&report_path);
+
+        assert!(
+            matches!(invalid, Err(ReportError::Invalid(ref validation)) if !validation.is_valid),
+            "Traced deprecated requirement not rejected."
+        );
+        assert!(written, "Report not written before failing.");
+
+        let below_threshold = report(
+            &db,
+            cfg(ReportOptions {
+                min_traced_ratio: Some(0.9),
+                min_covered_ratio: Some(0.0),
+                ..Default::default()
+            }),
+        )
+        .await;
+        let written = report_path.exists();
+        let _ = std::fs::remove_file(&report_path);
+
+        assert!(
+            matches!(below_threshold, Err(ReportError::Threshold(ref violations)) if violations.starts_with("Traced ratio")),
+            "Traced ratio below the minimum not rejected."
+        );
+        assert!(written, "Report not written before failing.");
+    }
+
+    #[tokio::test]
+    async fn untraceable_excluded_from_traced_ratio() {
+        let db = test_db("untraceable").await;
+        let mut org = req("org", &[]);
+        org.untraceable = true;
+        db.add_reqs(vec![req("a", &[]), req("b", &[]), org])
+            .await
+            .unwrap();
+        db.add_traces(
+            std::path::Path::new("src/lib.rs"),
+            &[mantra_schema::traces::TraceEntry {
+                ids: vec!["a".to_string()],
+                line: 1,
+                line_span: None,
+                item_name: None,
+                item_path: None,
+                test_name: None,
+            }],
+            1,
+        )
+        .await
+        .unwrap();
+
+        let overview = RequirementsOverview::try_from(&db).await;
+        let mut infos = Vec::new();
+        for id in ["a", "b", "org"] {
+            infos.push(RequirementInfo::try_from(&db, id, None).await.unwrap());
+        }
+
+        let overview = overview.unwrap();
+        assert_eq!(
+            (overview.untraceable_cnt, overview.traced_cnt),
+            (1, 1),
+            "Untraceable requirement not counted separately."
+        );
+        assert_eq!(
+            overview.traced_ratio, 0.5,
+            "Untraceable requirement part of the traced ratio."
+        );
+        assert_eq!(
+            RequirementsOverview::from_requirements(&infos),
+            overview,
+            "Overview of requirements differs from the database view."
+        );
+    }
+
+    #[tokio::test]
+    async fn archive_ties_snapshot_to_report() {
+        let dir = std::env::temp_dir().join(format!("mantra_archive_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db = test_db("archive_source").await;
+        db.add_reqs(vec![req("a", &[])]).await.unwrap();
+        std::fs::write(dir.join("report.json"), "{}").unwrap();
+
+        let manifest = ArchiveManifest {
+            project: Default::default(),
+            tag: super::Tag {
+                name: Some("v1".to_string()),
+                link: None,
+            },
+            branch: None,
+            creation_date: time::OffsetDateTime::now_utc(),
+            database: "mantra.db".into(),
+            files: vec![ManifestFile {
+                path: dir.join("report.json"),
+                format: ReportFormat::Json,
+                size: 2,
+            }],
+        };
+        let archived = archive(&db, &dir.join("archive"), manifest).await;
+
+        let snapshot = crate::db::MantraDb::new(&crate::db::Config {
+            url: Some(format!(
+                "sqlite://{}",
+                dir.join("archive/mantra.db").display()
+            )),
+            connect_attempts: 1,
+            retry_delay_ms: 0,
+            branch: None,
+        })
+        .await
+        .unwrap();
+        let snapshot_traced = snapshot.is_traced("a").await;
+        snapshot.pool().close().await;
+
+        let manifest: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(dir.join("archive/archive_manifest.json")).unwrap_or_default(),
+        )
+        .unwrap_or_default();
+        let report_copied = dir.join("archive/report.json").exists();
+
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(archived.is_ok(), "Archive not written.");
+        assert!(
+            snapshot_traced.is_ok(),
+            "Requirement missing in archived database."
+        );
+        assert!(report_copied, "Report file not copied into the archive.");
+        assert_eq!(manifest["tag"]["name"], "v1", "Report tag not in manifest.");
+        assert_eq!(
+            manifest["files"][0]["path"], "report.json",
+            "Report path not relative to the archive."
+        );
+    }
+
+    #[tokio::test]
+    async fn sorted_by_priority() {
+        let db = test_db("priority_sort").await;
+        let prioritized = |id: &str, priority: Option<u8>| Requirement {
+            priority,
+            ..req(id, &[])
+        };
+        db.add_reqs(vec![
+            prioritized("a", None),
+            prioritized("b", Some(2)),
+            prioritized("c", Some(1)),
+            prioritized("d", Some(2)),
+        ])
+        .await
+        .unwrap();
+
+        let tag = super::Tag {
+            name: None,
+            link: None,
+        };
+        let mut orders = Vec::new();
+        for sort in [ReportSort::Id, ReportSort::Priority] {
+            let context = ReportContext::try_from(
+                &db,
+                &Default::default(),
+                &tag,
+                None,
+                None,
+                &ReportOptions {
+                    sort,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            orders.push(
+                context
+                    .requirements
+                    .iter()
+                    .map(|info| (info.meta.id.clone(), info.meta.priority))
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        assert_eq!(
+            orders[0],
+            vec![
+                ("a".to_string(), None),
+                ("b".to_string(), Some(2)),
+                ("c".to_string(), Some(1)),
+                ("d".to_string(), Some(2)),
+            ],
+            "Requirements not ordered by ID, or priority not stored."
+        );
+        assert_eq!(
+            orders[1],
+            vec![
+                ("c".to_string(), Some(1)),
+                ("b".to_string(), Some(2)),
+                ("d".to_string(), Some(2)),
+                ("a".to_string(), None),
+            ],
+            "Requirements not ordered by priority, or without priority not placed last."
+        );
+    }
+}
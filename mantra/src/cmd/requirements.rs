@@ -1,9 +1,15 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
 
-use crate::db::{MantraDb, RequirementChanges};
+use crate::{
+    cfg::{DepthLimit, IdCase, IdPattern, ManualRules},
+    db::{MantraDb, RequirementChanges},
+};
 
 use ignore::{types::TypesBuilder, WalkBuilder};
-use mantra_schema::requirements::{Requirement, RequirementSchema};
+use mantra_schema::requirements::{DesignLink, ReqId, Requirement, RequirementSchema};
 use regex::Regex;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -36,27 +42,182 @@ pub enum RequirementsError {
     CouldNotAccessFile(String),
     #[error("{}", .0)]
     Deserialize(serde_json::Error),
+    #[error("Requirements file '{}' is not a valid TOML `RequirementSchema`. Cause: {}", .file, .cause)]
+    DeserializeToml {
+        file: String,
+        cause: toml::de::Error,
+    },
+    #[error("Requirements file '{}' does not adhere to the `RequirementSchema`:\n{}", .file, .violations.join("\n"))]
+    SchemaViolation {
+        file: String,
+        violations: Vec<String>,
+    },
     #[error("{}", .0)]
     DbError(crate::db::DbError),
+    #[error("{}", .0)]
+    IdMismatch(String),
+    #[error("{}", .0)]
+    DepthExceeded(String),
+    #[error("Requirement `{}` at '{}' has state '{}', which is not one of the allowed states: {}", .id, .origin, .state, .allowed.join(", "))]
+    UnknownState {
+        id: ReqId,
+        origin: String,
+        state: String,
+        allowed: Vec<String>,
+    },
+    #[error("Requirement `{}` is defined differently in '{}' and '{}'. Differing fields: {}", .id, .first_source, .second_source, .fields.join(", "))]
+    Conflict {
+        id: ReqId,
+        first_source: String,
+        second_source: String,
+        fields: Vec<&'static str>,
+    },
+    #[error("Design link '{}' of requirement `{}` is invalid. Cause: {}", .url, .id, .cause)]
+    InvalidDesignLink {
+        id: ReqId,
+        url: String,
+        cause: String,
+    },
 }
 
-pub async fn collect(db: &MantraDb, formats: &[Format]) -> Result<(), RequirementsError> {
-    for fmt in formats {
-        let req_changes = match fmt {
-            Format::FromWiki(wiki_cfg) => {
-                collect_from_wiki(db, &wiki_cfg.root, &wiki_cfg.origin, wiki_cfg.major_version)
-                    .await
+/// Requirements found in all configured sources, used to detect conflicting definitions.
+#[derive(Debug, Default)]
+struct RequirementSources(HashMap<ReqId, (Requirement, String)>);
+
+impl RequirementSources {
+    /// Adds the requirements found in the given source.
+    ///
+    /// Returns an error if a requirement was already found in another source with differing fields.
+    /// The origin is not compared, because it is set per source.
+    fn add(&mut self, reqs: &[Requirement], source: &str) -> Result<(), RequirementsError> {
+        for req in reqs {
+            match self.0.get(&req.id) {
+                Some((known, known_source)) => {
+                    let fields = differing_fields(known, req);
+
+                    if !fields.is_empty() {
+                        return Err(RequirementsError::Conflict {
+                            id: req.id.clone(),
+                            first_source: known_source.clone(),
+                            second_source: source.to_string(),
+                            fields,
+                        });
+                    }
+
+                    log::warn!(
+                        "Requirement `{}` is defined in '{}' and '{}'.",
+                        req.id,
+                        known_source,
+                        source
+                    );
+                }
+                None => {
+                    self.0
+                        .insert(req.id.clone(), (req.clone(), source.to_string()));
+                }
             }
-            Format::FromSchema { files } => {
+        }
+
+        Ok(())
+    }
+}
+
+fn differing_fields(a: &Requirement, b: &Requirement) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+
+    if a.title != b.title {
+        fields.push("title");
+    }
+    if a.parents != b.parents {
+        fields.push("parents");
+    }
+    if a.manual != b.manual {
+        fields.push("manual");
+    }
+    if a.deprecated != b.deprecated {
+        fields.push("deprecated");
+    }
+    if a.untraceable != b.untraceable {
+        fields.push("untraceable");
+    }
+    if a.data != b.data {
+        fields.push("data");
+    }
+    if a.priority != b.priority {
+        fields.push("priority");
+    }
+    if a.state != b.state {
+        fields.push("state");
+    }
+    if a.effort != b.effort {
+        fields.push("effort");
+    }
+    if a.design_links != b.design_links {
+        fields.push("design_links");
+    }
+
+    fields
+}
+
+/// Options applied to all requirements found during collection.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CollectOptions<'a> {
+    /// Pattern all requirement IDs must match.
+    pub id_pattern: Option<&'a IdPattern>,
+    /// Case requirement IDs and parent IDs are normalized to.
+    pub id_case: IdCase,
+    /// Maximum depth of requirement IDs in the hierarchy.
+    pub depth_limit: Option<&'a DepthLimit>,
+    /// Rules marking requirements as manual if they have no explicit manual marker.
+    pub manual_rules: Option<&'a ManualRules>,
+    /// States requirements may be in.
+    /// Any state is allowed if not set.
+    pub allowed_states: Option<&'a [String]>,
+    /// Validate requirements files against the JSON schema of the `RequirementSchema`.
+    pub validate_schema: bool,
+    /// Design links set in the *mantra* configuration that are added to the linked requirements.
+    pub design_links: &'a [ConfiguredDesignLink],
+    /// Fail on design links that are neither well-formed URLs nor paths to existing files.
+    pub check_design_links: bool,
+}
+
+/// Design link of a requirement that is set in the *mantra* configuration instead of the requirement source.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ConfiguredDesignLink {
+    /// ID of the linked requirement.
+    pub id: ReqId,
+    #[serde(flatten)]
+    pub link: DesignLink,
+}
+
+/// Requirements read from one configured format.
+enum FormatRequirements {
+    Wiki(Vec<Requirement>),
+    Schemas(Vec<RequirementSchema>),
+}
+
+pub async fn collect(
+    db: &MantraDb,
+    formats: &[Format],
+    options: CollectOptions<'_>,
+) -> Result<(), RequirementsError> {
+    let mut sources = RequirementSources::default();
+    let mut format_reqs = Vec::with_capacity(formats.len());
+
+    // all sources are read before storing any requirement,
+    // so conflicting definitions in later sources leave the database unchanged
+    for fmt in formats {
+        format_reqs.push(read_requirements(fmt, options, &mut sources).await?);
+    }
+
+    for reqs in format_reqs {
+        let req_changes = match reqs {
+            FormatRequirements::Wiki(reqs) => collect_from_wiki(db, reqs, options).await,
+            FormatRequirements::Schemas(schemas) => {
                 let mut changes = RequirementChanges::default();
 
-                for file in files {
-                    let content = tokio::fs::read_to_string(file).await.map_err(|_| {
-                        RequirementsError::CouldNotAccessFile(file.display().to_string())
-                    })?;
-                    let schema =
-                        serde_json::from_str(&content).map_err(RequirementsError::Deserialize)?;
-                    changes.merge(&mut collect_from_schema(db, schema).await?);
+                for schema in schemas {
+                    changes.merge(&mut collect_from_schema(db, schema, options).await?);
                 }
 
                 Ok(changes)
@@ -68,10 +229,166 @@ pub async fn collect(db: &MantraDb, formats: &[Format]) -> Result<(), Requiremen
     Ok(())
 }
 
+/// Reads the requirements of the given format, and adds them to the known sources.
+async fn read_requirements(
+    fmt: &Format,
+    options: CollectOptions<'_>,
+    sources: &mut RequirementSources,
+) -> Result<FormatRequirements, RequirementsError> {
+    match fmt {
+        Format::FromWiki(wiki_cfg) => {
+            let reqs = wiki_requirements(
+                &wiki_cfg.root,
+                &wiki_cfg.origin,
+                wiki_cfg.major_version,
+                sources,
+            )?;
+
+            if reqs.is_empty() {
+                log::warn!(
+                    "No requirements were found in wiki at '{}'.",
+                    wiki_cfg.root.display()
+                );
+            }
+
+            Ok(FormatRequirements::Wiki(reqs))
+        }
+        Format::FromSchema { files } => {
+            let mut schemas = Vec::with_capacity(files.len());
+            let mut empty_files = Vec::new();
+
+            for file in files {
+                let content = tokio::fs::read_to_string(file).await.map_err(|_| {
+                    RequirementsError::CouldNotAccessFile(file.display().to_string())
+                })?;
+
+                let schema = requirement_schema(file, &content, options.validate_schema)?;
+
+                if schema.requirements.is_empty() {
+                    log::warn!(
+                        "Requirements file '{}' was read, but contains no requirements.",
+                        file.display()
+                    );
+                    empty_files.push(file);
+                }
+
+                sources.add(&schema.requirements, &file.display().to_string())?;
+                schemas.push(schema);
+            }
+
+            if !empty_files.is_empty() {
+                log::warn!(
+                    "'{}' of '{}' requirements files were read, but contained no requirements.",
+                    empty_files.len(),
+                    files.len()
+                );
+            }
+
+            Ok(FormatRequirements::Schemas(schemas))
+        }
+    }
+}
+
+/// Parses the content of a requirements file adhering to the `RequirementSchema`.
+///
+/// Files with extension `.toml` are parsed as TOML, all other files as JSON.
+fn requirement_schema(
+    file: &Path,
+    content: &str,
+    validate: bool,
+) -> Result<RequirementSchema, RequirementsError> {
+    let is_toml = file
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+
+    if validate {
+        let validation = if is_toml {
+            toml::from_str(content)
+                .map_err(|err| vec![err.to_string()])
+                .and_then(|instance| validate_instance(&instance))
+        } else {
+            validate_schema(content)
+        };
+
+        validation.map_err(|violations| RequirementsError::SchemaViolation {
+            file: file.display().to_string(),
+            violations,
+        })?;
+    }
+
+    if is_toml {
+        toml::from_str(content).map_err(|err| RequirementsError::DeserializeToml {
+            file: file.display().to_string(),
+            cause: err,
+        })
+    } else {
+        serde_json::from_str(content).map_err(|err| {
+            log::error!(
+                "Requirements file '{}' does not adhere to the `RequirementSchema`.",
+                file.display()
+            );
+            RequirementsError::Deserialize(err)
+        })
+    }
+}
+
+/// Validates the content of a requirements file against the JSON schema of the `RequirementSchema`.
+///
+/// Returns one message per violation, pointing to the invalid field (e.g. `requirements[2].id`).
+fn validate_schema(content: &str) -> Result<(), Vec<String>> {
+    let instance: serde_json::Value =
+        serde_json::from_str(content).map_err(|err| vec![err.to_string()])?;
+
+    validate_instance(&instance)
+}
+
+fn validate_instance(instance: &serde_json::Value) -> Result<(), Vec<String>> {
+    let schema = serde_json::to_value(schemars::schema_for!(RequirementSchema))
+        .expect("Generated JSON schema must be serializable.");
+    let validator = jsonschema::validator_for(&schema)
+        .expect("Generated JSON schema of the `RequirementSchema` must be valid.");
+
+    let violations: Vec<String> = validator
+        .iter_errors(instance)
+        .map(|err| format!("{}: {}", schema_path(err.instance_path.as_str()), err))
+        .collect();
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+/// Converts a JSON pointer (e.g. `/requirements/2/id`) into the form `requirements[2].id`.
+fn schema_path(pointer: &str) -> String {
+    let mut path = String::new();
+
+    for segment in pointer.split('/').skip(1) {
+        if segment.parse::<usize>().is_ok() {
+            path.push_str(&format!("[{segment}]"));
+        } else {
+            if !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(&segment.replace("~1", "/").replace("~0", "~"));
+        }
+    }
+
+    if path.is_empty() {
+        "<root>".to_string()
+    } else {
+        path
+    }
+}
+
 pub async fn collect_from_schema(
     db: &MantraDb,
-    schema: RequirementSchema,
+    mut schema: RequirementSchema,
+    options: CollectOptions<'_>,
 ) -> Result<RequirementChanges, RequirementsError> {
+    apply_options(&mut schema.requirements, options)?;
+
     db.add_reqs(schema.requirements)
         .await
         .map_err(RequirementsError::DbError)
@@ -79,10 +396,30 @@ pub async fn collect_from_schema(
 
 async fn collect_from_wiki(
     db: &MantraDb,
+    mut reqs: Vec<Requirement>,
+    options: CollectOptions<'_>,
+) -> Result<RequirementChanges, RequirementsError> {
+    if reqs.is_empty() {
+        let changes = RequirementChanges {
+            new_generation: db.max_req_generation().await,
+            ..Default::default()
+        };
+        Ok(changes)
+    } else {
+        apply_options(&mut reqs, options)?;
+
+        db.add_reqs(reqs).await.map_err(RequirementsError::DbError)
+    }
+}
+
+/// Returns all requirements found in the Markdown files of the wiki at the given root.
+/// The root may also be a single Markdown file.
+fn wiki_requirements(
     root: &Path,
     origin: &str,
     version: Option<usize>,
-) -> Result<RequirementChanges, RequirementsError> {
+    sources: &mut RequirementSources,
+) -> Result<Vec<Requirement>, RequirementsError> {
     let mut reqs = Vec::new();
 
     if root.is_dir() {
@@ -119,11 +456,10 @@ async fn collect_from_wiki(
                     .replace(char::is_whitespace, "-");
                 let req_origin = format!("{}/{}", origin, file_stem);
 
-                reqs.append(&mut requirements_from_wiki_content(
-                    &content,
-                    &req_origin,
-                    version,
-                ));
+                let mut file_reqs = requirements_from_wiki_content(&content, &req_origin, version);
+                sources.add(&file_reqs, &dir_entry.path().display().to_string())?;
+
+                reqs.append(&mut file_reqs);
             }
         }
     } else {
@@ -131,22 +467,207 @@ async fn collect_from_wiki(
             .map_err(|_| RequirementsError::CouldNotAccessFile(root.display().to_string()))?;
 
         reqs = requirements_from_wiki_content(&content, origin, version);
+        sources.add(&reqs, &root.display().to_string())?;
     }
 
-    if reqs.is_empty() {
-        log::warn!("No requirements were found.");
+    Ok(reqs)
+}
 
-        let changes = RequirementChanges {
-            new_generation: db.max_req_generation().await,
-            ..Default::default()
-        };
-        Ok(changes)
-    } else {
-        db.add_reqs(reqs).await.map_err(RequirementsError::DbError)
+/// Requirements parsed from a wiki, independent of any database.
+///
+/// Requirements are taken as written in the wiki.
+/// Collect options like ID case normalization are not applied.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Wiki {
+    pub requirements: BTreeMap<ReqId, Requirement>,
+}
+
+impl TryFrom<&WikiConfig> for Wiki {
+    type Error = RequirementsError;
+
+    fn try_from(cfg: &WikiConfig) -> Result<Self, Self::Error> {
+        let mut sources = RequirementSources::default();
+        let reqs = wiki_requirements(&cfg.root, &cfg.origin, cfg.major_version, &mut sources)?;
+
+        Ok(Self {
+            requirements: reqs.into_iter().map(|req| (req.id.clone(), req)).collect(),
+        })
+    }
+}
+
+impl Wiki {
+    /// Returns the requirement changes needed to get from this wiki to the `other` wiki.
+    ///
+    /// The origin is not compared, because it depends on where the wiki is located.
+    /// Traces are not part of the wiki, so trace changes must be taken from the collected data instead.
+    pub fn diff(&self, other: &Wiki) -> WikiDiff {
+        let mut diff = WikiDiff::default();
+
+        for (id, req) in &self.requirements {
+            match other.requirements.get(id) {
+                Some(other_req) => {
+                    let fields = differing_fields(req, other_req);
+
+                    if !fields.is_empty() {
+                        diff.changed.push(ChangedRequirement {
+                            id: id.clone(),
+                            fields,
+                        });
+                    }
+                }
+                None => diff.removed.push(id.clone()),
+            }
+        }
+
+        diff.added = other
+            .requirements
+            .keys()
+            .filter(|id| !self.requirements.contains_key(*id))
+            .cloned()
+            .collect();
+
+        diff
+    }
+}
+
+/// Requirement changes between two wikis.
+/// All lists are ordered by requirement ID.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct WikiDiff {
+    /// Requirements that only exist in the other wiki.
+    pub added: Vec<ReqId>,
+    /// Requirements that no longer exist in the other wiki.
+    pub removed: Vec<ReqId>,
+    /// Requirements that exist in both wikis, but with differing fields.
+    pub changed: Vec<ChangedRequirement>,
+}
+
+impl WikiDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Requirement that exists in both wikis, but with differing fields.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ChangedRequirement {
+    pub id: ReqId,
+    /// Names of the differing fields (e.g. `title`, `deprecated`).
+    pub fields: Vec<&'static str>,
+}
+
+fn apply_options(
+    reqs: &mut [Requirement],
+    options: CollectOptions<'_>,
+) -> Result<(), RequirementsError> {
+    for req in reqs {
+        if !options.id_case.is_preserve() {
+            req.id = options.id_case.normalize(&req.id);
+
+            if let Some(parents) = &mut req.parents {
+                for parent in parents {
+                    *parent = options.id_case.normalize(parent);
+                }
+            }
+        }
+
+        if let Some(pattern) = options.id_pattern {
+            pattern
+                .check(&req.id, &req.origin)
+                .map_err(RequirementsError::IdMismatch)?;
+        }
+
+        if let Some(limit) = options.depth_limit {
+            limit
+                .check(&req.id, &req.origin)
+                .map_err(RequirementsError::DepthExceeded)?;
+        }
+
+        if let (Some(state), Some(allowed)) = (&req.state, options.allowed_states) {
+            if !allowed.contains(state) {
+                return Err(RequirementsError::UnknownState {
+                    id: req.id.clone(),
+                    origin: req.origin.clone(),
+                    state: state.clone(),
+                    allowed: allowed.to_vec(),
+                });
+            }
+        }
+
+        if req.manual.is_none() {
+            if let Some(rules) = options.manual_rules {
+                req.manual = Some(rules.is_manual(&req.id));
+            }
+        }
+
+        for configured in options
+            .design_links
+            .iter()
+            .filter(|configured| options.id_case.normalize(&configured.id) == req.id)
+        {
+            if !req.design_links.contains(&configured.link) {
+                req.design_links.push(configured.link.clone());
+            }
+        }
+
+        if options.check_design_links {
+            for link in &req.design_links {
+                check_design_link(&link.url).map_err(|cause| {
+                    RequirementsError::InvalidDesignLink {
+                        id: req.id.clone(),
+                        url: link.url.clone(),
+                        cause,
+                    }
+                })?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that the given design link is either a well-formed URL,
+/// or the path to an existing file relative to the working directory.
+fn check_design_link(url: &str) -> Result<(), String> {
+    if url.contains(char::is_whitespace) {
+        return Err("Design links must not contain whitespace.".to_string());
+    }
+
+    match url.split_once("://") {
+        Some((scheme, rest)) => {
+            let valid_scheme = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+            if !valid_scheme {
+                return Err(format!("'{scheme}' is not a valid URL scheme."));
+            }
+
+            // only file URLs may omit the host, e.g. `file:///docs/design.md`
+            if scheme != "file" && rest.split('/').next().unwrap_or_default().is_empty() {
+                return Err("URL has no host.".to_string());
+            }
+
+            Ok(())
+        }
+        None if Path::new(url).exists() => Ok(()),
+        None => Err("File does not exist.".to_string()),
     }
 }
 
 static REQ_ID_MATCHER: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+static REQ_HEADING_CANDIDATE: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+
+/// Returns `true` if the line is a heading starting with an ID in backticks.
+/// Such headings are likely meant to be requirements.
+fn looks_like_req_heading(line: &str) -> bool {
+    REQ_HEADING_CANDIDATE
+        .get_or_init(|| {
+            Regex::new(r"^#{1,6}\s+`[^`]+`")
+                .expect("Regex to match requirement-like headings could **not** be created.")
+        })
+        .is_match(line)
+}
 
 fn requirements_from_wiki_content(
     content: &str,
@@ -165,7 +686,7 @@ fn requirements_from_wiki_content(
         .expect("Regex to match the requirement ID could **not** be created.")
     });
 
-    for line in lines {
+    for (line_nr, line) in lines.enumerate() {
         if line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~") {
             in_verbatim_context = !in_verbatim_context;
         }
@@ -193,8 +714,9 @@ fn requirements_from_wiki_content(
                     }
                 }
 
-                let manual = marker == Some("manual".to_string());
+                let manual = (marker == Some("manual".to_string())).then_some(true);
                 let deprecated = marker == Some("deprecated".to_string());
+                let untraceable = marker == Some("untraceable".to_string());
 
                 let title = captures
                     .name("title")
@@ -209,11 +731,311 @@ fn requirements_from_wiki_content(
                     data: None,
                     manual,
                     deprecated,
+                    untraceable,
                     parents: None,
+                    priority: None,
+                    state: None,
+                    effort: None,
+                    design_links: Vec::new(),
                 });
+            } else if looks_like_req_heading(line) {
+                log::warn!(
+                    "Heading '{}' at line '{}' in '{}' looks like a requirement, but does not match the form '# `<id>`: <title>'. The heading is skipped.",
+                    line,
+                    line_nr + 1,
+                    origin
+                );
             }
         }
     }
 
     reqs
 }
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::test_db;
+
+    use super::{
+        apply_options, collect, looks_like_req_heading, requirement_schema,
+        requirements_from_wiki_content, validate_schema, ChangedRequirement, CollectOptions,
+        ConfiguredDesignLink, Format, RequirementSources, RequirementsError, Wiki, WikiConfig,
+    };
+
+    #[test]
+    fn wiki_diff() {
+        let wiki_dir =
+            std::env::temp_dir().join(format!("mantra-wiki-diff-test-{}", std::process::id()));
+        std::fs::create_dir_all(&wiki_dir).unwrap();
+        let old_file = wiki_dir.join("old.md");
+        let new_file = wiki_dir.join("new.md");
+        std::fs::write(
+            &old_file,
+            "# `kept`: Kept\n\n# `renamed`: Old title\n\n# `removed`: Removed",
+        )
+        .unwrap();
+        std::fs::write(
+            &new_file,
+            "# `kept`: Kept\n\n# `renamed`(deprecated): New title\n\n# `added`: Added",
+        )
+        .unwrap();
+
+        let wiki = |root| {
+            Wiki::try_from(&WikiConfig {
+                root,
+                origin: "wiki".to_string(),
+                major_version: None,
+            })
+            .unwrap()
+        };
+        let old_wiki = wiki(old_file);
+        let new_wiki = wiki(new_file);
+        std::fs::remove_dir_all(&wiki_dir).unwrap();
+
+        let diff = old_wiki.diff(&new_wiki);
+        assert_eq!(
+            diff.added,
+            vec!["added".to_string()],
+            "Added requirement not found."
+        );
+        assert_eq!(
+            diff.removed,
+            vec!["removed".to_string()],
+            "Removed requirement not found."
+        );
+        assert_eq!(
+            diff.changed,
+            vec![ChangedRequirement {
+                id: "renamed".to_string(),
+                fields: vec!["title", "deprecated"],
+            }],
+            "Changed fields not found."
+        );
+        assert!(
+            old_wiki.diff(&old_wiki).is_empty(),
+            "Identical wikis are not equal."
+        );
+    }
+
+    #[test]
+    fn colonless_heading_skipped() {
+        let content =
+            "# `valid`: Valid requirement\n\n# `missing_colon` Missing colon\n\n# Plain heading";
+
+        let reqs = requirements_from_wiki_content(content, "wiki", None);
+
+        assert_eq!(reqs.len(), 1, "Heading without colon taken as requirement.");
+        assert_eq!(reqs[0].id, "valid", "Wrong requirement extracted.");
+        assert!(
+            looks_like_req_heading("# `missing_colon` Missing colon"),
+            "Heading without colon not detected as requirement-like."
+        );
+        assert!(
+            !looks_like_req_heading("# Plain heading"),
+            "Plain heading detected as requirement-like."
+        );
+    }
+
+    #[test]
+    fn conflicting_sources() {
+        let reqs = requirements_from_wiki_content("# `a`: Title", "wiki", None);
+        let same_reqs = requirements_from_wiki_content("# `a`: Title", "other-wiki", None);
+        let changed_reqs = requirements_from_wiki_content("# `a`: Other title", "wiki", None);
+
+        let mut sources = RequirementSources::default();
+        sources.add(&reqs, "first.md").unwrap();
+
+        assert!(
+            sources.add(&same_reqs, "second.md").is_ok(),
+            "Same definition in other source is a conflict."
+        );
+
+        let conflict = sources.add(&changed_reqs, "third.md");
+        assert!(
+            matches!(
+                conflict,
+                Err(RequirementsError::Conflict { ref first_source, ref second_source, ref fields, .. })
+                    if first_source == "first.md" && second_source == "third.md" && fields == &vec!["title"]
+            ),
+            "Conflicting definition not detected."
+        );
+    }
+
+    #[tokio::test]
+    async fn conflicting_sources_not_stored() {
+        let dir = std::env::temp_dir().join(format!(
+            "mantra_conflicting_sources_test_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("first.json"),
+            r#"{"requirements":[{"id":"a","title":"A","origin":"x","deprecated":false},{"id":"b","title":"B","origin":"x","deprecated":false}]}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("second.json"),
+            r#"{"requirements":[{"id":"a","title":"Other title","origin":"x","deprecated":false}]}"#,
+        )
+        .unwrap();
+
+        let db = test_db("conflicting_sources").await;
+        let res = collect(
+            &db,
+            &[
+                Format::FromSchema {
+                    files: vec![dir.join("first.json")],
+                },
+                Format::FromSchema {
+                    files: vec![dir.join("second.json")],
+                },
+            ],
+            CollectOptions::default(),
+        )
+        .await;
+
+        let req_cnt: i64 = sqlx::query_scalar("select count(*) from Requirements")
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(
+            matches!(res, Err(RequirementsError::Conflict { ref id, .. }) if id == "a"),
+            "Conflicting definition not detected."
+        );
+        assert_eq!(
+            req_cnt, 0,
+            "Requirements of sources before the conflict were stored."
+        );
+    }
+
+    #[test]
+    fn schema_violation_paths() {
+        let violations = validate_schema(
+            r#"{"requirements":[{"id":"a","title":"A","origin":"x","deprecated":false},{"title":"B","origin":"x","deprecated":false}]}"#,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            violations,
+            vec![r#"requirements[1]: "id" is a required property"#.to_string()],
+            "Violation does not point to the invalid requirement."
+        );
+    }
+
+    #[test]
+    fn toml_requirements_file() {
+        let file = std::path::Path::new("requirements.toml");
+        let content = r#"
+            [[requirements]]
+            id = "a"
+            title = "A"
+            origin = "x"
+            deprecated = false
+
+            [[requirements]]
+            id = "a.b"
+            parents = ["a"]
+            title = "B"
+            origin = "x"
+            deprecated = false
+            priority = 1
+            "#;
+
+        let schema = requirement_schema(file, content, true).unwrap();
+
+        assert_eq!(
+            schema
+                .requirements
+                .iter()
+                .map(|req| req.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a", "a.b"],
+            "Requirements of TOML file not parsed."
+        );
+
+        let invalid = requirement_schema(file, "[[requirements]]\ntitle = \"A\"", false);
+        assert!(
+            matches!(invalid, Err(RequirementsError::DeserializeToml { file, .. }) if file == "requirements.toml"),
+            "Invalid TOML requirement not reported with its file."
+        );
+    }
+
+    #[test]
+    fn unknown_state_rejected() {
+        let allowed = vec!["draft".to_string(), "approved".to_string()];
+        let options = CollectOptions {
+            allowed_states: Some(&allowed),
+            ..Default::default()
+        };
+
+        let mut reqs = requirements_from_wiki_content("# `a`: A\n# `b`: B", "wiki", None);
+        reqs[0].state = Some("approved".to_string());
+
+        assert!(
+            apply_options(&mut reqs, options).is_ok(),
+            "Allowed state or missing state rejected."
+        );
+
+        reqs[1].state = Some("done".to_string());
+
+        assert!(
+            matches!(
+                apply_options(&mut reqs, options),
+                Err(RequirementsError::UnknownState { ref id, ref state, .. }) if id == "b" && state == "done"
+            ),
+            "Unknown state not rejected."
+        );
+    }
+
+    #[test]
+    fn configured_design_links_checked() {
+        let configured = |url: &str| ConfiguredDesignLink {
+            id: "a".to_string(),
+            link: mantra_schema::requirements::DesignLink {
+                url: url.to_string(),
+                title: Some("ADR-1".to_string()),
+            },
+        };
+        let valid = vec![configured("https://example.com/adr/1")];
+        let missing = vec![configured("docs/adr/missing.md")];
+
+        let mut reqs = requirements_from_wiki_content("# `a`: A\n# `b`: B", "wiki", None);
+        let res = apply_options(
+            &mut reqs,
+            CollectOptions {
+                design_links: &valid,
+                check_design_links: true,
+                ..Default::default()
+            },
+        );
+
+        assert!(res.is_ok(), "Valid design link rejected.");
+        assert_eq!(
+            reqs[0].design_links,
+            vec![valid[0].link.clone()],
+            "Configured design link not added to requirement."
+        );
+        assert!(
+            reqs[1].design_links.is_empty(),
+            "Design link added to other requirement."
+        );
+
+        let mut reqs = requirements_from_wiki_content("# `a`: A", "wiki", None);
+        assert!(
+            matches!(
+                apply_options(
+                    &mut reqs,
+                    CollectOptions {
+                        design_links: &missing,
+                        check_design_links: true,
+                        ..Default::default()
+                    },
+                ),
+                Err(RequirementsError::InvalidDesignLink { ref url, .. }) if url == "docs/adr/missing.md"
+            ),
+            "Design link to missing file not rejected."
+        );
+    }
+}
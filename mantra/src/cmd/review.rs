@@ -1,9 +1,12 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use mantra_schema::{requirements::ReqId, reviews::ReviewSchema};
 use time::PrimitiveDateTime;
 
-use crate::db::{DbError, MantraDb};
+use crate::{
+    cfg::IdCase,
+    db::{DbError, MantraDb},
+};
 
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub struct Review {
@@ -25,6 +28,8 @@ pub struct Review {
 pub struct VerifiedRequirement {
     pub id: ReqId,
     pub comment: Option<String>,
+    /// URLs or filepaths of documents supporting the verification.
+    pub evidence: Vec<String>,
 }
 
 #[derive(Debug, Clone, thiserror::Error)]
@@ -35,10 +40,19 @@ pub enum ReviewError {
     Parsing(PathBuf),
     #[error("{}", .0)]
     Db(DbError),
+    #[error("Invalid review file pattern. Cause: {}", .0)]
+    InvalidPattern(String),
+    #[error("Evidence '{}' for requirement `{}` in review file '{}' does not exist.", .evidence, .req_id, .file.display())]
+    MissingEvidence {
+        file: PathBuf,
+        req_id: ReqId,
+        evidence: String,
+    },
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ReviewConfig {
+    /// Review files, directories containing review files, or glob patterns matching review files.
     #[serde(
         alias = "filepaths",
         alias = "external-files",
@@ -47,24 +61,85 @@ pub struct ReviewConfig {
     pub files: Vec<PathBuf>,
 }
 
-pub async fn collect(db: &MantraDb, cfg: ReviewConfig) -> Result<usize, ReviewError> {
+/// File formats reviews may be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReviewFormat {
+    Toml,
+    Yaml,
+}
+
+impl ReviewFormat {
+    /// Detects the format by the file extension.
+    fn from_path(filepath: &Path) -> Option<Self> {
+        match filepath.extension().and_then(|s| s.to_str()) {
+            Some("toml") => Some(Self::Toml),
+            Some("yaml" | "yml") => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+
+    fn parse(&self, content: &str) -> Result<ReviewSchema, String> {
+        match self {
+            Self::Toml => toml::from_str(content).map_err(|err| err.to_string()),
+            Self::Yaml => serde_yaml_ng::from_str(content).map_err(|err| err.to_string()),
+        }
+    }
+}
+
+/// Parses the evidence of verifications that was stored as JSON array.
+pub(crate) fn evidence_from_db(evidence: Option<String>) -> Vec<String> {
+    evidence
+        .map(|e| serde_json::from_str(&e).expect("Evidence must be stored as JSON array."))
+        .unwrap_or_default()
+}
+
+/// Checks that all evidence filepaths of the review exist.
+/// Evidence containing `://` is taken as URL, and is not checked.
+fn check_evidence(review_file: &Path, review: &ReviewSchema) -> Result<(), ReviewError> {
+    let review_dir = review_file.parent().unwrap_or(Path::new(""));
+
+    for req in &review.requirements {
+        for evidence in &req.evidence {
+            if !evidence.contains("://") && !review_dir.join(evidence).exists() {
+                return Err(ReviewError::MissingEvidence {
+                    file: review_file.to_path_buf(),
+                    req_id: req.id.clone(),
+                    evidence: evidence.clone(),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Adds all reviews of the given configuration that are not yet in the database.
+/// If `verify_evidence` is set, evidence filepaths must exist.
+/// IDs of verified requirements are normalized to the given case.
+pub async fn collect(
+    db: &MantraDb,
+    cfg: ReviewConfig,
+    verify_evidence: bool,
+    id_case: IdCase,
+) -> Result<usize, ReviewError> {
     let mut review_cnt = 0;
 
-    for review_file in &cfg.files {
-        if !matches!(
-            review_file.extension().and_then(|s| s.to_str()),
-            Some("toml")
-        ) {
+    let review_files =
+        super::expand_files(&cfg.files, |file| ReviewFormat::from_path(file).is_some())
+            .map_err(|err| ReviewError::InvalidPattern(err.to_string()))?;
+
+    for review_file in &review_files {
+        let Some(format) = ReviewFormat::from_path(review_file) else {
             log::warn!(
-                "Only TOML format is supported for reviews. Skipped file '{}'.",
+                "Only TOML and YAML formats are supported for reviews. Skipped file '{}'.",
                 review_file.display()
             );
             continue;
-        }
+        };
 
         let file_content = std::fs::read_to_string(review_file)
             .map_err(|_| ReviewError::ReadingFile(review_file.to_path_buf()))?;
-        let review: ReviewSchema = toml::from_str(&file_content).map_err(|err| {
+        let mut review = format.parse(&file_content).map_err(|err| {
             log::error!(
                 "Failed parsing review file '{}': {}",
                 review_file.display(),
@@ -73,6 +148,14 @@ pub async fn collect(db: &MantraDb, cfg: ReviewConfig) -> Result<usize, ReviewEr
             ReviewError::Parsing(review_file.to_path_buf())
         })?;
 
+        for req in &mut review.requirements {
+            req.id = id_case.normalize(&req.id);
+        }
+
+        if verify_evidence {
+            check_evidence(review_file, &review)?;
+        }
+
         if db.review_exists(&review.name, &review.date).await {
             log::info!(
                 "Review '{}' already in the database.",
@@ -95,3 +178,80 @@ pub async fn collect(db: &MantraDb, cfg: ReviewConfig) -> Result<usize, ReviewEr
 pub async fn collect_from_schema(db: &MantraDb, review: ReviewSchema) -> Result<(), ReviewError> {
     db.add_review(review).await.map_err(ReviewError::Db)
 }
+
+#[cfg(test)]
+mod test {
+    use super::{check_evidence, ReviewError, ReviewFormat};
+
+    #[test]
+    fn yaml_review_equals_toml_review() {
+        let toml_review = ReviewFormat::Toml
+            .parse(
+                r#"
+name = "First review"
+date = "2024-05-12 10:30"
+reviewer = "Reviewer"
+
+[[requirement]]
+id = "a"
+comment = "Checked manually."
+"#,
+            )
+            .unwrap();
+        let yaml_review = ReviewFormat::Yaml
+            .parse(
+                r#"
+name: First review
+date: 2024-05-12 10:30
+reviewer: Reviewer
+requirement:
+  - id: a
+    comment: Checked manually.
+"#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            yaml_review, toml_review,
+            "YAML review differs from the same review in TOML."
+        );
+    }
+
+    #[test]
+    fn missing_evidence_detected() {
+        let review_dir =
+            std::env::temp_dir().join(format!("mantra_evidence_test_{}", std::process::id()));
+        std::fs::create_dir_all(&review_dir).unwrap();
+        std::fs::write(review_dir.join("protocol.pdf"), "").unwrap();
+
+        let review = ReviewFormat::Yaml
+            .parse(
+                r#"
+name: Review
+date: 2024-05-12 10:30
+reviewer: Reviewer
+requirement:
+  - id: a
+    evidence:
+      - protocol.pdf
+      - https://example.com/report.pdf
+  - id: b
+    evidence:
+      - missing.pdf
+"#,
+            )
+            .unwrap();
+
+        let res = check_evidence(&review_dir.join("review.yaml"), &review);
+        let _ = std::fs::remove_dir_all(&review_dir);
+
+        assert!(
+            matches!(
+                res,
+                Err(ReviewError::MissingEvidence { ref req_id, ref evidence, .. })
+                    if req_id == "b" && evidence == "missing.pdf"
+            ),
+            "Missing evidence not detected, or existing evidence and URLs reported."
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{
+    cmd::report::{ReportError, RequirementsOverview, TestsOverview},
+    db::{DbError, MantraDb},
+};
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct SummaryConfig {
+    /// Print only the traced, covered, and passed ratios in one line.
+    #[arg(long)]
+    pub oneline: bool,
+    /// Output format of the summary.
+    #[arg(long, value_enum, default_value_t = SummaryFormat::Text)]
+    pub format: SummaryFormat,
+    /// Print the summary as JSON object, same as `--format json`.
+    #[arg(long, conflicts_with = "format")]
+    pub json: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SummaryFormat {
+    #[default]
+    Text,
+    /// JSON object with the `traced`, `covered`, `passed`, and `verified` ratios, and the `tests` overview in one line.
+    Json,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SummaryError {
+    #[error("{}", .0)]
+    Db(DbError),
+    #[error("Could not read the tests overview. Cause: {}", .0)]
+    Tests(ReportError),
+    #[error("Could not serialize the summary. Cause: {}", .0)]
+    Serialize(serde_json::Error),
+}
+
+/// Ratios of traced, covered, passed, and verified requirements together with the overview of all tests.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct Summary {
+    pub traced: f64,
+    pub covered: f64,
+    pub passed: f64,
+    pub verified: f64,
+    pub tests: TestsOverview,
+}
+
+impl Summary {
+    pub fn new(overview: &RequirementsOverview, tests: TestsOverview) -> Self {
+        Self {
+            traced: overview.traced_ratio,
+            covered: overview.covered_ratio,
+            passed: overview.passed_ratio,
+            verified: overview.verified_ratio,
+            tests,
+        }
+    }
+}
+
+pub async fn summary(db: &MantraDb, cfg: &SummaryConfig) -> Result<(), SummaryError> {
+    let overview = db.coverage_overview().await.map_err(SummaryError::Db)?;
+    let tests = TestsOverview::try_from(db)
+        .await
+        .map_err(SummaryError::Tests)?;
+
+    let format = if cfg.json {
+        SummaryFormat::Json
+    } else {
+        cfg.format
+    };
+
+    match format {
+        SummaryFormat::Json => println!(
+            "{}",
+            serde_json::to_string(&Summary::new(&overview, tests))
+                .map_err(SummaryError::Serialize)?
+        ),
+        SummaryFormat::Text if cfg.oneline => println!("{}", oneline(&overview)),
+        SummaryFormat::Text => print!("{}", multiline(&overview, &tests)),
+    }
+
+    Ok(())
+}
+
+/// Formats the ratios in the form `85% traced, 72% covered, 68% passed`.
+pub fn oneline(overview: &RequirementsOverview) -> String {
+    format!(
+        "{:.0}% traced, {:.0}% covered, {:.0}% passed",
+        overview.traced_ratio * 100.0,
+        overview.covered_ratio * 100.0,
+        overview.passed_ratio * 100.0
+    )
+}
+
+fn multiline(overview: &RequirementsOverview, tests: &TestsOverview) -> String {
+    format!(
+        "Requirements: {}\nTraced: {} ({:.1}%)\nCovered: {} ({:.1}%)\nPassed: {} ({:.1}%)\nVerified: {} ({:.1}%)\nTests passed: {} of {} ({:.1}%)\n",
+        overview.req_cnt,
+        overview.traced_cnt,
+        overview.traced_ratio * 100.0,
+        overview.covered_cnt,
+        overview.covered_ratio * 100.0,
+        overview.passed_cnt,
+        overview.passed_ratio * 100.0,
+        overview.verified_cnt.unwrap_or_default(),
+        overview.verified_ratio * 100.0,
+        tests.passed_cnt,
+        tests.test_cnt,
+        tests.passed_ratio * 100.0
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cmd::report::{RequirementsOverview, TestsOverview};
+
+    use super::{multiline, oneline, Summary};
+
+    #[test]
+    fn summary_formats() {
+        let overview = RequirementsOverview {
+            req_cnt: 20,
+            untraceable_cnt: 0,
+            traced_cnt: 17,
+            traced_ratio: 0.85,
+            covered_cnt: 14,
+            covered_ratio: 0.72,
+            passed_cnt: 13,
+            passed_ratio: 0.68,
+            verified_cnt: Some(1),
+            verified_ratio: 0.05,
+            span_coverage: None,
+        };
+        let tests = TestsOverview {
+            test_cnt: 10,
+            ran_cnt: 9,
+            ran_ratio: 0.9,
+            passed_cnt: 8,
+            passed_ratio: 0.8,
+            failed_cnt: 1,
+            failed_ratio: 0.1,
+            skipped_cnt: 1,
+            skipped_ratio: 0.1,
+        };
+
+        assert_eq!(
+            oneline(&overview),
+            "85% traced, 72% covered, 68% passed",
+            "Oneline summary not formatted as expected."
+        );
+        assert_eq!(
+            multiline(&overview, &tests),
+            "Requirements: 20\nTraced: 17 (85.0%)\nCovered: 14 (72.0%)\nPassed: 13 (68.0%)\nVerified: 1 (5.0%)\nTests passed: 8 of 10 (80.0%)\n",
+            "Summary not formatted as expected."
+        );
+
+        let json = serde_json::to_value(Summary::new(&overview, tests)).unwrap();
+        assert_eq!(
+            (
+                &json["traced"],
+                &json["verified"],
+                &json["tests"]["passed_cnt"]
+            ),
+            (
+                &serde_json::json!(0.85),
+                &serde_json::json!(0.05),
+                &serde_json::json!(8)
+            ),
+            "JSON summary not formatted as expected."
+        );
+    }
+}
@@ -1,17 +1,25 @@
 use std::{
-    io::Read,
+    collections::{BTreeMap, HashMap, HashSet},
+    io::{Read, Write},
     path::{Path, PathBuf},
 };
 
-use crate::db::{MantraDb, TraceChanges};
+use crate::{
+    cfg::{IdCase, IdPattern},
+    db::{CollectProgress, CommitTracePk, CommitTraceStatus, MantraDb, TraceChanges},
+};
 
 use ignore::{types::TypesBuilder, WalkBuilder};
 use mantra_lang_tracing::{
-    collect::{AstCollector, PlainCollector, TraceCollector},
+    collect::{AstCollector, AstCollectorFn, PlainCollector, TraceCollector},
     lsif_graph::LsifGraph,
     path::SlashPathBuf,
 };
-use mantra_schema::traces::{TraceEntry, TraceSchema};
+use mantra_schema::{
+    requirements::ReqId,
+    traces::{FileTraces, TraceEntry, TraceSchema},
+};
+use object::{Object, ObjectSymbol};
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(untagged)]
@@ -25,6 +33,8 @@ pub enum TraceKind {
         )]
         files: Vec<PathBuf>,
     },
+    FromBinary(BinaryConfig),
+    FromGit(GitConfig),
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -34,6 +44,38 @@ pub struct SourceConfig {
     pub keep_path_absolute: bool,
     #[serde(default, alias = "lsif-data")]
     pub lsif_data: Option<Vec<PathBuf>>,
+    /// Set to store the canonical item path (e.g. `crate::module::func`) resolved from LSIF data for traced items.
+    #[serde(default, alias = "canonical-item-paths")]
+    pub canonical_item_paths: bool,
+    /// Set to store traces on test functions (e.g. `#[test]`) as declared coverage of the test.
+    #[serde(default, alias = "declared-coverage")]
+    pub declared_coverage: bool,
+}
+
+/// Configuration to collect traces from symbols of a compiled binary.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct BinaryConfig {
+    /// Path to the binary containing the symbols.
+    pub binary: PathBuf,
+    /// Path to a TOML file mapping symbol names to requirement IDs.
+    ///
+    /// e.g. `my_c_function = ["req_id"]`
+    #[serde(alias = "symbol-map")]
+    pub symbol_map: PathBuf,
+    /// Prefix that is stripped from filepaths taken from debug information.
+    #[serde(default, alias = "base-path")]
+    pub base_path: Option<PathBuf>,
+}
+
+/// Configuration to collect traces from requirements referenced in git commit messages.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct GitConfig {
+    /// Revision range passed to `git log` (e.g. `v1.0.0..HEAD`).
+    pub commits: String,
+    /// Path to the git repository.
+    /// Default is the current directory.
+    #[serde(default)]
+    pub repository: Option<PathBuf>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -44,46 +86,333 @@ pub enum TraceError {
     DbError(crate::db::DbError),
     #[error("{}", .0)]
     Deserialize(serde_json::Error),
+    #[error("{}", .0)]
+    DeserializeToml(toml::de::Error),
+    #[error("Could not read symbols of binary '{}'. Cause: {}", .0, .1)]
+    Binary(String, String),
+    #[error("{}", .0)]
+    IdMismatch(String),
+    #[error("Could not emit traces. Cause: {}", .0)]
+    Emit(String),
+    #[error("Could not read commits '{}'. Cause: {}", .0, .1)]
+    Git(String, String),
+    #[error("Could not serialize traces. Cause: {}", .0)]
+    Serialize(serde_json::Error),
+    #[error("'{}' traces refer to unknown requirements.", .0)]
+    UnknownRequirements(usize),
+    #[error("Trace at '{}' references deprecated requirement `{}`.", .0, .1)]
+    DeprecatedReqTraced(String, ReqId),
+    #[error("More than '{}' files found below '{}'. Add ignore rules (e.g. in `.gitignore` or `.ignore`) for dependencies or build output like `node_modules`, or increase `--max-files`.", .max, .root)]
+    TooManyFiles { root: String, max: usize },
 }
 
-pub async fn collect(db: &MantraDb, kinds: &[TraceKind]) -> Result<(), TraceError> {
-    for kind in kinds {
+/// Limits guarding the walk over source files against accidentally included dependencies or build output.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::Args)]
+pub struct WalkLimits {
+    /// Abort trace collection if more than the given number of files are found below one trace root.
+    #[arg(long)]
+    pub max_files: Option<usize>,
+    /// Skip source files larger than the given number of bytes.
+    #[arg(long)]
+    pub max_file_size: Option<u64>,
+}
+
+impl WalkLimits {
+    /// Returns `true` if the file exceeds the maximum file size, and logs a warning in that case.
+    fn exceeds_file_size(&self, filepath: &Path) -> bool {
+        let Some(max) = self.max_file_size else {
+            return false;
+        };
+
+        match std::fs::metadata(filepath) {
+            Ok(metadata) if metadata.len() > max => {
+                log::warn!(
+                    "File '{}' is skipped, because its size of '{}' bytes exceeds the maximum file size of '{}' bytes.",
+                    filepath.display(),
+                    metadata.len(),
+                    max
+                );
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum TraceCmd {
+    /// Print all traces found in the given file, without storing them in the database.
+    List(TraceListConfig),
+}
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct TraceListConfig {
+    /// File to collect traces from.
+    pub file: PathBuf,
+    /// Output format of the found traces.
+    #[arg(long, value_enum, default_value_t = TraceListFormat::Text)]
+    pub format: TraceListFormat,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum TraceListFormat {
+    #[default]
+    Text,
+    /// Traces of the file according to the `FileTraces` part of the *mantra* TraceSchema.
+    Json,
+}
+
+/// Options applied to all traces found during collection.
+pub struct CollectOptions<'a> {
+    /// Pattern all trace IDs must match.
+    pub id_pattern: Option<&'a IdPattern>,
+    /// Case trace IDs are normalized to.
+    pub id_case: IdCase,
+    /// Emits found traces as they are collected.
+    pub emitter: Option<TraceEmitter>,
+    /// Stores found traces in the database.
+    pub store: bool,
+    /// Traces to deprecated requirements are errors instead of warnings.
+    pub strict: bool,
+    /// Requirements that are deprecated, or have a deprecated ancestor.
+    /// Set by [`collect`] before any trace is collected.
+    pub deprecated_reqs: HashSet<ReqId>,
+    /// Limits of the walk over source files.
+    pub walk_limits: WalkLimits,
+    /// Skip trace sources and files that were already collected by an interrupted collect.
+    pub resume: bool,
+    /// Collect step and its progress for the trace source that is currently collected.
+    /// Set by [`collect`] for each trace source if traces are stored.
+    pub step: Option<(String, CollectProgress)>,
+    /// Only re-trace these files of source trace roots, and merge their traces into the current generation.
+    /// All files below the source roots are traced if not set.
+    pub changed_files: Option<Vec<PathBuf>>,
+    /// Commit traces found by the git trace sources collected so far.
+    pub commit_traces: Vec<CommitTracePk>,
+    /// Removes stored commit traces of the branch that are not part of [`Self::commit_traces`] once a git trace source is collected.
+    /// Set by [`collect`], so stale commit traces are only removed after the last git trace source.
+    pub remove_stale_commits: bool,
+}
+
+impl Default for CollectOptions<'_> {
+    fn default() -> Self {
+        Self {
+            id_pattern: None,
+            id_case: IdCase::default(),
+            emitter: None,
+            store: true,
+            strict: false,
+            deprecated_reqs: HashSet::new(),
+            walk_limits: WalkLimits::default(),
+            resume: false,
+            step: None,
+            changed_files: None,
+            commit_traces: Vec::new(),
+            remove_stale_commits: true,
+        }
+    }
+}
+
+impl CollectOptions<'_> {
+    fn emits_to_stdout(&self) -> bool {
+        self.emitter
+            .as_ref()
+            .is_some_and(|emitter| emitter.to_stdout)
+    }
+
+    /// Returns the generation of traces of the current trace source.
+    /// A resumed trace source keeps the generation of the interrupted collect,
+    /// and traces of incrementally re-traced files are merged into the current generation.
+    /// Traces that are not stored have no generation, and always get generation `0`.
+    async fn new_generation(&self, db: Option<&MantraDb>, incremental: bool) -> i64 {
+        match (
+            self.step
+                .as_ref()
+                .and_then(|(_, progress)| progress.generation),
+            db,
+        ) {
+            (Some(generation), _) => generation,
+            (None, None) => 0,
+            (None, Some(db)) if incremental => db.max_trace_generation().await,
+            (None, Some(db)) => db.max_trace_generation().await + 1,
+        }
+    }
+
+    /// Returns `true` if traces of the given file were already stored by an interrupted collect.
+    fn is_collected(&self, filepath: &Path) -> bool {
+        self.step.as_ref().is_some_and(|(_, progress)| {
+            progress
+                .collected_files
+                .contains(&SlashPathBuf::from(filepath).to_string())
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum EmitFormat {
+    /// One JSON record `{"filepath": <path>, "trace": <TraceEntry>}` per line.
+    #[default]
+    Ndjson,
+}
+
+/// Writes collected traces to stdout or a file.
+pub struct TraceEmitter {
+    out: Box<dyn std::io::Write + Send>,
+    to_stdout: bool,
+}
+
+#[derive(serde::Serialize)]
+struct EmittedTrace<'a> {
+    filepath: String,
+    trace: &'a TraceEntry,
+}
+
+impl TraceEmitter {
+    /// Creates an emitter that writes to the given file, or to stdout if no file is given.
+    pub fn new(_format: EmitFormat, filepath: Option<&Path>) -> Result<Self, TraceError> {
+        match filepath {
+            Some(path) => {
+                let file = std::fs::File::create(path)
+                    .map_err(|_| TraceError::CouldNotAccessFile(path.display().to_string()))?;
+                Ok(Self {
+                    out: Box::new(std::io::BufWriter::new(file)),
+                    to_stdout: false,
+                })
+            }
+            None => Ok(Self {
+                out: Box::new(std::io::stdout()),
+                to_stdout: true,
+            }),
+        }
+    }
+
+    pub fn emit(&mut self, filepath: &Path, trace: &TraceEntry) -> Result<(), TraceError> {
+        let record = EmittedTrace {
+            filepath: SlashPathBuf::from(filepath).to_string(),
+            trace,
+        };
+        let line =
+            serde_json::to_string(&record).map_err(|err| TraceError::Emit(err.to_string()))?;
+
+        writeln!(self.out, "{line}").map_err(|err| TraceError::Emit(err.to_string()))
+    }
+
+    pub fn flush(&mut self) -> Result<(), TraceError> {
+        self.out
+            .flush()
+            .map_err(|err| TraceError::Emit(err.to_string()))
+    }
+}
+
+/// Collects traces of all given trace kinds.
+///
+/// Without database, traces are not stored, and traces of deprecated requirements are not detected.
+pub async fn collect(
+    db: Option<&MantraDb>,
+    kinds: &[TraceKind],
+    options: &mut CollectOptions<'_>,
+) -> Result<(), TraceError> {
+    if let Some(db) = db {
+        options.deprecated_reqs = db.deprecated_req_ids().await.map_err(TraceError::DbError)?;
+        // traces of deprecated requirements with acknowledged validation errors are intentional
+        for id in db.suppressed_req_ids().await.map_err(TraceError::DbError)? {
+            options.deprecated_reqs.remove(&id);
+        }
+    }
+
+    let db = db.filter(|_| options.store);
+    // commit traces of all git sources are needed to decide which stored commit traces are stale
+    let last_git = kinds
+        .iter()
+        .rposition(|kind| matches!(kind, TraceKind::FromGit(_)));
+    let mut skipped_git = false;
+
+    for (index, kind) in kinds.iter().enumerate() {
+        if let Some(db) = db {
+            let step = format!("traces[{index}]");
+            let progress = if options.resume {
+                db.collect_progress(&step)
+                    .await
+                    .map_err(TraceError::DbError)?
+            } else {
+                CollectProgress::default()
+            };
+
+            if progress.completed {
+                log::info!("Trace source {} was already collected.", index + 1);
+                // commit traces of the skipped source are unknown, so no stored commit trace is stale
+                skipped_git |= matches!(kind, TraceKind::FromGit(_));
+                continue;
+            }
+
+            options.step = Some((step, progress));
+        }
+
+        options.remove_stale_commits = last_git == Some(index) && !skipped_git;
+
+        let _timer = crate::profile::phase(match kind {
+            TraceKind::FromSource(_) => "source",
+            TraceKind::FromSchema { .. } => "schema",
+            TraceKind::FromBinary(_) => "binary",
+            TraceKind::FromGit(_) => "git",
+        });
+
         let trace_changes = match kind {
-            TraceKind::FromSource(source_cfg) => trace_from_source(db, source_cfg).await,
+            TraceKind::FromSource(source_cfg) => trace_from_source(db, source_cfg, options).await,
             TraceKind::FromSchema { files } => {
                 let mut changes = TraceChanges::default();
 
                 for file in files {
-                    changes.merge(&mut trace_from_schema_file(db, file).await?);
+                    changes.merge(&mut trace_from_schema_file(db, file, options).await?);
                 }
 
                 Ok(changes)
             }
+            TraceKind::FromBinary(binary_cfg) => trace_from_binary(db, binary_cfg, options).await,
+            TraceKind::FromGit(git_cfg) => trace_from_git(db, git_cfg, options).await,
         }?;
-        println!("{trace_changes}");
+
+        if let Some(((step, _), db)) = options.step.take().zip(db) {
+            db.complete_collect_step(&step, trace_changes.new_generation)
+                .await
+                .map_err(TraceError::DbError)?;
+        }
+
+        if db.is_some() {
+            if options.emits_to_stdout() {
+                eprintln!("{trace_changes}");
+            } else {
+                println!("{trace_changes}");
+            }
+        }
+    }
+
+    if let Some(emitter) = &mut options.emitter {
+        emitter.flush()?;
     }
 
     Ok(())
 }
 
 pub async fn trace_from_schema_file(
-    db: &MantraDb,
+    db: Option<&MantraDb>,
     filepath: &Path,
+    options: &mut CollectOptions<'_>,
 ) -> Result<TraceChanges, TraceError> {
     let content = tokio::fs::read_to_string(filepath)
         .await
         .map_err(|_| TraceError::CouldNotAccessFile(filepath.to_string_lossy().to_string()))?;
     let schema = serde_json::from_str::<TraceSchema>(&content).map_err(TraceError::Deserialize)?;
 
-    trace_from_schema(db, &schema).await
+    trace_from_schema(db, &schema, options).await
 }
 
 pub async fn trace_from_schema(
-    db: &MantraDb,
+    db: Option<&MantraDb>,
     schema: &TraceSchema,
+    options: &mut CollectOptions<'_>,
 ) -> Result<TraceChanges, TraceError> {
-    let old_generation = db.max_trace_generation().await;
-    let new_generation = old_generation + 1;
+    let new_generation = options.new_generation(db, false).await;
 
     let mut changes = TraceChanges {
         new_generation,
@@ -91,10 +420,49 @@ pub async fn trace_from_schema(
     };
 
     for file_traces in &schema.traces {
-        let mut trace_changes = db
-            .add_traces(&file_traces.filepath, &file_traces.traces, new_generation)
-            .await
-            .map_err(TraceError::DbError)?;
+        let mut trace_changes = store_traces(
+            db,
+            &file_traces.filepath,
+            &file_traces.traces,
+            new_generation,
+            false,
+            options,
+        )
+        .await?;
+
+        changes.merge(&mut trace_changes);
+    }
+
+    Ok(changes)
+}
+
+/// Collects traces for symbols of a binary that are mapped to requirements.
+///
+/// The trace location is taken from debug information if available.
+/// Otherwise, the binary itself is used as trace location.
+pub async fn trace_from_binary(
+    db: Option<&MantraDb>,
+    cfg: &BinaryConfig,
+    options: &mut CollectOptions<'_>,
+) -> Result<TraceChanges, TraceError> {
+    let map_content = tokio::fs::read_to_string(&cfg.symbol_map)
+        .await
+        .map_err(|_| TraceError::CouldNotAccessFile(cfg.symbol_map.display().to_string()))?;
+    let symbol_map: HashMap<String, Vec<ReqId>> =
+        toml::from_str(&map_content).map_err(TraceError::DeserializeToml)?;
+
+    let file_traces = traces_from_binary(&cfg.binary, &symbol_map, cfg.base_path.as_deref())?;
+
+    let new_generation = options.new_generation(db, false).await;
+
+    let mut changes = TraceChanges {
+        new_generation,
+        ..Default::default()
+    };
+
+    for (filepath, traces) in file_traces {
+        let mut trace_changes =
+            store_traces(db, &filepath, &traces, new_generation, false, options).await?;
 
         changes.merge(&mut trace_changes);
     }
@@ -102,12 +470,256 @@ pub async fn trace_from_schema(
     Ok(changes)
 }
 
+fn traces_from_binary(
+    binary: &Path,
+    symbol_map: &HashMap<String, Vec<ReqId>>,
+    base_path: Option<&Path>,
+) -> Result<HashMap<PathBuf, Vec<TraceEntry>>, TraceError> {
+    let data = std::fs::read(binary)
+        .map_err(|_| TraceError::CouldNotAccessFile(binary.display().to_string()))?;
+    let obj = object::File::parse(data.as_slice())
+        .map_err(|err| TraceError::Binary(binary.display().to_string(), err.to_string()))?;
+
+    // debug information is optional
+    let loader = addr2line::Loader::new(binary).ok();
+
+    let mut found_symbols = HashSet::new();
+    let mut file_traces: HashMap<PathBuf, Vec<TraceEntry>> = HashMap::new();
+
+    for symbol in obj.symbols().chain(obj.dynamic_symbols()) {
+        if symbol.is_undefined() {
+            continue;
+        }
+
+        let Ok(name) = symbol.name() else {
+            continue;
+        };
+        let Some(ids) = symbol_map.get(name) else {
+            continue;
+        };
+
+        if !found_symbols.insert(name) {
+            continue;
+        }
+
+        let location = loader.as_ref().and_then(|loader| {
+            let location = loader.find_location(symbol.address()).ok()??;
+            Some((PathBuf::from(location.file?), location.line?))
+        });
+
+        let (filepath, line) = match location {
+            Some((filepath, line)) => {
+                let filepath = match base_path {
+                    Some(base) => filepath
+                        .strip_prefix(base)
+                        .map(|p| p.to_path_buf())
+                        .unwrap_or(filepath),
+                    None => filepath,
+                };
+                (filepath, line)
+            }
+            None => (binary.to_path_buf(), 0),
+        };
+
+        file_traces.entry(filepath).or_default().push(TraceEntry {
+            ids: ids.clone(),
+            line,
+            line_span: None,
+            item_name: Some(name.to_string()),
+            item_path: None,
+            test_name: None,
+        });
+    }
+
+    for symbol in symbol_map.keys() {
+        if !found_symbols.contains(symbol.as_str()) {
+            log::warn!(
+                "Symbol '{}' not found in binary '{}'.",
+                symbol,
+                binary.display()
+            );
+        }
+    }
+
+    Ok(file_traces)
+}
+
+/// Separates fields of one commit in the `git log` output.
+const GIT_FIELD_SEPARATOR: char = '\x1f';
+/// Separates commits in the `git log` output.
+const GIT_COMMIT_SEPARATOR: char = '\x1e';
+
+/// Commit that references requirements in its message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitTrace {
+    pub ids: Vec<ReqId>,
+    pub sha: String,
+    pub summary: String,
+    /// Commit date in ISO8601 format.
+    pub date: String,
+}
+
+pub async fn trace_from_git(
+    db: Option<&MantraDb>,
+    cfg: &GitConfig,
+    options: &mut CollectOptions<'_>,
+) -> Result<TraceChanges, TraceError> {
+    let mut cmd = tokio::process::Command::new("git");
+    if let Some(repository) = &cfg.repository {
+        cmd.arg("-C").arg(repository);
+    }
+
+    let output = cmd
+        .args([
+            "log",
+            "--format=%H%x1f%aI%x1f%s%x1f%b%x1e",
+            &cfg.commits,
+            "--",
+        ])
+        .output()
+        .await
+        .map_err(|err| TraceError::Git(cfg.commits.clone(), err.to_string()))?;
+
+    if !output.status.success() {
+        return Err(TraceError::Git(
+            cfg.commits.clone(),
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    let log = String::from_utf8_lossy(&output.stdout);
+    store_commit_traces(db, commit_traces_from_log(&log), options).await
+}
+
+/// Stores the given commit traces, and adds them to the commit traces found so far.
+///
+/// If set in the options, stored commit traces of the branch that were not found so far are removed.
+async fn store_commit_traces(
+    db: Option<&MantraDb>,
+    commit_traces: Vec<CommitTrace>,
+    options: &mut CollectOptions<'_>,
+) -> Result<TraceChanges, TraceError> {
+    let mut changes = TraceChanges::default();
+
+    for mut commit in commit_traces {
+        if !options.id_case.is_preserve() {
+            commit.ids = normalized_ids(&commit.ids, options.id_case);
+        }
+
+        if let Some(pattern) = options.id_pattern {
+            for id in &commit.ids {
+                pattern
+                    .check(id, format!("commit {}", commit.sha))
+                    .map_err(TraceError::IdMismatch)?;
+            }
+        }
+
+        let Some(db) = db else {
+            continue;
+        };
+
+        for id in &commit.ids {
+            let status = db
+                .add_commit_trace(id, &commit.sha, &commit.summary, &commit.date)
+                .await
+                .map_err(TraceError::DbError)?;
+            let pk = CommitTracePk {
+                req_id: id.clone(),
+                sha: commit.sha.clone(),
+            };
+
+            match status {
+                CommitTraceStatus::Inserted => {
+                    options.commit_traces.push(pk.clone());
+                    changes.inserted_commits.push(pk);
+                }
+                CommitTraceStatus::Unchanged => {
+                    options.commit_traces.push(pk);
+                    changes.unchanged_cnt += 1;
+                }
+                CommitTraceStatus::Unrelated => changes.unrelated_commits.push(pk),
+            }
+        }
+    }
+
+    if let Some(db) = db.filter(|_| options.remove_stale_commits) {
+        changes.removed_commits = db
+            .delete_stale_commit_traces(&options.commit_traces)
+            .await
+            .map_err(TraceError::DbError)?;
+    }
+
+    Ok(changes)
+}
+
+/// Extracts commits referencing requirements from `git log` output.
+///
+/// Requirements may be referenced in commit messages using `[req(<ids>)]` or `[req:<ids>]`.
+fn commit_traces_from_log(log: &str) -> Vec<CommitTrace> {
+    let matcher = commit_trace_matcher();
+    let mut commit_traces = Vec::new();
+
+    for commit in log.split(GIT_COMMIT_SEPARATOR) {
+        let mut fields = commit.trim_start().splitn(4, GIT_FIELD_SEPARATOR);
+        let (Some(sha), Some(date), Some(summary)) = (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let body = fields.next().unwrap_or_default();
+
+        let mut ids: Vec<ReqId> = Vec::new();
+        for capture in matcher
+            .captures_iter(summary)
+            .chain(matcher.captures_iter(body))
+        {
+            let Some(raw_ids) = capture.name("ids").or(capture.name("colon_ids")) else {
+                continue;
+            };
+
+            match mantra_lang_tracing::extract::extract_req_ids_from_str(raw_ids.as_str()) {
+                Ok(found_ids) => {
+                    for id in found_ids {
+                        if !ids.contains(&id) {
+                            ids.push(id);
+                        }
+                    }
+                }
+                Err(err) => log::warn!("Invalid requirement IDs in commit '{sha}'. Cause: {err}"),
+            }
+        }
+
+        if !ids.is_empty() {
+            commit_traces.push(CommitTrace {
+                ids,
+                sha: sha.to_string(),
+                summary: summary.to_string(),
+                date: date.to_string(),
+            });
+        }
+    }
+
+    commit_traces
+}
+
+static COMMIT_TRACE_MATCHER: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+
+fn commit_trace_matcher() -> &'static regex::Regex {
+    COMMIT_TRACE_MATCHER.get_or_init(|| {
+        regex::Regex::new(
+            r"\[(?:req|requirements)(?:\((?<ids>[^\)]+)\)|:\s*(?<colon_ids>[^\]]+))\]",
+        )
+        .expect("Regex to match requirement traces in commit messages could **not** be created.")
+    })
+}
+
 pub async fn trace_from_source(
-    db: &MantraDb,
+    db: Option<&MantraDb>,
     cfg: &SourceConfig,
+    options: &mut CollectOptions<'_>,
 ) -> Result<TraceChanges, TraceError> {
-    let old_generation = db.max_trace_generation().await;
-    let new_generation = old_generation + 1;
+    let new_generation = options
+        .new_generation(db, options.changed_files.is_some())
+        .await;
 
     let mut changes = TraceChanges {
         new_generation,
@@ -139,22 +751,38 @@ pub async fn trace_from_source(
         Some(lsif_graphs)
     };
 
-    if cfg.root.is_dir() || cfg.root == PathBuf::from("") || cfg.root == PathBuf::from("./") {
-        let root = if cfg.root == PathBuf::from("") || cfg.root == PathBuf::from("./") {
-            std::env::current_dir().expect("Current directory must be valid.")
-        } else {
-            cfg.root.clone()
-        };
+    if let Some(changed_files) = options.changed_files.clone() {
+        for (abs_filepath, filepath) in changed_source_files(cfg, &changed_files) {
+            if options.walk_limits.exceeds_file_size(&abs_filepath) {
+                continue;
+            }
 
-        let walk = WalkBuilder::new(&root)
-            .types(
-                TypesBuilder::new()
-                    .add_defaults()
-                    .select("all")
-                    .build()
-                    .expect("Could not create file filter."),
-            )
-            .build();
+            // traces of deleted files are removed by storing no traces
+            let mut traces = if abs_filepath.is_file() {
+                collect_traces(&abs_filepath, filepath.clone().into(), &lsif_graphs)?
+                    .unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+
+            if cfg.canonical_item_paths {
+                resolve_item_paths(&mut traces, &filepath, lsif_graphs.as_deref());
+            }
+            if !cfg.declared_coverage {
+                traces.iter_mut().for_each(|trace| trace.test_name = None);
+            }
+
+            let mut trace_changes =
+                store_traces(db, &filepath, &traces, new_generation, true, options).await?;
+
+            changes.merge(&mut trace_changes);
+        }
+
+        Ok(changes)
+    } else if let Some(root) = source_root_dir(&cfg.root) {
+        let walk = source_walk(&root);
+        let progress = crate::progress::spinner("Files processed");
+        let mut file_cnt = 0;
 
         for dir_entry_res in walk {
             let dir_entry = match dir_entry_res {
@@ -167,6 +795,23 @@ pub async fn trace_from_source(
                 .expect("No file type found for given entry. Note: stdin is not supported.")
                 .is_file()
             {
+                progress.inc(1);
+                file_cnt += 1;
+
+                if let Some(max) = options.walk_limits.max_files {
+                    if file_cnt > max {
+                        progress.finish_and_clear();
+                        return Err(TraceError::TooManyFiles {
+                            root: root.display().to_string(),
+                            max,
+                        });
+                    }
+                }
+
+                if options.walk_limits.exceeds_file_size(dir_entry.path()) {
+                    continue;
+                }
+
                 let filepath = if cfg.keep_path_absolute {
                     dir_entry.clone().into_path()
                 } else {
@@ -174,21 +819,37 @@ pub async fn trace_from_source(
                         .unwrap_or(dir_entry.clone().into_path())
                 };
 
-                if let Some(traces) =
+                if options.is_collected(&filepath) {
+                    continue;
+                }
+
+                if let Some(mut traces) =
                     collect_traces(dir_entry.path(), filepath.clone().into(), &lsif_graphs)?
                 {
-                    let mut trace_changes = db
-                        .add_traces(&filepath, &traces, new_generation)
-                        .await
-                        .map_err(TraceError::DbError)?;
+                    if cfg.canonical_item_paths {
+                        resolve_item_paths(&mut traces, &filepath, lsif_graphs.as_deref());
+                    }
+                    if !cfg.declared_coverage {
+                        traces.iter_mut().for_each(|trace| trace.test_name = None);
+                    }
+
+                    let mut trace_changes =
+                        store_traces(db, &filepath, &traces, new_generation, false, options)
+                            .await?;
 
                     changes.merge(&mut trace_changes);
                 }
             }
         }
 
+        progress.finish_and_clear();
+
         Ok(changes)
     } else {
+        if options.walk_limits.exceeds_file_size(&cfg.root) {
+            return Ok(changes);
+        }
+
         let filepath = if cfg.keep_path_absolute {
             cfg.root.to_path_buf()
         } else {
@@ -196,57 +857,1119 @@ pub async fn trace_from_source(
                 .unwrap_or(cfg.root.to_path_buf())
         };
 
-        if let Some(traces) = collect_traces(&cfg.root, filepath.clone().into(), &lsif_graphs)? {
-            db.add_traces(&filepath, &traces, new_generation)
-                .await
-                .map_err(TraceError::DbError)
+        if let Some(mut traces) = collect_traces(&cfg.root, filepath.clone().into(), &lsif_graphs)?
+        {
+            if cfg.canonical_item_paths {
+                resolve_item_paths(&mut traces, &filepath, lsif_graphs.as_deref());
+            }
+            if !cfg.declared_coverage {
+                traces.iter_mut().for_each(|trace| trace.test_name = None);
+            }
+
+            store_traces(db, &filepath, &traces, new_generation, false, options).await
         } else {
             Ok(changes)
         }
     }
 }
 
+/// Returns the directory to walk for the given source root, or `None` if the root is a single file.
+fn source_root_dir(root: &Path) -> Option<PathBuf> {
+    if root == Path::new("") || root == Path::new("./") {
+        Some(std::env::current_dir().expect("Current directory must be valid."))
+    } else if root.is_dir() {
+        Some(root.to_path_buf())
+    } else {
+        None
+    }
+}
+
+/// Returns the changed files located below the root of the given source configuration.
+///
+/// Each file is returned as path to access the file, and as path the file's traces are stored with.
+/// Changed files that no longer exist are included, so their traces may be removed.
+fn changed_source_files(cfg: &SourceConfig, changed_files: &[PathBuf]) -> Vec<(PathBuf, PathBuf)> {
+    let current_dir = std::env::current_dir().expect("Current directory must be valid.");
+    let root = source_root_dir(&cfg.root).unwrap_or(cfg.root.to_path_buf());
+    let abs_root = current_dir.join(&root);
+
+    changed_files
+        .iter()
+        .filter_map(|changed_file| {
+            let relative = current_dir
+                .join(changed_file)
+                .strip_prefix(&abs_root)
+                .ok()?
+                .to_path_buf();
+            // same path as used when walking the source root
+            let abs_filepath = if relative == Path::new("") {
+                root.clone()
+            } else {
+                root.join(relative)
+            };
+
+            let filepath = if cfg.keep_path_absolute {
+                abs_filepath.clone()
+            } else {
+                mantra_lang_tracing::path::make_relative(&abs_filepath, &root)
+                    .unwrap_or(abs_filepath.clone())
+            };
+
+            Some((abs_filepath, filepath))
+        })
+        .collect()
+}
+
+/// Reads the changed files to re-trace from the given file.
+/// The file must contain one filepath per line, as printed by `git diff --name-only`.
+pub fn read_changed_files(filepath: &Path) -> Result<Vec<PathBuf>, TraceError> {
+    let content = std::fs::read_to_string(filepath)
+        .map_err(|_| TraceError::CouldNotAccessFile(filepath.display().to_string()))?;
+
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
+
+fn source_walk(root: &Path) -> ignore::Walk {
+    WalkBuilder::new(root)
+        .types(
+            TypesBuilder::new()
+                .add_defaults()
+                .select("all")
+                .build()
+                .expect("Could not create file filter."),
+        )
+        .build()
+}
+
+/// Collector used to collect traces of a file, chosen by the file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FileCollector {
+    Rust,
+    Swift,
+    Kotlin,
+    Php,
+    Python,
+    /// Traces of the form `[req(<ids>)]` are searched in the file content.
+    PlainText,
+    /// No traces are collected, because the file is not a text file.
+    Skipped,
+}
+
+impl FileCollector {
+    pub fn for_file(filepath: &Path) -> Self {
+        match filepath.extension().and_then(|ext| ext.to_str()) {
+            Some("rs") => Self::Rust,
+            Some("swift") => Self::Swift,
+            Some("kt" | "kts") => Self::Kotlin,
+            Some("php") => Self::Php,
+            Some("py") => Self::Python,
+            _ if mime_guess::from_path(filepath)
+                .first()
+                .is_some_and(|mime| mime.type_() == "text") =>
+            {
+                Self::PlainText
+            }
+            _ => Self::Skipped,
+        }
+    }
+}
+
+impl std::fmt::Display for FileCollector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            FileCollector::Rust => "rust",
+            FileCollector::Swift => "swift",
+            FileCollector::Kotlin => "kotlin",
+            FileCollector::Php => "php",
+            FileCollector::Python => "python",
+            FileCollector::PlainText => "plain text",
+            FileCollector::Skipped => "skipped",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+/// Prints the collector chosen for every file of the configured source roots, without collecting any traces.
+/// A summary of the number of files per collector is printed at the end.
+pub fn explain(kinds: &[TraceKind], walk_limits: &WalkLimits) -> Result<(), TraceError> {
+    let mut files_per_collector: BTreeMap<FileCollector, usize> = BTreeMap::new();
+    let mut explain_file = |filepath: &Path| {
+        let collector = FileCollector::for_file(filepath);
+
+        match (collector, filepath.extension()) {
+            (FileCollector::Skipped, Some(ext)) => println!(
+                "{}: skipped: no collector for extension `.{}`",
+                filepath.display(),
+                ext.to_string_lossy()
+            ),
+            (FileCollector::Skipped, None) => println!(
+                "{}: skipped: no collector for files without extension",
+                filepath.display()
+            ),
+            _ if walk_limits.exceeds_file_size(filepath) => {
+                println!("{}: skipped: exceeds maximum file size", filepath.display());
+            }
+            _ => println!("{}: {}", filepath.display(), collector),
+        }
+
+        *files_per_collector.entry(collector).or_default() += 1;
+    };
+
+    for kind in kinds {
+        let TraceKind::FromSource(source_cfg) = kind else {
+            continue;
+        };
+
+        match source_root_dir(&source_cfg.root) {
+            Some(root) => {
+                let files = source_walk(&root)
+                    .flatten()
+                    .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()));
+
+                for (file_cnt, entry) in files.enumerate() {
+                    if walk_limits.max_files.is_some_and(|max| file_cnt >= max) {
+                        return Err(TraceError::TooManyFiles {
+                            root: root.display().to_string(),
+                            max: walk_limits.max_files.unwrap_or_default(),
+                        });
+                    }
+
+                    explain_file(entry.path());
+                }
+            }
+            None => explain_file(&source_cfg.root),
+        }
+    }
+
+    println!("\nFiles per collector:");
+    for (collector, cnt) in files_per_collector {
+        println!("  {collector}: {cnt}");
+    }
+
+    Ok(())
+}
+
+/// Prints all traces found in one file using the collector of the file's language.
+pub fn list(cfg: &TraceListConfig) -> Result<(), TraceError> {
+    let traces = collect_traces(&cfg.file, cfg.file.clone().into(), &None)?.unwrap_or_default();
+
+    match cfg.format {
+        TraceListFormat::Text => {
+            if traces.is_empty() {
+                println!("No traces found in '{}'.", cfg.file.display());
+            }
+
+            for trace in &traces {
+                match &trace.item_name {
+                    Some(item) => println!("{trace} for item '{item}'"),
+                    None => println!("{trace}"),
+                }
+            }
+        }
+        TraceListFormat::Json => {
+            let file_traces = FileTraces {
+                filepath: cfg.file.clone(),
+                traces,
+            };
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&file_traces).map_err(TraceError::Serialize)?
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct CheckTracesConfig {
+    /// Files to check.
+    /// Filepaths are read from stdin, one per line, if not set.
+    #[arg(long, value_delimiter = ',')]
+    pub files: Vec<PathBuf>,
+    /// JSON file adhering to the `RequirementSchema` that is used instead of the requirements in the database.
+    #[arg(long)]
+    pub requirements: Option<PathBuf>,
+    /// Configuration file the case of IDs is taken from.
+    /// Default is `mantra.toml`, and IDs are kept as written if the file cannot be read.
+    #[arg(long = "mantra-config")]
+    pub mantra_config: Option<PathBuf>,
+}
+
+/// Checks that all traces in the given files refer to known requirements.
+/// Offending traces are printed as `<file>:<line>: <id>`.
+///
+/// Only the given files are parsed, and nothing is stored in the database.
+/// The database is only needed if no requirements file is set.
+/// Files that do not exist (e.g. staged deletions) are skipped.
+pub async fn check_traces(
+    db: Option<&MantraDb>,
+    cfg: &CheckTracesConfig,
+) -> Result<(), TraceError> {
+    let files = if cfg.files.is_empty() {
+        let mut input = String::new();
+        std::io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|_| TraceError::CouldNotAccessFile("stdin".to_string()))?;
+        input
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(PathBuf::from)
+            .collect()
+    } else {
+        cfg.files.clone()
+    };
+
+    let config_path = cfg
+        .mantra_config
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("mantra.toml"));
+    let id_case = tokio::fs::read_to_string(&config_path)
+        .await
+        .ok()
+        .and_then(|content| toml::from_str::<crate::cfg::MantraConfigFile>(&content).ok())
+        .map(|mantra_cfg| mantra_cfg.id_case)
+        .unwrap_or_default();
+
+    let known_ids: HashSet<ReqId> =
+        match (&cfg.requirements, db) {
+            (Some(req_file), _) => {
+                let content = tokio::fs::read_to_string(req_file)
+                    .await
+                    .map_err(|_| TraceError::CouldNotAccessFile(req_file.display().to_string()))?;
+                serde_json::from_str::<mantra_schema::requirements::RequirementSchema>(&content)
+                    .map_err(TraceError::Deserialize)?
+                    .requirements
+                    .into_iter()
+                    .map(|req| id_case.normalize(&req.id))
+                    .collect()
+            }
+            (None, None) => return Err(TraceError::DbError(crate::db::DbError::Connect(
+                "Known requirements are taken from the database if no requirements file is set."
+                    .to_string(),
+            ))),
+            (None, Some(db)) => sqlx::query!("select id from Requirements")
+                .fetch_all(db.pool())
+                .await
+                .map_err(|err| TraceError::DbError(crate::db::DbError::Query(err.to_string())))?
+                .into_iter()
+                .map(|record| record.id)
+                .collect(),
+        };
+
+    let mut unknown_cnt = 0;
+
+    for file in files {
+        if !file.exists() {
+            log::info!("File '{}' does not exist and is skipped.", file.display());
+            continue;
+        }
+
+        let traces = collect_traces(&file, file.clone().into(), &None)?.unwrap_or_default();
+
+        for trace in traces {
+            let ids = normalized_ids(&trace.ids, id_case);
+
+            for id in ids.iter().filter(|id| !known_ids.contains(*id)) {
+                println!("{}:{}: {}", file.display(), trace.line, id);
+                unknown_cnt += 1;
+            }
+        }
+    }
+
+    if unknown_cnt == 0 {
+        Ok(())
+    } else {
+        Err(TraceError::UnknownRequirements(unknown_cnt))
+    }
+}
+
+/// Sets the canonical item path for traces whose span starts at an item definition in the LSIF data.
+fn resolve_item_paths(
+    traces: &mut [TraceEntry],
+    filepath: &Path,
+    lsif_graphs: Option<&[LsifGraph]>,
+) {
+    let Some(graphs) = lsif_graphs else {
+        return;
+    };
+    let filepath = SlashPathBuf::from(filepath).to_string();
+
+    for trace in traces {
+        let Some(span) = trace.line_span else {
+            continue;
+        };
+
+        // "-1" because lsif starts with line nr 0
+        trace.item_path = graphs.iter().find_map(|graph| {
+            graph.get_definition_identifier(&filepath, span.start.saturating_sub(1))
+        });
+    }
+}
+
+/// Returns the given IDs normalized to the given case.
+/// IDs that only differed in case are kept once.
+fn normalized_ids(ids: &[ReqId], id_case: IdCase) -> Vec<ReqId> {
+    let mut normalized: Vec<ReqId> = Vec::with_capacity(ids.len());
+
+    for id in ids {
+        let id = id_case.normalize(id);
+
+        if !normalized.contains(&id) {
+            normalized.push(id);
+        }
+    }
+
+    normalized
+}
+
+/// Validates, emits, and stores the traces found in one file.
+///
+/// Stored traces of the file that are not part of the given traces are removed if `replace` is set.
+async fn store_traces(
+    db: Option<&MantraDb>,
+    filepath: &Path,
+    traces: &[TraceEntry],
+    new_generation: i64,
+    replace: bool,
+    options: &mut CollectOptions<'_>,
+) -> Result<TraceChanges, TraceError> {
+    let normalized_traces: Vec<TraceEntry>;
+    let traces = if options.id_case.is_preserve() {
+        traces
+    } else {
+        normalized_traces = traces
+            .iter()
+            .map(|trace| TraceEntry {
+                ids: normalized_ids(&trace.ids, options.id_case),
+                ..trace.clone()
+            })
+            .collect();
+        &normalized_traces
+    };
+
+    if let Some(pattern) = options.id_pattern {
+        for trace in traces {
+            for id in &trace.ids {
+                pattern
+                    .check(id, format!("{}:{}", filepath.display(), trace.line))
+                    .map_err(TraceError::IdMismatch)?;
+            }
+        }
+    }
+
+    for trace in traces {
+        for id in trace
+            .ids
+            .iter()
+            .filter(|id| options.deprecated_reqs.contains(*id))
+        {
+            let location = format!("{}:{}", filepath.display(), trace.line);
+
+            if options.strict {
+                return Err(TraceError::DeprecatedReqTraced(location, id.clone()));
+            }
+
+            log::warn!("Trace at '{location}' references deprecated requirement `{id}`.");
+        }
+    }
+
+    if let Some(emitter) = &mut options.emitter {
+        for trace in traces {
+            emitter.emit(filepath, trace)?;
+        }
+    }
+
+    if let Some(db) = db {
+        let step = options.step.as_ref().map(|(step, _)| step.as_str());
+
+        // stale traces must be removed first, because unrelated traces of the file are added again when storing
+        let removed = if replace {
+            db.delete_stale_file_traces(filepath, traces)
+                .await
+                .map_err(TraceError::DbError)?
+        } else {
+            Vec::new()
+        };
+
+        let mut changes = db
+            .add_file_traces(filepath, traces, new_generation, step)
+            .await
+            .map_err(TraceError::DbError)?;
+        changes.removed = removed;
+
+        Ok(changes)
+    } else {
+        Ok(TraceChanges {
+            new_generation,
+            ..Default::default()
+        })
+    }
+}
+
 fn collect_traces(
     abs_filepath: &Path,
     rel_filepath: SlashPathBuf,
     lsif_graphs: &Option<Vec<LsifGraph>>,
 ) -> Result<Option<Vec<TraceEntry>>, TraceError> {
-    let is_textfile = mime_guess::from_path(abs_filepath)
-        .first()
-        .map(|mime| mime.type_() == "text")
-        .unwrap_or(false);
+    let extension_str = abs_filepath
+        .extension()
+        .map(|osstr| osstr.to_str().unwrap_or_default());
+
+    let file_collector = FileCollector::for_file(abs_filepath);
 
-    if !is_textfile {
+    if file_collector == FileCollector::Skipped {
         // Traces are only collected from text files
         return Ok(None);
     }
 
+    let _timer = crate::profile::phase(match extension_str {
+        Some(extension) => format!("traces (.{extension})"),
+        None => "traces (no extension)".to_string(),
+    });
+
     let content = std::fs::read_to_string(abs_filepath)
         .map_err(|_| TraceError::CouldNotAccessFile(abs_filepath.to_string_lossy().to_string()))?;
 
-    let extension_str = abs_filepath
-        .extension()
-        .map(|osstr| osstr.to_str().unwrap_or_default());
+    // LSIF data is only used by the Rust collector
+    let (lang, collect_fn, lang_name): (_, AstCollectorFn<Option<Vec<LsifGraph>>>, &str) =
+        match file_collector {
+            FileCollector::Rust => (
+                tree_sitter_rust::language(),
+                Box::new(mantra_rust_trace::collect_traces_in_rust),
+                "Rust",
+            ),
+            FileCollector::Swift => (
+                tree_sitter_swift::language(),
+                Box::new(|node, src, filepath, _| {
+                    mantra_swift_trace::collect_traces_in_swift(node, src, filepath, &())
+                }),
+                "Swift",
+            ),
+            FileCollector::Kotlin => (
+                tree_sitter_kotlin::language(),
+                Box::new(|node, src, filepath, _| {
+                    mantra_kotlin_trace::collect_traces_in_kotlin(node, src, filepath, &())
+                }),
+                "Kotlin",
+            ),
+            FileCollector::Php => (
+                tree_sitter_php::language_php(),
+                Box::new(|node, src, filepath, _| {
+                    mantra_php_trace::collect_traces_in_php(node, src, filepath, &())
+                }),
+                "PHP",
+            ),
+            FileCollector::Python => (
+                tree_sitter_python::language(),
+                Box::new(|node, src, filepath, _| {
+                    mantra_python_trace::collect_traces_in_python(node, src, filepath, &())
+                }),
+                "Python",
+            ),
+            FileCollector::PlainText | FileCollector::Skipped => {
+                let mut collector = PlainCollector::new(&content);
+                return Ok(collector.collect(&()));
+            }
+        };
 
-    if extension_str == Some("rs") {
-        match AstCollector::new(
-            content.as_bytes(),
-            &tree_sitter_rust::language(),
-            rel_filepath.to_string(),
-            Box::new(mantra_rust_trace::collect_traces_in_rust),
-        ) {
-            Some(mut collector) => {
-                return Ok(collector.collect(lsif_graphs));
+    let ast_collector = AstCollector::new(
+        content.as_bytes(),
+        &lang,
+        rel_filepath.to_string(),
+        collect_fn,
+    );
+
+    match ast_collector {
+        Some(mut collector) => Ok(collector.collect(lsif_graphs)),
+        None => {
+            log::warn!(
+                "Failed parsing {} code. File content taken as plain text: {}",
+                lang_name,
+                abs_filepath.display()
+            );
+            let mut collector = PlainCollector::new(&content);
+            Ok(collector.collect(&()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use mantra_schema::{
+        requirements::RequirementSchema,
+        traces::{FileTraces, TraceEntry, TraceSchema},
+    };
+
+    use crate::{
+        cfg::IdCase,
+        db::CommitTracePk,
+        test_util::{req, test_db},
+    };
+
+    use super::{
+        check_traces, collect, commit_traces_from_log, store_commit_traces, trace_from_schema,
+        traces_from_binary, CheckTracesConfig, CollectOptions, CommitTrace, EmitFormat,
+        FileCollector, GitConfig, TraceEmitter, TraceError, TraceKind, WalkLimits,
+    };
+
+    #[test]
+    fn commit_traces_from_messages() {
+        let log = "abc\x1f2024-05-01T10:00:00+02:00\x1fImplements [req:first]\x1fAlso [req(second, first)]\n\x1e\ndef\x1f2024-05-02T10:00:00+02:00\x1fUnrelated change\x1f\x1e\n";
+
+        let commit_traces = commit_traces_from_log(log);
+
+        assert_eq!(commit_traces.len(), 1, "Commit without references traced.");
+        assert_eq!(commit_traces[0].sha, "abc", "Wrong commit traced.");
+        assert_eq!(
+            commit_traces[0].ids,
+            vec!["first".to_string(), "second".to_string()],
+            "Referenced IDs not extracted once per commit."
+        );
+    }
+
+    #[tokio::test]
+    async fn commit_traces_replaced() {
+        let db = test_db("commit_traces").await;
+        db.add_reqs(vec![req("first", &[]), req("second", &[])])
+            .await
+            .unwrap();
+
+        let commit = |sha: &str, ids: &[&str]| CommitTrace {
+            ids: ids.iter().map(|id| id.to_string()).collect(),
+            sha: sha.to_string(),
+            summary: "Implements requirements".to_string(),
+            date: "2024-05-01T10:00:00+02:00".to_string(),
+        };
+        let pk = |id: &str, sha: &str| CommitTracePk {
+            req_id: id.to_string(),
+            sha: sha.to_string(),
+        };
+
+        store_commit_traces(
+            Some(&db),
+            vec![
+                commit("abc", &["first", "unknown"]),
+                commit("def", &["second"]),
+            ],
+            &mut CollectOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        // commit "def" was rewritten to "ghi"
+        let changes = store_commit_traces(
+            Some(&db),
+            vec![
+                commit("abc", &["first", "unknown"]),
+                commit("ghi", &["second"]),
+            ],
+            &mut CollectOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(changes.unchanged_cnt, 1, "Stored commit trace not kept.");
+        assert_eq!(
+            changes.inserted_commits,
+            vec![pk("second", "ghi")],
+            "New commit trace not inserted."
+        );
+        assert_eq!(
+            changes.unrelated_commits,
+            vec![pk("unknown", "abc")],
+            "Unrelated commit trace not reported separately."
+        );
+        assert_eq!(
+            changes.removed_commits,
+            vec![pk("second", "def")],
+            "Commit trace of rewritten commit not removed."
+        );
+
+        let rows: Vec<(String, String)> =
+            sqlx::query_as("select req_id, sha from CommitTraces order by sha")
+                .fetch_all(db.pool())
+                .await
+                .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                ("first".to_string(), "abc".to_string()),
+                ("second".to_string(), "ghi".to_string())
+            ],
+            "Commit traces of the branch not replaced."
+        );
+    }
+
+    #[tokio::test]
+    async fn check_traces_without_db() {
+        let dir = std::env::temp_dir().join(format!("mantra_check_traces_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let req_file = dir.join("requirements.json");
+        let schema = mantra_schema::requirements::RequirementSchema {
+            version: None,
+            requirements: vec![req("Known", &[])],
+        };
+        std::fs::write(&req_file, serde_json::to_string(&schema).unwrap()).unwrap();
+        let config_file = dir.join("mantra.toml");
+        std::fs::write(&config_file, "id-case = \"lower\"\n").unwrap();
+        let traced_file = dir.join("lib.rs");
+
+        let cfg = |ids: &str| {
+            std::fs::write(&traced_file, format!("#[req({ids})]\nfn traced() {{}}\n")).unwrap();
+            CheckTracesConfig {
+                // deleted file of a staged deletion
+                files: vec![traced_file.clone(), dir.join("deleted.rs")],
+                requirements: Some(req_file.clone()),
+                mantra_config: Some(config_file.clone()),
             }
-            None => {
-                log::warn!(
-                    "Failed parsing Rust code. File content taken as plain text: {}",
-                    abs_filepath.display()
-                );
+        };
+
+        let known = check_traces(None, &cfg("KNOWN")).await;
+        let unknown = check_traces(None, &cfg("KNOWN, other")).await;
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(
+            known.is_ok(),
+            "Trace not matched in the configured ID case."
+        );
+        assert!(
+            matches!(unknown, Err(TraceError::UnknownRequirements(1))),
+            "Unknown requirement not detected."
+        );
+    }
+
+    #[tokio::test]
+    async fn commit_traces_of_multiple_git_sources() {
+        let db = test_db("git_sources").await;
+        db.add_reqs(vec![req("first", &[]), req("second", &[])])
+            .await
+            .unwrap();
+
+        let repo = std::env::temp_dir().join(format!("mantra_git_sources_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&repo);
+        std::fs::create_dir_all(&repo).unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(&repo)
+                .args(["-c", "user.name=mantra", "-c", "user.email=mantra@test"])
+                .args(args)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "Git command {args:?} failed.");
+        };
+        git(&["init", "-q"]);
+        git(&[
+            "commit",
+            "-q",
+            "--allow-empty",
+            "-m",
+            "Implements [req:first]",
+        ]);
+        git(&[
+            "commit",
+            "-q",
+            "--allow-empty",
+            "-m",
+            "Implements [req:second]",
+        ]);
+
+        let sources = ["HEAD~1", "HEAD~1..HEAD"].map(|commits| {
+            TraceKind::FromGit(GitConfig {
+                commits: commits.to_string(),
+                repository: Some(repo.clone()),
+            })
+        });
+
+        collect(Some(&db), &sources, &mut CollectOptions::default())
+            .await
+            .unwrap();
+        let recollected = collect(Some(&db), &sources, &mut CollectOptions::default()).await;
+
+        let ids: Vec<(String,)> = sqlx::query_as("select req_id from CommitTraces order by req_id")
+            .fetch_all(db.pool())
+            .await
+            .unwrap();
+        let _ = std::fs::remove_dir_all(&repo);
+
+        assert!(recollected.is_ok(), "Git sources not collected again.");
+        assert_eq!(
+            ids,
+            vec![("first".to_string(),), ("second".to_string(),)],
+            "Commit traces of another git source removed."
+        );
+    }
+
+    #[test]
+    fn collector_per_extension() {
+        for (file, collector) in [
+            ("src/lib.rs", FileCollector::Rust),
+            ("Sources/App.swift", FileCollector::Swift),
+            ("build.gradle.kts", FileCollector::Kotlin),
+            ("src/Auth/Session.php", FileCollector::Php),
+            ("tests/test_login.py", FileCollector::Python),
+            ("README.md", FileCollector::PlainText),
+            ("assets/logo.png", FileCollector::Skipped),
+        ] {
+            assert_eq!(
+                FileCollector::for_file(Path::new(file)),
+                collector,
+                "Wrong collector chosen for '{file}'."
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn mixed_case_trace_matches_requirement() {
+        let db = test_db("id_case").await;
+
+        let reqs: RequirementSchema = serde_json::from_str(
+            r#"{"requirements":[{"id":"Foo","title":"Foo","origin":"x","deprecated":false},{"id":"Foo.Bar","parents":["Foo"],"title":"Bar","origin":"x","deprecated":false}]}"#,
+        )
+        .unwrap();
+        let collected_reqs = crate::cmd::requirements::collect_from_schema(
+            &db,
+            reqs,
+            crate::cmd::requirements::CollectOptions {
+                id_case: IdCase::Lower,
+                ..Default::default()
+            },
+        )
+        .await;
+
+        let traces = TraceSchema {
+            version: None,
+            traces: vec![FileTraces {
+                filepath: "src/lib.rs".into(),
+                traces: vec![TraceEntry {
+                    ids: vec!["FOO.bar".to_string(), "foo.BAR".to_string()],
+                    line: 1,
+                    line_span: None,
+                    item_name: None,
+                    item_path: None,
+                    test_name: None,
+                }],
+            }],
+        };
+        let collected_traces = trace_from_schema(
+            Some(&db),
+            &traces,
+            &mut CollectOptions {
+                id_case: IdCase::Lower,
+                ..Default::default()
+            },
+        )
+        .await;
+
+        let traced = db.is_traced("foo.bar").await;
+        let parent_traced = db.is_traced("foo").await;
+
+        assert!(
+            collected_reqs.is_ok(),
+            "Normalized requirements not stored."
+        );
+        assert!(
+            collected_traces.is_ok(),
+            "Traces with IDs only differing in case not stored."
+        );
+        assert!(
+            traced.unwrap(),
+            "Mixed-case trace not matched to requirement."
+        );
+        assert!(
+            parent_traced.unwrap(),
+            "Parent ID of requirement not normalized."
+        );
+    }
+
+    #[tokio::test]
+    async fn resumed_collect_equals_uninterrupted() {
+        let dir = std::env::temp_dir().join(format!("mantra_resume_test_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("first")).unwrap();
+        std::fs::create_dir_all(dir.join("second")).unwrap();
+        std::fs::write(dir.join("first/a.md"), "[req(a)]").unwrap();
+        std::fs::write(dir.join("first/b.md"), "[req(b)]").unwrap();
+        std::fs::write(dir.join("second/c.md"), "[req(a, b)]").unwrap();
+
+        let kinds: Vec<TraceKind> = ["first", "second"]
+            .into_iter()
+            .map(|root| {
+                serde_json::from_value(serde_json::json!({ "root": dir.join(root) })).unwrap()
+            })
+            .collect();
+        let reqs: RequirementSchema = serde_json::from_str(
+            r#"{"requirements":[{"id":"a","title":"A","origin":"x","deprecated":false},{"id":"b","title":"B","origin":"x","deprecated":false}]}"#,
+        )
+        .unwrap();
+
+        let mut trace_rows = Vec::new();
+
+        for interrupted in [false, true] {
+            let db = test_db(&format!("resume_{interrupted}")).await;
+            crate::cmd::requirements::collect_from_schema(&db, reqs.clone(), Default::default())
+                .await
+                .unwrap();
+
+            if interrupted {
+                // the interrupted collect only stored traces of the first file
+                db.add_file_traces(
+                    Path::new("a.md"),
+                    &[TraceEntry {
+                        ids: vec!["a".to_string()],
+                        line: 1,
+                        line_span: None,
+                        item_name: None,
+                        item_path: None,
+                        test_name: None,
+                    }],
+                    1,
+                    Some("traces[0]"),
+                )
+                .await
+                .unwrap();
             }
+
+            collect(
+                Some(&db),
+                &kinds,
+                &mut CollectOptions {
+                    resume: interrupted,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+            let rows: Vec<(String, String, i64, i64)> = sqlx::query_as(
+                "select req_id, filepath, line, generation from Traces order by filepath, req_id",
+            )
+            .fetch_all(db.pool())
+            .await
+            .unwrap();
+            trace_rows.push(rows);
         }
+
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            trace_rows[0].len(),
+            4,
+            "Traces of uninterrupted collect missing."
+        );
+        assert_eq!(
+            trace_rows[0], trace_rows[1],
+            "Resumed collect differs from uninterrupted collect."
+        );
+    }
+
+    #[tokio::test]
+    async fn traces_emitted_without_db() {
+        let dir = std::env::temp_dir().join(format!("mantra_no_db_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.md"), "[req(a)]").unwrap();
+        let emit_path = dir.join("traces.ndjson");
+
+        let kinds: Vec<TraceKind> =
+            vec![serde_json::from_value(serde_json::json!({ "root": dir.join("a.md") })).unwrap()];
+
+        let collected = collect(
+            None,
+            &kinds,
+            &mut CollectOptions {
+                emitter: Some(TraceEmitter::new(EmitFormat::Ndjson, Some(&emit_path)).unwrap()),
+                store: true,
+                ..Default::default()
+            },
+        )
+        .await;
+        let emitted = std::fs::read_to_string(&emit_path).unwrap();
+
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(collected.is_ok(), "Traces not collected without database.");
+        assert_eq!(emitted.lines().count(), 1, "Collected trace not emitted.");
     }
 
-    let mut collector = PlainCollector::new(&content);
-    Ok(collector.collect(&()))
+    #[tokio::test]
+    async fn changed_files_retraced_in_current_generation() {
+        let dir =
+            std::env::temp_dir().join(format!("mantra_changed_files_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.md"), "[req(a)]").unwrap();
+        std::fs::write(dir.join("b.md"), "[req(b)]").unwrap();
+        std::fs::write(dir.join("c.md"), "[req(a)]").unwrap();
+
+        let kinds: Vec<TraceKind> =
+            vec![serde_json::from_value(serde_json::json!({ "root": dir })).unwrap()];
+        let reqs: RequirementSchema = serde_json::from_str(
+            r#"{"requirements":[{"id":"a","title":"A","origin":"x","deprecated":false},{"id":"b","title":"B","origin":"x","deprecated":false}]}"#,
+        )
+        .unwrap();
+
+        let db = test_db("changed_files").await;
+        crate::cmd::requirements::collect_from_schema(&db, reqs, Default::default())
+            .await
+            .unwrap();
+
+        collect(Some(&db), &kinds, &mut CollectOptions::default())
+            .await
+            .unwrap();
+
+        std::fs::write(dir.join("a.md"), "\n[req(b)]\n[req(unknown)]").unwrap();
+        std::fs::remove_file(dir.join("b.md")).unwrap();
+
+        collect(
+            Some(&db),
+            &kinds,
+            &mut CollectOptions {
+                changed_files: Some(vec![dir.join("a.md"), dir.join("b.md")]),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let rows: Vec<(String, String, i64, i64)> = sqlx::query_as(
+            "select req_id, filepath, line, generation from Traces order by filepath, req_id",
+        )
+        .fetch_all(db.pool())
+        .await
+        .unwrap();
+        let unrelated_rows: Vec<(String, String, i64)> =
+            sqlx::query_as("select req_id, filepath, line from UnrelatedTraces")
+                .fetch_all(db.pool())
+                .await
+                .unwrap();
+
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            rows,
+            vec![
+                ("b".to_string(), "a.md".to_string(), 2, 1),
+                ("a".to_string(), "c.md".to_string(), 1, 1),
+            ],
+            "Changed files not re-traced into the current generation."
+        );
+        assert_eq!(
+            unrelated_rows,
+            vec![("unknown".to_string(), "a.md".to_string(), 3)],
+            "Unrelated trace of changed file not kept."
+        );
+    }
+
+    #[tokio::test]
+    async fn walk_aborted_on_too_many_files() {
+        let dir =
+            std::env::temp_dir().join(format!("mantra_max_files_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for file in ["a.md", "b.md", "c.md"] {
+            std::fs::write(dir.join(file), "[req(a)]").unwrap();
+        }
+        let kinds: Vec<TraceKind> =
+            vec![serde_json::from_value(serde_json::json!({ "root": dir })).unwrap()];
+
+        let db = test_db("max_files").await;
+        let res = collect(
+            Some(&db),
+            &kinds,
+            &mut CollectOptions {
+                walk_limits: WalkLimits {
+                    max_files: Some(2),
+                    max_file_size: None,
+                },
+                ..Default::default()
+            },
+        )
+        .await;
+
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(
+            matches!(res, Err(TraceError::TooManyFiles { max: 2, .. })),
+            "Walk not aborted after the maximum number of files: {res:?}"
+        );
+    }
+
+    #[test]
+    fn file_size_limit() {
+        let filepath =
+            std::env::temp_dir().join(format!("mantra_max_file_size_test_{}", std::process::id()));
+        std::fs::write(&filepath, "0123456789").unwrap();
+
+        let exceeded: Vec<bool> = [None, Some(10), Some(9)]
+            .into_iter()
+            .map(|max_file_size| {
+                WalkLimits {
+                    max_files: None,
+                    max_file_size,
+                }
+                .exceeds_file_size(&filepath)
+            })
+            .collect();
+
+        let _ = std::fs::remove_file(&filepath);
+
+        assert_eq!(
+            exceeded,
+            vec![false, false, true],
+            "Only files larger than the maximum size must be skipped."
+        );
+    }
+
+    /// Symbol looked up in the test binary itself.
+    #[no_mangle]
+    extern "C" fn mantra_binary_trace_test_symbol() -> u32 {
+        42
+    }
+
+    #[test]
+    fn traces_from_test_binary() {
+        assert_eq!(
+            std::hint::black_box(mantra_binary_trace_test_symbol)(),
+            42,
+            "Test symbol not callable."
+        );
+
+        let binary = std::env::current_exe().unwrap();
+        let symbol_map = [
+            ("mantra_binary_trace_test_symbol", "binary_req"),
+            ("mantra_unknown_test_symbol", "unknown_req"),
+        ]
+        .into_iter()
+        .map(|(symbol, id)| (symbol.to_string(), vec![id.to_string()]))
+        .collect();
+
+        let file_traces = traces_from_binary(&binary, &symbol_map, None).unwrap();
+        let traces: Vec<&TraceEntry> = file_traces.values().flatten().collect();
+
+        assert_eq!(
+            traces.len(),
+            1,
+            "Missing symbol traced, or symbol not found."
+        );
+        assert_eq!(
+            traces[0].ids,
+            vec!["binary_req".to_string()],
+            "Requirement of symbol not traced."
+        );
+        assert_eq!(
+            traces[0].item_name.as_deref(),
+            Some("mantra_binary_trace_test_symbol"),
+            "Symbol name not set as item name."
+        );
+    }
 }
@@ -1,11 +1,14 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
 
 use mantra_lang_tracing::path::SlashPathBuf;
 use mantra_schema::{
     coverage::{TestRunPk, TestState},
-    requirements::{ReqId, Requirement},
+    requirements::{DesignLink, ReqId, Requirement},
     reviews::ReviewSchema,
-    traces::TraceEntry,
+    traces::{LineSpan, TraceEntry},
     Line,
 };
 use sqlx::Pool;
@@ -19,6 +22,7 @@ pub type DB = sqlx::sqlite::Sqlite;
 #[derive(Debug)]
 pub struct MantraDb {
     pool: Pool<DB>,
+    branch: String,
 }
 
 #[derive(
@@ -74,24 +78,152 @@ impl std::fmt::Display for DeletedTraces {
     }
 }
 
+/// Test run removed by the retention policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeletedTestRun {
+    pub name: String,
+    pub date: String,
+    pub branch: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeletedTestRuns(Vec<DeletedTestRun>);
+
+impl std::ops::Deref for DeletedTestRuns {
+    type Target = Vec<DeletedTestRun>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for DeletedTestRuns {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            writeln!(f, "No test run was deleted.")
+        } else {
+            writeln!(f, "'{}' test runs deleted:", self.len())?;
+            for test_run in &self.0 {
+                write!(f, "- name='{}', date='{}'", test_run.name, test_run.date)?;
+                if !test_run.branch.is_empty() {
+                    write!(f, ", branch='{}'", test_run.branch)?;
+                }
+                writeln!(f)?;
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// Span of an existing trace that changed since the last collection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceSpanChange {
+    pub trace: TracePk,
+    pub old_span: LineSpan,
+    pub new_span: LineSpan,
+    /// Name of the item that was previously linked to the trace.
+    pub old_item: Option<String>,
+    /// Name of the item that is now linked to the trace.
+    pub new_item: Option<String>,
+}
+
+impl TraceSpanChange {
+    /// `true` if the trace is now linked to a different item than before.
+    /// This may be the result of a moved or renamed item.
+    pub fn item_changed(&self) -> bool {
+        matches!((&self.old_item, &self.new_item), (Some(old), Some(new)) if old != new)
+    }
+}
+
+impl std::fmt::Display for TraceSpanChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} changed span from '{}:{}' to '{}:{}'",
+            self.trace,
+            self.old_span.start,
+            self.old_span.end,
+            self.new_span.start,
+            self.new_span.end
+        )?;
+
+        if self.item_changed() {
+            write!(
+                f,
+                " and item from '{}' to '{}'",
+                self.old_item.as_deref().unwrap_or_default(),
+                self.new_item.as_deref().unwrap_or_default()
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct TraceChanges {
     pub inserted: Vec<TracePk>,
+    pub inserted_commits: Vec<CommitTracePk>,
+    /// Commit traces referencing requirements that do not exist.
+    pub unrelated_commits: Vec<CommitTracePk>,
+    /// Traces of re-traced files that no longer exist.
+    pub removed: Vec<TracePk>,
+    /// Commit traces of the branch that are no longer found in the traced commits.
+    pub removed_commits: Vec<CommitTracePk>,
     pub unchanged_cnt: usize,
+    pub span_changes: Vec<TraceSpanChange>,
     pub new_generation: i64,
 }
 
 impl TraceChanges {
     pub fn merge(&mut self, other: &mut Self) {
         self.inserted.append(&mut other.inserted);
+        self.inserted_commits.append(&mut other.inserted_commits);
+        self.unrelated_commits.append(&mut other.unrelated_commits);
+        self.removed.append(&mut other.removed);
+        self.removed_commits.append(&mut other.removed_commits);
         self.unchanged_cnt += other.unchanged_cnt;
+        self.span_changes.append(&mut other.span_changes);
     }
 }
 
 impl std::fmt::Display for TraceChanges {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.inserted_commits.is_empty() {
+            writeln!(f, "'{}' commit traces added:", self.inserted_commits.len())?;
+            for trace in &self.inserted_commits {
+                writeln!(f, "- `{}`", trace)?;
+            }
+        }
+
+        if !self.unrelated_commits.is_empty() {
+            writeln!(
+                f,
+                "'{}' unrelated commit traces found:",
+                self.unrelated_commits.len()
+            )?;
+            for trace in &self.unrelated_commits {
+                writeln!(f, "- `{}`", trace)?;
+            }
+        }
+
+        if !self.removed_commits.is_empty() {
+            writeln!(
+                f,
+                "'{}' commit traces removed:",
+                self.removed_commits.len()
+            )?;
+            for trace in &self.removed_commits {
+                writeln!(f, "- `{}`", trace)?;
+            }
+        }
+
         if self.inserted.is_empty() {
-            if self.unchanged_cnt == 0 {
+            if self.unchanged_cnt == 0
+                && self.inserted_commits.is_empty()
+                && self.unrelated_commits.is_empty()
+            {
                 writeln!(f, "No traces found.")?;
             } else {
                 writeln!(f, "'{}' traces kept.", self.unchanged_cnt)?;
@@ -103,11 +235,68 @@ impl std::fmt::Display for TraceChanges {
             }
         }
 
+        if !self.removed.is_empty() {
+            writeln!(f, "'{}' traces removed:", self.removed.len())?;
+            for trace in &self.removed {
+                writeln!(f, "- `{}`", trace)?;
+            }
+        }
+
+        if !self.span_changes.is_empty() {
+            writeln!(f, "'{}' trace spans changed:", self.span_changes.len())?;
+            for change in &self.span_changes {
+                writeln!(f, "- {}", change)?;
+            }
+        }
+
         Ok(())
     }
 }
 
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Progress of one step of an interrupted collect.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CollectProgress {
+    /// Generation of the data added in the step.
+    /// `None` if the step was not started.
+    pub generation: Option<i64>,
+    /// Files whose traces were fully stored in the step.
+    pub collected_files: HashSet<String>,
+    /// `true` if all data of the step was collected.
+    pub completed: bool,
+}
+
+/// Trace and test status of a requirement recorded at the end of a collect.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RequirementStatus {
+    pub traced: bool,
+    pub covered: bool,
+    pub passed: bool,
+}
+
+/// Outcome of adding a commit trace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommitTraceStatus {
+    Inserted,
+    /// The commit trace was already stored.
+    Unchanged,
+    /// No requirement with the referenced ID exists.
+    Unrelated,
+}
+
+/// Primary key of a requirement referenced in a commit message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitTracePk {
+    pub req_id: ReqId,
+    pub sha: String,
+}
+
+impl std::fmt::Display for CommitTracePk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "id=`{}`, commit='{}'", self.req_id, self.sha)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct DeletedRequirements(Vec<Requirement>);
 
 impl std::ops::Deref for DeletedRequirements {
@@ -197,6 +386,36 @@ pub struct Config {
     /// Default is a SQLite file named `mantra.db` that is located in the current directory.
     #[arg(long, alias = "db-url", env = "MANTRA_DB")]
     pub url: Option<String>,
+    /// Maximum number of attempts to connect to the database.
+    #[arg(long = "db-connect-attempts", default_value_t = 3)]
+    pub connect_attempts: u32,
+    /// Delay in milliseconds before the first connection retry.
+    /// The delay is doubled for every further retry.
+    #[arg(long = "db-retry-delay", default_value_t = 100)]
+    pub retry_delay_ms: u64,
+    /// Branch that collected traces and test runs belong to.
+    /// Reports only consider data of this branch.
+    /// Default is the unnamed default branch.
+    #[arg(long, env = "MANTRA_BRANCH")]
+    pub branch: Option<String>,
+}
+
+/// Groups of tables that may be cleared separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum)]
+pub enum DbTable {
+    /// Requirements, their hierarchy, validation suppressions, and status history.
+    /// Clearing requirements also clears their traces, coverage, and verifications.
+    Requirements,
+    /// Traces and unrelated traces.
+    /// Clearing traces also clears coverage of these traces.
+    Traces,
+    /// Test runs and their tests.
+    /// Clearing tests also clears their coverage.
+    Tests,
+    /// Test coverage and unrelated test coverage.
+    Coverage,
+    /// Reviews and their verified requirements.
+    Reviews,
 }
 
 #[derive(Debug, Clone, thiserror::Error)]
@@ -215,39 +434,383 @@ pub enum DbError {
     Update(String),
     #[error("The database contains invalid data. Cause: {}", .0)]
     Validate(String),
+    #[error("Requirement `{}` does not exist.", .0)]
+    UnknownRequirement(ReqId),
     // #[error("{}", .0)]
     // ForeignKeyViolation(String),
 }
 
 static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!();
 
+/// Temporary database copy that is removed once it is no longer needed.
+#[derive(Debug)]
+pub struct DbSnapshot {
+    pub db: MantraDb,
+    path: PathBuf,
+}
+
+/// Path for a new temporary database file.
+fn snapshot_path() -> PathBuf {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    std::env::temp_dir().join(format!(
+        "mantra_snapshot_{}_{}.db",
+        std::process::id(),
+        nanos
+    ))
+}
+
+impl DbSnapshot {
+    /// Closes the snapshot database and removes its file.
+    pub async fn remove(self) {
+        self.db.pool.close().await;
+        let _ = tokio::fs::remove_file(&self.path).await;
+    }
+}
+
 impl MantraDb {
     pub async fn new(cfg: &Config) -> Result<Self, DbError> {
         let url = cfg
             .url
             .clone()
             .unwrap_or("sqlite://mantra.db?mode=rwc".to_string());
-        let pool = Pool::<DB>::connect(&url)
-            .await
-            .map_err(|err| DbError::Connect(err.to_string()))?;
+        let max_attempts = cfg.connect_attempts.max(1);
+        let mut delay = std::time::Duration::from_millis(cfg.retry_delay_ms);
+        let mut attempt = 1;
+
+        let pool = loop {
+            match Pool::<DB>::connect(&url).await {
+                Ok(pool) => break pool,
+                Err(err) if attempt < max_attempts => {
+                    log::warn!(
+                        "Connection attempt '{}' of '{}' to the database failed. Retrying in '{}ms'. Cause: {}",
+                        attempt,
+                        max_attempts,
+                        delay.as_millis(),
+                        err
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay = delay.saturating_mul(2);
+                    attempt += 1;
+                }
+                Err(err) => {
+                    return Err(DbError::Connect(format!(
+                        "Failed after '{}' attempts: {}",
+                        attempt, err
+                    )))
+                }
+            }
+        };
 
         MIGRATOR
             .run(&pool)
             .await
             .map_err(|err| DbError::Migrate(err.to_string()))?;
 
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            branch: cfg.branch.clone().unwrap_or_default(),
+        })
+    }
+
+    /// The branch new traces and test runs are added to.
+    /// An empty string is the default branch.
+    pub fn branch(&self) -> &str {
+        &self.branch
+    }
+
+    /// Writes a copy of the database content to the given file, replacing an existing file.
+    ///
+    /// The copy is serialized from the database content using `vacuum into`,
+    /// so databases that are not backed by a file (e.g. in-memory databases) are exported the same way.
+    pub async fn export(&self, path: &Path) -> Result<(), DbError> {
+        if path.exists() {
+            tokio::fs::remove_file(path)
+                .await
+                .map_err(|err| DbError::Query(err.to_string()))?;
+        }
+
+        let path_str = path.display().to_string();
+
+        sqlx::query!("vacuum into $1", path_str)
+            .execute(&self.pool)
+            .await
+            .map_err(|err| DbError::Query(err.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Creates a temporary copy of the database where all views are replaced by tables holding the view content.
+    ///
+    /// Queries on the copy do not recompute views, but the copy is not updated if new data is collected.
+    pub async fn materialized_snapshot(&self) -> Result<DbSnapshot, DbError> {
+        let path = snapshot_path();
+        let path_str = path.display().to_string();
+
+        sqlx::query!("vacuum into $1", path_str)
+            .execute(&self.pool)
+            .await
+            .map_err(|err| DbError::Query(err.to_string()))?;
+
+        let snapshot = match MantraDb::new(&Config {
+            url: Some(format!("sqlite://{}", path_str)),
+            connect_attempts: 1,
+            retry_delay_ms: 0,
+            branch: Some(self.branch.clone()),
+        })
+        .await
+        {
+            Ok(snapshot) => snapshot,
+            Err(err) => {
+                let _ = tokio::fs::remove_file(&path).await;
+                return Err(err);
+            }
+        };
+
+        let snapshot = DbSnapshot { db: snapshot, path };
+
+        match snapshot.db.materialize_views().await {
+            Ok(_) => Ok(snapshot),
+            Err(err) => {
+                snapshot.remove().await;
+                Err(err)
+            }
+        }
+    }
+
+    /// Replaces all views with tables holding the current view content.
+    ///
+    /// Views are materialized after the views they depend on, so every view is only computed once.
+    /// Views that cannot be computed are kept as views.
+    async fn materialize_views(&self) -> Result<(), DbError> {
+        let views = sqlx::query!(
+            r#"select name as "name!", sql as "sql!" from sqlite_master where type = 'view'"#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| DbError::Query(err.to_string()))?;
+
+        let mut pending: Vec<(String, HashSet<String>)> = views
+            .into_iter()
+            .map(|view| {
+                let identifiers = view
+                    .sql
+                    .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .filter(|ident| *ident != view.name)
+                    .map(|ident| ident.to_string())
+                    .collect();
+                (view.name, identifiers)
+            })
+            .collect();
+
+        // one connection is needed, because the pragma is set per connection
+        let mut conn = self
+            .pool
+            .acquire()
+            .await
+            .map_err(|err| DbError::Connect(err.to_string()))?;
+
+        // prevents validation of dependent views on rename
+        sqlx::query("pragma legacy_alter_table = on")
+            .execute(&mut *conn)
+            .await
+            .map_err(|err| DbError::Update(err.to_string()))?;
+
+        while !pending.is_empty() {
+            let ready_pos = pending
+                .iter()
+                .position(|(_, identifiers)| {
+                    !pending.iter().any(|(other, _)| identifiers.contains(other))
+                })
+                // cyclic name references can only be caused by shadowing names, so any order is fine
+                .unwrap_or_default();
+            let (view, _) = pending.remove(ready_pos);
+
+            let create = format!(
+                r#"create table "Materialized{0}" as select * from "{0}""#,
+                view
+            );
+
+            if let Err(err) = sqlx::raw_sql(&create).execute(&mut *conn).await {
+                log::debug!("View '{}' is not materialized. Cause: {}", view, err);
+                continue;
+            }
+
+            let replace = format!(
+                r#"drop view "{0}"; alter table "Materialized{0}" rename to "{0}";"#,
+                view
+            );
+
+            sqlx::raw_sql(&replace)
+                .execute(&mut *conn)
+                .await
+                .map_err(|err| {
+                    DbError::Update(format!(
+                        "Could not materialize view '{}'. Cause: {}",
+                        view, err
+                    ))
+                })?;
+        }
+
+        sqlx::query("pragma legacy_alter_table = off")
+            .execute(&mut *conn)
+            .await
+            .map_err(|err| DbError::Update(err.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Creates a temporary copy of the SQLite database file at the given path with all migrations applied.
+    ///
+    /// The copy considers data of the set branch, and the given file is not modified,
+    /// even if migrations must be applied to the copy.
+    pub async fn file_snapshot(&self, db_file: &Path) -> Result<DbSnapshot, DbError> {
+        let path = snapshot_path();
+
+        tokio::fs::copy(db_file, &path).await.map_err(|err| {
+            DbError::Connect(format!(
+                "Could not copy database file '{}'. Cause: {}",
+                db_file.display(),
+                err
+            ))
+        })?;
+
+        match MantraDb::new(&Config {
+            url: Some(format!("sqlite://{}", path.display())),
+            connect_attempts: 1,
+            retry_delay_ms: 0,
+            branch: Some(self.branch.clone()),
+        })
+        .await
+        {
+            Ok(db) => Ok(DbSnapshot { db, path }),
+            Err(err) => {
+                let _ = tokio::fs::remove_file(&path).await;
+                Err(err)
+            }
+        }
+    }
+
+    /// Adds all data of the SQLite database file at the given path that is not yet in this database.
+    ///
+    /// Entries with the same primary key are only taken once,
+    /// so for requirements with the same ID, the data already in this database is kept.
+    /// Tests and coverage of test runs that exist in both databases are combined.
+    /// The given file is not modified, even if migrations must be applied to it.
+    pub async fn merge_from(&self, db_file: &Path) -> Result<(), DbError> {
+        let input = self.file_snapshot(db_file).await?;
+        // the copy must be closed, so the attached file is not locked
+        input.db.pool.close().await;
+
+        let res = self.insert_from(&input.path).await;
+        input.remove().await;
+        res
+    }
+
+    async fn insert_from(&self, db_file: &Path) -> Result<(), DbError> {
+        // progress and status history describe collect runs of the input only
+        let tables = sqlx::query!(
+            r#"select name as "name!" from sqlite_master
+            where type = 'table' and name not like 'sqlite_%'
+                and name not in ('_sqlx_migrations', 'CollectProgress', 'StatusHistory')"#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| DbError::Query(err.to_string()))?;
+
+        // one connection is needed, because the attached database and the pragma are set per connection
+        let mut conn = self
+            .pool
+            .acquire()
+            .await
+            .map_err(|err| DbError::Connect(err.to_string()))?;
+
+        // tables are merged in any order, and both databases are consistent on their own
+        sqlx::query("pragma foreign_keys = off")
+            .execute(&mut *conn)
+            .await
+            .map_err(|err| DbError::Update(err.to_string()))?;
+        sqlx::query("attach database $1 as merged")
+            .bind(db_file.display().to_string())
+            .execute(&mut *conn)
+            .await
+            .map_err(|err| DbError::Connect(err.to_string()))?;
+
+        let mut res = Ok(());
+        for table in tables {
+            // both databases have the same migrations applied, so columns are in the same order
+            if let Err(err) = sqlx::query(&format!(
+                r#"insert or ignore into main."{0}" select * from merged."{0}""#,
+                table.name
+            ))
+            .execute(&mut *conn)
+            .await
+            {
+                res = Err(DbError::Insert(format!(
+                    "Merging table '{}' failed with error: {}",
+                    table.name, err
+                )));
+                break;
+            }
+        }
+
+        if res.is_ok() {
+            // test runs split across databases only count the tests of their part
+            res = sqlx::query(
+                "update TestRuns set nr_of_tests = max(nr_of_tests,
+                    (select count(*) from Tests t where t.test_run_name = TestRuns.name and t.test_run_date = TestRuns.date and t.branch = TestRuns.branch)
+                    + (select count(*) from SkippedTests s where s.test_run_name = TestRuns.name and s.test_run_date = TestRuns.date and s.branch = TestRuns.branch))",
+            )
+            .execute(&mut *conn)
+            .await
+            .map(|_| ())
+            .map_err(|err| DbError::Update(err.to_string()));
+        }
+
+        let _ = sqlx::query("detach database merged")
+            .execute(&mut *conn)
+            .await;
+        sqlx::query("pragma foreign_keys = on")
+            .execute(&mut *conn)
+            .await
+            .map_err(|err| DbError::Update(err.to_string()))?;
+
+        res
     }
 
-    pub async fn add_reqs(&self, reqs: Vec<Requirement>) -> Result<RequirementChanges, DbError> {
+    pub async fn add_reqs(
+        &self,
+        mut reqs: Vec<Requirement>,
+    ) -> Result<RequirementChanges, DbError> {
+        // requirements without manual marker that were not matched by manual rules are not manual
+        for req in &mut reqs {
+            req.manual.get_or_insert(false);
+        }
+
+        // cycles are checked before anything is written, so a rejected hierarchy leaves the database unchanged
+        let new_hierarchies = self.new_req_hierarchies(&reqs).await?;
+        let mut hierarchies = self.req_hierarchies().await?;
+        hierarchies.extend(new_hierarchies.iter().cloned());
+
+        if let Some(cycle) = find_hierarchy_cycle(&hierarchies) {
+            return Err(DbError::Validate(format!(
+                "Cyclic requirement hierarchy: {}",
+                cycle.join(" -> ")
+            )));
+        }
+
         let mut changes = RequirementChanges::default();
         let old_generation = self.max_req_generation().await;
         let new_generation = old_generation + 1;
         changes.new_generation = new_generation;
 
         for req in &reqs {
+            let manual = req.manual.unwrap_or_default();
+
             if let Ok(existing_record) = sqlx::query!(
-                "select id, title, origin, data, manual, deprecated from Requirements where id = $1",
+                "select id, title, origin, data, manual, deprecated, untraceable, priority, state, effort from Requirements where id = $1",
                 req.id
             )
             .fetch_one(&self.pool)
@@ -257,12 +820,20 @@ impl MantraDb {
                     id: existing_record.id,
                     title: existing_record.title,
                     origin: existing_record.origin,
+                    // parsed data compares equal regardless of key order and whitespace
                     data: existing_record.data.map(|a| {
-                        serde_json::to_value(a).expect("Requirement info must be valid JSON.")
+                        serde_json::from_str(&a).expect("Requirement data must be valid JSON.")
                     }),
-                    manual: existing_record.manual,
+                    manual: Some(existing_record.manual),
                     deprecated: existing_record.deprecated,
+                    untraceable: existing_record.untraceable,
                     parents: None,
+                    priority: existing_record
+                        .priority
+                        .and_then(|p| u8::try_from(p).ok()),
+                    state: existing_record.state,
+                    effort: existing_record.effort,
+                    design_links: self.design_links(&req.id).await?,
                 };
                 if req != &existing_req {
                     changes.updated.push(RequirementUpdate {
@@ -274,27 +845,37 @@ impl MantraDb {
                 }
 
                 let _ = sqlx::query!(
-                    "update Requirements set generation = $2, title = $3, origin = $4, data = $5, manual = $6, deprecated = $7 where id = $1",
+                    "update Requirements set generation = $2, title = $3, origin = $4, data = $5, manual = $6, deprecated = $7, priority = $8, state = $9, effort = $10, untraceable = $11 where id = $1",
                     req.id,
                     new_generation,
                     req.title,
                     req.origin,
                     req.data,
-                    req.manual,
+                    manual,
                     req.deprecated,
+                    req.priority,
+                    req.state,
+                    req.effort,
+                    req.untraceable,
                 )
                 .execute(&self.pool)
                 .await;
+
+                self.set_design_links(&req.id, &req.design_links).await?;
             } else {
                 let res = sqlx::query!(
-                    "insert into Requirements (id, generation, title, origin, data, manual, deprecated) values ($1, $2, $3, $4, $5, $6, $7)",
+                    "insert into Requirements (id, generation, title, origin, data, manual, deprecated, priority, state, effort, untraceable) values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
                     req.id,
                     new_generation,
                     req.title,
                     req.origin,
                     req.data,
-                    req.manual,
+                    manual,
                     req.deprecated,
+                    req.priority,
+                    req.state,
+                    req.effort,
+                    req.untraceable,
                 )
                 .execute(&self.pool)
                 .await;
@@ -306,67 +887,76 @@ impl MantraDb {
                         err
                     );
                 } else {
+                    self.set_design_links(&req.id, &req.design_links).await?;
                     changes.inserted.push(req.clone());
                 }
             }
         }
 
-        for req in &changes.inserted {
-            if let Some((parent, _)) = req.id.rsplit_once('.') {
-                let parent_exists =
-                    sqlx::query!("select id from requirements where id = $1", parent)
-                        .fetch_one(&self.pool)
-                        .await
-                        .is_ok();
-
-                let existing_parent = if parent_exists {
-                    parent.to_string()
-                } else {
-                    self.get_req_parent(parent)
-                        .await
-                        .ok_or(DbError::Insert(format!(
-                            "Parent is missing for child='{}'.",
-                            req.id
-                        )))?
-                };
-
-                let res = sqlx::query!(
-                    "insert or ignore into RequirementHierarchies (parent_id, child_id) values ($1, $2)",
-                    existing_parent,
-                    req.id,
-                )
-                .execute(&self.pool)
-                .await;
+        for (child, parent) in new_hierarchies
+            .into_iter()
+            .filter(|(child, _)| changes.inserted.iter().any(|req| &req.id == child))
+        {
+            let res = sqlx::query!(
+                "insert or ignore into RequirementHierarchies (parent_id, child_id) values ($1, $2)",
+                parent,
+                child,
+            )
+            .execute(&self.pool)
+            .await;
 
-                if let Err(err) = res {
-                    return Err(DbError::Insert(format!(
-                        "Adding requirement hierarchy for parent='{}' and child='{}' failed with error: {}",
-                        existing_parent, req.id, err
-                    )));
-                }
-            } else if let Some(parents) = &req.parents {
-                for parent in parents {
-                    let res = sqlx::query!(
-                        "insert or ignore into RequirementHierarchies (parent_id, child_id) values ($1, $2)",
-                        parent,
-                        req.id,
-                    )
-                    .execute(&self.pool)
-                    .await;
-    
-                    if let Err(err) = res {
-                        return Err(DbError::Insert(format!(
-                            "Adding requirement hierarchy for parent='{}' and child='{}' failed with error: {}",
-                            parent, req.id, err
-                        )));
-                    }
-                }
+            if let Err(err) = res {
+                return Err(DbError::Insert(format!(
+                    "Adding requirement hierarchy for parent='{}' and child='{}' failed with error: {}",
+                    parent, child, err
+                )));
             }
         }
 
         Ok(changes)
     }
 
+    /// Returns the design links of the given requirement in the order they were defined.
+    pub async fn design_links(&self, id: &str) -> Result<Vec<DesignLink>, DbError> {
+        let records = sqlx::query!(
+            "select url, title from DesignLinks where req_id = $1 order by rowid",
+            id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| DbError::Query(err.to_string()))?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| DesignLink {
+                url: record.url,
+                title: record.title,
+            })
+            .collect())
+    }
+
+    /// Replaces the design links of the given requirement.
+    async fn set_design_links(&self, id: &str, links: &[DesignLink]) -> Result<(), DbError> {
+        sqlx::query!("delete from DesignLinks where req_id = $1", id)
+            .execute(&self.pool)
+            .await
+            .map_err(|err| DbError::Delete(err.to_string()))?;
+
+        for link in links {
+            sqlx::query!(
+                "insert or ignore into DesignLinks (req_id, url, title) values ($1, $2, $3)",
+                id,
+                link.url,
+                link.title,
+            )
+            .execute(&self.pool)
+            .await
+            .map_err(|err| DbError::Insert(err.to_string()))?;
+        }
+
+        Ok(())
+    }
+
     pub async fn delete_req_generations(
         &self,
         before: i64,
@@ -374,7 +964,7 @@ impl MantraDb {
         let mut deleted = DeletedRequirements::default();
 
         if let Ok(old_reqs) = sqlx::query!(
-            "select id, title, origin, data, manual, deprecated from Requirements where generation < $1",
+            "select id, title, origin, data, manual, deprecated, untraceable, priority, state, effort from Requirements where generation < $1",
             before
         )
         .fetch_all(&self.pool)
@@ -385,11 +975,16 @@ impl MantraDb {
                     id: old_req.id,
                     title: old_req.title,
                     origin: old_req.origin,
-                    data: old_req.data.map(|a| serde_json::to_value(a)
-                        .expect("Requirement info must be valid JSON.")),
-                    manual: old_req.manual,
+                    data: old_req.data.map(|a| serde_json::from_str(&a)
+                        .expect("Requirement data must be valid JSON.")),
+                    manual: Some(old_req.manual),
                     deprecated: old_req.deprecated,
+                    untraceable: old_req.untraceable,
                     parents: None,
+                    priority: old_req.priority.and_then(|p| u8::try_from(p).ok()),
+                    state: old_req.state,
+                    effort: old_req.effort,
+                    design_links: Vec::new(),
                 })
             }
         }
@@ -422,21 +1017,55 @@ impl MantraDb {
             .await;
     }
 
-    async fn get_req_parent(&self, mut id: &str) -> Option<String> {
-        while let Some((parent, _)) = id.rsplit_once('.') {
-            let parent_exists = sqlx::query!("select id from requirements where id = $1", parent)
-                .fetch_one(&self.pool)
-                .await
-                .is_ok();
+    /// Returns the `(child, parent)` hierarchies created by adding the given requirements.
+    ///
+    /// Only requirements that are not stored yet create hierarchies.
+    /// Children per ID (e.g. `a.b`) are linked to the stored or given requirement with the longest prefix ID.
+    async fn new_req_hierarchies(
+        &self,
+        reqs: &[Requirement],
+    ) -> Result<Vec<(ReqId, ReqId)>, DbError> {
+        let stored_ids: HashSet<ReqId> = sqlx::query!("select id from Requirements")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|err| DbError::Query(err.to_string()))?
+            .into_iter()
+            .map(|record| record.id)
+            .collect();
+        let known_ids: HashSet<&str> = stored_ids
+            .iter()
+            .map(String::as_str)
+            .chain(reqs.iter().map(|req| req.id.as_str()))
+            .collect();
+
+        let mut hierarchies = Vec::new();
+
+        for req in reqs.iter().filter(|req| !stored_ids.contains(&req.id)) {
+            if req.id.contains('.') {
+                let mut id = req.id.as_str();
+
+                let parent = loop {
+                    match id.rsplit_once('.') {
+                        Some((parent, _)) if known_ids.contains(parent) => break parent,
+                        Some((parent, _)) => id = parent,
+                        None => {
+                            return Err(DbError::Insert(format!(
+                                "Parent is missing for child='{}'.",
+                                req.id
+                            )))
+                        }
+                    }
+                };
 
-            if parent_exists {
-                return Some(parent.to_string());
-            } else {
-                id = parent;
+                hierarchies.push((req.id.clone(), parent.to_string()));
+            } else if let Some(parents) = &req.parents {
+                for parent in parents {
+                    hierarchies.push((req.id.clone(), parent.clone()));
+                }
             }
         }
 
-        None
+        Ok(hierarchies)
     }
 
     pub async fn add_traces(
@@ -445,6 +1074,27 @@ impl MantraDb {
         traces: &[TraceEntry],
         new_generation: i64,
     ) -> Result<TraceChanges, DbError> {
+        self.add_file_traces(filepath, traces, new_generation, None)
+            .await
+    }
+
+    /// Stores the traces of one file in one transaction.
+    ///
+    /// If a collect step is given, the file is marked as collected in this step in the same transaction,
+    /// so an interrupted collect may be resumed after the last fully stored file.
+    pub async fn add_file_traces(
+        &self,
+        filepath: &Path,
+        traces: &[TraceEntry],
+        new_generation: i64,
+        collect_step: Option<&str>,
+    ) -> Result<TraceChanges, DbError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|err| DbError::Insert(err.to_string()))?;
+
         let mut changes = TraceChanges {
             new_generation,
             ..Default::default()
@@ -458,31 +1108,63 @@ impl MantraDb {
             let line_span = trace.line_span;
 
             for id in &trace.ids {
-                if (sqlx::query!("select req_id, filepath, line from Traces where req_id = $1 and filepath = $2 and line = $3", id, file_str, line).fetch_one(&self.pool).await).is_ok() {
-                    let _ = sqlx::query!("update Traces set generation = $4 where req_id = $1 and filepath = $2 and line = $3", id, file_str, line, new_generation).execute(&self.pool).await;
+                if (sqlx::query!("select req_id, filepath, line from Traces where req_id = $1 and filepath = $2 and line = $3 and branch = $4", id, file_str, line, self.branch).fetch_one(&mut *tx).await).is_ok() {
+                    let _ = sqlx::query!("update Traces set generation = $4 where req_id = $1 and filepath = $2 and line = $3 and branch = $5", id, file_str, line, new_generation, self.branch).execute(&mut *tx).await;
                     changes.unchanged_cnt += 1;
 
                     if let Some(span) = line_span {
                         let start = span.start;
                         let end = span.end;
 
-                        let _ = sqlx::query!("insert or replace into TraceSpans (req_id, filepath, line, start, end) values ($1, $2, $3, $4, $5)",
+                        if let Ok(Some(old_span)) = sqlx::query!("select start, end from TraceSpans where req_id = $1 and filepath = $2 and line = $3 and branch = $4", id, file_str, line, self.branch).fetch_optional(&mut *tx).await {
+                            let old_span = LineSpan { start: old_span.start as Line, end: old_span.end as Line };
+
+                            if old_span != span {
+                                let old_item = sqlx::query!("select name from TracedItems where filepath = $1 and line = $2 and branch = $3", file_str, old_span.start, self.branch)
+                                    .fetch_optional(&mut *tx)
+                                    .await
+                                    .ok()
+                                    .flatten()
+                                    .map(|r| r.name);
+
+                                let span_change = TraceSpanChange {
+                                    trace: TracePk { req_id: id.clone(), filepath: file.clone(), line },
+                                    old_span,
+                                    new_span: span,
+                                    old_item,
+                                    new_item: trace.item_name.clone(),
+                                };
+
+                                if span_change.item_changed() {
+                                    log::warn!("Trace {} is now linked to item '{}' instead of '{}'. The trace may have been detached from its intended item.",
+                                        span_change.trace,
+                                        span_change.new_item.as_deref().unwrap_or_default(),
+                                        span_change.old_item.as_deref().unwrap_or_default());
+                                }
+
+                                changes.span_changes.push(span_change);
+                            }
+                        }
+
+                        let _ = sqlx::query!("insert or replace into TraceSpans (req_id, filepath, line, start, end, branch) values ($1, $2, $3, $4, $5, $6)",
                             id,
                             file_str,
                             line,
                             start,
                             end,
-                        ).execute(&self.pool).await;
+                            self.branch,
+                        ).execute(&mut *tx).await;
                     }
                 } else {
                     let res = sqlx::query!(
-                        "insert into Traces (req_id, filepath, line, generation) values ($1, $2, $3, $4)",
+                        "insert into Traces (req_id, filepath, line, generation, branch) values ($1, $2, $3, $4, $5)",
                         id,
                         file_str,
                         line,
                         new_generation,
+                        self.branch,
                     )
-                    .execute(&self.pool)
+                    .execute(&mut *tx)
                     .await;
 
                     if let Err(sqlx::Error::Database(err)) = res {
@@ -491,12 +1173,13 @@ impl MantraDb {
                                 id, file_str, line);
                             
                                 let res = sqlx::query!(
-                                    "insert into UnrelatedTraces (req_id, filepath, line) values ($1, $2, $3)",
+                                    "insert into UnrelatedTraces (req_id, filepath, line, branch) values ($1, $2, $3, $4)",
                                     id,
                                     file_str,
                                     line,
+                                    self.branch,
                                 )
-                                .execute(&self.pool)
+                                .execute(&mut *tx)
                                 .await;
 
                             if let Err(err) = res {
@@ -514,37 +1197,366 @@ impl MantraDb {
                             let start = span.start;
                             let end = span.end;
 
-                            let _ = sqlx::query!("insert into TraceSpans (req_id, filepath, line, start, end) values ($1, $2, $3, $4, $5)",
+                            let _ = sqlx::query!("insert into TraceSpans (req_id, filepath, line, start, end, branch) values ($1, $2, $3, $4, $5, $6)",
                                 id,
                                 file_str,
                                 line,
                                 start,
                                 end,
-                            ).execute(&self.pool).await;
+                                self.branch,
+                            ).execute(&mut *tx).await;
                         }
                     }
                 }
             }
 
-            if let Some(span) = line_span{
-                if let Some(item_name) = &trace.item_name {
-                    let _ = sqlx::query!("insert or replace into TracedItems (name, filepath, line) values ($1, $2, $3)",
+            // removes declared coverage of traces no longer set on a test function
+            for id in &trace.ids {
+                let _ = match &trace.test_name {
+                    Some(test_name) => sqlx::query!(
+                        "insert or replace into DeclaredCoverage (req_id, branch, test_name, filepath, line) select req_id, branch, $1, filepath, line from Traces where req_id = $2 and filepath = $3 and line = $4 and branch = $5",
+                        test_name,
+                        id,
+                        file_str,
+                        line,
+                        self.branch,
+                    )
+                    .execute(&mut *tx)
+                    .await,
+                    None => sqlx::query!(
+                        "delete from DeclaredCoverage where req_id = $1 and filepath = $2 and line = $3 and branch = $4",
+                        id,
+                        file_str,
+                        line,
+                        self.branch,
+                    )
+                    .execute(&mut *tx)
+                    .await,
+                };
+            }
+
+            if let Some(span) = line_span{
+                if let Some(item_name) = trace.item_name.as_ref().or(trace.item_path.as_ref()) {
+                    let _ = sqlx::query!("insert or replace into TracedItems (name, filepath, line, branch, path) values ($1, $2, $3, $4, $5)",
                         item_name,
                         file_str,
                         span.start,
-                    ).execute(&self.pool).await;
+                        self.branch,
+                        trace.item_path,
+                    ).execute(&mut *tx).await;
                 }
             }
             
         }
 
+        if let Some(step) = collect_step {
+            sqlx::query!(
+                "insert or replace into CollectProgress (step, branch, generation, filepath) values ($1, $2, $3, $4)",
+                step,
+                self.branch,
+                new_generation,
+                file_str,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|err| DbError::Insert(err.to_string()))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|err| DbError::Insert(err.to_string()))?;
+
         Ok(changes)
     }
 
-    pub async fn max_trace_generation(&self) -> i64 {
-        if let Ok(record) = sqlx::query!("select max(generation) as nr from Traces")
-            .fetch_one(&self.pool)
+    /// Adds a trace for a requirement referenced in a commit message.
+    ///
+    /// The commit SHA is the location of the trace instead of a file and line,
+    /// so a requirement referenced in one commit is traced once per branch.
+    pub async fn add_commit_trace(
+        &self,
+        req_id: &str,
+        sha: &str,
+        summary: &str,
+        date: &str,
+    ) -> Result<CommitTraceStatus, DbError> {
+        let req_exists = sqlx::query!("select id from Requirements where id = $1", req_id)
+            .fetch_optional(&self.pool)
             .await
+            .map_err(|err| DbError::Query(err.to_string()))?
+            .is_some();
+
+        if !req_exists {
+            log::warn!(
+                "Unrelated commit trace. No requirement with id `{}` found for commit '{}'.",
+                req_id,
+                sha
+            );
+            return Ok(CommitTraceStatus::Unrelated);
+        }
+
+        let res = sqlx::query!(
+            "insert or ignore into CommitTraces (req_id, branch, sha, summary, date) values ($1, $2, $3, $4, $5)",
+            req_id,
+            self.branch,
+            sha,
+            summary,
+            date,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|err| {
+            DbError::Insert(format!(
+                "Adding commit trace for id=`{}`, commit='{}' failed with error: {}",
+                req_id, sha, err
+            ))
+        })?;
+
+        if res.rows_affected() > 0 {
+            Ok(CommitTraceStatus::Inserted)
+        } else {
+            Ok(CommitTraceStatus::Unchanged)
+        }
+    }
+
+    /// Deletes stored commit traces of the branch that are not part of the given commit traces.
+    ///
+    /// Needed, because commits may be rewritten or no longer reference a requirement.
+    pub async fn delete_stale_commit_traces(
+        &self,
+        commit_traces: &[CommitTracePk],
+    ) -> Result<Vec<CommitTracePk>, DbError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|err| DbError::Delete(err.to_string()))?;
+
+        let stored_traces = sqlx::query_as!(
+            CommitTracePk,
+            "select req_id, sha from CommitTraces where branch = $1",
+            self.branch
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|err| DbError::Query(err.to_string()))?;
+
+        let mut deleted = Vec::new();
+
+        for stored in stored_traces {
+            if commit_traces.contains(&stored) {
+                continue;
+            }
+
+            sqlx::query!(
+                "delete from CommitTraces where req_id = $1 and sha = $2 and branch = $3",
+                stored.req_id,
+                stored.sha,
+                self.branch
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|err| DbError::Delete(err.to_string()))?;
+
+            deleted.push(stored);
+        }
+
+        tx.commit()
+            .await
+            .map_err(|err| DbError::Delete(err.to_string()))?;
+
+        Ok(deleted)
+    }
+
+    /// Returns the progress of the given step of an interrupted collect.
+    pub async fn collect_progress(&self, step: &str) -> Result<CollectProgress, DbError> {
+        let records = sqlx::query!(
+            "select generation, filepath from CollectProgress where step = $1 and branch = $2",
+            step,
+            self.branch
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| DbError::Query(err.to_string()))?;
+
+        let mut progress = CollectProgress::default();
+
+        for record in records {
+            progress.generation = Some(record.generation);
+
+            if record.filepath.is_empty() {
+                progress.completed = true;
+            } else {
+                progress.collected_files.insert(record.filepath);
+            }
+        }
+
+        Ok(progress)
+    }
+
+    /// Marks the given step of a collect as completed.
+    /// Files marked as collected in this step are no longer needed to resume the step.
+    pub async fn complete_collect_step(&self, step: &str, generation: i64) -> Result<(), DbError> {
+        sqlx::query!(
+            "delete from CollectProgress where step = $1 and branch = $2",
+            step,
+            self.branch
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|err| DbError::Delete(err.to_string()))?;
+
+        sqlx::query!(
+            "insert into CollectProgress (step, branch, generation, filepath) values ($1, $2, $3, '')",
+            step,
+            self.branch,
+            generation,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|err| DbError::Insert(err.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Removes the progress of all steps, so the next collect starts from scratch.
+    pub async fn clear_collect_progress(&self) -> Result<(), DbError> {
+        sqlx::query!("delete from CollectProgress where branch = $1", self.branch)
+            .execute(&self.pool)
+            .await
+            .map_err(|err| DbError::Delete(err.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Records the trace and test status of all requirements for the given generation.
+    /// Only the given and the previous generation are kept.
+    pub async fn record_status(&self, generation: i64) -> Result<(), DbError> {
+        let status = self.current_status().await?;
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|err| DbError::Insert(err.to_string()))?;
+
+        sqlx::query!(
+            "delete from StatusHistory where branch = $1 and generation >= $2",
+            self.branch,
+            generation
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| DbError::Delete(err.to_string()))?;
+
+        for (id, req_status) in status {
+            sqlx::query!(
+                "insert into StatusHistory (generation, branch, req_id, traced, covered, passed) values ($1, $2, $3, $4, $5, $6)",
+                generation,
+                self.branch,
+                id,
+                req_status.traced,
+                req_status.covered,
+                req_status.passed,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|err| DbError::Insert(err.to_string()))?;
+        }
+
+        sqlx::query!(
+            "delete from StatusHistory where branch = $1 and generation < (select max(generation) from StatusHistory where branch = $1 and generation < $2)",
+            self.branch,
+            generation
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| DbError::Delete(err.to_string()))?;
+
+        tx.commit()
+            .await
+            .map_err(|err| DbError::Insert(err.to_string()))
+    }
+
+    /// Returns the recorded status of the collect before the latest one,
+    /// or `None` if less than two collects were recorded.
+    pub async fn previous_status(
+        &self,
+    ) -> Result<Option<(i64, HashMap<ReqId, RequirementStatus>)>, DbError> {
+        let Some(generation) = sqlx::query!(
+            r#"select generation as "generation!: i64" from StatusHistory where branch = $1 group by generation order by generation desc limit 1 offset 1"#,
+            self.branch
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|err| DbError::Query(err.to_string()))?
+        .map(|record| record.generation) else {
+            return Ok(None);
+        };
+
+        let records = sqlx::query!(
+            r#"select req_id, traced as "traced!: bool", covered as "covered!: bool", passed as "passed!: bool"
+            from StatusHistory where branch = $1 and generation = $2"#,
+            self.branch,
+            generation
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| DbError::Query(err.to_string()))?;
+
+        let status = records
+            .into_iter()
+            .map(|record| {
+                (
+                    record.req_id,
+                    RequirementStatus {
+                        traced: record.traced,
+                        covered: record.covered,
+                        passed: record.passed,
+                    },
+                )
+            })
+            .collect();
+
+        Ok(Some((generation, status)))
+    }
+
+    async fn current_status(&self) -> Result<Vec<(ReqId, RequirementStatus)>, DbError> {
+        let records = sqlx::query!(
+            r#"select r.id,
+                r.id in (select id from TracedRequirements where branch = $1) as "traced!: bool",
+                r.id in (select id from CoveredRequirements where branch = $1) as "covered!: bool",
+                r.id in (select id from PassedCoveredRequirements where branch = $1) as "passed!: bool"
+            from Requirements r"#,
+            self.branch
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| DbError::Query(err.to_string()))?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| {
+                (
+                    record.id,
+                    RequirementStatus {
+                        traced: record.traced,
+                        covered: record.covered,
+                        passed: record.passed,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    /// Returns the highest trace generation of the branch of this database.
+    pub async fn max_trace_generation(&self) -> i64 {
+        if let Ok(record) = sqlx::query!(
+            "select max(generation) as nr from Traces where branch = $1",
+            self.branch
+        )
+        .fetch_one(&self.pool)
+        .await
         {
             record.nr.unwrap_or_default()
         } else {
@@ -553,9 +1565,86 @@ impl MantraDb {
     }
 
     pub async fn reset_trace_generation(&self) {
-        let _ = sqlx::query!("update Traces set generation = 0")
-            .execute(&self.pool)
-            .await;
+        let _ = sqlx::query!(
+            "update Traces set generation = 0 where branch = $1",
+            self.branch
+        )
+        .execute(&self.pool)
+        .await;
+    }
+
+    /// Deletes stored traces and unrelated traces of the given file that are not part of the given traces.
+    ///
+    /// Needed if single files are re-traced into the current generation,
+    /// because traces removed from these files would otherwise remain in the current generation.
+    pub async fn delete_stale_file_traces(
+        &self,
+        filepath: &Path,
+        traces: &[TraceEntry],
+    ) -> Result<Vec<TracePk>, DbError> {
+        let file = SlashPathBuf::from(filepath);
+        let file_str = file.to_string();
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|err| DbError::Delete(err.to_string()))?;
+
+        // unrelated traces are added again when the file's traces are stored
+        sqlx::query!(
+            "delete from UnrelatedTraces where filepath = $1 and branch = $2",
+            file_str,
+            self.branch
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| DbError::Delete(err.to_string()))?;
+
+        let stored_traces = sqlx::query!(
+            "select req_id, line from Traces where filepath = $1 and branch = $2",
+            file_str,
+            self.branch
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|err| DbError::Query(err.to_string()))?;
+
+        let mut deleted = Vec::new();
+
+        for stored in stored_traces {
+            let line: Line = stored.line.try_into().expect("Line must be u32.");
+
+            if traces
+                .iter()
+                .any(|trace| trace.line == line && trace.ids.contains(&stored.req_id))
+            {
+                continue;
+            }
+
+            sqlx::query!(
+                "delete from Traces where req_id = $1 and filepath = $2 and line = $3 and branch = $4",
+                stored.req_id,
+                file_str,
+                stored.line,
+                self.branch
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|err| DbError::Delete(err.to_string()))?;
+
+            deleted.push(TracePk {
+                req_id: stored.req_id,
+                filepath: file.clone(),
+                line,
+            });
+        }
+
+        tx.commit()
+            .await
+            .map_err(|err| DbError::Delete(err.to_string()))?;
+
+        Ok(deleted)
     }
 
     pub async fn delete_trace_generations(
@@ -565,8 +1654,9 @@ impl MantraDb {
         let mut deleted_traces = DeletedTraces::default();
 
         if let Ok(old_traces) = sqlx::query!(
-            "select req_id, filepath, line from Traces where generation < $1",
-            before
+            "select req_id, filepath, line from Traces where generation < $1 and branch = $2",
+            before,
+            self.branch
         )
         .fetch_all(&self.pool)
         .await
@@ -580,9 +1670,13 @@ impl MantraDb {
             }
         }
 
-        let _ = sqlx::query!("delete from Traces where generation < $1", before)
-            .execute(&self.pool)
-            .await;
+        let _ = sqlx::query!(
+            "delete from Traces where generation < $1 and branch = $2",
+            before,
+            self.branch
+        )
+        .execute(&self.pool)
+        .await;
 
         Ok(if deleted_traces.is_empty() {
             None
@@ -601,16 +1695,17 @@ impl MantraDb {
     ) -> Result<bool, DbError> {
         // Note: absolute or relative filepath must match with how the trace paths were added
         let file = SlashPathBuf::from(trace_filepath);
-        let file_str = file.to_string();
+        let file_str = self.stored_trace_filepath(file.to_string()).await;
 
         let query_result = sqlx::query!(
-                "insert or ignore into TestCoverage (req_id, test_run_name, test_run_date, test_name, trace_filepath, trace_line) values ($1, $2, $3, $4, $5, $6)",
+                "insert or ignore into TestCoverage (req_id, test_run_name, test_run_date, test_name, trace_filepath, trace_line, branch) values ($1, $2, $3, $4, $5, $6, $7)",
                 req_id,
                 test_run.name,
                 test_run.date,
                 test_name,
                 file_str,
                 trace_line,
+                self.branch,
             )
             .execute(&self.pool)
             .await;
@@ -618,13 +1713,14 @@ impl MantraDb {
         if let Err(sqlx::Error::Database(sqlx_db_error)) = &query_result {
             if sqlx_db_error.kind() == sqlx::error::ErrorKind::ForeignKeyViolation {
                 let query_result = sqlx::query!(
-                    "insert or ignore into UnrelatedTestCoverage (req_id, test_run_name, test_run_date, test_name, trace_filepath, trace_line) values ($1, $2, $3, $4, $5, $6)",
+                    "insert or ignore into UnrelatedTestCoverage (req_id, test_run_name, test_run_date, test_name, trace_filepath, trace_line, branch) values ($1, $2, $3, $4, $5, $6, $7)",
                     req_id,
                     test_run.name,
                     test_run.date,
                     test_name,
                     file_str,
                     trace_line,
+                    self.branch,
                 )
                 .execute(&self.pool)
                 .await;
@@ -651,6 +1747,28 @@ impl MantraDb {
         Ok(true)
     }
 
+    /// Returns the filepath as stored for traces.
+    ///
+    /// On case-insensitive filesystems, coverage tools may use a different case than the trace collection.
+    /// The filepath of a trace that only differs in case is therefore used, so the coverage is linked to the trace.
+    async fn stored_trace_filepath(&self, filepath: String) -> String {
+        if !cfg!(any(target_os = "windows", target_os = "macos")) {
+            return filepath;
+        }
+
+        sqlx::query!(
+            "select filepath from Traces where filepath = $1 collate nocase and branch = $2 limit 1",
+            filepath,
+            self.branch
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten()
+        .map(|record| record.filepath)
+        .unwrap_or(filepath)
+    }
+
     pub async fn add_test(
         &self,
         test_run: &TestRunPk,
@@ -658,6 +1776,7 @@ impl MantraDb {
         filepath: &Path,
         line: Line,
         state: TestState,
+        tags: &[String],
     ) -> Result<(), DbError> {
         let file = SlashPathBuf::from(filepath);
         let file_str = file.to_string();
@@ -667,13 +1786,14 @@ impl MantraDb {
                 let passed = state == TestState::Passed;
 
                 let _ = sqlx::query!(
-                    "insert or ignore into Tests (name, test_run_name, test_run_date, filepath, line, passed) values ($1, $2, $3, $4, $5, $6)",
+                    "insert or ignore into Tests (name, test_run_name, test_run_date, filepath, line, passed, branch) values ($1, $2, $3, $4, $5, $6, $7)",
                     name,
                     test_run.name,
                     test_run.date,
                     file_str,
                     line,
                     passed,
+                    self.branch,
                 )
                 .execute(&self.pool)
                 .await
@@ -683,16 +1803,36 @@ impl MantraDb {
                         name, test_run.name, test_run.date, file_str, line, err
                     ))
                 })?;
+
+                for tag in tags {
+                    sqlx::query!(
+                        "insert or ignore into TestTags (test_run_name, test_run_date, test_name, tag, branch) values ($1, $2, $3, $4, $5)",
+                        test_run.name,
+                        test_run.date,
+                        name,
+                        tag,
+                        self.branch,
+                    )
+                    .execute(&self.pool)
+                    .await
+                    .map_err(|err| {
+                        DbError::Insert(format!(
+                            "Adding tag '{}' for test='{}', test-run='{}' at {} failed with error: {}",
+                            tag, name, test_run.name, test_run.date, err
+                        ))
+                    })?;
+                }
             }
             TestState::Skipped { reason } => {
                 sqlx::query!(
-                        "insert or ignore into SkippedTests (name, test_run_name, test_run_date, filepath, line, reason) values ($1, $2, $3, $4, $5, $6)",
+                        "insert or ignore into SkippedTests (name, test_run_name, test_run_date, filepath, line, reason, branch) values ($1, $2, $3, $4, $5, $6, $7)",
                         name,
                         test_run.name,
                         test_run.date,
                         file_str,
                         line,
                         reason,
+                        self.branch,
                     )
                     .execute(&self.pool)
                     .await
@@ -717,12 +1857,13 @@ impl MantraDb {
         logs: Option<String>,
     ) -> Result<(), DbError> {
         let _ = sqlx::query!(
-            "insert or ignore into TestRuns (name, date, nr_of_tests, data, logs) values ($1, $2, $3, $4, $5)",
+            "insert or ignore into TestRuns (name, date, nr_of_tests, data, logs, branch) values ($1, $2, $3, $4, $5, $6)",
             name,
             date,
             nr_of_tests,
             data,
             logs,
+            self.branch,
         )
         .execute(&self.pool)
         .await
@@ -737,14 +1878,62 @@ impl MantraDb {
     }
 
     pub async fn test_run_exists(&self, name: &str, date: &time::OffsetDateTime) -> bool {
-        sqlx::query!("select * from TestRuns where name = $1 and date = $2", name, date).fetch_one(&self.pool).await.is_ok()
+        sqlx::query!("select * from TestRuns where name = $1 and date = $2 and branch = $3", name, date, self.branch).fetch_one(&self.pool).await.is_ok()
     }
 
-    pub async fn is_valid(&self) -> Result<(), DbError> {
-        let record = sqlx::query!("select count(*) as invalid_cnt from InvalidRequirements")
-            .fetch_one(&self.pool)
+    /// Returns all `(child, parent)` pairs of the requirement hierarchy.
+    pub async fn req_hierarchies(&self) -> Result<Vec<(ReqId, ReqId)>, DbError> {
+        let records = sqlx::query!("select child_id, parent_id from RequirementHierarchies")
+            .fetch_all(&self.pool)
             .await
-            .map_err(|err| DbError::Validate(err.to_string()))?;
+            .map_err(|err| DbError::Query(err.to_string()))?;
+
+        Ok(records
+            .into_iter()
+            .map(|r| (r.child_id, r.parent_id))
+            .collect())
+    }
+
+    /// Returns the path of a cycle in the requirement hierarchy if one exists.
+    pub async fn hierarchy_cycle(&self) -> Result<Option<Vec<ReqId>>, DbError> {
+        Ok(find_hierarchy_cycle(&self.req_hierarchies().await?))
+    }
+
+    pub async fn is_valid(&self) -> Result<(), DbError> {
+        // foreign keys are not enforced while databases are merged
+        let record = sqlx::query!(
+            r#"
+            select count(*) as "dangling_cnt!: i64"
+            from RequirementHierarchies
+            where child_id not in (select id from Requirements)
+                or parent_id not in (select id from Requirements)
+            "#
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|err| DbError::Validate(err.to_string()))?;
+
+        if record.dangling_cnt != 0 {
+            return Err(DbError::Validate(format!(
+                "'{}' requirement hierarchy entries refer to unknown requirements.",
+                record.dangling_cnt
+            )));
+        }
+
+        if let Some(cycle) = self.hierarchy_cycle().await? {
+            return Err(DbError::Validate(format!(
+                "Cyclic requirement hierarchy: {}",
+                cycle.join(" -> ")
+            )));
+        }
+
+        let record = sqlx::query!(
+            "select count(*) as invalid_cnt from InvalidRequirements where branch = $1",
+            self.branch
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|err| DbError::Validate(err.to_string()))?;
 
         if record.invalid_cnt == 0 {
             Ok(())
@@ -756,6 +1945,138 @@ impl MantraDb {
         }
     }
 
+    /// Replaces the acknowledged validation errors with the given suppressions.
+    /// Suppressions of unknown requirements are skipped.
+    pub async fn set_suppressions(
+        &self,
+        suppressions: &[crate::cfg::ValidationSuppression],
+    ) -> Result<(), DbError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|err| DbError::Insert(err.to_string()))?;
+
+        sqlx::query!("delete from ValidationSuppressions")
+            .execute(&mut *tx)
+            .await
+            .map_err(|err| DbError::Delete(err.to_string()))?;
+
+        for suppression in suppressions {
+            let res = sqlx::query!(
+                "insert or replace into ValidationSuppressions (req_id, reason) select id, $2 from Requirements where id = $1",
+                suppression.id,
+                suppression.reason,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|err| DbError::Insert(err.to_string()))?;
+
+            if res.rows_affected() == 0 {
+                log::warn!(
+                    "Suppression skipped. No requirement with id `{}` found.",
+                    suppression.id
+                );
+            }
+        }
+
+        tx.commit()
+            .await
+            .map_err(|err| DbError::Insert(err.to_string()))
+    }
+
+    /// Returns the IDs of all requirements with acknowledged validation errors.
+    pub async fn suppressed_req_ids(&self) -> Result<HashSet<ReqId>, DbError> {
+        sqlx::query!("select req_id from ValidationSuppressions")
+            .fetch_all(&self.pool)
+            .await
+            .map(|records| records.into_iter().map(|record| record.req_id).collect())
+            .map_err(|err| DbError::Query(err.to_string()))
+    }
+
+    /// Returns the IDs of all requirements that are deprecated, or have a deprecated ancestor.
+    pub async fn deprecated_req_ids(&self) -> Result<HashSet<ReqId>, DbError> {
+        sqlx::query!(r#"select id as "id!" from DeprecatedRequirements"#)
+            .fetch_all(&self.pool)
+            .await
+            .map(|records| records.into_iter().map(|record| record.id).collect())
+            .map_err(|err| DbError::Query(err.to_string()))
+    }
+
+    /// Returns `true` if the requirement is traced directly, or indirectly through all of its sub-requirements.
+    ///
+    /// **Note:** Only traces of the set branch are considered.
+    pub async fn is_traced(&self, req_id: &str) -> Result<bool, DbError> {
+        self.ensure_req_exists(req_id).await?;
+
+        sqlx::query!(
+            "select id from TracedRequirements where id = $1 and branch = $2",
+            req_id,
+            self.branch
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map(|record| record.is_some())
+        .map_err(|err| DbError::Query(err.to_string()))
+    }
+
+    /// Returns `true` if the requirement is covered by at least one test, directly or indirectly through all of its sub-requirements.
+    ///
+    /// **Note:** Only test runs of the set branch are considered.
+    pub async fn is_covered(&self, req_id: &str) -> Result<bool, DbError> {
+        self.ensure_req_exists(req_id).await?;
+
+        sqlx::query!(
+            "select id from CoveredRequirements where id = $1 and branch = $2",
+            req_id,
+            self.branch
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map(|record| record.is_some())
+        .map_err(|err| DbError::Query(err.to_string()))
+    }
+
+    /// Returns `true` if the requirement is covered, and all tests covering the requirement or its sub-requirements passed.
+    ///
+    /// **Note:** Only test runs of the set branch are considered.
+    pub async fn is_passed(&self, req_id: &str) -> Result<bool, DbError> {
+        self.ensure_req_exists(req_id).await?;
+
+        sqlx::query!(
+            "select id from PassedCoveredRequirements where id = $1 and branch = $2",
+            req_id,
+            self.branch
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map(|record| record.is_some())
+        .map_err(|err| DbError::Query(err.to_string()))
+    }
+
+    /// Returns the trace, coverage, and verification overview over all requirements for the set branch.
+    pub async fn coverage_overview(
+        &self,
+    ) -> Result<crate::cmd::report::RequirementsOverview, DbError> {
+        crate::cmd::report::RequirementsOverview::try_from(self)
+            .await
+            .map_err(|err| DbError::Query(err.to_string()))
+    }
+
+    async fn ensure_req_exists(&self, req_id: &str) -> Result<(), DbError> {
+        let exists = sqlx::query!("select id from Requirements where id = $1", req_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|err| DbError::Query(err.to_string()))?
+            .is_some();
+
+        if exists {
+            Ok(())
+        } else {
+            Err(DbError::UnknownRequirement(req_id.to_string()))
+        }
+    }
+
     pub fn pool(&self) -> &Pool<DB> {
         // workaround for custom queries
         &self.pool
@@ -769,7 +2090,8 @@ impl MantraDb {
         .await
         .map_err(|err| DbError::Delete(err.to_string()))?;
         let _ = sqlx::query!(
-            "delete from Traces where generation < (select max(generation) from Traces)"
+            "delete from Traces where branch = $1 and generation < (select max(generation) from Traces where branch = $1)",
+            self.branch
         )
         .execute(&self.pool)
         .await
@@ -818,34 +2140,44 @@ impl MantraDb {
         Ok(())
     }
 
+    /// Deletes traces of the set branch.
     pub async fn delete_traces(&self, cfg: &DeleteTracesConfig) -> Result<(), DbError> {
         let ids = cfg.req_ids.as_deref().unwrap_or_default();
 
         if ids.is_empty() {
             if let Some(before) = cfg.before {
-                let _ = sqlx::query!("delete from Traces where generation < $1", before)
-                    .execute(&self.pool)
-                    .await
-                    .map_err(|err| DbError::Delete(err.to_string()))?;
+                let _ = sqlx::query!(
+                    "delete from Traces where generation < $1 and branch = $2",
+                    before,
+                    self.branch
+                )
+                .execute(&self.pool)
+                .await
+                .map_err(|err| DbError::Delete(err.to_string()))?;
             }
         } else {
             for id in ids {
                 match cfg.before {
                     Some(before) => {
                         sqlx::query!(
-                            "delete from Traces where req_id = $1 and generation < $2",
+                            "delete from Traces where req_id = $1 and generation < $2 and branch = $3",
                             id,
-                            before
+                            before,
+                            self.branch
                         )
                         .execute(&self.pool)
                         .await
                         .map_err(|err| DbError::Delete(err.to_string()))?;
                     }
                     None => {
-                        sqlx::query!("delete from Traces where req_id = $1", id)
-                            .execute(&self.pool)
-                            .await
-                            .map_err(|err| DbError::Delete(err.to_string()))?;
+                        sqlx::query!(
+                            "delete from Traces where req_id = $1 and branch = $2",
+                            id,
+                            self.branch
+                        )
+                        .execute(&self.pool)
+                        .await
+                        .map_err(|err| DbError::Delete(err.to_string()))?;
                     }
                 };
             }
@@ -889,12 +2221,22 @@ impl MantraDb {
         .map_err(|err| DbError::Insert(err.to_string()))?;
 
         for req in review.requirements {
+            let evidence = if req.evidence.is_empty() {
+                None
+            } else {
+                Some(
+                    serde_json::to_string(&req.evidence)
+                        .expect("List of evidence must be serializable."),
+                )
+            };
+
             let res = sqlx::query!(
-                "insert or replace into ManuallyVerified (req_id, review_name, review_date, comment) values ($1, $2, $3, $4)",
+                "insert or replace into ManuallyVerified (req_id, review_name, review_date, comment, evidence) values ($1, $2, $3, $4, $5)",
                 req.id,
                 review.name,
                 review.date,
                 req.comment,
+                evidence,
             )
             .execute(&self.pool)
             .await;
@@ -908,11 +2250,12 @@ impl MantraDb {
                     );
 
                     let res = sqlx::query!(
-                        "insert or replace into UnrelatedManuallyVerified (req_id, review_name, review_date, comment) values ($1, $2, $3, $4)",
+                        "insert or replace into UnrelatedManuallyVerified (req_id, review_name, review_date, comment, evidence) values ($1, $2, $3, $4, $5)",
                         req.id,
                         review.name,
                         review.date,
                         req.comment,
+                        evidence,
                     )
                     .execute(&self.pool)
                     .await;
@@ -965,15 +2308,64 @@ impl MantraDb {
         Ok(())
     }
 
+    /// Deletes test runs that are not among the `keep_last` most recent runs with the same name and branch,
+    /// or that are older than `keep_days`.
+    /// Tests and coverage of deleted test runs are deleted as well.
+    ///
+    /// No test run is deleted if neither `keep_last` nor `keep_days` is set.
+    pub async fn prune_test_runs(
+        &self,
+        keep_last: Option<u32>,
+        keep_days: Option<u32>,
+    ) -> Result<DeletedTestRuns, DbError> {
+        if keep_last.is_none() && keep_days.is_none() {
+            log::warn!("No retention policy set. All test runs are kept.");
+            return Ok(DeletedTestRuns::default());
+        }
+
+        let expired = sqlx::query_as!(
+            DeletedTestRun,
+            r#"
+            select name as "name!", date as "date!", branch as "branch!"
+            from (
+                select name, date, branch, row_number() over (partition by name, branch order by date desc) as run_nr
+                from TestRuns
+            )
+            where ($1 is not null and run_nr > $1)
+                or ($2 is not null and unixepoch(date) < unixepoch('now', '-' || $2 || ' days'))
+            order by name, date, branch
+            "#,
+            keep_last,
+            keep_days
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| DbError::Query(err.to_string()))?;
+
+        for test_run in &expired {
+            sqlx::query!(
+                "delete from TestRuns where name = $1 and date = $2 and branch = $3",
+                test_run.name,
+                test_run.date,
+                test_run.branch
+            )
+            .execute(&self.pool)
+            .await
+            .map_err(|err| DbError::Delete(err.to_string()))?;
+        }
+
+        Ok(DeletedTestRuns(expired))
+    }
+
     pub async fn prune(&self) -> Result<(), DbError> {
         let _ = sqlx::query!(
-            "delete from Tests where name not in (select test_name from TestCoverage)"
+            "delete from Tests where (test_run_name, test_run_date, branch, name) not in (select test_run_name, test_run_date, branch, test_name from TestCoverage)"
         )
         .execute(&self.pool)
         .await
         .map_err(|err| DbError::Delete(err.to_string()))?;
         let _ =
-        sqlx::query!("delete from TestRuns where (name, date) not in (select test_run_name, test_run_date from TestCoverage)")
+        sqlx::query!("delete from TestRuns where (name, date, branch) not in (select test_run_name, test_run_date, branch from TestCoverage)")
             .execute(&self.pool)
             .await
             .map_err(|err| DbError::Delete(err.to_string()))?;
@@ -986,30 +2378,802 @@ impl MantraDb {
         Ok(())
     }
 
-    pub async fn clear(&self) -> Result<(), DbError> {
-        let _ = sqlx::query!(
-            "delete from Requirements"
+    /// Clears only the given tables.
+    /// Tables are cleared in an order that respects foreign key relations.
+    ///
+    /// The progress of interrupted collects is always cleared,
+    /// because resuming would skip data that was removed.
+    pub async fn clear_tables(&self, tables: &[DbTable]) -> Result<(), DbError> {
+        if !tables.is_empty() {
+            sqlx::query!("delete from CollectProgress")
+                .execute(&self.pool)
+                .await
+                .map_err(|err| DbError::Delete(err.to_string()))?;
+        }
+
+        if tables.contains(&DbTable::Coverage) {
+            sqlx::query!("delete from TestCoverage")
+                .execute(&self.pool)
+                .await
+                .map_err(|err| DbError::Delete(err.to_string()))?;
+            sqlx::query!("delete from UnrelatedTestCoverage")
+                .execute(&self.pool)
+                .await
+                .map_err(|err| DbError::Delete(err.to_string()))?;
+        }
+
+        if tables.contains(&DbTable::Tests) {
+            sqlx::query!("delete from TestRuns")
+                .execute(&self.pool)
+                .await
+                .map_err(|err| DbError::Delete(err.to_string()))?;
+        }
+
+        if tables.contains(&DbTable::Reviews) {
+            sqlx::query!("delete from Reviews")
+                .execute(&self.pool)
+                .await
+                .map_err(|err| DbError::Delete(err.to_string()))?;
+        }
+
+        if tables.contains(&DbTable::Traces) {
+            sqlx::query!("delete from Traces")
+                .execute(&self.pool)
+                .await
+                .map_err(|err| DbError::Delete(err.to_string()))?;
+            sqlx::query!("delete from UnrelatedTraces")
+                .execute(&self.pool)
+                .await
+                .map_err(|err| DbError::Delete(err.to_string()))?;
+            sqlx::query!("delete from CommitTraces")
+                .execute(&self.pool)
+                .await
+                .map_err(|err| DbError::Delete(err.to_string()))?;
+        }
+
+        if tables.contains(&DbTable::Requirements) {
+            if !tables.contains(&DbTable::Traces) {
+                log::warn!("Clearing requirements also clears all traces, coverage, and verifications linked to requirements.");
+            }
+
+            sqlx::query!("delete from ValidationSuppressions")
+                .execute(&self.pool)
+                .await
+                .map_err(|err| DbError::Delete(err.to_string()))?;
+            sqlx::query!("delete from Requirements")
+                .execute(&self.pool)
+                .await
+                .map_err(|err| DbError::Delete(err.to_string()))?;
+            // history has no foreign key to requirements, so removed requirements are detected
+            sqlx::query!("delete from StatusHistory")
+                .execute(&self.pool)
+                .await
+                .map_err(|err| DbError::Delete(err.to_string()))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Searches for a cycle in the given `(child, parent)` pairs.
+///
+/// The cycle is returned as path from a child over its parents back to the same child.
+fn find_hierarchy_cycle(hierarchies: &[(ReqId, ReqId)]) -> Option<Vec<ReqId>> {
+    let mut parents: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (child, parent) in hierarchies {
+        parents
+            .entry(child.as_str())
+            .or_default()
+            .push(parent.as_str());
+    }
+
+    let mut done: HashSet<&str> = HashSet::new();
+
+    for start in parents.keys() {
+        if done.contains(start) {
+            continue;
+        }
+
+        // depth-first search with explicit stack to not overflow on deep hierarchies
+        let mut path: Vec<&str> = vec![start];
+        let mut next_parent: Vec<usize> = vec![0];
+
+        while let Some(id) = path.last().copied() {
+            let idx = next_parent
+                .last_mut()
+                .expect("Index exists for every path entry.");
+            let parent = parents.get(id).and_then(|p| p.get(*idx)).copied();
+            *idx += 1;
+
+            match parent {
+                Some(parent) if path.contains(&parent) => {
+                    let cycle_start = path
+                        .iter()
+                        .position(|id| *id == parent)
+                        .expect("Parent is in path.");
+                    let mut cycle: Vec<ReqId> = path[cycle_start..]
+                        .iter()
+                        .map(|id| id.to_string())
+                        .collect();
+                    cycle.push(parent.to_string());
+                    return Some(cycle);
+                }
+                Some(parent) if !done.contains(parent) => {
+                    path.push(parent);
+                    next_parent.push(0);
+                }
+                Some(_) => {}
+                None => {
+                    done.insert(id);
+                    path.pop();
+                    next_parent.pop();
+                }
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::{req, test_db, test_db_on_branch};
+
+    #[tokio::test]
+    async fn design_links_kept_in_order() {
+        let db = test_db("design_link").await;
+
+        let link = |url: &str| mantra_schema::requirements::DesignLink {
+            url: url.to_string(),
+            title: None,
+        };
+        let mut linked = req("a", &[]);
+        linked.design_links = vec![link("docs/z.md"), link("docs/a.md")];
+
+        db.add_reqs(vec![linked.clone()]).await.unwrap();
+        let stored = db.design_links("a").await;
+        let recollected = db.add_reqs(vec![linked]).await;
+
+        assert_eq!(
+            stored.unwrap(),
+            vec![link("docs/z.md"), link("docs/a.md")],
+            "Design links not stored in defined order."
+        );
+        assert_eq!(
+            recollected.unwrap().unchanged_cnt,
+            1,
+            "Requirement with same design links marked as updated."
+        );
+    }
+
+    #[tokio::test]
+    async fn status_history_keeps_previous_collect() {
+        let db = test_db("status").await;
+
+        db.add_reqs(vec![req("a", &[])]).await.unwrap();
+        db.record_status(1).await.unwrap();
+        let first = db.previous_status().await.unwrap();
+
+        db.add_reqs(vec![req("a", &[]), req("b", &[])])
+            .await
+            .unwrap();
+        db.record_status(2).await.unwrap();
+        db.record_status(3).await.unwrap();
+        let previous = db.previous_status().await.unwrap();
+
+        assert!(first.is_none(), "Previous status without previous collect.");
+        let (generation, status) = previous.expect("Previous status recorded.");
+        assert_eq!(
+            generation, 2,
+            "Status not compared to the previous collect."
+        );
+        assert_eq!(
+            status.get("b"),
+            Some(&super::RequirementStatus::default()),
+            "Status of untraced requirement not recorded."
+        );
+    }
+
+    #[tokio::test]
+    async fn cyclic_hierarchy_rejected() {
+        let db = test_db("cycle").await;
+
+        db.add_reqs(vec![req("a", &[]), req("a.b", &[])])
+            .await
+            .unwrap();
+        let res = db
+            .add_reqs(vec![req("c", &["a.b"]), req("d", &["c"])])
+            .await;
+        assert!(res.is_ok(), "Acyclic hierarchy rejected.");
+
+        // `e.f` is child of `e` per ID, and `e` sets `e.f` as its parent
+        let res = db.add_reqs(vec![req("e", &["e.f"]), req("e.f", &[])]).await;
+
+        let (stored_cnt,): (i64,) =
+            sqlx::query_as("select count(*) from Requirements where id in ('e', 'e.f')")
+                .fetch_one(db.pool())
+                .await
+                .unwrap();
+
+        let err = res.unwrap_err().to_string();
+        assert!(
+            err.contains("e -> e.f -> e") || err.contains("e.f -> e -> e.f"),
+            "Cycle path not reported: {err}"
+        );
+        assert_eq!(stored_cnt, 0, "Requirements of rejected hierarchy stored.");
+    }
+
+    #[tokio::test]
+    async fn reordered_data_unchanged() {
+        let db = test_db("data").await;
+
+        let mut first = req("a", &[]);
+        first.data =
+            Some(serde_json::from_str(r#"{"info": "x", "nested": {"b": 2, "a": 1}}"#).unwrap());
+        db.add_reqs(vec![first]).await.unwrap();
+
+        let mut reordered = req("a", &[]);
+        reordered.data = Some(
+            serde_json::from_str("{\n  \"nested\": { \"a\": 1, \"b\": 2 },\n  \"info\": \"x\"\n}")
+                .unwrap(),
+        );
+        let changes = db.add_reqs(vec![reordered]).await;
+
+        let changes = changes.unwrap();
+        assert_eq!(
+            changes.unchanged_cnt, 1,
+            "Reordered data not detected as unchanged."
+        );
+        assert!(
+            changes.updated.is_empty(),
+            "Reordered data detected as update."
+        );
+    }
+
+    #[tokio::test]
+    async fn dangling_hierarchy_is_invalid() {
+        let db = test_db("hierarchy").await;
+
+        db.add_reqs(vec![req("parent", &[]), req("parent.child", &["parent"])])
+            .await
+            .unwrap();
+        let consistent = db.is_valid().await;
+
+        let mut conn = db.pool().acquire().await.unwrap();
+        sqlx::query("pragma foreign_keys = off")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        sqlx::query("insert into RequirementHierarchies (child_id, parent_id) values ('parent.child', 'removed')")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        sqlx::query("pragma foreign_keys = on")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        drop(conn);
+        let dangling = db.is_valid().await;
+
+        assert!(consistent.is_ok(), "Consistent hierarchy is invalid.");
+        assert!(
+            dangling.is_err(),
+            "Hierarchy entry to unknown requirement is valid."
+        );
+    }
+
+    #[tokio::test]
+    async fn suppressed_deprecated_trace_is_valid() {
+        let db = test_db("suppression").await;
+
+        let mut deprecated = req("old", &[]);
+        deprecated.deprecated = true;
+        db.add_reqs(vec![deprecated]).await.unwrap();
+        db.add_traces(
+            std::path::Path::new("src/migration.rs"),
+            &[mantra_schema::traces::TraceEntry {
+                ids: vec!["old".to_string()],
+                line: 1,
+                line_span: None,
+                item_name: None,
+                item_path: None,
+                test_name: None,
+            }],
+            1,
         )
-        .execute(&self.pool)
         .await
-        .map_err(|err| DbError::Delete(err.to_string()))?;
-    let _ = sqlx::query!(
-        "delete from UnrelatedTraces"
-    )
-    .execute(&self.pool)
-    .await
-    .map_err(|err| DbError::Delete(err.to_string()))?;
-        let _ =
-        sqlx::query!("delete from TestRuns")
-            .execute(&self.pool)
+        .unwrap();
+
+        let unsuppressed = db.is_valid().await;
+        let suppression = |id: &str| crate::cfg::ValidationSuppression {
+            id: id.to_string(),
+            reason: "Documents the migration.".to_string(),
+        };
+        let stored = db
+            .set_suppressions(&[suppression("old"), suppression("unknown")])
+            .await;
+        let suppressed = db.is_valid().await;
+        let suppressed_ids = db.suppressed_req_ids().await;
+
+        assert!(
+            unsuppressed.is_err(),
+            "Traced deprecated requirement is valid."
+        );
+        assert!(
+            stored.is_ok(),
+            "Suppression of unknown requirement not skipped."
+        );
+        assert!(
+            suppressed.is_ok(),
+            "Suppressed validation error still reported."
+        );
+        assert_eq!(
+            suppressed_ids.unwrap(),
+            ["old".to_string()].into_iter().collect(),
+            "Suppression of unknown requirement stored."
+        );
+    }
+
+    #[tokio::test]
+    async fn merged_shards_combine_test_runs() {
+        let shards = [test_db("merge_shard1").await, test_db("merge_shard2").await];
+        let date = time::macros::datetime!(2024-05-12 10:30 UTC);
+
+        for (db, shard) in shards.iter().zip(["shard1", "shard2"]) {
+            db.add_reqs(vec![req("a", &[])]).await.unwrap();
+            db.add_traces(
+                std::path::Path::new("src/lib.rs"),
+                &[mantra_schema::traces::TraceEntry {
+                    ids: vec!["a".to_string()],
+                    line: 1,
+                    line_span: None,
+                    item_name: None,
+                    item_path: None,
+                    test_name: None,
+                }],
+                1,
+            )
             .await
-            .map_err(|err| DbError::Delete(err.to_string()))?;
-        let _ =
-        sqlx::query!("delete from Reviews")
-            .execute(&self.pool)
+            .unwrap();
+            db.add_test_run("ci", &date, 1, None, None).await.unwrap();
+            sqlx::query("insert into Tests (test_run_name, test_run_date, name, filepath, line, passed) values ('ci', $1, $2, 'tests/it.rs', 1, true)")
+                .bind(date)
+                .bind(shard)
+                .execute(db.pool())
+                .await
+                .unwrap();
+            db.add_coverage(
+                &mantra_schema::coverage::TestRunPk {
+                    name: "ci".to_string(),
+                    date,
+                },
+                shard,
+                std::path::Path::new("src/lib.rs"),
+                1,
+                "a",
+            )
             .await
-            .map_err(|err| DbError::Delete(err.to_string()))?;
+            .unwrap();
+            db.pool().close().await;
+        }
 
-        Ok(())
+        let merged = test_db("merge_merged").await;
+        let res1 = merged.merge_from(shards[0].path()).await;
+        let res2 = merged.merge_from(shards[1].path()).await;
+        let counts: Result<(i64, i64, i64, i64), _> = sqlx::query_as(
+            "select (select count(*) from Traces), (select count(*) from Tests),
+                (select count(*) from TestCoverage), (select nr_of_tests from TestRuns)",
+        )
+        .fetch_one(merged.pool())
+        .await;
+
+        assert!(res1.is_ok() && res2.is_ok(), "Merging shards failed.");
+        assert_eq!(
+            counts.unwrap(),
+            (1, 2, 2, 2),
+            "Same trace not deduplicated, or tests of the same test run not combined."
+        );
+    }
+
+    #[tokio::test]
+    async fn query_trace_and_coverage_state() {
+        let db = test_db("query").await;
+
+        db.add_reqs(vec![req("a", &[]), req("a.b", &[])])
+            .await
+            .unwrap();
+        db.add_traces(
+            std::path::Path::new("src/lib.rs"),
+            &[mantra_schema::traces::TraceEntry {
+                ids: vec!["a.b".to_string()],
+                line: 1,
+                line_span: None,
+                item_name: None,
+                item_path: None,
+                test_name: None,
+            }],
+            1,
+        )
+        .await
+        .unwrap();
+
+        let parent_traced = db.is_traced("a").await;
+        let parent_covered = db.is_covered("a").await;
+        let overview = db.coverage_overview().await;
+        let unknown = db.is_traced("unknown").await;
+
+        assert!(
+            parent_traced.unwrap(),
+            "Parent not indirectly traced through its only child."
+        );
+        assert!(
+            !parent_covered.unwrap(),
+            "Requirement without tests covered."
+        );
+        let overview = overview.unwrap();
+        assert_eq!(overview.req_cnt, 2, "Wrong number of requirements.");
+        assert_eq!(
+            overview.traced_cnt, 2,
+            "Wrong number of traced requirements."
+        );
+        assert!(
+            matches!(unknown, Err(super::DbError::UnknownRequirement(_))),
+            "Unknown requirement not reported."
+        );
+    }
+
+    #[tokio::test]
+    async fn coverage_with_mixed_separators() {
+        let db = test_db("separator").await;
+
+        db.add_reqs(vec![req("a", &[])]).await.unwrap();
+        db.add_traces(
+            std::path::Path::new("src\\cmd/mod.rs"),
+            &[mantra_schema::traces::TraceEntry {
+                ids: vec!["a".to_string()],
+                line: 1,
+                line_span: None,
+                item_name: None,
+                item_path: None,
+                test_name: None,
+            }],
+            1,
+        )
+        .await
+        .unwrap();
+
+        let test_run = mantra_schema::coverage::TestRunPk {
+            name: "unit".to_string(),
+            date: time::OffsetDateTime::UNIX_EPOCH,
+        };
+        db.add_test_run(&test_run.name, &test_run.date, 2, None, None)
+            .await
+            .unwrap();
+
+        let mut linked = Vec::new();
+        for (test, filepath) in [
+            ("backslash", "src\\cmd\\mod.rs"),
+            ("dot", "./src/cmd/mod.rs"),
+        ] {
+            db.add_test(
+                &test_run,
+                test,
+                std::path::Path::new("tests/test.rs"),
+                1,
+                mantra_schema::coverage::TestState::Passed,
+                &[],
+            )
+            .await
+            .unwrap();
+            linked.push(
+                db.add_coverage(&test_run, test, std::path::Path::new(filepath), 1, "a")
+                    .await,
+            );
+        }
+
+        for res in linked {
+            assert!(
+                res.unwrap(),
+                "Coverage with differing separators added as unrelated coverage."
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn materialized_views() {
+        let db = test_db("materialize").await;
+
+        db.add_reqs(vec![req("a", &[]), req("a.b", &[])])
+            .await
+            .unwrap();
+        db.add_traces(
+            std::path::Path::new("src/lib.rs"),
+            &[mantra_schema::traces::TraceEntry {
+                ids: vec!["a.b".to_string()],
+                line: 1,
+                line_span: None,
+                item_name: None,
+                item_path: None,
+                test_name: None,
+            }],
+            1,
+        )
+        .await
+        .unwrap();
+
+        let snapshot = db.materialized_snapshot().await.unwrap();
+        let view_cnt = sqlx::query!(
+            r#"select count(*) as "cnt!: i64" from sqlite_master where type = 'view' and name = 'TracedRequirements'"#
+        )
+        .fetch_one(snapshot.db.pool())
+        .await;
+        let parent_traced = snapshot.db.is_traced("a").await;
+        let overview = snapshot.db.coverage_overview().await;
+
+        snapshot.remove().await;
+
+        assert_eq!(view_cnt.unwrap().cnt, 0, "View not materialized.");
+        assert!(
+            parent_traced.unwrap(),
+            "Materialized view content differs from view."
+        );
+        assert_eq!(
+            overview.unwrap().traced_cnt,
+            2,
+            "Wrong number of traced requirements in materialized views."
+        );
+    }
+
+    #[tokio::test]
+    async fn full_clear_empties_all_tables() {
+        use clap::ValueEnum;
+
+        let db = test_db("full_clear").await;
+
+        db.add_reqs(vec![req("a", &[])]).await.unwrap();
+        db.add_traces(
+            std::path::Path::new("src/lib.rs"),
+            &[mantra_schema::traces::TraceEntry {
+                ids: vec!["a".to_string()],
+                line: 1,
+                line_span: None,
+                item_name: None,
+                item_path: None,
+                test_name: None,
+            }],
+            1,
+        )
+        .await
+        .unwrap();
+        db.set_suppressions(&[crate::cfg::ValidationSuppression {
+            id: "a".to_string(),
+            reason: "Migration".to_string(),
+        }])
+        .await
+        .unwrap();
+        db.complete_collect_step("requirements", 1).await.unwrap();
+        db.record_status(1).await.unwrap();
+
+        db.clear_tables(super::DbTable::value_variants())
+            .await
+            .unwrap();
+
+        let tables: Vec<(String,)> = sqlx::query_as(
+            "select name from sqlite_master where type = 'table' and name not like 'sqlite_%' and name not like '_sqlx_%'",
+        )
+        .fetch_all(db.pool())
+        .await
+        .unwrap();
+        let mut filled_tables = Vec::new();
+        for (table,) in tables {
+            let (cnt,): (i64,) = sqlx::query_as(&format!("select count(*) from {table}"))
+                .fetch_one(db.pool())
+                .await
+                .unwrap();
+            if cnt > 0 {
+                filled_tables.push(table);
+            }
+        }
+
+        assert!(
+            filled_tables.is_empty(),
+            "Tables not cleared: {filled_tables:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn same_test_run_on_branches() {
+        let db = test_db("branch_test_runs").await;
+        let feature_db = super::MantraDb::new(&super::Config {
+            url: Some(format!("sqlite://{}", db.path().display())),
+            connect_attempts: 1,
+            retry_delay_ms: 0,
+            branch: Some("feature".to_string()),
+        })
+        .await
+        .unwrap();
+
+        let test_run = mantra_schema::coverage::TestRunPk {
+            name: "ci".to_string(),
+            date: time::OffsetDateTime::now_utc(),
+        };
+        for (branch_db, state) in [
+            (&*db, mantra_schema::coverage::TestState::Passed),
+            (&feature_db, mantra_schema::coverage::TestState::Failed),
+        ] {
+            branch_db
+                .add_test_run(&test_run.name, &test_run.date, 1, None, None)
+                .await
+                .unwrap();
+            branch_db
+                .add_test(
+                    &test_run,
+                    "test",
+                    std::path::Path::new("tests/it.rs"),
+                    1,
+                    state,
+                    &[],
+                )
+                .await
+                .unwrap();
+        }
+
+        let tests: Vec<(String, bool)> =
+            sqlx::query_as("select branch, passed from Tests order by branch")
+                .fetch_all(db.pool())
+                .await
+                .unwrap();
+        feature_db.pool().close().await;
+
+        assert_eq!(
+            tests,
+            vec![("".to_string(), true), ("feature".to_string(), false)],
+            "Test run with the same name and date not stored per branch."
+        );
+    }
+
+    #[tokio::test]
+    async fn traces_per_branch() {
+        let db = test_db_on_branch("branch_traces", Some("feature")).await;
+        let main_db = super::MantraDb::new(&super::Config {
+            url: Some(format!("sqlite://{}", db.path().display())),
+            connect_attempts: 1,
+            retry_delay_ms: 0,
+            branch: None,
+        })
+        .await
+        .unwrap();
+
+        db.add_reqs(vec![req("a", &[]), req("a.b", &[])])
+            .await
+            .unwrap();
+        db.add_traces(
+            std::path::Path::new("src/lib.rs"),
+            &[mantra_schema::traces::TraceEntry {
+                ids: vec!["a.b".to_string()],
+                line: 1,
+                line_span: None,
+                item_name: None,
+                item_path: None,
+                test_name: None,
+            }],
+            1,
+        )
+        .await
+        .unwrap();
+
+        let feature_traced = db.is_traced("a").await;
+        let feature_overview = db.coverage_overview().await;
+        let main_traced = main_db.is_traced("a").await;
+        let main_overview = main_db.coverage_overview().await;
+        main_db.pool().close().await;
+
+        assert!(feature_traced.unwrap(), "Trace of the branch not found.");
+        assert_eq!(
+            feature_overview.unwrap().traced_cnt,
+            2,
+            "Wrong number of traced requirements on the branch."
+        );
+        assert!(
+            !main_traced.unwrap(),
+            "Trace of another branch affects the traced state."
+        );
+        assert_eq!(
+            main_overview.unwrap().traced_cnt,
+            0,
+            "Trace of another branch affects the overview."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_retention() {
+        let db = test_db("retention").await;
+
+        let now = time::OffsetDateTime::now_utc();
+        for days in [1, 2, 40] {
+            db.add_test_run("unit", &(now - time::Duration::days(days)), 0, None, None)
+                .await
+                .unwrap();
+        }
+        db.add_test_run("e2e", &(now - time::Duration::days(40)), 0, None, None)
+            .await
+            .unwrap();
+        // same run on another branch is only the latest run of its branch
+        sqlx::query("insert into TestRuns (name, date, nr_of_tests, branch) select name, date, nr_of_tests, 'feature' from TestRuns where name = 'unit' order by date limit 1")
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        let by_count = db.prune_test_runs(Some(2), None).await;
+        let by_age = db.prune_test_runs(None, Some(30)).await;
+        let no_policy = db.prune_test_runs(None, None).await;
+
+        let by_count = by_count.unwrap();
+        assert_eq!(by_count.len(), 1, "Wrong number of runs deleted by count.");
+        assert_eq!(
+            (by_count[0].name.as_str(), by_count[0].branch.as_str()),
+            ("unit", ""),
+            "Wrong test run deleted by count."
+        );
+        let by_age = by_age.unwrap();
+        assert_eq!(
+            by_age
+                .iter()
+                .map(|run| (run.name.as_str(), run.branch.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("e2e", ""), ("unit", "feature")],
+            "Wrong test runs deleted by age."
+        );
+        assert!(
+            no_policy.unwrap().is_empty(),
+            "Test runs deleted without retention policy."
+        );
+    }
+
+    #[tokio::test]
+    async fn connect_retried_with_backoff() {
+        let path = std::env::temp_dir().join(format!(
+            "mantra_connect_retry_test_{}.db",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        // without `mode=rwc`, connecting fails until the database file exists
+        let cfg = |connect_attempts| super::Config {
+            url: Some(format!("sqlite://{}", path.display())),
+            connect_attempts,
+            retry_delay_ms: 20,
+            branch: None,
+        };
+
+        let start = std::time::Instant::now();
+        let failed = super::MantraDb::new(&cfg(3)).await;
+        let elapsed = start.elapsed();
+
+        let writer = {
+            let path = path.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                std::fs::write(path, "").unwrap();
+            })
+        };
+        let retried = super::MantraDb::new(&cfg(10)).await;
+        writer.await.unwrap();
+        if let Ok(db) = &retried {
+            db.pool().close().await;
+        }
+        let _ = std::fs::remove_file(&path);
+
+        assert!(
+            matches!(failed, Err(super::DbError::Connect(ref msg)) if msg.contains("'3' attempts")),
+            "Connection error not returned after all attempts."
+        );
+        assert!(
+            elapsed >= std::time::Duration::from_millis(60),
+            "Delay not doubled between attempts."
+        );
+        assert!(
+            retried.is_ok(),
+            "Connection not retried until the database was available."
+        );
     }
 }
@@ -1,13 +1,18 @@
 use cfg::MantraConfigPath;
 use cmd::{
-    coverage::CoverageError, report::ReportError, requirements::RequirementsError,
-    review::ReviewError, trace::TraceError,
+    annotate::AnnotateError, coverage::CoverageError, doctor::DoctorError, export::ExportError,
+    merge::MergeError, outline::OutlineError, report::ReportError, requirements::RequirementsError,
+    review::ReviewError, summary::SummaryError, trace::TraceError,
 };
 use db::DbError;
 
 pub mod cfg;
 pub mod cmd;
 pub mod db;
+pub mod profile;
+pub mod progress;
+#[cfg(test)]
+mod test_util;
 
 #[derive(Debug, thiserror::Error)]
 pub enum MantraError {
@@ -21,6 +26,8 @@ pub enum MantraError {
     AddProject(DbError),
     #[error("Failed to update coverage data. Cause: {}", .0)]
     Coverage(CoverageError),
+    #[error("Failed to report uncovered traces. Cause: {}", .0)]
+    UncoveredReport(CoverageError),
     #[error("Failed to deprecate requirements. Cause: {}", .0)]
     DeprecateReq(DbError),
     #[error("Failed to add manual requirements. Cause: {}", .0)]
@@ -37,9 +44,200 @@ pub enum MantraError {
     Prune(DbError),
     #[error("Failed to clear the database. Cause: {}", .0)]
     Clear(DbError),
+    #[error("Failed to track the collect progress. Cause: {}", .0)]
+    Progress(DbError),
+    #[error("Failed to record the requirement status. Cause: {}", .0)]
+    StatusHistory(DbError),
+    #[error("Failed to store the validation suppressions. Cause: {}", .0)]
+    Suppressions(DbError),
+    #[error("Setup problems found. {}", .0)]
+    Doctor(DoctorError),
+    #[error("Trace check failed. Cause: {}", .0)]
+    CheckTraces(TraceError),
+    #[error("Failed to export the requirement outline. Cause: {}", .0)]
+    Outline(OutlineError),
+    #[error("Failed to create the summary. Cause: {}", .0)]
+    Summary(SummaryError),
+    #[error("Failed to annotate the source file. Cause: {}", .0)]
+    Annotate(AnnotateError),
+    #[error("Failed to merge the databases. Cause: {}", .0)]
+    Merge(MergeError),
+    #[error("Failed to export the collected data. Cause: {}", .0)]
+    Export(ExportError),
+    #[error("Coverage thresholds not met. {}", .0)]
+    Threshold(String),
+    #[error("Failed to compute the coverage ratios for the thresholds. Cause: {}", .0)]
+    ThresholdOverview(DbError),
+}
+
+/// Exit codes of the mantra binary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MantraExitCode {
+    /// The command finished successfully.
+    Success = 0,
+    /// Invalid arguments, configuration, or input files.
+    Usage = 1,
+    /// Collected data is invalid, or checks failed.
+    Validation = 2,
+    /// The database could not be accessed or updated.
+    Db = 3,
+}
+
+impl MantraExitCode {
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+}
+
+impl From<&DbError> for MantraExitCode {
+    fn from(value: &DbError) -> Self {
+        match value {
+            DbError::Validate(_) | DbError::UnknownRequirement(_) => MantraExitCode::Validation,
+            _ => MantraExitCode::Db,
+        }
+    }
+}
+
+impl From<&TraceError> for MantraExitCode {
+    fn from(value: &TraceError) -> Self {
+        match value {
+            TraceError::DbError(err) => err.into(),
+            TraceError::IdMismatch(_)
+            | TraceError::UnknownRequirements(_)
+            | TraceError::DeprecatedReqTraced(..) => MantraExitCode::Validation,
+            _ => MantraExitCode::Usage,
+        }
+    }
+}
+
+impl From<&CoverageError> for MantraExitCode {
+    fn from(value: &CoverageError) -> Self {
+        match value {
+            CoverageError::Db(err) => err.into(),
+            _ => MantraExitCode::Usage,
+        }
+    }
+}
+
+impl From<&MantraError> for MantraExitCode {
+    fn from(value: &MantraError) -> Self {
+        match value {
+            MantraError::DbSetup(_) => MantraExitCode::Db,
+            MantraError::AddProject(err)
+            | MantraError::DeprecateReq(err)
+            | MantraError::AddManualReq(err)
+            | MantraError::Delete(err)
+            | MantraError::Prune(err)
+            | MantraError::Clear(err)
+            | MantraError::Progress(err)
+            | MantraError::StatusHistory(err)
+            | MantraError::Suppressions(err)
+            | MantraError::ThresholdOverview(err) => err.into(),
+            MantraError::Trace(err) | MantraError::CheckTraces(err) => err.into(),
+            MantraError::Extract(err) => match err {
+                RequirementsError::DbError(err) => err.into(),
+                RequirementsError::IdMismatch(_)
+                | RequirementsError::DepthExceeded(_)
+                | RequirementsError::UnknownState { .. }
+                | RequirementsError::Conflict { .. }
+                | RequirementsError::InvalidDesignLink { .. } => MantraExitCode::Validation,
+                _ => MantraExitCode::Usage,
+            },
+            MantraError::Coverage(err) | MantraError::UncoveredReport(err) => err.into(),
+            MantraError::Review(err) => match err {
+                ReviewError::Db(err) => err.into(),
+                ReviewError::MissingEvidence { .. } => MantraExitCode::Validation,
+                _ => MantraExitCode::Usage,
+            },
+            MantraError::Report(err) => match err {
+                ReportError::Db(_) => MantraExitCode::Db,
+                ReportError::Materialize(err)
+                | ReportError::Compare(err)
+                | ReportError::Archive(err)
+                | ReportError::Changes(err) => err.into(),
+                ReportError::Validate(_) | ReportError::Invalid(_) | ReportError::Threshold(_) => {
+                    MantraExitCode::Validation
+                }
+                _ => MantraExitCode::Usage,
+            },
+            MantraError::Collect(_) => MantraExitCode::Usage,
+            MantraError::Doctor(_) | MantraError::Threshold(_) => MantraExitCode::Validation,
+            MantraError::Outline(err) => match err {
+                OutlineError::Db(err) => err.into(),
+                OutlineError::UnknownRoot(_) => MantraExitCode::Usage,
+            },
+            MantraError::Summary(err) => match err {
+                SummaryError::Db(err) => err.into(),
+                SummaryError::Tests(_) => MantraExitCode::Db,
+                SummaryError::Serialize(_) => MantraExitCode::Usage,
+            },
+            MantraError::Annotate(err) => match err {
+                AnnotateError::Db(err) => err.into(),
+                AnnotateError::CouldNotAccessFile(_) => MantraExitCode::Usage,
+            },
+            MantraError::Merge(err) => match err {
+                MergeError::MissingInput(_) | MergeError::InputIsOutput(_) => MantraExitCode::Usage,
+                MergeError::Output(err) | MergeError::Input(_, err) => err.into(),
+            },
+            MantraError::Export(err) => match err {
+                ExportError::Db(err) => err.into(),
+                ExportError::Requirements(_) => MantraExitCode::Db,
+                ExportError::Serialize(_) | ExportError::Write(_) => MantraExitCode::Usage,
+            },
+        }
+    }
 }
 
 pub async fn run(cfg: cfg::Config) -> Result<(), MantraError> {
+    progress::enable(!cfg.quiet);
+    profile::enable(cfg.profile);
+
+    // listing traces of one file does not need the database
+    if let cmd::Cmd::Trace(cfg::TraceCliConfig {
+        cmd: Some(cmd::trace::TraceCmd::List(list_cfg)),
+        ..
+    }) = &cfg.cmd
+    {
+        return cmd::trace::list(list_cfg).map_err(MantraError::Trace);
+    }
+
+    // explaining the trace collection does not collect anything
+    if let cmd::Cmd::Trace(trace_cfg) = &cfg.cmd {
+        if trace_cfg.explain {
+            let collect_file = read_config_file(&trace_cfg.config).await?;
+            return cmd::trace::explain(&collect_file.traces, &trace_cfg.walk_limits)
+                .map_err(MantraError::Trace);
+        }
+
+        // traces that are not stored only need the database to detect traces of deprecated requirements
+        if trace_cfg.no_store && !trace_cfg.strict {
+            return trace(None, trace_cfg.clone()).await;
+        }
+    }
+
+    // known requirements are taken from the given file instead of the database
+    if let cmd::Cmd::CheckTraces(check_cfg) = &cfg.cmd {
+        if check_cfg.requirements.is_some() {
+            return cmd::trace::check_traces(None, check_cfg)
+                .await
+                .map_err(MantraError::CheckTraces);
+        }
+    }
+
+    // merge works on the given database files instead of the configured database
+    if let cmd::Cmd::Merge(merge_cfg) = &cfg.cmd {
+        return cmd::merge::merge(merge_cfg)
+            .await
+            .map_err(MantraError::Merge);
+    }
+
+    // doctor opens the database itself to report connection problems as failed check
+    if let cmd::Cmd::Doctor(doctor_cfg) = &cfg.cmd {
+        return cmd::doctor::doctor(&cfg.db, doctor_cfg)
+            .await
+            .map_err(MantraError::Doctor);
+    }
+
     let db = db::MantraDb::new(&cfg.db)
         .await
         .map_err(MantraError::DbSetup)?;
@@ -49,46 +247,231 @@ pub async fn run(cfg: cfg::Config) -> Result<(), MantraError> {
             .await
             .map_err(MantraError::Report),
         cmd::Cmd::Collect(collect_cfg) => collect(&db, collect_cfg).await,
-        cmd::Cmd::Prune => db.prune().await.map_err(MantraError::Prune),
-        cmd::Cmd::Clear => db.clear().await.map_err(MantraError::Clear),
+        cmd::Cmd::Trace(trace_cfg) => trace(Some(&db), trace_cfg).await,
+        cmd::Cmd::Coverage { cmd } => match cmd {
+            cmd::coverage::CoverageCmd::Report(uncovered_cfg) => {
+                cmd::coverage::report(&db, &uncovered_cfg)
+                    .await
+                    .map_err(MantraError::UncoveredReport)
+            }
+        },
+        cmd::Cmd::Prune(prune_cfg) => {
+            if prune_cfg.test_runs {
+                let deleted = db
+                    .prune_test_runs(prune_cfg.keep_last, prune_cfg.keep_days)
+                    .await
+                    .map_err(MantraError::Prune)?;
+                println!("{deleted}");
+            }
+
+            db.prune().await.map_err(MantraError::Prune)
+        }
+        cmd::Cmd::Clear(clear_cfg) => db
+            .clear_tables(&clear_cfg.tables())
+            .await
+            .map_err(MantraError::Clear),
+        cmd::Cmd::CheckTraces(check_cfg) => cmd::trace::check_traces(Some(&db), &check_cfg)
+            .await
+            .map_err(MantraError::CheckTraces),
+        cmd::Cmd::Outline(outline_cfg) => cmd::outline::outline(&db, &outline_cfg)
+            .await
+            .map_err(MantraError::Outline),
+        cmd::Cmd::Summary(summary_cfg) => cmd::summary::summary(&db, &summary_cfg)
+            .await
+            .map_err(MantraError::Summary),
+        cmd::Cmd::Annotate(annotate_cfg) => cmd::annotate::annotate(&db, &annotate_cfg)
+            .await
+            .map_err(MantraError::Annotate),
+        cmd::Cmd::Export { cmd } => cmd::export::export(&db, &cmd)
+            .await
+            .map_err(MantraError::Export),
+        cmd::Cmd::Doctor(_) => unreachable!("Doctor is handled before the database is opened."),
+        cmd::Cmd::Merge(_) => unreachable!("Merge is handled before the database is opened."),
     }
 }
 
-async fn collect(db: &db::MantraDb, cfg: MantraConfigPath) -> Result<(), MantraError> {
+async fn read_config_file(cfg: &MantraConfigPath) -> Result<cfg::MantraConfigFile, MantraError> {
     let collect_cfg = tokio::fs::read_to_string(&cfg.filepath)
         .await
         .map_err(|_| {
             MantraError::Collect(format!("Could not read file '{}'.", cfg.filepath.display()))
         })?;
-    let collect_file: cfg::MantraConfigFile = toml::from_str(&collect_cfg).map_err(|err| {
+    toml::from_str(&collect_cfg).map_err(|err| {
         MantraError::Collect(format!(
             "Could not read the TOML configuration. Cause: {}",
             err
         ))
-    })?;
+    })
+}
+
+async fn trace(db: Option<&db::MantraDb>, cfg: cfg::TraceCliConfig) -> Result<(), MantraError> {
+    let collect_file = read_config_file(&cfg.config).await?;
+    let id_pattern = collect_file
+        .id_pattern()
+        .map_err(|err| MantraError::Collect(format!("Invalid ID pattern. Cause: {}", err)))?;
+
+    let emitter = match cfg.emit_traces {
+        Some(format) => Some(
+            cmd::trace::TraceEmitter::new(format, cfg.emit_to.as_deref())
+                .map_err(MantraError::Trace)?,
+        ),
+        None => None,
+    };
+
+    let changed_files = match &cfg.changed_files {
+        Some(filepath) => {
+            Some(cmd::trace::read_changed_files(filepath).map_err(MantraError::Trace)?)
+        }
+        None => None,
+    };
+
+    if let Some(db) = db.filter(|_| !cfg.resume) {
+        db.clear_collect_progress()
+            .await
+            .map_err(MantraError::Progress)?;
+    }
+
+    let mut options = cmd::trace::CollectOptions {
+        id_pattern: id_pattern.as_ref(),
+        id_case: collect_file.id_case,
+        emitter,
+        store: !cfg.no_store,
+        strict: cfg.strict,
+        walk_limits: cfg.walk_limits,
+        resume: cfg.resume,
+        changed_files,
+        ..Default::default()
+    };
 
-    cmd::requirements::collect(db, &collect_file.requirements)
+    cmd::trace::collect(db, &collect_file.traces, &mut options)
+        .await
+        .map_err(MantraError::Trace)?;
+
+    match db {
+        Some(db) => db
+            .clear_collect_progress()
+            .await
+            .map_err(MantraError::Progress),
+        None => Ok(()),
+    }
+}
+
+async fn collect(db: &db::MantraDb, cfg: cfg::CollectCliConfig) -> Result<(), MantraError> {
+    for (name, ratio) in [
+        ("--min-traced-ratio", cfg.min_traced_ratio),
+        ("--min-covered-ratio", cfg.min_covered_ratio),
+    ] {
+        if ratio.is_some_and(|ratio| !(0.0..=1.0).contains(&ratio)) {
+            return Err(MantraError::Collect(format!(
+                "`{name}` must be between 0 and 1."
+            )));
+        }
+    }
+
+    let collect_file = read_config_file(&cfg.config).await?;
+
+    let id_pattern = collect_file
+        .id_pattern()
+        .map_err(|err| MantraError::Collect(format!("Invalid ID pattern. Cause: {}", err)))?;
+
+    let depth_limit = collect_file.depth_limit();
+
+    let manual_rules = collect_file
+        .manual_rules()
+        .map_err(|err| MantraError::Collect(format!("Invalid manual rule. Cause: {}", err)))?;
+
+    let mut suppressions = collect_file.suppressions.clone();
+    for suppression in &mut suppressions {
+        if suppression.reason.trim().is_empty() {
+            return Err(MantraError::Collect(format!(
+                "Suppression of requirement `{}` has no reason.",
+                suppression.id
+            )));
+        }
+        suppression.id = collect_file.id_case.normalize(&suppression.id);
+    }
+
+    if !cfg.resume {
+        db.clear_collect_progress()
+            .await
+            .map_err(MantraError::Progress)?;
+    }
+
+    let reqs_collected = cfg.resume
+        && db
+            .collect_progress(REQUIREMENTS_STEP)
+            .await
+            .map_err(MantraError::Progress)?
+            .completed;
+
+    if reqs_collected {
+        log::info!("Requirements were already collected.");
+    } else {
+        let timer = profile::phase("requirements");
+        cmd::requirements::collect(
+            db,
+            &collect_file.requirements,
+            cmd::requirements::CollectOptions {
+                id_pattern: id_pattern.as_ref(),
+                id_case: collect_file.id_case,
+                depth_limit: depth_limit.as_ref(),
+                manual_rules: manual_rules.as_ref(),
+                allowed_states: collect_file.allowed_states(),
+                validate_schema: cfg.validate_schema,
+                design_links: &collect_file.design_links,
+                check_design_links: cfg.check_design_links,
+            },
+        )
         .await
         .map_err(MantraError::Extract)?;
+        timer.finish();
+
+        db.complete_collect_step(REQUIREMENTS_STEP, db.max_req_generation().await)
+            .await
+            .map_err(MantraError::Progress)?;
+    }
 
-    cmd::trace::collect(db, &collect_file.traces)
+    db.set_suppressions(&suppressions)
         .await
-        .map_err(MantraError::Trace)?;
+        .map_err(MantraError::Suppressions)?;
 
-    if let Some(coverage) = collect_file.coverage {
-        for file in coverage.files {
-            let coverage_changes = cmd::coverage::collect_from_path(db, &file)
-                .await
-                .map_err(MantraError::Coverage)?;
+    let mut trace_options = cmd::trace::CollectOptions {
+        id_pattern: id_pattern.as_ref(),
+        id_case: collect_file.id_case,
+        strict: cfg.strict,
+        walk_limits: cfg.walk_limits,
+        resume: cfg.resume,
+        ..Default::default()
+    };
+
+    let timer = profile::phase("traces");
+    cmd::trace::collect(Some(db), &collect_file.traces, &mut trace_options)
+        .await
+        .map_err(MantraError::Trace)?;
+    timer.finish();
 
-            println!("{coverage_changes}");
+    if let Some(mut coverage) = collect_file.coverage {
+        let _timer = profile::phase("coverage");
+        if cfg.wait_coverage.is_some() {
+            coverage.wait_secs = cfg.wait_coverage;
         }
+
+        cmd::coverage::collect(
+            db,
+            &coverage,
+            cfg.skip_missing_coverage,
+            collect_file.id_case,
+        )
+        .await
+        .map_err(MantraError::Coverage)?;
     }
 
     if let Some(review) = collect_file.review {
-        let added_review_cnt = cmd::review::collect(db, review)
-            .await
-            .map_err(MantraError::Review)?;
+        let _timer = profile::phase("reviews");
+        let added_review_cnt =
+            cmd::review::collect(db, review, cfg.check_evidence, collect_file.id_case)
+                .await
+                .map_err(MantraError::Review)?;
 
         if added_review_cnt == 0 {
             println!("No review was added.");
@@ -97,5 +480,117 @@ async fn collect(db: &db::MantraDb, cfg: MantraConfigPath) -> Result<(), MantraE
         }
     }
 
+    db.record_status(db.max_req_generation().await)
+        .await
+        .map_err(MantraError::StatusHistory)?;
+
+    db.clear_collect_progress()
+        .await
+        .map_err(MantraError::Progress)?;
+
+    if cfg.min_traced_ratio.is_some() || cfg.min_covered_ratio.is_some() {
+        check_thresholds(db, cfg.min_traced_ratio, cfg.min_covered_ratio).await?;
+    }
+
     Ok(())
 }
+
+async fn check_thresholds(
+    db: &db::MantraDb,
+    min_traced_ratio: Option<f64>,
+    min_covered_ratio: Option<f64>,
+) -> Result<(), MantraError> {
+    let overview = db
+        .coverage_overview()
+        .await
+        .map_err(MantraError::ThresholdOverview)?;
+    let violations = threshold_violations(&overview, min_traced_ratio, min_covered_ratio);
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(MantraError::Threshold(violations.join(" ")))
+    }
+}
+
+/// Returns one message per ratio that is below its minimum.
+pub(crate) fn threshold_violations(
+    overview: &cmd::report::RequirementsOverview,
+    min_traced_ratio: Option<f64>,
+    min_covered_ratio: Option<f64>,
+) -> Vec<String> {
+    [
+        ("Traced", overview.traced_ratio, min_traced_ratio),
+        ("Covered", overview.covered_ratio, min_covered_ratio),
+    ]
+    .into_iter()
+    .filter_map(|(metric, ratio, min)| {
+        let min = min?;
+        (ratio < min).then(|| {
+            format!(
+                "{metric} ratio {:.1}% is {:.1} percentage points below the minimum of {:.1}%.",
+                ratio * 100.0,
+                (min - ratio) * 100.0,
+                min * 100.0
+            )
+        })
+    })
+    .collect()
+}
+
+/// Collect step of the requirements, used to skip requirements when resuming an interrupted collect.
+const REQUIREMENTS_STEP: &str = "requirements";
+
+#[cfg(test)]
+mod test {
+    use super::{threshold_violations, DbError, MantraError, MantraExitCode, TraceError};
+
+    #[test]
+    fn failed_checks_differ_from_db_errors() {
+        let check = MantraError::CheckTraces(TraceError::UnknownRequirements(1));
+        let db = MantraError::CheckTraces(TraceError::DbError(DbError::Query(String::new())));
+
+        assert_eq!(
+            MantraExitCode::from(&check),
+            MantraExitCode::Validation,
+            "Failed check not reported as validation failure."
+        );
+        assert_eq!(
+            MantraExitCode::from(&db),
+            MantraExitCode::Db,
+            "Database error not reported as such."
+        );
+    }
+
+    #[test]
+    fn ratios_below_threshold() {
+        let overview = crate::cmd::report::RequirementsOverview {
+            req_cnt: 20,
+            untraceable_cnt: 0,
+            traced_cnt: 17,
+            traced_ratio: 0.85,
+            covered_cnt: 14,
+            covered_ratio: 0.7,
+            passed_cnt: 13,
+            passed_ratio: 0.65,
+            verified_cnt: None,
+            verified_ratio: 0.0,
+            span_coverage: None,
+        };
+
+        assert!(
+            threshold_violations(&overview, Some(0.85), Some(0.5)).is_empty(),
+            "Ratios at or above the minimum reported as violation."
+        );
+        assert_eq!(
+            threshold_violations(&overview, Some(0.9), Some(0.75)),
+            vec![
+                "Traced ratio 85.0% is 5.0 percentage points below the minimum of 90.0%."
+                    .to_string(),
+                "Covered ratio 70.0% is 5.0 percentage points below the minimum of 75.0%."
+                    .to_string()
+            ],
+            "Violated thresholds not reported."
+        );
+    }
+}
@@ -1,16 +1,43 @@
 use clap::Parser;
+use mantra::MantraExitCode;
 
 #[tokio::main]
 async fn main() {
-    let cfg = mantra::cfg::Config::parse();
+    let cfg = match mantra::cfg::Config::try_parse() {
+        Ok(cfg) => cfg,
+        Err(err) => {
+            let _ = err.print();
+
+            // help and version output are also returned as error
+            let exit_code = if err.use_stderr() {
+                MantraExitCode::Usage
+            } else {
+                MantraExitCode::Success
+            };
+            std::process::exit(exit_code.code());
+        }
+    };
+
+    let log_level = if cfg.quiet {
+        log::LevelFilter::Warn
+    } else {
+        log::LevelFilter::Info
+    };
 
     env_logger::builder()
-        .filter_level(log::LevelFilter::Info)
+        .filter_level(log_level)
         .format_target(false)
         .init();
 
-    if let Err(err) = mantra::run(cfg).await {
+    let res = mantra::run(cfg).await;
+
+    // printed to stderr to keep machine-readable output on stdout intact
+    if let Some(profile) = mantra::profile::summary() {
+        eprint!("{profile}");
+    }
+
+    if let Err(err) = res {
         println!("{err}");
-        std::process::exit(-1);
+        std::process::exit(MantraExitCode::from(&err).code());
     }
 }
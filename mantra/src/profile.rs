@@ -0,0 +1,168 @@
+//! Timing of the phases of long running commands.
+//!
+//! Phases are only recorded if enabled via [`enable`],
+//! and the recorded timings are printed using [`summary`].
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, MutexGuard,
+    },
+    time::{Duration, Instant},
+};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static PROFILE: Mutex<Profile> = Mutex::new(Profile {
+    active: Vec::new(),
+    phases: Vec::new(),
+});
+
+struct Profile {
+    /// Names of the currently running phases, with the innermost phase last.
+    active: Vec<String>,
+    phases: Vec<Phase>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Phase {
+    /// Names of the enclosing phases and the name of this phase.
+    path: Vec<String>,
+    /// Start of the first run of this phase.
+    start: Instant,
+    /// Summed up duration of all runs of this phase.
+    duration: Duration,
+}
+
+fn profile() -> MutexGuard<'static, Profile> {
+    PROFILE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Enables recording the duration of phases.
+pub fn enable(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Starts timing the phase with the given name.
+/// The duration is recorded once the returned timer is finished or dropped.
+/// Phases started before the timer is finished are recorded as part of this phase.
+///
+/// Durations of phases with the same name in the same enclosing phase are summed up,
+/// so phases may be timed per file, and are shown once in the summary.
+pub(crate) fn phase(name: impl Into<String>) -> PhaseTimer {
+    if is_enabled() {
+        profile().active.push(name.into());
+    }
+
+    PhaseTimer {
+        recorded: is_enabled(),
+        start: Instant::now(),
+    }
+}
+
+pub(crate) struct PhaseTimer {
+    /// `false` if profiling is disabled.
+    recorded: bool,
+    start: Instant,
+}
+
+impl PhaseTimer {
+    pub(crate) fn finish(self) {
+        // duration is recorded on drop
+    }
+}
+
+impl Drop for PhaseTimer {
+    fn drop(&mut self) {
+        if !self.recorded {
+            return;
+        }
+
+        let elapsed = self.start.elapsed();
+        let mut profile = profile();
+        let path = profile.active.clone();
+        profile.active.pop();
+
+        match profile.phases.iter_mut().find(|phase| phase.path == path) {
+            Some(phase) => phase.duration += elapsed,
+            None => profile.phases.push(Phase {
+                path,
+                start: self.start,
+                duration: elapsed,
+            }),
+        }
+    }
+}
+
+/// Returns the recorded phases ordered by their start, and clears them.
+/// Phases started within another phase are indented.
+///
+/// `None` if profiling is disabled, or no phase was recorded.
+pub fn summary() -> Option<String> {
+    let phases = std::mem::take(&mut profile().phases);
+    if phases.is_empty() {
+        return None;
+    }
+
+    Some(format_summary(phases))
+}
+
+fn format_summary(mut phases: Vec<Phase>) -> String {
+    phases.sort_by_key(|phase| phase.start);
+
+    let mut out = String::from("Profile:\n");
+
+    for phase in phases {
+        let depth = phase.path.len();
+
+        out.push_str(&format!(
+            "{:indent$}{:<width$} {:>10.1}ms\n",
+            "",
+            phase.path.last().map(String::as_str).unwrap_or_default(),
+            phase.duration.as_secs_f64() * 1000.0,
+            indent = 2 * depth,
+            width = 40usize.saturating_sub(2 * depth),
+        ));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use super::{format_summary, Phase};
+
+    #[test]
+    fn nested_phases_indented() {
+        let start = Instant::now();
+        let phase = |path: &[&str], offset: u64, duration: u64| Phase {
+            path: path.iter().map(|name| name.to_string()).collect(),
+            start: start + Duration::from_millis(offset),
+            duration: Duration::from_millis(duration),
+        };
+
+        // enclosing phases are recorded after their inner phases finished
+        let phases = vec![
+            phase(&["traces", "traces (.rs)"], 5, 3),
+            phase(&["traces"], 0, 10),
+            phase(&["coverage"], 10, 2),
+        ];
+
+        assert_eq!(
+            format_summary(phases),
+            format!(
+                "Profile:\n  {:<38} {:>10.1}ms\n    {:<36} {:>10.1}ms\n  {:<38} {:>10.1}ms\n",
+                "traces", 10.0, "traces (.rs)", 3.0, "coverage", 2.0
+            ),
+            "Phases not ordered by start, or nested phases not indented."
+        );
+    }
+}
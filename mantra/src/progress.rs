@@ -0,0 +1,56 @@
+//! Progress indicators for long running commands.
+//!
+//! Indicators are hidden unless enabled via [`enable`],
+//! so library users do not get unexpected terminal output.
+
+use std::{
+    io::IsTerminal,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Enables progress indicators.
+/// Indicators stay hidden if stdout is not a terminal.
+pub fn enable(enabled: bool) {
+    ENABLED.store(
+        enabled && std::io::stdout().is_terminal(),
+        Ordering::Relaxed,
+    );
+}
+
+fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Creates a spinner for work of unknown length.
+pub(crate) fn spinner(msg: &'static str) -> ProgressBar {
+    if !is_enabled() {
+        return ProgressBar::hidden();
+    }
+
+    let spinner = ProgressBar::new_spinner().with_message(msg);
+    spinner.set_style(
+        ProgressStyle::with_template("{spinner} {msg}: {pos}").expect("Spinner template is valid."),
+    );
+    spinner.enable_steady_tick(Duration::from_millis(100));
+    spinner
+}
+
+/// Creates a progress bar for work of known length.
+pub(crate) fn bar(len: usize, msg: &'static str) -> ProgressBar {
+    if !is_enabled() {
+        return ProgressBar::hidden();
+    }
+
+    let bar = ProgressBar::new(len as u64).with_message(msg);
+    bar.set_style(
+        ProgressStyle::with_template("{msg} [{bar:40}] {pos}/{len}")
+            .expect("Progress bar template is valid.")
+            .progress_chars("=> "),
+    );
+    bar
+}
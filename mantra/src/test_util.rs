@@ -0,0 +1,76 @@
+//! Helpers shared by the tests of all modules.
+
+use std::path::{Path, PathBuf};
+
+use mantra_schema::requirements::Requirement;
+
+use crate::db::{Config, MantraDb};
+
+/// Database stored in a temporary SQLite file.
+/// The file is removed once the database is dropped.
+pub struct TestDb {
+    db: MantraDb,
+    path: PathBuf,
+}
+
+impl TestDb {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl std::ops::Deref for TestDb {
+    type Target = MantraDb;
+
+    fn deref(&self) -> &Self::Target {
+        &self.db
+    }
+}
+
+impl Drop for TestDb {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Creates a database of the default branch in a temporary file that is unique per name and test process.
+pub async fn test_db(name: &str) -> TestDb {
+    test_db_on_branch(name, None).await
+}
+
+/// Creates a database for the given branch in a temporary file that is unique per name and test process.
+pub async fn test_db_on_branch(name: &str, branch: Option<&str>) -> TestDb {
+    let path = std::env::temp_dir().join(format!("mantra_{name}_test_{}.db", std::process::id()));
+    // leftovers of an aborted test run must not affect the test
+    let _ = std::fs::remove_file(&path);
+
+    let db = MantraDb::new(&Config {
+        url: Some(format!("sqlite://{}?mode=rwc", path.display())),
+        connect_attempts: 1,
+        retry_delay_ms: 0,
+        branch: branch.map(|branch| branch.to_string()),
+    })
+    .await
+    .expect("Temporary test database must be creatable.");
+
+    TestDb { db, path }
+}
+
+/// Requirement with its ID as title, the given parents, and all optional fields unset.
+pub fn req(id: &str, parents: &[&str]) -> Requirement {
+    Requirement {
+        id: id.to_string(),
+        parents: (!parents.is_empty())
+            .then(|| parents.iter().map(|parent| parent.to_string()).collect()),
+        title: id.to_string(),
+        origin: "test".to_string(),
+        manual: None,
+        deprecated: false,
+        untraceable: false,
+        data: None,
+        priority: None,
+        state: None,
+        effort: None,
+        design_links: Vec::new(),
+    }
+}
@@ -20,6 +20,9 @@ fn main() {
         &std::path::PathBuf::from("ReviewSchema.json"),
     );
 
+    let sbor_schema = schemars::schema_for!(mantra_schema::sbor::SborSchema);
+    write_schema(&sbor_schema, &std::path::PathBuf::from("SborSchema.json"));
+
     let report_schema = schemars::schema_for!(mantra::cmd::report::ReportContext);
     write_schema(
         &report_schema,
@@ -48,6 +48,9 @@ pub struct Test {
     pub state: TestState,
     #[serde(default, alias = "covered-files")]
     pub covered_files: Vec<CoveredFile>,
+    /// Tags to categorize the test (e.g. unit, integration, e2e).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 #[derive(
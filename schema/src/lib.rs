@@ -3,6 +3,7 @@ use serde::Serializer;
 pub mod coverage;
 pub mod requirements;
 pub mod reviews;
+pub mod sbor;
 pub mod traces;
 
 pub type Line = u32;
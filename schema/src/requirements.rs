@@ -8,9 +8,7 @@ pub struct RequirementSchema {
 /// Type alias for a requirement ID.
 pub type ReqId = String;
 
-#[derive(
-    Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
-)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub struct Requirement {
     /// ID of the requirement.
     pub id: ReqId,
@@ -21,9 +19,44 @@ pub struct Requirement {
     /// Link to the origin the requirement is defined.
     pub origin: String,
     /// true: Marks the requirement to require manual verification.
-    pub manual: bool,
+    /// If not set, the manual rules of the *mantra* configuration decide.
+    #[serde(default)]
+    pub manual: Option<bool>,
     /// true: Marks the requirement to be deprecated.
     pub deprecated: bool,
+    /// true: Marks the requirement as not traceable in code by definition (e.g. organizational requirements).
+    /// Untraceable requirements are excluded from the traced ratio.
+    #[serde(default)]
+    pub untraceable: bool,
     /// Field to store custom information per requirement.
     pub data: Option<serde_json::Value>,
+    /// Optional priority of the requirement.
+    /// Lower values mean higher priority.
+    pub priority: Option<u8>,
+    /// Optional workflow state of the requirement (e.g. `draft` or `approved`).
+    /// Allowed states may be restricted in the *mantra* configuration.
+    pub state: Option<String>,
+    /// Optional effort estimate of the requirement (e.g. story points).
+    /// Used to weight coverage ratios by the work done instead of the number of requirements.
+    #[serde(default)]
+    pub effort: Option<f64>,
+    /// Optional links to supplementary design documentation of the requirement
+    /// (e.g. design documents or architecture decision records).
+    #[serde(default, alias = "design-links")]
+    pub design_links: Vec<DesignLink>,
+}
+
+/// Link from a requirement to a document describing its design.
+///
+/// Unlike the origin of a requirement, design links do not define the requirement,
+/// but document how it is realized.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
+)]
+pub struct DesignLink {
+    /// URL or filepath of the document.
+    pub url: String,
+    /// Optional title of the document.
+    #[serde(default)]
+    pub title: Option<String>,
 }
@@ -39,4 +39,8 @@ pub struct ReviewSchema {
 pub struct VerifiedRequirement {
     pub id: ReqId,
     pub comment: Option<String>,
+    /// URLs or filepaths of documents supporting the verification (e.g. test protocols).
+    /// Filepaths are relative to the review file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub evidence: Vec<String>,
 }
@@ -0,0 +1,124 @@
+use std::path::PathBuf;
+
+use crate::{requirements::ReqId, Line};
+
+/// Software bill of requirements (SBOR).
+///
+/// Analogous to a software bill of materials, the SBOR lists every requirement of a project
+/// with its origin, hierarchy, trace locations, and verification status.
+/// The format is independent of the *mantra* report, and only changes with the schema version.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct SborSchema {
+    #[serde(serialize_with = "crate::serialize_schema_version")]
+    pub version: Option<String>,
+    /// Project the requirements belong to.
+    #[serde(default)]
+    pub project: SborProject,
+    /// Creation date of the SBOR given in ISO8601 format.
+    #[serde(
+        alias = "creation-date",
+        serialize_with = "time::serde::iso8601::serialize",
+        deserialize_with = "time::serde::iso8601::deserialize"
+    )]
+    #[schemars(with = "String")]
+    pub creation_date: time::OffsetDateTime,
+    /// All requirements ordered by ID.
+    pub requirements: Vec<SborRequirement>,
+}
+
+#[derive(
+    Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
+)]
+pub struct SborProject {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub repository: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct SborRequirement {
+    pub id: ReqId,
+    pub title: String,
+    /// Link to the origin the requirement is defined.
+    pub origin: String,
+    /// IDs of the parent requirements.
+    #[serde(default)]
+    pub parents: Vec<ReqId>,
+    /// Optional workflow state of the requirement (e.g. `draft` or `approved`).
+    pub state: Option<String>,
+    /// Optional priority of the requirement.
+    /// Lower values mean higher priority.
+    pub priority: Option<u8>,
+    pub status: SborStatus,
+    /// Locations in code the requirement is traced.
+    #[serde(default)]
+    pub traces: Vec<SborTrace>,
+    /// Tests that directly cover the requirement.
+    #[serde(default)]
+    pub tests: Vec<SborTest>,
+    /// Reviews that manually verified the requirement.
+    #[serde(default)]
+    pub reviews: Vec<SborReview>,
+}
+
+/// Verification status of a requirement.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum SborStatus {
+    /// The requirement is traced, and all tests covering it passed.
+    Passed,
+    /// The requirement is covered by at least one failed test.
+    Failed,
+    /// The requirement is traced, but not covered by any test.
+    Uncovered,
+    /// The requirement is not traced.
+    Untraced,
+    /// The requirement requires manual verification, and was verified in a review.
+    Verified,
+    /// The requirement requires manual verification, but was not verified in any review.
+    Unverified,
+    /// The requirement cannot be traced in code by definition.
+    Untraceable,
+    /// The requirement is deprecated.
+    Deprecated,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
+)]
+pub struct SborTrace {
+    pub filepath: PathBuf,
+    pub line: Line,
+    /// Optional name of the traced item (e.g. a function).
+    #[serde(alias = "item-name")]
+    pub item_name: Option<String>,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
+)]
+pub struct SborTest {
+    #[serde(alias = "test-run")]
+    pub test_run: String,
+    /// Test run date given in ISO8601 format.
+    #[serde(
+        alias = "test-run-date",
+        serialize_with = "time::serde::iso8601::serialize",
+        deserialize_with = "time::serde::iso8601::deserialize"
+    )]
+    #[schemars(with = "String")]
+    pub test_run_date: time::OffsetDateTime,
+    pub name: String,
+    pub passed: bool,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
+)]
+pub struct SborReview {
+    pub name: String,
+    /// Review date in the format `<year>-<month>-<day> <hour>:<minute>`.
+    pub date: String,
+}
@@ -35,6 +35,16 @@ pub struct TraceEntry {
     /// Optional name that is linked to this trace entry
     #[serde(alias = "item-name")]
     pub item_name: Option<String>,
+    /// Optional canonical path of the linked item (e.g. `crate::module::func`).
+    ///
+    /// In contrast to lines, the path stays stable if code is moved inside a file.
+    #[serde(default, alias = "item-path", skip_serializing_if = "Option::is_none")]
+    pub item_path: Option<String>,
+    /// Optional name of the test function the trace is set on.
+    ///
+    /// Traces on test functions declare that the test covers the traced requirements.
+    #[serde(default, alias = "test-name", skip_serializing_if = "Option::is_none")]
+    pub test_name: Option<String>,
 }
 
 impl std::fmt::Display for TraceEntry {